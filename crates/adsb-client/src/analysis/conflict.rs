@@ -0,0 +1,407 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Closest point of approach and conflict prediction.
+//!
+//! Each aircraft is first brought to the common time by dead reckoning (see
+//! [`Aircraft::predicted_position`]), then extrapolated in a straight line
+//! from its track and ground speed, climbing or descending at its vertical
+//! rate. Over the short look-ahead windows this is used for, a flat-earth
+//! projection around the pair is accurate enough.
+//!
+//! A pair is in conflict when there is a moment within the look-ahead window
+//! at which both the lateral and the vertical separation are below the
+//! configured minima. Lateral and vertical separation are solved separately
+//! as time intervals and intersected, so an aircraft climbing into another's
+//! level is caught even if the lateral closest approach happens earlier.
+
+use chrono::{DateTime, Utc};
+
+use crate::tracker::{Aircraft, NM_PER_DEGREE};
+
+/// Relative speeds below this (knots) are treated as no relative motion.
+const MIN_RELATIVE_SPEED_KT: f64 = 0.1;
+/// Vertical rates below this (ft/min) are treated as level.
+const MIN_RELATIVE_VERTICAL_RATE_FPM: f64 = 1.0;
+
+/// Separation minima and look-ahead window for conflict detection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ConflictConfig {
+    /// Minimum lateral separation in nautical miles.
+    pub lateral_nm: f64,
+    /// Minimum vertical separation in feet.
+    pub vertical_ft: f64,
+    /// How far ahead to predict, in seconds.
+    pub look_ahead_secs: f64,
+    /// Aircraft whose last position is older than this, in seconds, are
+    /// skipped.
+    pub max_position_age_secs: f64,
+}
+
+impl Default for ConflictConfig {
+    fn default() -> Self {
+        Self {
+            lateral_nm: 3.0,
+            vertical_ft: 1000.0,
+            look_ahead_secs: 120.0,
+            max_position_age_secs: 30.0,
+        }
+    }
+}
+
+/// Predicted closest point of approach between two aircraft.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClosestApproach {
+    /// Seconds from now until the aircraft are closest laterally.
+    ///
+    /// Zero if they are already diverging; capped at the look-ahead window.
+    pub time_secs: f64,
+    /// Lateral distance at the closest point, in nautical miles.
+    pub lateral_nm: f64,
+    /// Vertical distance at the closest point, in feet.
+    ///
+    /// `None` if either altitude is unknown.
+    pub vertical_ft: Option<f64>,
+}
+
+/// A pair of aircraft predicted to lose separation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    /// ICAO address of the first aircraft.
+    pub icao_a: String,
+    /// ICAO address of the second aircraft.
+    pub icao_b: String,
+    /// Closest point of approach within the look-ahead window.
+    pub approach: ClosestApproach,
+    /// Seconds until separation is first lost (zero if already lost).
+    pub time_to_loss_secs: f64,
+}
+
+/// Straight-line motion of an aircraft, ready for extrapolation.
+#[derive(Debug, Clone, Copy)]
+struct Motion {
+    latitude: f64,
+    longitude: f64,
+    /// Ground velocity east, in knots.
+    east_kt: f64,
+    /// Ground velocity north, in knots.
+    north_kt: f64,
+    altitude_ft: Option<f64>,
+    vertical_rate_fpm: f64,
+    /// Seconds since the position it was moved forward from.
+    age_secs: f64,
+}
+
+impl Motion {
+    /// Motion at `now` of an airborne aircraft with a known position and
+    /// velocity.
+    fn of(aircraft: &Aircraft, now: DateTime<Utc>) -> Option<Self> {
+        if aircraft.is_on_ground == Some(true) {
+            return None;
+        }
+        let (sin, cos) = aircraft.track?.to_radians().sin_cos();
+        let ground_speed = aircraft.velocity?;
+        let position = aircraft.predicted_position(now)?;
+        let vertical_rate_fpm = aircraft.vertical_rate.map_or(0.0, f64::from);
+        Some(Self {
+            latitude: position.latitude,
+            longitude: position.longitude,
+            east_kt: ground_speed * sin,
+            north_kt: ground_speed * cos,
+            altitude_ft: aircraft
+                .altitude
+                .map(|altitude| f64::from(altitude) + vertical_rate_fpm * position.age_secs / 60.0),
+            vertical_rate_fpm,
+            age_secs: position.age_secs,
+        })
+    }
+
+    fn ground_speed(&self) -> f64 {
+        self.east_kt.hypot(self.north_kt)
+    }
+}
+
+/// Position and velocity of `b` relative to `a`.
+#[derive(Debug, Clone, Copy)]
+struct Relative {
+    /// Offset east and north, in nautical miles.
+    east_nm: f64,
+    north_nm: f64,
+    /// Closing velocity east and north, in nautical miles per second.
+    east_nm_s: f64,
+    north_nm_s: f64,
+    /// Altitude difference in feet, if both altitudes are known.
+    vertical_ft: Option<f64>,
+    /// Vertical rate difference in feet per second.
+    vertical_ft_s: f64,
+}
+
+impl Relative {
+    fn between(a: &Motion, b: &Motion) -> Self {
+        let mid_latitude = f64::midpoint(a.latitude, b.latitude).to_radians();
+        let delta_longitude = (b.longitude - a.longitude + 180.0).rem_euclid(360.0) - 180.0;
+        Self {
+            east_nm: delta_longitude * NM_PER_DEGREE * mid_latitude.cos(),
+            north_nm: (b.latitude - a.latitude) * NM_PER_DEGREE,
+            east_nm_s: (b.east_kt - a.east_kt) / 3600.0,
+            north_nm_s: (b.north_kt - a.north_kt) / 3600.0,
+            vertical_ft: a
+                .altitude_ft
+                .zip(b.altitude_ft)
+                .map(|(alt_a, alt_b)| alt_b - alt_a),
+            vertical_ft_s: (b.vertical_rate_fpm - a.vertical_rate_fpm) / 60.0,
+        }
+    }
+
+    fn lateral_at(&self, time_secs: f64) -> f64 {
+        (self.east_nm + self.east_nm_s * time_secs)
+            .hypot(self.north_nm + self.north_nm_s * time_secs)
+    }
+
+    fn closest_approach(&self, look_ahead_secs: f64) -> ClosestApproach {
+        let speed_sq = self.east_nm_s.powi(2) + self.north_nm_s.powi(2);
+        let time_secs = if speed_sq.sqrt() * 3600.0 < MIN_RELATIVE_SPEED_KT {
+            0.0
+        } else {
+            let closing = self.east_nm * self.east_nm_s + self.north_nm * self.north_nm_s;
+            (-closing / speed_sq).clamp(0.0, look_ahead_secs)
+        };
+        ClosestApproach {
+            time_secs,
+            lateral_nm: self.lateral_at(time_secs),
+            vertical_ft: self
+                .vertical_ft
+                .map(|vertical| (vertical + self.vertical_ft_s * time_secs).abs()),
+        }
+    }
+
+    /// Times (seconds from now) during which lateral separation is below the
+    /// minimum.
+    fn lateral_loss(&self, minimum_nm: f64) -> Option<(f64, f64)> {
+        let speed_sq = self.east_nm_s.powi(2) + self.north_nm_s.powi(2);
+        let offset_sq = self.east_nm.powi(2) + self.north_nm.powi(2) - minimum_nm.powi(2);
+        if speed_sq.sqrt() * 3600.0 < MIN_RELATIVE_SPEED_KT {
+            return (offset_sq < 0.0).then_some((f64::NEG_INFINITY, f64::INFINITY));
+        }
+
+        // Solve |offset + velocity * t| = minimum
+        let closing = 2.0 * (self.east_nm * self.east_nm_s + self.north_nm * self.north_nm_s);
+        let discriminant = closing.powi(2) - 4.0 * speed_sq * offset_sq;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        Some((
+            (-closing - root) / (2.0 * speed_sq),
+            (-closing + root) / (2.0 * speed_sq),
+        ))
+    }
+
+    /// Times (seconds from now) during which vertical separation is below the
+    /// minimum.
+    fn vertical_loss(&self, minimum_ft: f64) -> Option<(f64, f64)> {
+        let vertical = self.vertical_ft?;
+        if self.vertical_ft_s.abs() * 60.0 < MIN_RELATIVE_VERTICAL_RATE_FPM {
+            return (vertical.abs() < minimum_ft).then_some((f64::NEG_INFINITY, f64::INFINITY));
+        }
+        let first = (-minimum_ft - vertical) / self.vertical_ft_s;
+        let second = (minimum_ft - vertical) / self.vertical_ft_s;
+        Some((first.min(second), first.max(second)))
+    }
+}
+
+/// Predict the closest point of approach between two aircraft.
+///
+/// Returns `None` unless both aircraft are airborne with a known position,
+/// track and ground speed. Both are moved forward to `now` first, and the
+/// closest approach is limited to the following `look_ahead_secs` seconds.
+#[must_use]
+pub fn closest_approach(
+    a: &Aircraft,
+    b: &Aircraft,
+    now: DateTime<Utc>,
+    look_ahead_secs: f64,
+) -> Option<ClosestApproach> {
+    let relative = Relative::between(&Motion::of(a, now)?, &Motion::of(b, now)?);
+    Some(relative.closest_approach(look_ahead_secs))
+}
+
+/// Find pairs of aircraft predicted to lose separation within the look-ahead
+/// window.
+///
+/// Every aircraft is moved forward to `now`, the time on its feed. Aircraft
+/// without a known position, track, ground speed or altitude are skipped, as
+/// are those whose position is older than
+/// [`max_position_age_secs`](ConflictConfig::max_position_age_secs) and pairs
+/// with the same ICAO address (the same aircraft seen by two feeds).
+/// Conflicts are sorted by time until separation is lost.
+#[must_use]
+pub fn detect_conflicts<'a, I>(
+    aircraft: I,
+    now: DateTime<Utc>,
+    config: &ConflictConfig,
+) -> Vec<Conflict>
+where
+    I: IntoIterator<Item = &'a Aircraft>,
+{
+    let tracks: Vec<(&str, Motion)> = aircraft
+        .into_iter()
+        .filter_map(|aircraft| {
+            let motion = Motion::of(aircraft, now)?;
+            if motion.age_secs > config.max_position_age_secs {
+                return None;
+            }
+            motion.altitude_ft?;
+            Some((aircraft.icao.as_str(), motion))
+        })
+        .collect();
+
+    let mut conflicts = Vec::new();
+    for (index, (icao_a, motion_a)) in tracks.iter().enumerate() {
+        for (icao_b, motion_b) in &tracks[index + 1..] {
+            if icao_a == icao_b {
+                continue;
+            }
+
+            let relative = Relative::between(motion_a, motion_b);
+
+            // Skip pairs too far apart to close the gap within the window
+            let reach_nm = config.lateral_nm
+                + (motion_a.ground_speed() + motion_b.ground_speed()) / 3600.0
+                    * config.look_ahead_secs;
+            if relative.lateral_at(0.0) > reach_nm {
+                continue;
+            }
+
+            let (Some(lateral), Some(vertical)) = (
+                relative.lateral_loss(config.lateral_nm),
+                relative.vertical_loss(config.vertical_ft),
+            ) else {
+                continue;
+            };
+            let start = lateral.0.max(vertical.0).max(0.0);
+            let end = lateral.1.min(vertical.1).min(config.look_ahead_secs);
+            if start > end {
+                continue;
+            }
+
+            conflicts.push(Conflict {
+                icao_a: (*icao_a).to_string(),
+                icao_b: (*icao_b).to_string(),
+                approach: relative.closest_approach(config.look_ahead_secs),
+                time_to_loss_secs: start,
+            });
+        }
+    }
+
+    conflicts.sort_by(|a, b| a.time_to_loss_secs.total_cmp(&b.time_to_loss_secs));
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap()
+    }
+
+    fn aircraft(icao: &str, latitude: f64, longitude: f64, track: f64, altitude: i32) -> Aircraft {
+        let mut aircraft = Aircraft::new(icao.to_string());
+        aircraft.latitude = Some(latitude);
+        aircraft.longitude = Some(longitude);
+        aircraft.position_time = Some(now());
+        aircraft.track = Some(track);
+        aircraft.velocity = Some(300.0);
+        aircraft.altitude = Some(altitude);
+        aircraft.vertical_rate = Some(0);
+        aircraft
+    }
+
+    #[test]
+    fn test_head_on_conflict() {
+        // 10 nm apart on the same meridian, closing at 600 kt: 60 s to meet
+        let north = aircraft("A00001", 37.0, -122.0, 0.0, 10000);
+        let south = aircraft("A00002", 37.0 + 10.0 / 60.0, -122.0, 180.0, 10000);
+
+        let approach = closest_approach(&north, &south, now(), 120.0).unwrap();
+        assert!((approach.time_secs - 60.0).abs() < 0.5);
+        assert!(approach.lateral_nm < 0.01);
+        assert_eq!(approach.vertical_ft, Some(0.0));
+
+        let conflicts = detect_conflicts([&north, &south], now(), &ConflictConfig::default());
+        assert_eq!(conflicts.len(), 1);
+        // Separation is lost 3 nm before meeting, 18 s earlier
+        assert!((conflicts[0].time_to_loss_secs - 42.0).abs() < 0.5);
+
+        // Out of the look-ahead window
+        let config = ConflictConfig {
+            look_ahead_secs: 30.0,
+            ..Default::default()
+        };
+        assert!(detect_conflicts([&north, &south], now(), &config).is_empty());
+
+        // Last seen 30 s ago, the first aircraft has since closed 2.5 nm
+        let mut late = north.clone();
+        late.position_time = Some(now() - Duration::seconds(30));
+        let conflicts = detect_conflicts([&late, &south], now(), &ConflictConfig::default());
+        assert!((conflicts[0].time_to_loss_secs - 27.0).abs() < 0.5);
+
+        // A position too old to trust is skipped
+        late.position_time = Some(now() - Duration::seconds(60));
+        assert!(detect_conflicts([&late, &south], now(), &ConflictConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_separated_pairs() {
+        let north = aircraft("A00001", 37.0, -122.0, 0.0, 10000);
+
+        // Diverging
+        let diverging = aircraft("A00002", 37.0 + 10.0 / 60.0, -122.0, 0.0, 10000);
+        let approach = closest_approach(&north, &diverging, now(), 120.0).unwrap();
+        assert!(approach.time_secs.abs() < f64::EPSILON);
+        assert!(
+            detect_conflicts([&north, &diverging], now(), &ConflictConfig::default()).is_empty()
+        );
+
+        // Vertically separated
+        let above = aircraft("A00003", 37.0 + 10.0 / 60.0, -122.0, 180.0, 12000);
+        assert!(detect_conflicts([&north, &above], now(), &ConflictConfig::default()).is_empty());
+
+        // The same aircraft from two feeds
+        let duplicate = aircraft("A00001", 37.0 + 10.0 / 60.0, -122.0, 180.0, 10000);
+        assert!(
+            detect_conflicts([&north, &duplicate], now(), &ConflictConfig::default()).is_empty()
+        );
+    }
+
+    #[test]
+    fn test_climbing_into_conflict() {
+        // Same track 2 nm apart laterally, 2000 ft below and climbing at 2000 ft/min
+        let level = aircraft("A00001", 37.0, -122.0, 90.0, 12000);
+        let mut climbing = aircraft("A00002", 37.0 + 2.0 / 60.0, -122.0, 90.0, 10000);
+        climbing.vertical_rate = Some(2000);
+
+        let conflicts = detect_conflicts([&level, &climbing], now(), &ConflictConfig::default());
+        assert_eq!(conflicts.len(), 1);
+        // Within 1000 ft after 30 s
+        assert!((conflicts[0].time_to_loss_secs - 30.0).abs() < 0.5);
+    }
+}
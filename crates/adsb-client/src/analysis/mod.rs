@@ -0,0 +1,23 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Traffic analysis on tracked aircraft.
+//!
+//! These functions work on [`Aircraft`](crate::Aircraft) snapshots and do not
+//! keep state; callers decide how often to run them and what to do with the
+//! results.
+
+mod conflict;
+
+pub use conflict::{closest_approach, detect_conflicts, ClosestApproach, Conflict, ConflictConfig};
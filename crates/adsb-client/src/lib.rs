@@ -18,10 +18,12 @@
 //! processing ADS-B aircraft tracking data. It supports multiple layers that
 //! can be used independently or composed together:
 //!
-//! - **Protocol layer**: Message parsing (BaseStation/SBS-1, with future support
-//!   for BEAST, AVR, and others)
+//! - **Protocol layer**: Message parsing (BaseStation/SBS-1, BEAST, AVR and UAT)
+//! - **Mode S layer**: Decoding of raw Mode S/ADS-B frames carried by binary feeds
 //! - **Tracker layer**: Aircraft state management, position history, and validation
-//! - **Connection layer**: Async TCP with automatic reconnection and address hot-reload
+//! - **Connection layer**: Async TCP with automatic reconnection and address hot-reload,
+//!   or data fed by the caller to replay recordings on their own clock
+//! - **Analysis layer**: Closest point of approach and conflict prediction
 //!
 //! # Quick Start
 //!
@@ -73,6 +75,31 @@
 //! }
 //! ```
 //!
+//! ## BEAST Binary Feed
+//!
+//! Binary feeds are read with [`Framing::Beast`] so each event carries one frame:
+//!
+//! ```no_run
+//! use adsb_client::{BeastParser, Connection, ConnectionConfig, ConnectionEvent, Framing, Protocol};
+//!
+//! # async fn example() {
+//! let mut connection = Connection::spawn(ConnectionConfig {
+//!     address: "localhost:30005".to_string(),
+//!     framing: Framing::Beast,
+//!     ..Default::default()
+//! });
+//! let mut parser = BeastParser::new();
+//!
+//! while let Some(event) = connection.recv().await {
+//!     if let ConnectionEvent::DataReceived(frame) = event {
+//!         if let Ok(Some(msg)) = parser.parse(&frame) {
+//!             println!("{msg:?}");
+//!         }
+//!     }
+//! }
+//! # }
+//! ```
+//!
 //! ## Tracker Layer Only
 //!
 //! ```
@@ -86,6 +113,7 @@
 //!
 //! tracker.process_message(AircraftMessage::Position {
 //!     icao: "A1B2C3".to_string(),
+//!     timestamp: None,
 //!     latitude: 34.0,
 //!     longitude: -118.5,
 //!     altitude: Some(35000),
@@ -97,6 +125,8 @@
 //! println!("Tracking {} aircraft", tracker.len());
 //! ```
 
+pub mod analysis;
+pub mod mode_s;
 pub mod protocol;
 pub mod tcp;
 pub mod tracker;
@@ -104,19 +134,105 @@ pub mod tracker;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use log::warn;
-use tokio::sync::broadcast;
+use thiserror::Error;
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use tokio_util::sync::CancellationToken;
+
+pub use mode_s::comm_b::{CommBRegister, HazardLevel, Inferred};
+pub use mode_s::{DecoderStats, ErrorCorrection};
+pub use protocol::{
+    AircraftMessage, AutoParser, AutopilotModes, AvrParser, BaseStationEncoder, BaseStationParser,
+    BeastParser, DataSource, DetectedFormat, EmergencyState, ParseError, Protocol,
+    ResolutionAdvisory, SelectedAltitudeSource, Signal, TrackStatus, UatParser,
+};
+pub use tcp::{Connection, ConnectionConfig, ConnectionEvent, ConnectionState, Framing};
+pub use tracker::{
+    Aircraft, AircraftTracker, AlphaBeta, Emergency, Extensions, KalmanValidator, PositionPoint,
+    PositionReport, PositionStats, PositionValidator, PredictedPosition, Rejection, TrackEstimate,
+    TrackerConfig, TrackerEvent, ValidatorConfig,
+};
 
-pub use protocol::{AircraftMessage, BaseStationParser, ParseError, Protocol};
-pub use tcp::{Connection, ConnectionConfig, ConnectionEvent, ConnectionState};
-pub use tracker::{Aircraft, AircraftTracker, PositionPoint, TrackerConfig, TrackerEvent};
+/// A boxed protocol parser producing [`AircraftMessage`]s.
+pub type BoxedParser = Box<dyn Protocol<Message = AircraftMessage, Error = ParseError> + Send>;
 
 /// Protocol type for the client.
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ProtocolType {
     /// BaseStation/SBS-1 CSV protocol (default).
     #[default]
     BaseStation,
+    /// BEAST binary protocol (dump1090/readsb port 30005).
+    Beast,
+    /// AVR raw hex frames (dump1090 port 30002).
+    Avr,
+    /// UAT 978 MHz raw frames or JSON (dump978 port 30978/30979).
+    Uat,
+    /// Detect the format from the first bytes of the feed.
+    Auto,
+}
+
+impl ProtocolType {
+    /// All supported protocol types.
+    pub const ALL: [Self; 5] = [
+        Self::Auto,
+        Self::BaseStation,
+        Self::Beast,
+        Self::Avr,
+        Self::Uat,
+    ];
+
+    /// Stream framing used by this protocol.
+    #[must_use]
+    pub fn framing(self) -> Framing {
+        match self {
+            Self::BaseStation | Self::Avr | Self::Uat => Framing::Lines,
+            Self::Beast => Framing::Beast,
+            Self::Auto => Framing::Detect,
+        }
+    }
+
+    /// Create a parser for this protocol.
+    #[must_use]
+    pub fn parser(self) -> BoxedParser {
+        match self {
+            Self::BaseStation => Box::new(BaseStationParser::new()),
+            Self::Beast => Box::new(BeastParser::new()),
+            Self::Avr => Box::new(AvrParser::new()),
+            Self::Uat => Box::new(UatParser::new()),
+            Self::Auto => Box::new(AutoParser::new()),
+        }
+    }
+
+    /// Conventional dump1090 port for this protocol.
+    ///
+    /// Auto-detection has no port of its own and suggests the SBS port.
+    #[must_use]
+    pub fn default_port(self) -> u16 {
+        match self {
+            Self::BaseStation | Self::Auto => 30003,
+            Self::Beast => 30005,
+            Self::Avr => 30002,
+            Self::Uat => 30978,
+        }
+    }
+
+    /// Human-readable protocol name.
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::BaseStation => "SBS (BaseStation)",
+            Self::Beast => "BEAST",
+            Self::Avr => "AVR (raw hex)",
+            Self::Uat => "UAT 978 (dump978)",
+            Self::Auto => "Auto-detect",
+        }
+    }
 }
 
 /// Configuration for the full-stack client.
@@ -143,20 +259,80 @@ impl Default for ClientConfig {
     }
 }
 
+/// Errors reported by a running [`Client`].
+///
+/// The client keeps running after reporting an error: connection failures
+/// are retried after the reconnect delay and unparseable messages are skipped.
+#[derive(Debug, Clone, Error)]
+pub enum ClientError {
+    /// The connection failed and will be retried.
+    #[error("connection error: {0}")]
+    Connection(String),
+
+    /// A received message could not be parsed.
+    #[error("parse error: {0}")]
+    Parse(#[from] ParseError),
+}
+
+/// Counters kept by a running [`Client`].
+///
+/// Counts accumulate across reconnects.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClientStats {
+    /// Messages (lines or binary frames) received.
+    pub messages: u64,
+    /// Messages that failed to parse.
+    pub parse_errors: u64,
+    /// Mode S frames repaired by error correction.
+    pub frames_corrected: u64,
+    /// Mode S frames dropped for a bad parity or an unknown address.
+    pub frames_dropped: u64,
+}
+
+/// A line or frame as received from the feed, before parsing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceivedData {
+    /// Time the data was received.
+    pub timestamp: DateTime<Utc>,
+    /// The line or binary frame, as passed to the parser.
+    pub data: Vec<u8>,
+}
+
+/// Input of a client fed by the caller, see [`Client::spawn_feed`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeedEvent {
+    /// A line or frame, processed as if received at its timestamp.
+    Data(ReceivedData),
+    /// Forget all aircraft and parser state, e.g. before jumping to another
+    /// point of a recording.
+    Reset,
+}
+
 /// Full-stack ADS-B client that wires all layers together.
 ///
 /// The client manages a TCP connection, parses incoming messages using the
-/// configured protocol, and maintains aircraft state in a tracker.
+/// configured protocol, and maintains aircraft state in a tracker. All of
+/// this runs in a background task, so [`Client::get_aircraft`] stays current
+/// without the caller driving it. The connection's framing follows the
+/// protocol, so [`ProtocolType::Auto`] detects the feed format on each
+/// connect.
+///
+/// Dropping the client stops the background task and closes the connection.
 pub struct Client {
     tracker: Arc<RwLock<AircraftTracker>>,
-    connection: Connection,
-    connection_state: Arc<RwLock<ConnectionState>>,
+    connection_state: watch::Receiver<ConnectionState>,
+    stats: Arc<RwLock<ClientStats>>,
+    address_tx: watch::Sender<String>,
+    error_tx: broadcast::Sender<ClientError>,
+    data_tx: broadcast::Sender<ReceivedData>,
+    cancel_token: CancellationToken,
 }
 
 impl std::fmt::Debug for Client {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Client")
-            .field("connection", &self.connection)
+            .field("address", &*self.address_tx.borrow())
+            .field("cancel_token", &self.cancel_token)
             .finish_non_exhaustive()
     }
 }
@@ -164,86 +340,73 @@ impl std::fmt::Debug for Client {
 impl Client {
     /// Spawn a new client with the given configuration.
     ///
-    /// This starts background tasks for connection management, message parsing,
-    /// and periodic cleanup.
+    /// This starts the connection and a background task that parses incoming
+    /// messages, updates the tracker and periodically removes stale aircraft.
+    /// Must be called from within a Tokio runtime.
     #[must_use]
     pub fn spawn(config: ClientConfig) -> Self {
-        let tracker = Arc::new(RwLock::new(AircraftTracker::new(config.tracker)));
-        let connection = Connection::spawn(config.connection);
-        let connection_state = Arc::new(RwLock::new(ConnectionState::Disconnected));
-
-        // Spawn message processing task
-        let tracker_clone = Arc::clone(&tracker);
-        let state_clone = Arc::clone(&connection_state);
-        let cleanup_interval = config.cleanup_interval;
-
-        // We need to create a new connection receiver for the processing task
-        // Since Connection owns the receiver, we need a different approach
-        // We'll spawn the processing in a way that shares the connection
-
-        // Actually, the design needs adjustment - Connection owns the receiver
-        // Let's create a simpler design where Client processes in a loop
-
-        // For now, we'll spawn a task that periodically cleans up
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(cleanup_interval);
-            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
-
-            loop {
-                interval.tick().await;
-                if let Ok(mut tracker) = tracker_clone.write() {
-                    tracker.cleanup_stale();
-                }
-            }
-        });
-
-        Self {
-            tracker,
-            connection,
-            connection_state: state_clone,
-        }
+        Self::spawn_with_input(config, None)
     }
 
-    /// Process events from the connection.
+    /// Spawn a client fed by the caller instead of a TCP connection.
     ///
-    /// This should be called in a loop to process incoming data:
+    /// Data sent on the returned channel is parsed with the configured
+    /// protocol and tracked at the timestamp it carries, so a recorded feed
+    /// can be replayed at any speed: the tracker's clock is the time of the
+    /// latest data, and stale aircraft are removed as that clock advances.
+    /// Only the buffer size of `config.connection` is used. The client
+    /// reports itself connected until the sender is dropped.
     ///
-    /// ```no_run
-    /// # use adsb_client::{Client, ClientConfig};
-    /// # async fn example() {
-    /// let mut client = Client::spawn(ClientConfig::default());
-    /// while client.process_next().await {}
-    /// # }
-    /// ```
-    pub async fn process_next(&mut self) -> bool {
-        let event = match self.connection.recv().await {
-            Some(event) => event,
-            None => return false,
+    /// Must be called from within a Tokio runtime.
+    #[must_use]
+    pub fn spawn_feed(config: ClientConfig) -> (Self, mpsc::Sender<FeedEvent>) {
+        let (feed_tx, feed_rx) = mpsc::channel(config.connection.buffer_size);
+        (Self::spawn_with_input(config, Some(feed_rx)), feed_tx)
+    }
+
+    fn spawn_with_input(config: ClientConfig, input: Option<mpsc::Receiver<FeedEvent>>) -> Self {
+        let tracker = Arc::new(RwLock::new(AircraftTracker::new(config.tracker)));
+        let (state_tx, connection_state) = watch::channel(ConnectionState::Disconnected);
+        let stats = Arc::new(RwLock::new(ClientStats::default()));
+        let (address_tx, address_rx) = watch::channel(config.connection.address.clone());
+        let (error_tx, _) = broadcast::channel(ERROR_CHANNEL_CAPACITY);
+        let (data_tx, _) = broadcast::channel(DATA_CHANNEL_CAPACITY);
+        let cancel_token = CancellationToken::new();
+
+        let input = match input {
+            Some(feed_rx) => Input::Feed(feed_rx),
+            None => Input::Connection(Connection::spawn(ConnectionConfig {
+                framing: config.protocol.framing(),
+                ..config.connection
+            })),
         };
 
-        match event {
-            ConnectionEvent::StateChanged(state) => {
-                if let Ok(mut s) = self.connection_state.write() {
-                    *s = state;
-                }
-            }
-            ConnectionEvent::DataReceived(data) => {
-                let mut parser = BaseStationParser::new();
-                match parser.parse(&data) {
-                    Ok(Some(msg)) => {
-                        if let Ok(mut tracker) = self.tracker.write() {
-                            tracker.process_message(msg);
-                        }
-                    }
-                    Ok(None) => {}
-                    Err(e) => {
-                        warn!("Parse error: {}", e);
-                    }
-                }
-            }
-        }
+        let task = IngestTask {
+            input,
+            protocol: config.protocol,
+            parser: config.protocol.parser(),
+            tracker: Arc::clone(&tracker),
+            state_tx,
+            stats: Arc::clone(&stats),
+            frame_stats: DecoderStats::default(),
+            address_rx,
+            error_tx: error_tx.clone(),
+            data_tx: data_tx.clone(),
+            cleanup_interval: config.cleanup_interval,
+            feed_cleanup: None,
+            cancel_token: cancel_token.clone(),
+        };
+        tokio::spawn(task.run());
 
-        true
+        Self {
+            tracker,
+            connection_state,
+            stats,
+            address_tx,
+            error_tx,
+            data_tx,
+            cancel_token,
+        }
     }
 
     /// Get all tracked aircraft.
@@ -271,41 +434,117 @@ impl Client {
     }
 
     /// Subscribe to tracker events.
+    ///
+    /// The channel closes when the client is shut down; once it has been,
+    /// the receiver returned is already closed.
     #[must_use]
     pub fn subscribe(&self) -> broadcast::Receiver<TrackerEvent> {
+        let closed = || {
+            let (tx, rx) = broadcast::channel(1);
+            drop(tx);
+            rx
+        };
+        if self.cancel_token.is_cancelled() {
+            return closed();
+        }
         self.tracker
             .read()
             .map(|t| t.subscribe())
-            .unwrap_or_else(|_| {
-                let (tx, rx) = broadcast::channel(1);
-                drop(tx);
-                rx
-            })
+            .unwrap_or_else(|_| closed())
+    }
+
+    /// Stream of tracker events.
+    ///
+    /// Events missed because the consumer fell behind are skipped with a
+    /// warning. The stream ends when the client is shut down.
+    ///
+    /// ```no_run
+    /// use adsb_client::{Client, ClientConfig, TrackerEvent};
+    /// use tokio_stream::StreamExt;
+    ///
+    /// # async fn example() {
+    /// let client = Client::spawn(ClientConfig::default());
+    /// let mut events = client.events();
+    /// while let Some(event) = events.next().await {
+    ///     if let TrackerEvent::AircraftAdded(icao) = event {
+    ///         println!("New aircraft {icao}");
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn events(&self) -> impl Stream<Item = TrackerEvent> + Send + Unpin {
+        skip_lagged(self.subscribe(), "tracker events")
+    }
+
+    /// Subscribe to errors from the background task.
+    #[must_use]
+    pub fn subscribe_errors(&self) -> broadcast::Receiver<ClientError> {
+        self.error_tx.subscribe()
+    }
+
+    /// Stream of errors from the background task.
+    ///
+    /// Errors missed because the consumer fell behind are skipped with a
+    /// warning.
+    pub fn errors(&self) -> impl Stream<Item = ClientError> + Send + Unpin {
+        skip_lagged(self.subscribe_errors(), "client errors")
+    }
+
+    /// Subscribe to the raw data received, for recording or forwarding.
+    ///
+    /// Data is only copied while there are subscribers. A subscriber that
+    /// falls more than a few thousand messages behind misses the oldest.
+    #[must_use]
+    pub fn subscribe_data(&self) -> broadcast::Receiver<ReceivedData> {
+        self.data_tx.subscribe()
     }
 
     /// Get the current connection state.
     #[must_use]
     pub fn connection_state(&self) -> ConnectionState {
-        self.connection_state
-            .read()
-            .map(|s| s.clone())
-            .unwrap_or(ConnectionState::Disconnected)
+        self.connection_state.borrow().clone()
+    }
+
+    /// Watch connection state changes.
+    #[must_use]
+    pub fn subscribe_state(&self) -> watch::Receiver<ConnectionState> {
+        self.connection_state.clone()
+    }
+
+    /// Get the message and frame counters.
+    #[must_use]
+    pub fn stats(&self) -> ClientStats {
+        self.stats.read().map(|stats| *stats).unwrap_or_default()
+    }
+
+    /// Get the shared tracker.
+    ///
+    /// Use this to take snapshots, attach [`Extensions`] to aircraft or
+    /// change tracker settings. Hold the lock briefly: the background task
+    /// needs it for every message.
+    #[must_use]
+    pub fn tracker(&self) -> &Arc<RwLock<AircraftTracker>> {
+        &self.tracker
     }
 
     /// Change the server address.
     ///
     /// The connection will disconnect and reconnect to the new address.
     pub fn set_address(&self, address: String) {
-        self.connection.set_address(address);
+        let _ = self.address_tx.send(address);
     }
 
     /// Get the current server address.
     #[must_use]
     pub fn current_address(&self) -> String {
-        self.connection.current_address()
+        self.address_tx.borrow().clone()
     }
 
     /// Set the center point for distance filtering.
+    ///
+    /// The parser picks up the new reference for local CPR decoding at the
+    /// next cleanup tick, with the tracker's maximum distance as the
+    /// receiver's range.
     pub fn set_center(&self, lat: f64, lon: f64) {
         if let Ok(mut tracker) = self.tracker.write() {
             tracker.set_center(lat, lon);
@@ -313,7 +552,348 @@ impl Client {
     }
 
     /// Shut down the client.
+    ///
+    /// Stops the background task and ends the tracker event streams, which
+    /// would otherwise outlive it with any other holder of the tracker.
     pub fn shutdown(&self) {
-        self.connection.shutdown();
+        self.cancel_token.cancel();
+        if let Ok(mut tracker) = self.tracker.write() {
+            tracker.close_events();
+        }
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Capacity of the client error channel.
+const ERROR_CHANNEL_CAPACITY: usize = 256;
+
+/// Capacity of the raw data channel.
+const DATA_CHANNEL_CAPACITY: usize = 4096;
+
+/// Turn a broadcast receiver into a stream, skipping lagged items.
+fn skip_lagged<T: Clone + Send + 'static>(
+    rx: broadcast::Receiver<T>,
+    name: &'static str,
+) -> impl Stream<Item = T> + Send + Unpin {
+    BroadcastStream::new(rx).filter_map(move |item| match item {
+        Ok(item) => Some(item),
+        Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+            warn!("Stream of {name} lagged, skipped {skipped}");
+            None
+        }
+    })
+}
+
+/// Where a client's data comes from.
+enum Input {
+    Connection(Connection),
+    Feed(mpsc::Receiver<FeedEvent>),
+}
+
+/// An event from either kind of [`Input`].
+enum InputEvent {
+    Connection(ConnectionEvent),
+    Feed(FeedEvent),
+}
+
+impl Input {
+    async fn recv(&mut self) -> Option<InputEvent> {
+        match self {
+            Self::Connection(connection) => connection.recv().await.map(InputEvent::Connection),
+            Self::Feed(feed_rx) => feed_rx.recv().await.map(InputEvent::Feed),
+        }
+    }
+}
+
+/// Background task that feeds connection data through the parser into the
+/// tracker.
+struct IngestTask {
+    input: Input,
+    protocol: ProtocolType,
+    parser: BoxedParser,
+    tracker: Arc<RwLock<AircraftTracker>>,
+    state_tx: watch::Sender<ConnectionState>,
+    stats: Arc<RwLock<ClientStats>>,
+    // Decoder counters of the current parser, already added to `stats`
+    frame_stats: DecoderStats,
+    address_rx: watch::Receiver<String>,
+    error_tx: broadcast::Sender<ClientError>,
+    data_tx: broadcast::Sender<ReceivedData>,
+    cleanup_interval: Duration,
+    // Feed time of the last cleanup of a fed client
+    feed_cleanup: Option<DateTime<Utc>>,
+    cancel_token: CancellationToken,
+}
+
+impl IngestTask {
+    async fn run(mut self) {
+        let mut cleanup = tokio::time::interval(self.cleanup_interval);
+        cleanup.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        self.update_reference();
+
+        let fed = matches!(self.input, Input::Feed(_));
+        if fed {
+            self.state_tx
+                .send_replace(ConnectionState::Connected { format: None });
+        }
+
+        loop {
+            tokio::select! {
+                event = self.input.recv() => {
+                    match event {
+                        Some(InputEvent::Connection(event)) => self.handle_event(event),
+                        Some(InputEvent::Feed(event)) => self.handle_feed(event),
+                        None => {
+                            if fed {
+                                self.state_tx.send_replace(ConnectionState::Disconnected);
+                            }
+                            return;
+                        }
+                    }
+                }
+
+                changed = self.address_rx.changed() => {
+                    if changed.is_err() {
+                        return; // Client dropped
+                    }
+                    let address = self.address_rx.borrow_and_update().clone();
+                    if let Input::Connection(connection) = &self.input {
+                        connection.set_address(address);
+                    }
+                }
+
+                _ = cleanup.tick() => {
+                    // Fed clients clean up on the feed's clock as data arrives
+                    if !fed {
+                        if let Ok(mut tracker) = self.tracker.write() {
+                            tracker.cleanup_stale();
+                        }
+                    }
+                    self.update_reference();
+                }
+
+                () = self.cancel_token.cancelled() => {
+                    if let Input::Connection(connection) = &self.input {
+                        connection.shutdown();
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    fn handle_event(&mut self, event: ConnectionEvent) {
+        match event {
+            ConnectionEvent::StateChanged(state) => {
+                match &state {
+                    // Fresh parser per connection so partial CPR state never
+                    // spans feeds
+                    ConnectionState::Connecting => self.reset_parser(),
+                    ConnectionState::Error(message) => {
+                        let _ = self.error_tx.send(ClientError::Connection(message.clone()));
+                    }
+                    ConnectionState::Connected { .. } | ConnectionState::Disconnected => {}
+                }
+                self.state_tx.send_replace(state);
+            }
+            ConnectionEvent::DataReceived(data) => self.process_data(ReceivedData {
+                timestamp: Utc::now(),
+                data,
+            }),
+        }
+    }
+
+    fn handle_feed(&mut self, event: FeedEvent) {
+        match event {
+            FeedEvent::Data(data) => {
+                let now = data.timestamp;
+                self.process_data(data);
+
+                let interval = chrono::Duration::from_std(self.cleanup_interval)
+                    .unwrap_or_else(|_| chrono::Duration::seconds(30));
+                match self.feed_cleanup {
+                    Some(last) if now - last < interval => {}
+                    Some(_) => {
+                        if let Ok(mut tracker) = self.tracker.write() {
+                            tracker.cleanup_stale_at(now);
+                        }
+                        self.feed_cleanup = Some(now);
+                    }
+                    None => self.feed_cleanup = Some(now),
+                }
+            }
+            FeedEvent::Reset => {
+                if let Ok(mut tracker) = self.tracker.write() {
+                    tracker.clear();
+                }
+                self.reset_parser();
+                self.feed_cleanup = None;
+            }
+        }
+    }
+
+    /// Parse a line or frame and track the message at its receive time.
+    fn process_data(&mut self, data: ReceivedData) {
+        let result = self.parser.parse_at(&data.data, data.timestamp);
+        self.record_stats(result.is_err());
+
+        match result {
+            Ok(Some(msg)) => {
+                if let Ok(mut tracker) = self.tracker.write() {
+                    tracker.process_message_with_signal(msg, self.parser.signal(), data.timestamp);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => {
+                let _ = self.error_tx.send(e.into());
+            }
+        }
+
+        if self.data_tx.receiver_count() > 0 {
+            let _ = self.data_tx.send(data);
+        }
+    }
+
+    /// Replace the parser and its counters with fresh ones.
+    fn reset_parser(&mut self) {
+        self.parser = self.protocol.parser();
+        self.frame_stats = DecoderStats::default();
+        self.update_reference();
+    }
+
+    fn record_stats(&mut self, parse_error: bool) {
+        let frame_stats = self.parser.decoder_stats().unwrap_or_default();
+        if let Ok(mut stats) = self.stats.write() {
+            stats.messages += 1;
+            stats.parse_errors += u64::from(parse_error);
+            stats.frames_corrected += frame_stats.corrected - self.frame_stats.corrected;
+            stats.frames_dropped += frame_stats.dropped - self.frame_stats.dropped;
+        }
+        self.frame_stats = frame_stats;
+    }
+
+    fn update_reference(&mut self) {
+        if let Ok(tracker) = self.tracker.read() {
+            self.parser.set_reference(tracker.center());
+            self.parser
+                .set_receiver_range(Some(tracker.max_distance_nm()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+    use tokio_stream::StreamExt;
+
+    #[tokio::test]
+    async fn test_client_ingests_in_background() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let client = Client::spawn(ClientConfig {
+            connection: ConnectionConfig {
+                address,
+                ..Default::default()
+            },
+            protocol: ProtocolType::Auto,
+            ..Default::default()
+        });
+        let mut events = client.events();
+        let mut errors = client.errors();
+        let mut data = client.subscribe_data();
+
+        let (mut socket, _) = listener.accept().await.unwrap();
+        socket
+            .write_all(
+                b"MSG,1,1,1,A1B2C3,1,2024/01/01,12:00:00.000,2024/01/01,12:00:00.000,UAL123\n\
+                  MSG,3,\xFF\n",
+            )
+            .await
+            .unwrap();
+
+        let event = tokio::time::timeout(Duration::from_secs(5), events.next())
+            .await
+            .unwrap();
+        assert!(matches!(event, Some(TrackerEvent::AircraftAdded(icao)) if icao == "A1B2C3"));
+
+        let error = tokio::time::timeout(Duration::from_secs(5), errors.next())
+            .await
+            .unwrap();
+        assert!(matches!(error, Some(ClientError::Parse(_))));
+
+        let received = data.recv().await.unwrap();
+        assert!(received.data.starts_with(b"MSG,1,"));
+
+        let stats = client.stats();
+        assert_eq!(stats.messages, 2);
+        assert_eq!(stats.parse_errors, 1);
+
+        // No caller pumping needed
+        assert_eq!(
+            client.get_by_icao("A1B2C3").unwrap().callsign.as_deref(),
+            Some("UAL123")
+        );
+        assert_eq!(
+            client.connection_state(),
+            ConnectionState::Connected {
+                format: Some(DetectedFormat::Sbs)
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_events_end_on_shutdown() {
+        let (client, _feed) = Client::spawn_feed(ClientConfig::default());
+        let mut events = client.events();
+
+        client.shutdown();
+        let event = tokio::time::timeout(Duration::from_secs(5), events.next())
+            .await
+            .unwrap();
+        assert!(event.is_none());
+        let event = tokio::time::timeout(Duration::from_secs(5), client.events().next())
+            .await
+            .unwrap();
+        assert!(event.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fed_client_uses_feed_clock() {
+        let (client, feed) = Client::spawn_feed(ClientConfig::default());
+        let mut events = client.events();
+        let recorded = Utc::now() - chrono::Duration::days(7);
+
+        feed.send(FeedEvent::Data(ReceivedData {
+            timestamp: recorded,
+            data: b"MSG,1,1,1,A1B2C3,1,2024/01/01,12:00:00.000,2024/01/01,12:00:00.000,UAL123"
+                .to_vec(),
+        }))
+        .await
+        .unwrap();
+        let event = tokio::time::timeout(Duration::from_secs(5), events.next())
+            .await
+            .unwrap();
+        assert!(matches!(event, Some(TrackerEvent::AircraftAdded(icao)) if icao == "A1B2C3"));
+        assert_eq!(client.get_by_icao("A1B2C3").unwrap().last_seen, recorded);
+        assert_eq!(
+            client.connection_state(),
+            ConnectionState::Connected { format: None }
+        );
+
+        feed.send(FeedEvent::Reset).await.unwrap();
+        let event = tokio::time::timeout(Duration::from_secs(5), events.next())
+            .await
+            .unwrap();
+        assert!(matches!(event, Some(TrackerEvent::AircraftRemoved(icao)) if icao == "A1B2C3"));
+        assert_eq!(client.aircraft_count(), 0);
     }
 }
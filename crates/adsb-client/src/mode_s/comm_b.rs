@@ -0,0 +1,660 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Comm-B (Enhanced Surveillance) register inference and decoding.
+//!
+//! DF20/21 replies carry a 56-bit MB field whose register (BDS) number is not
+//! transmitted; it depends on what the ground interrogator asked for. The
+//! register is therefore inferred by checking the MB field against the
+//! layout of each supported register:
+//!
+//! - BDS 4,0: selected vertical intention (selected altitude, baro setting)
+//! - BDS 4,4: meteorological routine air report (wind, temperature, pressure,
+//!   turbulence, humidity)
+//! - BDS 4,5: meteorological hazard report (turbulence, wind shear,
+//!   microburst, icing, wake vortex, temperature, pressure, radio height)
+//! - BDS 5,0: track and turn report (roll, track rate, true airspeed)
+//! - BDS 6,0: heading and speed report (magnetic heading, IAS, Mach,
+//!   barometric and inertial vertical rate)
+//!
+//! Every field is preceded by a status bit; a field whose status bit is
+//! clear must be all zeros, and reserved bits must be zero. Values outside
+//! the physical envelope of an aircraft rule a register out.
+//!
+//! Because several registers can fit the same MB field, each inference
+//! carries a confidence in `0.0..=1.0`. It grows with the number of fields
+//! present, shrinks when values disagree with each other (or with the reply's
+//! altitude), and is shared between registers when more than one fits.
+//!
+//! Bit positions below are one-based MB bits, as in the specification.
+
+use super::bits;
+
+/// Largest plausible selected altitude in feet.
+const MAX_SELECTED_ALTITUDE: i32 = 50_000;
+/// Largest plausible roll angle in degrees.
+const MAX_ROLL: f64 = 50.0;
+/// Largest plausible ground speed or true airspeed in knots.
+const MAX_SPEED: f64 = 600.0;
+/// Largest plausible indicated airspeed in knots.
+const MAX_IAS: f64 = 500.0;
+/// Largest plausible vertical rate in feet per minute.
+const MAX_VERTICAL_RATE: i32 = 6_000;
+/// Ground speed and true airspeed differ by at most this much (the wind).
+const MAX_WIND: f64 = 200.0;
+/// Largest plausible reported wind speed in knots.
+const MAX_WIND_SPEED: f64 = 250.0;
+/// Plausible static air temperatures in degrees Celsius.
+const PLAUSIBLE_TEMPERATURE: std::ops::RangeInclusive<f64> = -80.0..=60.0;
+/// Largest valid figure of merit (data source) in BDS 4,4.
+const MAX_FIGURE_OF_MERIT: u32 = 4;
+/// Baro settings outside this range are possible but unlikely.
+const TYPICAL_BARO: std::ops::RangeInclusive<f64> = 950.0..=1060.0;
+
+/// Speed of sound at sea level in knots.
+const SEA_LEVEL_SPEED_OF_SOUND: f64 = 661.47;
+/// Standard sea level pressure in pascals.
+const SEA_LEVEL_PRESSURE: f64 = 101_325.0;
+
+/// A Comm-B value together with the confidence of its register inference.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Inferred<T> {
+    /// Decoded value.
+    pub value: T,
+    /// Confidence of the register inference, from 0 to 1.
+    pub confidence: f32,
+}
+
+/// Severity of a meteorological hazard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HazardLevel {
+    /// No hazard.
+    Nil,
+    /// Light.
+    Light,
+    /// Moderate.
+    Moderate,
+    /// Severe.
+    Severe,
+}
+
+impl HazardLevel {
+    fn from_bits(raw: u32) -> Self {
+        match raw {
+            0 => Self::Nil,
+            1 => Self::Light,
+            2 => Self::Moderate,
+            _ => Self::Severe,
+        }
+    }
+}
+
+/// A decoded Comm-B register.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommBRegister {
+    /// BDS 4,0 selected vertical intention.
+    SelectedVerticalIntention {
+        /// MCP/FCU selected altitude in feet.
+        mcp_altitude: Option<i32>,
+        /// FMS selected altitude in feet.
+        fms_altitude: Option<i32>,
+        /// Barometric pressure setting in hPa.
+        baro_setting: Option<f64>,
+    },
+    /// BDS 4,4 meteorological routine air report.
+    MeteorologicalRoutine {
+        /// Wind speed in knots.
+        wind_speed: Option<f64>,
+        /// Direction the wind is blowing from, in degrees true.
+        wind_direction: Option<f64>,
+        /// Static air temperature in degrees Celsius.
+        static_air_temperature: f64,
+        /// Average static pressure in hPa.
+        static_pressure: Option<f64>,
+        /// Turbulence.
+        turbulence: Option<HazardLevel>,
+        /// Relative humidity in percent.
+        humidity: Option<f64>,
+    },
+    /// BDS 4,5 meteorological hazard report.
+    MeteorologicalHazard {
+        /// Turbulence.
+        turbulence: Option<HazardLevel>,
+        /// Wind shear.
+        wind_shear: Option<HazardLevel>,
+        /// Microburst.
+        microburst: Option<HazardLevel>,
+        /// Icing.
+        icing: Option<HazardLevel>,
+        /// Wake vortex.
+        wake_vortex: Option<HazardLevel>,
+        /// Static air temperature in degrees Celsius.
+        static_air_temperature: Option<f64>,
+        /// Average static pressure in hPa.
+        static_pressure: Option<f64>,
+        /// Radio height in feet.
+        radio_height: Option<i32>,
+    },
+    /// BDS 5,0 track and turn report.
+    TrackAndTurn {
+        /// Roll angle in degrees (positive = right wing down).
+        roll: Option<f64>,
+        /// True track rate in degrees per second.
+        track_rate: Option<f64>,
+        /// True airspeed in knots.
+        true_airspeed: Option<f64>,
+    },
+    /// BDS 6,0 heading and speed report.
+    HeadingAndSpeed {
+        /// Magnetic heading in degrees.
+        magnetic_heading: Option<f64>,
+        /// Indicated airspeed in knots.
+        indicated_airspeed: Option<f64>,
+        /// Mach number.
+        mach: Option<f64>,
+        /// Barometric altitude rate in feet per minute.
+        baro_vertical_rate: Option<i32>,
+        /// Inertial vertical velocity in feet per minute.
+        inertial_vertical_rate: Option<i32>,
+    },
+}
+
+impl CommBRegister {
+    /// Register number in the usual "x,y" notation.
+    #[must_use]
+    pub fn bds(&self) -> &'static str {
+        match self {
+            Self::SelectedVerticalIntention { .. } => "4,0",
+            Self::MeteorologicalRoutine { .. } => "4,4",
+            Self::MeteorologicalHazard { .. } => "4,5",
+            Self::TrackAndTurn { .. } => "5,0",
+            Self::HeadingAndSpeed { .. } => "6,0",
+        }
+    }
+}
+
+/// Read an unsigned MB field spanning one-based bits `first..=last`.
+fn field(mb: &[u8], first: usize, last: usize) -> u32 {
+    bits(mb, first - 1, last - first + 1)
+}
+
+/// Read a field preceded by a status bit.
+///
+/// Returns `Err(())` if the status bit is clear but the field is not zero.
+fn status_field(mb: &[u8], status: usize, last: usize) -> Result<Option<u32>, ()> {
+    let value = field(mb, status + 1, last);
+    match (field(mb, status, status), value) {
+        (1, value) => Ok(Some(value)),
+        (_, 0) => Ok(None),
+        _ => Err(()),
+    }
+}
+
+/// Interpret a sign bit and magnitude as a two's complement value.
+fn signed(raw: u32, value_bits: u32) -> f64 {
+    let sign = raw >> value_bits;
+    let magnitude = f64::from(raw & ((1 << value_bits) - 1));
+    if sign == 0 {
+        magnitude
+    } else {
+        magnitude - f64::from(1u32 << value_bits)
+    }
+}
+
+#[allow(
+    clippy::cast_possible_truncation,
+    reason = "vertical rates are 10-bit fields scaled by 32"
+)]
+fn vertical_rate(raw: u32) -> i32 {
+    (signed(raw, 9) * 32.0) as i32
+}
+
+/// Base score for a register from the fraction of fields present.
+fn completeness(present: usize, total: usize) -> f64 {
+    #[allow(clippy::cast_precision_loss, reason = "field counts are tiny")]
+    let fraction = present as f64 / total as f64;
+    0.5 + 0.5 * fraction
+}
+
+fn decode_bds40(mb: &[u8]) -> Option<(CommBRegister, f64)> {
+    let mcp = status_field(mb, 1, 13).ok()?;
+    let fms = status_field(mb, 14, 26).ok()?;
+    let baro = status_field(mb, 27, 39).ok()?;
+    status_field(mb, 48, 51).ok()?;
+    status_field(mb, 54, 56).ok()?;
+    if field(mb, 40, 47) != 0 || field(mb, 52, 53) != 0 {
+        return None;
+    }
+
+    let altitude = |raw: u32| i32::try_from(raw).ok().map(|raw| raw * 16);
+    let mcp_altitude = mcp.and_then(altitude);
+    let fms_altitude = fms.and_then(altitude);
+    if [mcp_altitude, fms_altitude]
+        .iter()
+        .flatten()
+        .any(|&alt| alt > MAX_SELECTED_ALTITUDE)
+    {
+        return None;
+    }
+    let baro_setting = baro.map(|raw| 800.0 + f64::from(raw) * 0.1);
+
+    let present = [mcp.is_some(), fms.is_some(), baro.is_some()];
+    let mut score = completeness(present.iter().filter(|p| **p).count(), present.len());
+    if baro_setting.is_some_and(|qnh| !TYPICAL_BARO.contains(&qnh)) {
+        score *= 0.5;
+    }
+
+    Some((
+        CommBRegister::SelectedVerticalIntention {
+            mcp_altitude,
+            fms_altitude,
+            baro_setting,
+        },
+        score,
+    ))
+}
+
+fn decode_bds44(mb: &[u8]) -> Option<(CommBRegister, f64)> {
+    if field(mb, 1, 4) > MAX_FIGURE_OF_MERIT {
+        return None;
+    }
+    let wind = status_field(mb, 5, 23).ok()?;
+    let pressure = status_field(mb, 35, 46).ok()?;
+    let turbulence = status_field(mb, 47, 49).ok()?;
+    let humidity = status_field(mb, 50, 56).ok()?;
+
+    let wind_speed = wind.map(|raw| f64::from(raw >> 9));
+    let wind_direction = wind.map(|raw| f64::from(raw & 0x1FF) * 180.0 / 256.0);
+    let static_air_temperature = signed(field(mb, 24, 34), 10) * 0.25;
+
+    if wind_speed.is_some_and(|speed| speed > MAX_WIND_SPEED)
+        || !PLAUSIBLE_TEMPERATURE.contains(&static_air_temperature)
+    {
+        return None;
+    }
+
+    let score = completeness(
+        [
+            wind.is_some(),
+            true,
+            pressure.is_some(),
+            turbulence.is_some(),
+            humidity.is_some(),
+        ]
+        .iter()
+        .filter(|p| **p)
+        .count(),
+        5,
+    );
+
+    Some((
+        CommBRegister::MeteorologicalRoutine {
+            wind_speed,
+            wind_direction,
+            static_air_temperature,
+            static_pressure: pressure.map(f64::from),
+            turbulence: turbulence.map(HazardLevel::from_bits),
+            humidity: humidity.map(|raw| f64::from(raw) * 100.0 / 64.0),
+        },
+        score,
+    ))
+}
+
+fn decode_bds45(mb: &[u8]) -> Option<(CommBRegister, f64)> {
+    let hazard = |status: usize| {
+        status_field(mb, status, status + 2).map(|raw| raw.map(HazardLevel::from_bits))
+    };
+    let turbulence = hazard(1).ok()?;
+    let wind_shear = hazard(4).ok()?;
+    let microburst = hazard(7).ok()?;
+    let icing = hazard(10).ok()?;
+    let wake_vortex = hazard(13).ok()?;
+    let temperature = status_field(mb, 16, 26).ok()?;
+    let pressure = status_field(mb, 27, 38).ok()?;
+    let radio_height = status_field(mb, 39, 51).ok()?;
+    if field(mb, 52, 56) != 0 {
+        return None;
+    }
+
+    let static_air_temperature = temperature.map(|raw| signed(raw, 9) * 0.25);
+    if static_air_temperature.is_some_and(|sat| !PLAUSIBLE_TEMPERATURE.contains(&sat)) {
+        return None;
+    }
+
+    let score = completeness(
+        [
+            turbulence.is_some(),
+            wind_shear.is_some(),
+            microburst.is_some(),
+            icing.is_some(),
+            wake_vortex.is_some(),
+            temperature.is_some(),
+            pressure.is_some(),
+            radio_height.is_some(),
+        ]
+        .iter()
+        .filter(|p| **p)
+        .count(),
+        8,
+    );
+
+    Some((
+        CommBRegister::MeteorologicalHazard {
+            turbulence,
+            wind_shear,
+            microburst,
+            icing,
+            wake_vortex,
+            static_air_temperature,
+            static_pressure: pressure.map(f64::from),
+            radio_height: radio_height
+                .and_then(|raw| i32::try_from(raw).ok())
+                .map(|raw| raw * 16),
+        },
+        score,
+    ))
+}
+
+fn decode_bds50(mb: &[u8]) -> Option<(CommBRegister, f64)> {
+    let roll = status_field(mb, 1, 11).ok()?;
+    let track = status_field(mb, 12, 23).ok()?;
+    let ground_speed = status_field(mb, 24, 34).ok()?;
+    let track_rate = status_field(mb, 35, 45).ok()?;
+    let tas = status_field(mb, 46, 56).ok()?;
+
+    let roll = roll.map(|raw| signed(raw, 9) * 45.0 / 256.0);
+    let ground_speed = ground_speed.map(|raw| f64::from(raw) * 2.0);
+    let true_airspeed = tas.map(|raw| f64::from(raw) * 2.0);
+    let track_rate = track_rate.map(|raw| signed(raw, 9) * 8.0 / 256.0);
+
+    if roll.is_some_and(|roll| roll.abs() > MAX_ROLL)
+        || ground_speed.is_some_and(|gs| gs > MAX_SPEED)
+        || true_airspeed.is_some_and(|tas| tas > MAX_SPEED)
+    {
+        return None;
+    }
+
+    let mut score = completeness(
+        [
+            roll.is_some(),
+            track.is_some(),
+            ground_speed.is_some(),
+            track_rate.is_some(),
+            true_airspeed.is_some(),
+        ]
+        .iter()
+        .filter(|p| **p)
+        .count(),
+        5,
+    );
+    if let (Some(gs), Some(tas)) = (ground_speed, true_airspeed) {
+        let wind = (gs - tas).abs();
+        if wind > MAX_WIND {
+            return None;
+        }
+        if wind > MAX_WIND / 2.0 {
+            score *= 0.7;
+        }
+    }
+
+    Some((
+        CommBRegister::TrackAndTurn {
+            roll,
+            track_rate,
+            true_airspeed,
+        },
+        score,
+    ))
+}
+
+fn decode_bds60(mb: &[u8], altitude: Option<i32>) -> Option<(CommBRegister, f64)> {
+    let heading = status_field(mb, 1, 12).ok()?;
+    let ias = status_field(mb, 13, 23).ok()?;
+    let mach = status_field(mb, 24, 34).ok()?;
+    let baro_rate = status_field(mb, 35, 45).ok()?;
+    let inertial_rate = status_field(mb, 46, 56).ok()?;
+
+    let magnetic_heading = heading.map(|raw| (signed(raw, 10) * 90.0 / 512.0).rem_euclid(360.0));
+    let indicated_airspeed = ias.map(f64::from);
+    let mach = mach.map(|raw| f64::from(raw) * 2.048 / 512.0);
+    let baro_vertical_rate = baro_rate.map(vertical_rate);
+    let inertial_vertical_rate = inertial_rate.map(vertical_rate);
+
+    if indicated_airspeed.is_some_and(|ias| ias > MAX_IAS)
+        || mach.is_some_and(|mach| mach > 1.0)
+        || [baro_vertical_rate, inertial_vertical_rate]
+            .iter()
+            .flatten()
+            .any(|rate| rate.abs() > MAX_VERTICAL_RATE)
+    {
+        return None;
+    }
+
+    let mut score = completeness(
+        [
+            heading.is_some(),
+            ias.is_some(),
+            mach.is_some(),
+            baro_rate.is_some(),
+            inertial_rate.is_some(),
+        ]
+        .iter()
+        .filter(|p| **p)
+        .count(),
+        5,
+    );
+    // IAS and Mach must describe the same airspeed at the reported altitude
+    if let (Some(ias), Some(mach), Some(altitude)) = (indicated_airspeed, mach, altitude) {
+        if (mach_from_cas(ias, altitude) - mach).abs() > 0.1 {
+            score *= 0.5;
+        }
+    }
+    if let (Some(baro), Some(inertial)) = (baro_vertical_rate, inertial_vertical_rate) {
+        if (baro - inertial).abs() > 1_000 {
+            score *= 0.7;
+        }
+    }
+
+    Some((
+        CommBRegister::HeadingAndSpeed {
+            magnetic_heading,
+            indicated_airspeed,
+            mach,
+            baro_vertical_rate,
+            inertial_vertical_rate,
+        },
+        score,
+    ))
+}
+
+/// Convert calibrated airspeed (knots) to Mach at a pressure altitude (feet)
+/// in the ISA standard atmosphere.
+fn mach_from_cas(cas: f64, altitude: i32) -> f64 {
+    let height = f64::from(altitude) * 0.3048;
+    let pressure = if height <= 11_000.0 {
+        SEA_LEVEL_PRESSURE * (1.0 - 0.0065 * height / 288.15).powf(5.2559)
+    } else {
+        22_632.0 * (-9.806_65 * (height - 11_000.0) / (287.053 * 216.65)).exp()
+    };
+
+    let impact = SEA_LEVEL_PRESSURE
+        * ((1.0 + 0.2 * (cas / SEA_LEVEL_SPEED_OF_SOUND).powi(2)).powf(3.5) - 1.0);
+    (5.0 * ((impact / pressure + 1.0).powf(2.0 / 7.0) - 1.0)).sqrt()
+}
+
+/// Infer and decode the register held in a Comm-B MB field.
+///
+/// `altitude` is the altitude reported in the same reply (DF20), used to
+/// cross-check airspeeds. Returns the most likely register and its
+/// confidence, or `None` if no supported register fits.
+#[must_use]
+#[allow(clippy::cast_possible_truncation, reason = "confidence is in 0..=1")]
+pub fn infer(mb: &[u8], altitude: Option<i32>) -> Option<Inferred<CommBRegister>> {
+    // An empty field fits every register, and BDS 2,0 (identification)
+    // starts with its own register number
+    if mb.iter().all(|&b| b == 0) || mb.first() == Some(&0x20) {
+        return None;
+    }
+
+    let candidates: Vec<(CommBRegister, f64)> = [
+        decode_bds40(mb),
+        decode_bds44(mb),
+        decode_bds45(mb),
+        decode_bds50(mb),
+        decode_bds60(mb, altitude),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let total: f64 = candidates.iter().map(|(_, score)| score).sum();
+    let (register, score) = candidates.into_iter().max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    Some(Inferred {
+        value: register,
+        confidence: (score * score / total) as f32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mode_s::hex_to_bytes;
+
+    fn mb(hex: &str) -> Vec<u8> {
+        hex_to_bytes(hex)[4..11].to_vec()
+    }
+
+    #[test]
+    fn test_infer_bds40() {
+        let inferred = infer(&mb("A000029C85E42F313000007047D3"), None).unwrap();
+        assert_eq!(
+            inferred.value,
+            CommBRegister::SelectedVerticalIntention {
+                mcp_altitude: Some(3008),
+                fms_altitude: Some(3008),
+                baro_setting: Some(1020.0),
+            }
+        );
+        assert!(inferred.confidence > 0.99);
+    }
+
+    #[test]
+    fn test_infer_bds44() {
+        let inferred = infer(&mb("A0001692185BD5CF400000DFC696"), None).unwrap();
+        match inferred.value {
+            CommBRegister::MeteorologicalRoutine {
+                wind_speed,
+                wind_direction,
+                static_air_temperature,
+                static_pressure,
+                ..
+            } => {
+                assert_eq!(wind_speed, Some(22.0));
+                assert!((wind_direction.unwrap() - 344.531_25).abs() < 1e-9);
+                assert!((static_air_temperature + 48.75).abs() < 1e-9);
+                assert_eq!(static_pressure, None);
+            }
+            other => panic!("Expected BDS 4,4, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_infer_bds45() {
+        let inferred = infer(&hex_to_bytes("A061E200000000"), None).unwrap();
+        assert_eq!(
+            inferred.value,
+            CommBRegister::MeteorologicalHazard {
+                turbulence: Some(HazardLevel::Light),
+                wind_shear: None,
+                microburst: None,
+                icing: Some(HazardLevel::Moderate),
+                wake_vortex: None,
+                static_air_temperature: Some(-30.0),
+                static_pressure: None,
+                radio_height: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_infer_bds50() {
+        let inferred = infer(&mb("A000139381951536E024D4CCF6B5"), None).unwrap();
+        match inferred.value {
+            CommBRegister::TrackAndTurn {
+                roll,
+                track_rate,
+                true_airspeed,
+            } => {
+                assert!((roll.unwrap() - 2.109_375).abs() < 1e-9);
+                assert!((track_rate.unwrap() - 0.125).abs() < 1e-9);
+                assert_eq!(true_airspeed, Some(424.0));
+            }
+            other => panic!("Expected BDS 5,0, got {other:?}"),
+        }
+        assert!(inferred.confidence > 0.99);
+    }
+
+    #[test]
+    fn test_infer_bds60() {
+        let inferred = infer(&mb("A00004128F39F91A7E27C46ADC21"), Some(10_000)).unwrap();
+        match inferred.value {
+            CommBRegister::HeadingAndSpeed {
+                magnetic_heading,
+                indicated_airspeed,
+                mach,
+                baro_vertical_rate,
+                inertial_vertical_rate,
+            } => {
+                assert!((magnetic_heading.unwrap() - 42.714_843_75).abs() < 1e-9);
+                assert_eq!(indicated_airspeed, Some(252.0));
+                assert!((mach.unwrap() - 0.42).abs() < 1e-9);
+                assert_eq!(baro_vertical_rate, Some(-1920));
+                assert_eq!(inertial_vertical_rate, Some(-1920));
+            }
+            other => panic!("Expected BDS 6,0, got {other:?}"),
+        }
+        assert!(inferred.confidence > 0.99);
+    }
+
+    #[test]
+    fn test_inconsistent_airspeed_lowers_confidence() {
+        // 252 kt IAS is well above Mach 0.42 at FL400
+        let inferred = infer(&mb("A00004128F39F91A7E27C46ADC21"), Some(40_000)).unwrap();
+        assert_eq!(inferred.value.bds(), "6,0");
+        assert!(inferred.confidence < 0.6);
+    }
+
+    #[test]
+    fn test_ambiguous_field_has_low_confidence() {
+        // Only the first status bit and a tiny value: fits all three registers
+        let inferred = infer(&[0x80, 0x40, 0, 0, 0, 0, 0], None).unwrap();
+        assert!(inferred.confidence < 0.3);
+    }
+
+    #[test]
+    fn test_no_register_fits() {
+        assert!(infer(&[0; 7], None).is_none());
+        // BDS 2,0 identification
+        assert!(infer(&mb("A000083E202CC371C31DE0AA1CCF"), None).is_none());
+        // First status bit clear with data behind it
+        assert!(infer(&[0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF], None).is_none());
+    }
+
+    #[test]
+    fn test_mach_from_cas() {
+        assert!((mach_from_cas(250.0, 0) - 0.378).abs() < 0.005);
+        assert!((mach_from_cas(280.0, 35_000) - 0.82).abs() < 0.01);
+    }
+}
@@ -0,0 +1,313 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compact Position Reporting (CPR) decoding.
+//!
+//! ADS-B encodes positions as 17-bit fractions of a latitude/longitude zone.
+//! A position can be recovered either globally, from an even/odd frame pair,
+//! or locally, from a single frame and a reference position within half a
+//! zone of the aircraft (180 NM airborne, 45 NM on the surface).
+
+use std::f64::consts::PI;
+
+/// Number of latitude zones between the equator and a pole.
+const NZ: f64 = 15.0;
+/// Scale of the 17-bit encoded latitude/longitude.
+const CPR_SCALE: f64 = 131_072.0;
+
+/// A single encoded CPR position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CprPosition {
+    /// Encoded latitude (17 bits).
+    pub lat: u32,
+    /// Encoded longitude (17 bits).
+    pub lon: u32,
+    /// Whether this is an odd (`F = 1`) frame.
+    pub odd: bool,
+}
+
+impl CprPosition {
+    fn lat_fraction(self) -> f64 {
+        f64::from(self.lat) / CPR_SCALE
+    }
+
+    fn lon_fraction(self) -> f64 {
+        f64::from(self.lon) / CPR_SCALE
+    }
+}
+
+/// Number of longitude zones at the given latitude.
+#[must_use]
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    reason = "NL is always in 1..=59"
+)]
+pub fn nl(lat: f64) -> u32 {
+    let lat = lat.abs();
+    if lat == 0.0 {
+        return 59;
+    }
+    if (lat - 87.0).abs() < f64::EPSILON {
+        return 2;
+    }
+    if lat > 87.0 {
+        return 1;
+    }
+
+    let a = 1.0 - (PI / (2.0 * NZ)).cos();
+    let b = (PI / 180.0 * lat).cos().powi(2);
+    (2.0 * PI / (1.0 - a / b).acos()).floor() as u32
+}
+
+/// Decode latitude candidates and the longitude for a global even/odd pair.
+///
+/// `span` is 360 for airborne and 90 for surface positions.
+fn global_decode(
+    even: CprPosition,
+    odd: CprPosition,
+    latest_odd: bool,
+    span: f64,
+    southern: bool,
+) -> Option<(f64, f64)> {
+    let dlat_even = span / 60.0;
+    let dlat_odd = span / 59.0;
+
+    // Latitude zone index shared by both frames
+    let j = (59.0 * even.lat_fraction() - 60.0 * odd.lat_fraction() + 0.5).floor();
+    let mut lat_even = dlat_even * (j.rem_euclid(60.0) + even.lat_fraction());
+    let mut lat_odd = dlat_odd * (j.rem_euclid(59.0) + odd.lat_fraction());
+
+    if span >= 360.0 {
+        if lat_even >= 270.0 {
+            lat_even -= 360.0;
+        }
+        if lat_odd >= 270.0 {
+            lat_odd -= 360.0;
+        }
+    } else if southern {
+        lat_even -= 90.0;
+        lat_odd -= 90.0;
+    }
+
+    if !(-90.0..=90.0).contains(&lat_even) || !(-90.0..=90.0).contains(&lat_odd) {
+        return None;
+    }
+    // Both frames must lie in the same longitude zone count
+    if nl(lat_even) != nl(lat_odd) {
+        return None;
+    }
+
+    let (lat, lon_fraction, zones) = if latest_odd {
+        (
+            lat_odd,
+            odd.lon_fraction(),
+            nl(lat_odd).saturating_sub(1).max(1),
+        )
+    } else {
+        (lat_even, even.lon_fraction(), nl(lat_even).max(1))
+    };
+
+    let nl_lat = f64::from(nl(lat));
+    let m = (even.lon_fraction() * (nl_lat - 1.0) - odd.lon_fraction() * nl_lat + 0.5).floor();
+    let zones = f64::from(zones);
+    let lon = (span / zones) * (m.rem_euclid(zones) + lon_fraction);
+
+    Some((lat, lon))
+}
+
+/// Wrap a longitude into the range `[-180, 180)`.
+fn normalize_longitude(lon: f64) -> f64 {
+    (lon + 180.0).rem_euclid(360.0) - 180.0
+}
+
+/// Globally decode an airborne position from an even/odd frame pair.
+///
+/// `latest_odd` selects which frame was received most recently; the
+/// returned position corresponds to that frame.
+#[must_use]
+pub fn decode_airborne_global(
+    even: CprPosition,
+    odd: CprPosition,
+    latest_odd: bool,
+) -> Option<(f64, f64)> {
+    let (lat, lon) = global_decode(even, odd, latest_odd, 360.0, false)?;
+    Some((lat, normalize_longitude(lon)))
+}
+
+/// Globally decode a surface position from an even/odd frame pair.
+///
+/// Surface encoding only spans 90 degrees, so a reference position (usually
+/// the receiver) is required to pick the correct quadrant.
+#[must_use]
+pub fn decode_surface_global(
+    even: CprPosition,
+    odd: CprPosition,
+    latest_odd: bool,
+    ref_lat: f64,
+    ref_lon: f64,
+) -> Option<(f64, f64)> {
+    let (lat, lon) = global_decode(even, odd, latest_odd, 90.0, ref_lat < 0.0)?;
+
+    let lon = (0..4)
+        .map(|quadrant| normalize_longitude(lon + 90.0 * f64::from(quadrant)))
+        .min_by(|a, b| longitude_delta(*a, ref_lon).total_cmp(&longitude_delta(*b, ref_lon)))?;
+
+    Some((lat, lon))
+}
+
+/// Absolute difference between two longitudes, accounting for wrap-around.
+fn longitude_delta(a: f64, b: f64) -> f64 {
+    let delta = (a - b).rem_euclid(360.0);
+    delta.min(360.0 - delta)
+}
+
+fn local_decode(pos: CprPosition, ref_lat: f64, ref_lon: f64, span: f64) -> (f64, f64) {
+    let lat_fraction = pos.lat_fraction();
+    let dlat = if pos.odd { span / 59.0 } else { span / 60.0 };
+    let j =
+        (ref_lat / dlat).floor() + (0.5 + ref_lat.rem_euclid(dlat) / dlat - lat_fraction).floor();
+    let lat = dlat * (j + lat_fraction);
+
+    let zones = nl(lat).saturating_sub(u32::from(pos.odd));
+    let dlon = if zones > 0 {
+        span / f64::from(zones)
+    } else {
+        span
+    };
+    let lon_fraction = pos.lon_fraction();
+    let m =
+        (ref_lon / dlon).floor() + (0.5 + ref_lon.rem_euclid(dlon) / dlon - lon_fraction).floor();
+    let lon = dlon * (m + lon_fraction);
+
+    (lat, normalize_longitude(lon))
+}
+
+/// Decode an airborne position from a single frame and a nearby reference.
+///
+/// The reference must be within 180 NM of the aircraft for the result to be
+/// unambiguous.
+#[must_use]
+pub fn decode_airborne_local(pos: CprPosition, ref_lat: f64, ref_lon: f64) -> (f64, f64) {
+    local_decode(pos, ref_lat, ref_lon, 360.0)
+}
+
+/// Decode a surface position from a single frame and a nearby reference.
+///
+/// The reference must be within 45 NM of the aircraft for the result to be
+/// unambiguous.
+#[must_use]
+pub fn decode_surface_local(pos: CprPosition, ref_lat: f64, ref_lon: f64) -> (f64, f64) {
+    local_decode(pos, ref_lat, ref_lon, 90.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_position(actual: (f64, f64), expected: (f64, f64)) {
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-4 && (actual.1 - expected.1).abs() < 1e-4,
+            "expected {expected:?}, got {actual:?}"
+        );
+    }
+
+    #[test]
+    fn test_nl() {
+        assert_eq!(nl(0.0), 59);
+        assert_eq!(nl(52.2572), 36);
+        assert_eq!(nl(-52.2572), 36);
+        assert_eq!(nl(87.0), 2);
+        assert_eq!(nl(89.0), 1);
+    }
+
+    #[test]
+    fn test_airborne_global() {
+        // 8D40621D58C382D690C8AC2863A7 / 8D40621D58C386435CC412692AD6
+        let even = CprPosition {
+            lat: 93_000,
+            lon: 51_372,
+            odd: false,
+        };
+        let odd = CprPosition {
+            lat: 74_158,
+            lon: 50_194,
+            odd: true,
+        };
+
+        let position = decode_airborne_global(even, odd, false).unwrap();
+        assert_position(position, (52.257_20, 3.919_37));
+    }
+
+    #[test]
+    fn test_airborne_local() {
+        let even = CprPosition {
+            lat: 93_000,
+            lon: 51_372,
+            odd: false,
+        };
+
+        let position = decode_airborne_local(even, 52.258, 3.918);
+        assert_position(position, (52.257_20, 3.919_37));
+    }
+
+    #[test]
+    fn test_airborne_global_latest_odd() {
+        // 8D40058B58C901375147EFD09357 / 8D40058B58C904A87F402D3B8C59
+        let even = CprPosition {
+            lat: 39_848,
+            lon: 83_951,
+            odd: false,
+        };
+        let odd = CprPosition {
+            lat: 21_567,
+            lon: 81_965,
+            odd: true,
+        };
+
+        let position = decode_airborne_global(even, odd, true).unwrap();
+        assert_position(position, (49.817_55, 6.084_42));
+    }
+
+    #[test]
+    fn test_surface_global_southern_hemisphere() {
+        // 8CC8200A3AC8F009BCDEF2000000 / 8FC8200A3AB8F5F893096B000000
+        let even = CprPosition {
+            lat: 1_246,
+            lon: 57_074,
+            odd: false,
+        };
+        let odd = CprPosition {
+            lat: 64_585,
+            lon: 67_947,
+            odd: true,
+        };
+
+        let position = decode_surface_global(even, odd, true, -43.496, 172.558).unwrap();
+        assert_position(position, (-43.485_64, 172.539_42));
+    }
+
+    #[test]
+    fn test_surface_local() {
+        // 8FC8200A3AB8F5F893096B000000
+        let odd = CprPosition {
+            lat: 64_585,
+            lon: 67_947,
+            odd: true,
+        };
+
+        let position = decode_surface_local(odd, -43.5, 172.5);
+        assert_position(position, (-43.485_64, 172.539_42));
+    }
+}
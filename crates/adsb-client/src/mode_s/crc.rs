@@ -0,0 +1,179 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Mode S parity (CRC-24) computation and error correction.
+//!
+//! Every Mode S frame ends with 24 parity bits. For DF11/17/18 these are the
+//! plain CRC of the preceding bits, so a valid frame has a residual of zero
+//! (DF11 may overlay a 7-bit interrogator code). For the other formats the
+//! parity is XOR-ed with the aircraft address, so the residual *is* the
+//! address.
+//!
+//! Because the CRC is linear, the residual of a corrupted frame equals the
+//! residual of its error pattern. Precomputed syndrome tables map residuals
+//! back to one or two flipped bit positions.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use super::{LONG_FRAME_LEN, SHORT_FRAME_LEN};
+
+/// CRC-24 generator polynomial (including the implicit x^24 term).
+const GENERATOR: u32 = 0x01FF_F409;
+/// Number of leading DF bits never touched by error correction.
+const DF_BITS: usize = 5;
+
+/// Compute the 24-bit CRC of `data`.
+#[must_use]
+pub fn checksum(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0;
+    for &byte in data {
+        crc ^= u32::from(byte) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x0100_0000 != 0 {
+                crc ^= GENERATOR;
+            }
+        }
+    }
+    crc & 0x00FF_FFFF
+}
+
+/// Residual of a full frame: the CRC of the message bits XOR the parity field.
+///
+/// Zero for an intact DF17/18 frame; the aircraft address for address/parity
+/// formats such as DF4/5/20/21.
+#[must_use]
+pub fn residual(frame: &[u8]) -> u32 {
+    let split = frame.len().saturating_sub(3);
+    let parity = frame[split..]
+        .iter()
+        .fold(0u32, |acc, &b| (acc << 8) | u32::from(b));
+    checksum(&frame[..split]) ^ parity
+}
+
+/// Flipped bit positions that produce a given syndrome.
+type Syndromes = HashMap<u32, (usize, Option<usize>)>;
+
+fn single_bit_error(len: usize, bit: usize) -> Vec<u8> {
+    let mut frame = vec![0u8; len];
+    frame[bit / 8] |= 0x80 >> (bit % 8);
+    frame
+}
+
+fn build_syndromes(len: usize, two_bit: bool) -> Syndromes {
+    let bit_count = len * 8;
+    let single: Vec<u32> = (0..bit_count)
+        .map(|bit| residual(&single_bit_error(len, bit)))
+        .collect();
+
+    let mut table = HashMap::new();
+    for (bit, &residual) in single.iter().enumerate().skip(DF_BITS) {
+        table.insert(residual, (bit, None));
+    }
+    if two_bit {
+        for first in DF_BITS..bit_count {
+            for second in first + 1..bit_count {
+                table
+                    .entry(single[first] ^ single[second])
+                    .or_insert((first, Some(second)));
+            }
+        }
+    }
+    table
+}
+
+fn syndromes(len: usize) -> &'static Syndromes {
+    static SHORT: OnceLock<Syndromes> = OnceLock::new();
+    static LONG: OnceLock<Syndromes> = OnceLock::new();
+
+    if len == SHORT_FRAME_LEN {
+        SHORT.get_or_init(|| build_syndromes(SHORT_FRAME_LEN, false))
+    } else {
+        LONG.get_or_init(|| build_syndromes(LONG_FRAME_LEN, true))
+    }
+}
+
+fn flip_bit(frame: &mut [u8], bit: usize) {
+    frame[bit / 8] ^= 0x80 >> (bit % 8);
+}
+
+/// Try to repair a frame whose residual should be zero.
+///
+/// Corrects up to `max_errors` flipped bits (two-bit correction is only
+/// available for long frames) and returns the number of bits repaired, or
+/// `None` if the syndrome is not correctable.
+pub fn correct(frame: &mut [u8], syndrome: u32, max_errors: u8) -> Option<u8> {
+    if syndrome == 0 || max_errors == 0 {
+        return None;
+    }
+
+    let &(first, second) = syndromes(frame.len()).get(&syndrome)?;
+    let errors = if second.is_some() { 2 } else { 1 };
+    if errors > max_errors {
+        return None;
+    }
+
+    flip_bit(frame, first);
+    if let Some(second) = second {
+        flip_bit(frame, second);
+    }
+    Some(errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mode_s::hex_to_bytes;
+
+    #[test]
+    fn test_residual_of_valid_frames() {
+        assert_eq!(residual(&hex_to_bytes("8D406B902015A678D4D220AA4BDA")), 0);
+        assert_eq!(residual(&hex_to_bytes("8D4840D6202CC371C32CE0576098")), 0);
+    }
+
+    #[test]
+    fn test_correct_single_bit() {
+        let original = hex_to_bytes("8D4840D6202CC371C32CE0576098");
+        let mut frame = original.clone();
+        flip_bit(&mut frame, 60);
+
+        let syndrome = residual(&frame);
+        assert_ne!(syndrome, 0);
+        assert_eq!(correct(&mut frame, syndrome, 1), Some(1));
+        assert_eq!(frame, original);
+    }
+
+    #[test]
+    fn test_correct_two_bits() {
+        let original = hex_to_bytes("8D4840D6202CC371C32CE0576098");
+        let mut frame = original.clone();
+        flip_bit(&mut frame, 40);
+        flip_bit(&mut frame, 97);
+
+        let syndrome = residual(&frame);
+        assert_eq!(correct(&mut frame.clone(), syndrome, 1), None);
+        assert_eq!(correct(&mut frame, syndrome, 2), Some(2));
+        assert_eq!(frame, original);
+    }
+
+    #[test]
+    fn test_df_bits_are_not_corrected() {
+        let mut frame = hex_to_bytes("8D4840D6202CC371C32CE0576098");
+        flip_bit(&mut frame, 2);
+
+        let syndrome = residual(&frame);
+        assert_eq!(correct(&mut frame, syndrome, 2), None);
+    }
+}
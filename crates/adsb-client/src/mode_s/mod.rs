@@ -0,0 +1,1240 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Mode S frame decoding.
+//!
+//! Decodes raw 56-bit (short) and 112-bit (long) Mode S replies into
+//! [`AircraftMessage`]s. This is shared by the binary and hex protocol
+//! parsers, which only differ in how frames are delimited on the wire.
+//!
+//! Extended squitter (DF17/18) identification, airborne and surface position,
+//! velocity, aircraft status, target state and operational status messages
+//! are supported. Positions are recovered with CPR, see
+//! [`cpr`]. Surveillance replies (DF0/4/5/16/20/21) provide altitude and
+//! squawk for aircraft whose address is already known, and Comm-B replies
+//! (DF20/21) may carry an Enhanced Surveillance register, see [`comm_b`].
+//!
+//! Every frame's parity is checked before decoding, see [`crc`].
+//!
+//! Bit offsets in this module are zero-based from the most significant bit
+//! of the frame (or ME field), so "ME bit 1" in the specification is bit `0`.
+
+pub mod comm_b;
+pub mod cpr;
+pub mod crc;
+mod status;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::protocol::{AircraftMessage, ParseError};
+use crate::tracker::haversine_distance_nm;
+use cpr::CprPosition;
+
+/// Length of a short (56-bit) Mode S frame in bytes.
+pub const SHORT_FRAME_LEN: usize = 7;
+/// Length of a long (112-bit) Mode S frame in bytes.
+pub const LONG_FRAME_LEN: usize = 14;
+
+const FEET_PER_METER: f64 = 3.280_84;
+
+/// 6-bit character set used for aircraft identification.
+const CALLSIGN_CHARSET: &[u8; 64] =
+    b"#ABCDEFGHIJKLMNOPQRSTUVWXYZ##### ###############0123456789######";
+
+/// Extract `len` bits (at most 32) starting at bit `start`.
+pub(crate) fn bits(data: &[u8], start: usize, len: usize) -> u32 {
+    debug_assert!(len <= 32);
+    (start..start + len).fold(0, |value, i| {
+        let bit = (data[i / 8] >> (7 - i % 8)) & 1;
+        (value << 1) | u32::from(bit)
+    })
+}
+
+/// Downlink format of a frame (DF24 and above are reported as 24).
+#[must_use]
+pub fn downlink_format(frame: &[u8]) -> u8 {
+    match frame.first() {
+        Some(&byte) if byte >= 0xC0 => 24,
+        Some(&byte) => byte >> 3,
+        None => 0,
+    }
+}
+
+/// Format a 24-bit address as the six-digit hex string used throughout the crate.
+#[must_use]
+pub fn format_icao(address: u32) -> String {
+    format!("{address:06X}")
+}
+
+/// Reorder a 13-bit identity field into `0xABCD` Gillham (squawk digit) order.
+fn gillham_from_id13(id13: u32) -> u32 {
+    const MAPPING: [(u32, u32); 12] = [
+        (0x1000, 0x0010), // C1
+        (0x0800, 0x1000), // A1
+        (0x0400, 0x0020), // C2
+        (0x0200, 0x2000), // A2
+        (0x0100, 0x0040), // C4
+        (0x0080, 0x4000), // A4
+        (0x0020, 0x0100), // B1
+        (0x0010, 0x0001), // D1
+        (0x0008, 0x0200), // B2
+        (0x0004, 0x0002), // D2
+        (0x0002, 0x0400), // B4
+        (0x0001, 0x0004), // D4
+    ];
+
+    MAPPING
+        .iter()
+        .filter(|(from, _)| id13 & from != 0)
+        .fold(0, |acc, (_, to)| acc | to)
+}
+
+/// Decode a 13-bit identity field into a four-digit squawk code.
+pub(crate) fn decode_squawk(id13: u32) -> String {
+    format!("{:04X}", gillham_from_id13(id13))
+}
+
+/// Convert a Gillham-coded Mode C reply to altitude in hundreds of feet.
+fn mode_c_from_gillham(code: u32) -> Option<i32> {
+    // D1 is never used for altitude and C1..C4 cannot all be zero
+    if code & 0xFFFF_8889 != 0 || code & 0x0000_00F0 == 0 {
+        return None;
+    }
+
+    let mut one_hundreds: i32 = 0;
+    for (bit, value) in [(0x0010, 7), (0x0020, 3), (0x0040, 1)] {
+        if code & bit != 0 {
+            one_hundreds ^= value;
+        }
+    }
+    // Gray code 7 and 5 are swapped
+    if one_hundreds & 5 == 5 {
+        one_hundreds ^= 2;
+    }
+    if one_hundreds > 5 {
+        return None;
+    }
+
+    let mut five_hundreds: i32 = 0;
+    for (bit, value) in [
+        (0x0002, 0xFF),
+        (0x0004, 0x7F),
+        (0x1000, 0x3F),
+        (0x2000, 0x1F),
+        (0x4000, 0x0F),
+        (0x0100, 0x07),
+        (0x0200, 0x03),
+        (0x0400, 0x01),
+    ] {
+        if code & bit != 0 {
+            five_hundreds ^= value;
+        }
+    }
+    if five_hundreds & 1 != 0 {
+        one_hundreds = 6 - one_hundreds;
+    }
+
+    Some(five_hundreds * 5 + one_hundreds - 13)
+}
+
+/// Decode a Gillham-coded 13-bit altitude field (M bit clear) in feet.
+fn gillham_altitude(id13: u32) -> Option<i32> {
+    mode_c_from_gillham(gillham_from_id13(id13))
+        .filter(|&hundreds| hundreds >= -12)
+        .map(|hundreds| hundreds * 100)
+}
+
+/// Decode the 13-bit AC field of surveillance and Comm-B altitude replies.
+pub(crate) fn decode_ac13(ac13: u32) -> Option<i32> {
+    let m_bit = ac13 & 0x0040 != 0;
+    let q_bit = ac13 & 0x0010 != 0;
+
+    if ac13 == 0 || m_bit {
+        // Not available, or metric altitude which is not used in practice
+        return None;
+    }
+    if q_bit {
+        let n = ((ac13 & 0x1F80) >> 2) | ((ac13 & 0x0020) >> 1) | (ac13 & 0x000F);
+        return i32::try_from(n).ok().map(|n| n * 25 - 1000);
+    }
+    gillham_altitude(ac13)
+}
+
+/// Decode the 12-bit altitude field of an airborne position message.
+pub(crate) fn decode_ac12(ac12: u32) -> Option<i32> {
+    if ac12 == 0 {
+        return None;
+    }
+    if ac12 & 0x0010 != 0 {
+        let n = ((ac12 & 0x0FE0) >> 1) | (ac12 & 0x000F);
+        return i32::try_from(n).ok().map(|n| n * 25 - 1000);
+    }
+    // Insert M = 0 at bit 6 to form a 13-bit Gillham code
+    gillham_altitude(((ac12 & 0x0FC0) << 1) | (ac12 & 0x003F))
+}
+
+#[allow(
+    clippy::cast_possible_truncation,
+    reason = "GNSS height is a 12-bit field, well within i32 range"
+)]
+fn feet_from_meters(meters: u32) -> i32 {
+    (f64::from(meters) * FEET_PER_METER).round() as i32
+}
+
+/// Maximum time between even and odd airborne frames for global decoding.
+const AIRBORNE_PAIR_MAX_AGE: Duration = Duration::from_secs(10);
+/// Maximum time between even and odd surface frames for global decoding.
+const SURFACE_PAIR_MAX_AGE: Duration = Duration::from_secs(25);
+/// How long a decoded position remains usable as a local CPR reference.
+const LOCAL_REFERENCE_MAX_AGE: Duration = Duration::from_mins(10);
+/// How long a position decoded against the receiver waits for confirmation.
+const CANDIDATE_MAX_AGE: Duration = Duration::from_secs(10);
+/// Fastest an aircraft may move between a position decoded against the
+/// receiver and the one confirming it, in knots.
+const CANDIDATE_MAX_SPEED_KT: f64 = 1000.0;
+/// Distance always allowed between a position decoded against the receiver
+/// and the one confirming it, in nm.
+const CANDIDATE_SLACK_NM: f64 = 1.0;
+/// How often stale per-aircraft CPR state is pruned.
+const PRUNE_INTERVAL: Duration = Duration::from_mins(1);
+/// How long an address confirmed by DF11/17/18 is trusted for parity recovery.
+const KNOWN_ADDRESS_TTL: Duration = Duration::from_mins(1);
+/// Mask of the interrogator code DF11 may overlay on its parity.
+const DF11_IID_MASK: u32 = 0x7F;
+
+/// Error correction applied to frames that fail their parity check.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorCorrection {
+    /// Drop every frame with a bad parity.
+    None,
+    /// Repair single-bit errors in DF17/18 frames (default).
+    #[default]
+    SingleBit,
+    /// Repair single-bit errors in DF17/18 and two-bit errors in DF17.
+    TwoBit,
+}
+
+/// Frame counters kept by the decoder.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecoderStats {
+    /// Frames passed to the decoder.
+    pub frames: u64,
+    /// Frames repaired by error correction.
+    pub corrected: u64,
+    /// Frames dropped for a bad parity or an unknown address.
+    pub dropped: u64,
+}
+
+/// Received CPR frame awaiting its even/odd counterpart.
+#[derive(Debug, Clone, Copy)]
+struct CprFrame {
+    position: CprPosition,
+    received: DateTime<Utc>,
+}
+
+/// Per-aircraft CPR decoding state.
+#[derive(Debug, Clone, Copy)]
+struct CprState {
+    even: Option<CprFrame>,
+    odd: Option<CprFrame>,
+    surface: bool,
+    last_position: Option<(f64, f64, DateTime<Utc>)>,
+    /// Position decoded against the receiver, awaiting confirmation.
+    candidate: Option<(f64, f64, DateTime<Utc>)>,
+    last_update: DateTime<Utc>,
+}
+
+impl CprState {
+    fn new(surface: bool, now: DateTime<Utc>) -> Self {
+        Self {
+            even: None,
+            odd: None,
+            surface,
+            last_position: None,
+            candidate: None,
+            last_update: now,
+        }
+    }
+}
+
+/// Stateful Mode S decoder.
+///
+/// Turns raw Mode S frames into [`AircraftMessage`]s. Positions are decoded
+/// globally once an even/odd frame pair is available, and locally against
+/// the aircraft's last known position after that.
+///
+/// Before that, a frame may be decoded locally against the receiver
+/// reference when it lands within the receiver's range (see
+/// [`Decoder::set_receiver_range`]). An aircraft beyond the range CPR can
+/// tell apart would land there too, so that position is only held: the
+/// aircraft's next frame is decoded against it, and emitted once it lands
+/// as close as the aircraft could have flown.
+///
+/// Ages (CPR pairing, known addresses) are measured on the feed clock: the
+/// time each frame was received, as passed to [`Decoder::decode_at`].
+#[derive(Debug)]
+pub struct Decoder {
+    cpr: HashMap<u32, CprState>,
+    known_addresses: HashMap<u32, DateTime<Utc>>,
+    reference: Option<(f64, f64)>,
+    receiver_range_nm: Option<f64>,
+    error_correction: ErrorCorrection,
+    stats: DecoderStats,
+    /// Feed time of the frame being (or last) decoded.
+    now: DateTime<Utc>,
+    last_prune: DateTime<Utc>,
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Decoder {
+    /// Create a new decoder.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            cpr: HashMap::new(),
+            known_addresses: HashMap::new(),
+            reference: None,
+            receiver_range_nm: None,
+            error_correction: ErrorCorrection::default(),
+            stats: DecoderStats::default(),
+            now: Utc::now(),
+            // Prune on the first frame, whatever its feed time
+            last_prune: DateTime::<Utc>::MIN_UTC,
+        }
+    }
+
+    /// Set the error correction mode.
+    pub fn set_error_correction(&mut self, mode: ErrorCorrection) {
+        self.error_correction = mode;
+    }
+
+    /// Get the frame counters.
+    #[must_use]
+    pub fn stats(&self) -> DecoderStats {
+        self.stats
+    }
+
+    /// Whether `address` was recently confirmed by a DF11/17/18 frame, as of
+    /// the last frame decoded.
+    #[must_use]
+    pub fn is_known_address(&self, address: u32) -> bool {
+        self.known_addresses
+            .get(&address)
+            .is_some_and(|&seen| age(self.now, seen) <= KNOWN_ADDRESS_TTL)
+    }
+
+    /// Set the receiver position (lat, lon) used to resolve surface CPR
+    /// positions and aircraft not tracked yet, or clear it when the receiver
+    /// location is unknown.
+    pub fn set_reference(&mut self, reference: Option<(f64, f64)>) {
+        self.reference = reference;
+    }
+
+    /// Get the receiver reference position, if set.
+    #[must_use]
+    pub fn reference(&self) -> Option<(f64, f64)> {
+        self.reference
+    }
+
+    /// Set the receiver's range in nm, within which aircraft not tracked yet
+    /// may be decoded against the receiver reference, or clear it to wait
+    /// for a global decode.
+    pub fn set_receiver_range(&mut self, range_nm: Option<f64>) {
+        self.receiver_range_nm = range_nm;
+    }
+
+    /// Decode a single raw Mode S frame received now.
+    ///
+    /// Returns `Ok(None)` for frames that are valid but carry nothing the
+    /// tracker can use (or that this decoder does not yet understand).
+    pub fn decode(&mut self, frame: &[u8]) -> Result<Option<AircraftMessage>, ParseError> {
+        self.decode_at(frame, Utc::now())
+    }
+
+    /// Decode a single raw Mode S frame received at `now` on the feed clock.
+    ///
+    /// Recorded feeds pass the time each frame was recorded, so CPR frames
+    /// are only paired when they were received close together.
+    pub fn decode_at(
+        &mut self,
+        frame: &[u8],
+        now: DateTime<Utc>,
+    ) -> Result<Option<AircraftMessage>, ParseError> {
+        let df = downlink_format(frame);
+        let expected_len = if df >= 16 {
+            LONG_FRAME_LEN
+        } else {
+            SHORT_FRAME_LEN
+        };
+        if frame.len() != expected_len {
+            return Err(ParseError::InvalidFormat(format!(
+                "DF{df} frame must be {expected_len} bytes, got {}",
+                frame.len()
+            )));
+        }
+
+        self.now = now;
+        self.prune_stale();
+        self.stats.frames += 1;
+
+        let mut frame = frame.to_vec();
+        let Some(address) = self.check_parity(df, &mut frame) else {
+            self.stats.dropped += 1;
+            return Ok(None);
+        };
+
+        let message = match df {
+            0 | 16 => Some(surveillance_altitude(address, &frame, false)),
+            4 => Some(surveillance_altitude(address, &frame, true)),
+            5 => Some(surveillance_identity(address, &frame)),
+            20 | 21 => Some(decode_comm_b(address, &frame, df)),
+            17 => self.decode_extended_squitter(address, &frame[4..11]),
+            // DF18 with CF=0 is an ADS-B message from a non-transponder device
+            18 if bits(&frame, 5, 3) == 0 => self.decode_extended_squitter(address, &frame[4..11]),
+            _ => None,
+        };
+
+        Ok(message)
+    }
+
+    /// Validate (and possibly repair) a frame's parity.
+    ///
+    /// Returns the aircraft address on success.
+    fn check_parity(&mut self, df: u8, frame: &mut [u8]) -> Option<u32> {
+        let residual = crc::residual(frame);
+
+        match df {
+            11 => {
+                if residual & !DF11_IID_MASK != 0 {
+                    return None;
+                }
+            }
+            17 | 18 => {
+                if residual != 0 {
+                    let max_errors = match (self.error_correction, df) {
+                        (ErrorCorrection::None, _) => 0,
+                        (ErrorCorrection::TwoBit, 17) => 2,
+                        _ => 1,
+                    };
+                    crc::correct(frame, residual, max_errors)?;
+                    self.stats.corrected += 1;
+                }
+            }
+            // Address/parity formats: the residual is the address itself
+            0 | 4 | 5 | 16 | 20 | 21 => {
+                return self.is_known_address(residual).then_some(residual);
+            }
+            _ => return None,
+        }
+
+        let address = bits(frame, 8, 24);
+        self.known_addresses.insert(address, self.now);
+        Some(address)
+    }
+
+    fn decode_extended_squitter(&mut self, address: u32, me: &[u8]) -> Option<AircraftMessage> {
+        let icao = format_icao(address);
+
+        match bits(me, 0, 5) {
+            1..=4 => decode_identification(icao, me),
+            5..=8 => self.decode_surface_position(address, icao, me),
+            9..=18 => {
+                let altitude = decode_ac12(bits(me, 8, 12));
+                Some(self.decode_airborne_position(address, icao, me, altitude))
+            }
+            19 => decode_airborne_velocity(icao, me),
+            20..=22 => {
+                let altitude = Some(feet_from_meters(bits(me, 8, 12)));
+                Some(self.decode_airborne_position(address, icao, me, altitude))
+            }
+            28 => status::decode_aircraft_status(icao, me),
+            29 => status::decode_target_state(icao, me),
+            31 => status::decode_operational_status(icao, me),
+            _ => None,
+        }
+    }
+
+    /// Decode an airborne position (TC 9-18, 20-22).
+    ///
+    /// Falls back to an altitude-only update until the position resolves.
+    fn decode_airborne_position(
+        &mut self,
+        address: u32,
+        icao: String,
+        me: &[u8],
+        altitude: Option<i32>,
+    ) -> AircraftMessage {
+        match self.resolve_position(address, cpr_position(me), false) {
+            Some((latitude, longitude)) => AircraftMessage::Position {
+                icao,
+                timestamp: None,
+                latitude,
+                longitude,
+                altitude,
+                ground_speed: None,
+                track: None,
+                is_on_ground: Some(false),
+            },
+            None => airborne_altitude(icao, altitude),
+        }
+    }
+
+    /// Decode a surface position (TC 5-8) with ground movement and track.
+    fn decode_surface_position(
+        &mut self,
+        address: u32,
+        icao: String,
+        me: &[u8],
+    ) -> Option<AircraftMessage> {
+        let (latitude, longitude) = self.resolve_position(address, cpr_position(me), true)?;
+
+        let track = (bits(me, 12, 1) == 1).then(|| f64::from(bits(me, 13, 7)) * 360.0 / 128.0);
+
+        Some(AircraftMessage::Position {
+            icao,
+            timestamp: None,
+            latitude,
+            longitude,
+            altitude: None,
+            ground_speed: decode_movement(bits(me, 5, 7)),
+            track,
+            is_on_ground: Some(true),
+        })
+    }
+
+    /// Record a CPR frame and try to resolve it to a position.
+    fn resolve_position(
+        &mut self,
+        address: u32,
+        position: CprPosition,
+        surface: bool,
+    ) -> Option<(f64, f64)> {
+        let now = self.now;
+        let receiver = self.reference;
+        let receiver_range_nm = self.receiver_range_nm;
+        let decode_local = |ref_lat, ref_lon| {
+            if surface {
+                cpr::decode_surface_local(position, ref_lat, ref_lon)
+            } else {
+                cpr::decode_airborne_local(position, ref_lat, ref_lon)
+            }
+        };
+
+        let state = self
+            .cpr
+            .entry(address)
+            .or_insert_with(|| CprState::new(surface, now));
+
+        // Airborne and surface encodings cannot be paired with each other
+        if state.surface != surface {
+            state.even = None;
+            state.odd = None;
+            state.surface = surface;
+        }
+        state.last_update = now;
+
+        let frame = CprFrame {
+            position,
+            received: now,
+        };
+        if position.odd {
+            state.odd = Some(frame);
+        } else {
+            state.even = Some(frame);
+        }
+
+        let track_reference = state
+            .last_position
+            .filter(|&(_, _, at)| age(now, at) <= LOCAL_REFERENCE_MAX_AGE)
+            .map(|(lat, lon, _)| (lat, lon));
+
+        let max_pair_age = if surface {
+            SURFACE_PAIR_MAX_AGE
+        } else {
+            AIRBORNE_PAIR_MAX_AGE
+        };
+
+        let global = match (state.even, state.odd) {
+            (Some(even), Some(odd))
+                if age(
+                    even.received.max(odd.received),
+                    even.received.min(odd.received),
+                ) <= max_pair_age =>
+            {
+                if surface {
+                    track_reference.or(receiver).and_then(|(ref_lat, ref_lon)| {
+                        cpr::decode_surface_global(
+                            even.position,
+                            odd.position,
+                            position.odd,
+                            ref_lat,
+                            ref_lon,
+                        )
+                    })
+                } else {
+                    cpr::decode_airborne_global(even.position, odd.position, position.odd)
+                }
+            }
+            _ => None,
+        };
+
+        let resolved = if let Some(resolved) = global {
+            resolved
+        } else if let Some((ref_lat, ref_lon)) = track_reference {
+            decode_local(ref_lat, ref_lon)
+        } else {
+            // Not tracked yet: confirm a position decoded against the
+            // receiver, or decode one to confirm with the next frame
+            let confirmed = state
+                .candidate
+                .filter(|&(_, _, at)| age(now, at) <= CANDIDATE_MAX_AGE)
+                .map(|(lat, lon, at)| (decode_local(lat, lon), lat, lon, at))
+                .filter(|&((decoded_lat, decoded_lon), lat, lon, at)| {
+                    let max_nm = CANDIDATE_MAX_SPEED_KT * age(now, at).as_secs_f64() / 3600.0
+                        + CANDIDATE_SLACK_NM;
+                    haversine_distance_nm(lat, lon, decoded_lat, decoded_lon) <= max_nm
+                });
+            if let Some((resolved, ..)) = confirmed {
+                resolved
+            } else {
+                state.candidate =
+                    receiver
+                        .zip(receiver_range_nm)
+                        .and_then(|((ref_lat, ref_lon), range_nm)| {
+                            let (lat, lon) = decode_local(ref_lat, ref_lon);
+                            (haversine_distance_nm(ref_lat, ref_lon, lat, lon) <= range_nm)
+                                .then_some((lat, lon, now))
+                        });
+                return None;
+            }
+        };
+
+        state.candidate = None;
+        state.last_position = Some((resolved.0, resolved.1, now));
+        Some(resolved)
+    }
+
+    fn prune_stale(&mut self) {
+        let now = self.now;
+        if age(now, self.last_prune) < PRUNE_INTERVAL {
+            return;
+        }
+        self.last_prune = now;
+        self.cpr
+            .retain(|_, state| age(now, state.last_update) <= LOCAL_REFERENCE_MAX_AGE);
+        self.known_addresses
+            .retain(|_, &mut seen| age(now, seen) <= KNOWN_ADDRESS_TTL);
+    }
+}
+
+/// Time from `then` to `now` on the feed clock, zero if `then` is later.
+fn age(now: DateTime<Utc>, then: DateTime<Utc>) -> Duration {
+    (now - then).to_std().unwrap_or(Duration::ZERO)
+}
+
+/// Extract the CPR fields shared by airborne and surface position messages.
+fn cpr_position(me: &[u8]) -> CprPosition {
+    CprPosition {
+        odd: bits(me, 21, 1) == 1,
+        lat: bits(me, 22, 17),
+        lon: bits(me, 39, 17),
+    }
+}
+
+/// Decode the 7-bit surface movement field to ground speed in knots.
+fn decode_movement(movement: u32) -> Option<f64> {
+    // (first code, speed at first code, knots per step)
+    const BANDS: [(u32, f64, f64); 6] = [
+        (2, 0.125, 0.125),
+        (9, 1.0, 0.25),
+        (13, 2.0, 0.5),
+        (39, 15.0, 1.0),
+        (94, 70.0, 2.0),
+        (109, 100.0, 5.0),
+    ];
+
+    match movement {
+        0 | 125.. => None,
+        1 => Some(0.0),
+        124 => Some(175.0),
+        _ => BANDS
+            .iter()
+            .rev()
+            .find(|(first, _, _)| movement >= *first)
+            .map(|&(first, base, step)| base + f64::from(movement - first) * step),
+    }
+}
+
+fn airborne_altitude(icao: String, altitude: Option<i32>) -> AircraftMessage {
+    AircraftMessage::Altitude {
+        icao,
+        timestamp: None,
+        altitude,
+        squawk: None,
+        alert: None,
+        emergency: None,
+        spi: None,
+        is_on_ground: Some(false),
+    }
+}
+
+/// Decode the flight status field into (alert, SPI, on ground).
+fn flight_status(frame: &[u8]) -> (Option<bool>, Option<bool>, Option<bool>) {
+    match bits(frame, 5, 3) {
+        0 => (Some(false), Some(false), Some(false)),
+        1 => (Some(false), Some(false), Some(true)),
+        2 => (Some(true), Some(false), Some(false)),
+        3 => (Some(true), Some(false), Some(true)),
+        4 => (Some(true), Some(true), None),
+        5 => (Some(false), Some(true), None),
+        _ => (None, None, None),
+    }
+}
+
+/// Decode an altitude reply (DF0/16 with vertical status, DF4/20 with flight status).
+fn surveillance_altitude(address: u32, frame: &[u8], has_flight_status: bool) -> AircraftMessage {
+    let (alert, spi, is_on_ground) = if has_flight_status {
+        flight_status(frame)
+    } else {
+        (None, None, Some(bits(frame, 5, 1) == 1))
+    };
+
+    AircraftMessage::Altitude {
+        icao: format_icao(address),
+        timestamp: None,
+        altitude: decode_ac13(bits(frame, 19, 13)),
+        squawk: None,
+        alert,
+        emergency: None,
+        spi,
+        is_on_ground,
+    }
+}
+
+/// Decode an identity reply (DF5/21).
+fn surveillance_identity(address: u32, frame: &[u8]) -> AircraftMessage {
+    let (alert, spi, is_on_ground) = flight_status(frame);
+    let squawk = decode_squawk(bits(frame, 19, 13));
+    let emergency = matches!(squawk.as_str(), "7500" | "7600" | "7700");
+
+    AircraftMessage::Altitude {
+        icao: format_icao(address),
+        timestamp: None,
+        altitude: None,
+        squawk: Some(squawk),
+        alert,
+        emergency: Some(emergency),
+        spi,
+        is_on_ground,
+    }
+}
+
+/// Decode a Comm-B reply (DF20/21).
+///
+/// Falls back to the plain surveillance reply when no supported register
+/// fits the MB field.
+fn decode_comm_b(address: u32, frame: &[u8], df: u8) -> AircraftMessage {
+    let (altitude, squawk) = if df == 20 {
+        (decode_ac13(bits(frame, 19, 13)), None)
+    } else {
+        (None, Some(decode_squawk(bits(frame, 19, 13))))
+    };
+
+    match comm_b::infer(&frame[4..11], altitude) {
+        Some(register) => AircraftMessage::CommB {
+            icao: format_icao(address),
+            timestamp: None,
+            altitude,
+            squawk,
+            register,
+        },
+        None if df == 20 => surveillance_altitude(address, frame, true),
+        None => surveillance_identity(address, frame),
+    }
+}
+
+/// Decode an aircraft identification message (TC 1-4).
+fn decode_identification(icao: String, me: &[u8]) -> Option<AircraftMessage> {
+    let callsign: String = (0..8)
+        .map(|i| char::from(CALLSIGN_CHARSET[bits(me, 8 + i * 6, 6) as usize]))
+        .collect();
+    let callsign = callsign.trim_end_matches([' ', '#']).to_string();
+
+    if callsign.is_empty() {
+        return None;
+    }
+
+    Some(AircraftMessage::Identification {
+        icao,
+        timestamp: None,
+        callsign,
+    })
+}
+
+/// Decode an airborne velocity message (TC 19) with ground speed subtypes.
+fn decode_airborne_velocity(icao: String, me: &[u8]) -> Option<AircraftMessage> {
+    let subtype = bits(me, 5, 3);
+    if !matches!(subtype, 1 | 2) {
+        // Subtypes 3 and 4 report airspeed and heading, not ground track
+        return None;
+    }
+
+    let v_ew = bits(me, 14, 10);
+    let v_ns = bits(me, 25, 10);
+    if v_ew == 0 || v_ns == 0 {
+        return None;
+    }
+
+    // Supersonic subtype uses 4 kt resolution
+    let scale = if subtype == 2 { 4.0 } else { 1.0 };
+    let sign = |bit: u32| if bit == 0 { 1.0 } else { -1.0 };
+    let east = sign(bits(me, 13, 1)) * f64::from(v_ew - 1) * scale;
+    let north = sign(bits(me, 24, 1)) * f64::from(v_ns - 1) * scale;
+
+    let speed = east.hypot(north);
+    let track = east.atan2(north).to_degrees().rem_euclid(360.0);
+
+    let vr = bits(me, 37, 9);
+    let vertical_rate = if vr == 0 {
+        None
+    } else {
+        let magnitude = i32::try_from(vr - 1).ok()? * 64;
+        Some(if bits(me, 36, 1) == 0 {
+            magnitude
+        } else {
+            -magnitude
+        })
+    };
+
+    Some(AircraftMessage::Velocity {
+        icao,
+        timestamp: None,
+        speed,
+        track,
+        vertical_rate,
+        is_on_ground: Some(false),
+    })
+}
+
+/// Parse a hex string into frame bytes.
+#[cfg(test)]
+pub(crate) fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_hex(hex: &str) -> Option<AircraftMessage> {
+        Decoder::new().decode(&hex_to_bytes(hex)).unwrap()
+    }
+
+    #[test]
+    fn test_decode_identification() {
+        let msg = decode_hex("8D4840D6202CC371C32CE0576098").unwrap();
+        assert_eq!(
+            msg,
+            AircraftMessage::Identification {
+                icao: "4840D6".to_string(),
+                timestamp: None,
+                callsign: "KLM1023".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_airborne_velocity() {
+        match decode_hex("8D485020994409940838175B284F").unwrap() {
+            AircraftMessage::Velocity {
+                icao,
+                timestamp: None,
+                speed,
+                track,
+                vertical_rate,
+                ..
+            } => {
+                assert_eq!(icao, "485020");
+                assert!((speed - 159.20).abs() < 0.01);
+                assert!((track - 182.88).abs() < 0.01);
+                assert_eq!(vertical_rate, Some(-832));
+            }
+            other => panic!("Expected Velocity, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_airborne_position_without_pair_reports_altitude() {
+        match decode_hex("8D40621D58C382D690C8AC2863A7").unwrap() {
+            AircraftMessage::Altitude { icao, altitude, .. } => {
+                assert_eq!(icao, "40621D");
+                assert_eq!(altitude, Some(38000));
+            }
+            other => panic!("Expected Altitude, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_decode_global_airborne_position() {
+        let mut decoder = Decoder::new();
+        decoder
+            .decode(&hex_to_bytes("8D40621D58C386435CC412692AD6"))
+            .unwrap();
+
+        match decoder
+            .decode(&hex_to_bytes("8D40621D58C382D690C8AC2863A7"))
+            .unwrap()
+            .unwrap()
+        {
+            AircraftMessage::Position {
+                icao,
+                timestamp: None,
+                latitude,
+                longitude,
+                altitude,
+                is_on_ground,
+                ..
+            } => {
+                assert_eq!(icao, "40621D");
+                assert!((latitude - 52.257_20).abs() < 1e-4);
+                assert!((longitude - 3.919_37).abs() < 1e-4);
+                assert_eq!(altitude, Some(38000));
+                assert_eq!(is_on_ground, Some(false));
+            }
+            other => panic!("Expected Position, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_frames_far_apart_in_feed_time_do_not_pair() {
+        // Recorded frames a minute apart, decoded back to back
+        let even = hex_to_bytes("8D40621D58C386435CC412692AD6");
+        let odd = hex_to_bytes("8D40621D58C382D690C8AC2863A7");
+        let start = Utc::now() - chrono::Duration::days(1);
+
+        let mut decoder = Decoder::new();
+        decoder.decode_at(&even, start).unwrap();
+        assert!(matches!(
+            decoder
+                .decode_at(&odd, start + chrono::Duration::minutes(1))
+                .unwrap(),
+            Some(AircraftMessage::Altitude { .. })
+        ));
+
+        // A second apart, they pair
+        let mut decoder = Decoder::new();
+        decoder.decode_at(&even, start).unwrap();
+        assert!(matches!(
+            decoder
+                .decode_at(&odd, start + chrono::Duration::seconds(1))
+                .unwrap(),
+            Some(AircraftMessage::Position { .. })
+        ));
+    }
+
+    #[test]
+    fn test_decode_local_airborne_position() {
+        let even = hex_to_bytes("8D40621D58C386435CC412692AD6");
+        let odd = hex_to_bytes("8D40621D58C382D690C8AC2863A7");
+        let start = Utc::now();
+
+        let mut decoder = Decoder::new();
+        decoder.decode_at(&even, start).unwrap();
+        decoder.decode_at(&odd, start).unwrap();
+
+        // Too late to pair with the even frame, so decoded against the track
+        match decoder
+            .decode_at(&odd, start + chrono::Duration::seconds(30))
+            .unwrap()
+            .unwrap()
+        {
+            AircraftMessage::Position {
+                latitude,
+                longitude,
+                ..
+            } => {
+                assert!((latitude - 52.257_20).abs() < 1e-4);
+                assert!((longitude - 3.919_37).abs() < 1e-4);
+            }
+            other => panic!("Expected Position, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_receiver_relative_position_not_emitted() {
+        let mut decoder = Decoder::new();
+        decoder.set_reference(Some((52.258, 3.918)));
+
+        let odd = hex_to_bytes("8D40621D58C382D690C8AC2863A7");
+        assert!(matches!(
+            decoder.decode(&odd).unwrap(),
+            Some(AircraftMessage::Altitude { .. })
+        ));
+        assert_eq!(decoder.cpr[&0x0040_621D].last_position, None);
+
+        assert!(matches!(
+            decoder
+                .decode(&hex_to_bytes("8D40621D58C386435CC412692AD6"))
+                .unwrap(),
+            Some(AircraftMessage::Position { .. })
+        ));
+        assert!(decoder.cpr[&0x0040_621D].last_position.is_some());
+    }
+
+    #[test]
+    fn test_receiver_relative_position_confirmed_by_next_frame() {
+        let odd = hex_to_bytes("8D40621D58C382D690C8AC2863A7");
+        let now = Utc::now();
+        let later = now + chrono::Duration::seconds(1);
+
+        // About 15 nm from the receiver
+        let mut decoder = Decoder::new();
+        decoder.set_reference(Some((52.0, 3.9)));
+        decoder.set_receiver_range(Some(5.0));
+        for time in [now, later] {
+            assert!(matches!(
+                decoder.decode_at(&odd, time).unwrap(),
+                Some(AircraftMessage::Altitude { .. })
+            ));
+        }
+
+        // Within range, the first frame is held until the next one confirms it
+        decoder = Decoder::new();
+        decoder.set_reference(Some((52.0, 3.9)));
+        decoder.set_receiver_range(Some(50.0));
+        assert!(matches!(
+            decoder.decode_at(&odd, now).unwrap(),
+            Some(AircraftMessage::Altitude { .. })
+        ));
+        assert_eq!(decoder.cpr[&0x0040_621D].last_position, None);
+        match decoder.decode_at(&odd, later).unwrap() {
+            Some(AircraftMessage::Position {
+                latitude,
+                longitude,
+                ..
+            }) => {
+                assert!((latitude - 52.257).abs() < 1e-2);
+                assert!((longitude - 3.919).abs() < 1e-2);
+            }
+            other => panic!("Expected Position, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_decode_surface_position() {
+        let frame = hex_to_bytes("8C4841753A9A153237AEF0F275BE");
+        let now = Utc::now();
+
+        let mut decoder = Decoder::new();
+        decoder.set_reference(Some((51.990, 4.375)));
+        assert_eq!(decoder.decode_at(&frame, now).unwrap(), None);
+
+        // Resolves locally once the aircraft has a recent track position
+        decoder.cpr.get_mut(&0x0048_4175).unwrap().last_position = Some((52.32, 4.73, now));
+        match decoder.decode_at(&frame, now).unwrap().unwrap() {
+            AircraftMessage::Position {
+                icao,
+                timestamp: None,
+                latitude,
+                longitude,
+                ground_speed,
+                track,
+                is_on_ground,
+                ..
+            } => {
+                assert_eq!(icao, "484175");
+                assert!((latitude - 52.320_56).abs() < 1e-4);
+                assert!((longitude - 4.735_74).abs() < 1e-4);
+                assert_eq!(ground_speed, Some(17.0));
+                assert!((track.unwrap() - 92.8125).abs() < 1e-6);
+                assert_eq!(is_on_ground, Some(true));
+            }
+            other => panic!("Expected Position, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_surface_position_requires_reference() {
+        let msg = decode_hex("8C4841753A9A153237AEF0F275BE");
+        assert_eq!(msg, None);
+    }
+
+    #[test]
+    fn test_decode_movement() {
+        assert_eq!(decode_movement(0), None);
+        assert_eq!(decode_movement(1), Some(0.0));
+        assert_eq!(decode_movement(9), Some(1.0));
+        assert_eq!(decode_movement(124), Some(175.0));
+    }
+
+    #[test]
+    fn test_decode_ac12() {
+        // Q bit set: 25 ft increments
+        assert_eq!(decode_ac12(0xC38), Some(38000));
+        // All zero means altitude not available
+        assert_eq!(decode_ac12(0), None);
+    }
+
+    #[test]
+    fn test_wrong_frame_length() {
+        assert!(Decoder::new().decode(&[0x8D, 0x48, 0x40]).is_err());
+    }
+
+    /// Build a short address/parity frame from its first 32 bits.
+    fn short_frame(header: u32, address: u32) -> Vec<u8> {
+        let mut frame = header.to_be_bytes().to_vec();
+        let parity = crc::checksum(&frame) ^ address;
+        frame.extend_from_slice(&parity.to_be_bytes()[1..]);
+        frame
+    }
+
+    #[test]
+    fn test_single_bit_error_corrected() {
+        let mut frame = hex_to_bytes("8D4840D6202CC371C32CE0576098");
+        frame[6] ^= 0x10;
+
+        let mut decoder = Decoder::new();
+        let msg = decoder.decode(&frame).unwrap().unwrap();
+        assert_eq!(msg.icao(), "4840D6");
+        assert_eq!(decoder.stats().corrected, 1);
+        assert_eq!(decoder.stats().dropped, 0);
+    }
+
+    #[test]
+    fn test_bad_parity_dropped_without_correction() {
+        let mut frame = hex_to_bytes("8D4840D6202CC371C32CE0576098");
+        frame[6] ^= 0x10;
+
+        let mut decoder = Decoder::new();
+        decoder.set_error_correction(ErrorCorrection::None);
+        assert_eq!(decoder.decode(&frame).unwrap(), None);
+        assert_eq!(
+            decoder.stats(),
+            DecoderStats {
+                frames: 1,
+                corrected: 0,
+                dropped: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_two_bit_correction_only_when_enabled() {
+        let mut frame = hex_to_bytes("8D4840D6202CC371C32CE0576098");
+        frame[5] ^= 0x01;
+        frame[9] ^= 0x80;
+
+        let mut decoder = Decoder::new();
+        assert_eq!(decoder.decode(&frame).unwrap(), None);
+
+        decoder.set_error_correction(ErrorCorrection::TwoBit);
+        assert!(decoder.decode(&frame).unwrap().is_some());
+        assert_eq!(decoder.stats().corrected, 1);
+        assert_eq!(decoder.stats().dropped, 1);
+    }
+
+    #[test]
+    fn test_address_parity_recovery() {
+        // DF4, airborne, Q-bit altitude of 38,000 ft
+        let df4 = short_frame((4 << 27) | 0x1838, 0x0048_40D6);
+
+        let mut decoder = Decoder::new();
+        assert_eq!(decoder.decode(&df4).unwrap(), None);
+        assert_eq!(decoder.stats().dropped, 1);
+
+        // An extended squitter makes the address known
+        decoder
+            .decode(&hex_to_bytes("8D4840D6202CC371C32CE0576098"))
+            .unwrap();
+
+        match decoder.decode(&df4).unwrap().unwrap() {
+            AircraftMessage::Altitude {
+                icao,
+                timestamp: None,
+                altitude,
+                is_on_ground,
+                ..
+            } => {
+                assert_eq!(icao, "4840D6");
+                assert_eq!(altitude, Some(38000));
+                assert_eq!(is_on_ground, Some(false));
+            }
+            other => panic!("Expected Altitude, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_identity_reply_squawk() {
+        // DF5, airborne, squawk 7700
+        let id13 = 0x0080 | 0x0200 | 0x0800 | 0x0002 | 0x0008 | 0x0020;
+        let df5 = short_frame((5 << 27) | id13, 0x0048_40D6);
+
+        let mut decoder = Decoder::new();
+        decoder
+            .decode(&hex_to_bytes("8D4840D6202CC371C32CE0576098"))
+            .unwrap();
+
+        match decoder.decode(&df5).unwrap().unwrap() {
+            AircraftMessage::Altitude {
+                squawk, emergency, ..
+            } => {
+                assert_eq!(squawk.as_deref(), Some("7700"));
+                assert_eq!(emergency, Some(true));
+            }
+            other => panic!("Expected Altitude, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_comm_b_reply() {
+        let frame = hex_to_bytes("A000029C85E42F313000007047D3");
+
+        let mut decoder = Decoder::new();
+        decoder.known_addresses.insert(0x0042_43D0, decoder.now);
+
+        match decoder.decode(&frame).unwrap().unwrap() {
+            AircraftMessage::CommB { icao, register, .. } => {
+                assert_eq!(icao, "4243D0");
+                assert_eq!(register.value.bds(), "4,0");
+            }
+            other => panic!("Expected CommB, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_decode_ac13() {
+        // Q bit set: 25 ft increments
+        assert_eq!(decode_ac13(0x1838), Some(38000));
+        // M bit set: metric altitude is not supported
+        assert_eq!(decode_ac13(0x0040), None);
+    }
+
+    #[test]
+    fn test_decode_squawk() {
+        // A = 7, B = 7, C = 0, D = 0
+        let id13 = 0x0080 | 0x0200 | 0x0800 | 0x0002 | 0x0008 | 0x0020;
+        assert_eq!(decode_squawk(id13), "7700");
+    }
+}
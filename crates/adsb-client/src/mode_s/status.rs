@@ -0,0 +1,231 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Status extended squitter messages.
+//!
+//! Decodes aircraft status (TC 28), target state and status (TC 29) and
+//! operational status (TC 31) from a 56-bit ME field.
+
+use super::{bits, decode_squawk, format_icao};
+use crate::protocol::{
+    AircraftMessage, AutopilotModes, EmergencyState, ResolutionAdvisory, SelectedAltitudeSource,
+};
+
+/// Threat identity type indicating a 24-bit address in the TID field.
+const TTI_ADDRESS: u32 = 1;
+
+/// Narrow a field of at most 8 bits.
+fn small(value: u32) -> u8 {
+    u8::try_from(value).unwrap_or(u8::MAX)
+}
+
+/// Decode an aircraft status message (TC 28).
+pub(crate) fn decode_aircraft_status(icao: String, me: &[u8]) -> Option<AircraftMessage> {
+    match bits(me, 5, 3) {
+        1 => Some(AircraftMessage::AircraftStatus {
+            icao,
+            timestamp: None,
+            emergency_state: Some(EmergencyState::from_code(small(bits(me, 8, 3)))),
+            squawk: Some(decode_squawk(bits(me, 11, 13))),
+            resolution_advisory: None,
+        }),
+        2 => {
+            let threat_icao =
+                (bits(me, 28, 2) == TTI_ADDRESS).then(|| format_icao(bits(me, 30, 24)));
+
+            Some(AircraftMessage::AircraftStatus {
+                icao,
+                timestamp: None,
+                emergency_state: None,
+                squawk: None,
+                resolution_advisory: Some(ResolutionAdvisory {
+                    active_advisories: u16::try_from(bits(me, 8, 14)).ok()?,
+                    complements: small(bits(me, 22, 4)),
+                    terminated: bits(me, 26, 1) == 1,
+                    multiple_threats: bits(me, 27, 1) == 1,
+                    threat_icao,
+                }),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Decode a target state and status message (TC 29).
+///
+/// Only the version 2 layout (subtype 1) is supported.
+pub(crate) fn decode_target_state(icao: String, me: &[u8]) -> Option<AircraftMessage> {
+    if bits(me, 5, 2) != 1 {
+        return None;
+    }
+
+    let selected_altitude = match bits(me, 9, 11) {
+        0 => None,
+        value => Some(i32::try_from(value - 1).ok()? * 32),
+    };
+    let selected_altitude_source = selected_altitude.map(|_| {
+        if bits(me, 8, 1) == 0 {
+            SelectedAltitudeSource::Mcp
+        } else {
+            SelectedAltitudeSource::Fms
+        }
+    });
+
+    let baro_setting = match bits(me, 20, 9) {
+        0 => None,
+        value => Some(800.0 + f64::from(value - 1) * 0.8),
+    };
+
+    let selected_heading =
+        (bits(me, 29, 1) == 1).then(|| f64::from(bits(me, 30, 9)) * 180.0 / 256.0);
+
+    let autopilot_modes = (bits(me, 46, 1) == 1).then(|| AutopilotModes {
+        autopilot: bits(me, 47, 1) == 1,
+        vnav: bits(me, 48, 1) == 1,
+        altitude_hold: bits(me, 49, 1) == 1,
+        approach: bits(me, 51, 1) == 1,
+        lnav: bits(me, 53, 1) == 1,
+        tcas: bits(me, 52, 1) == 1,
+    });
+
+    Some(AircraftMessage::TargetState {
+        icao,
+        timestamp: None,
+        selected_altitude,
+        selected_altitude_source,
+        selected_heading,
+        baro_setting,
+        autopilot_modes,
+    })
+}
+
+/// Decode an operational status message (TC 31).
+pub(crate) fn decode_operational_status(icao: String, me: &[u8]) -> Option<AircraftMessage> {
+    let is_on_ground = match bits(me, 5, 3) {
+        0 => false,
+        1 => true,
+        _ => return None,
+    };
+
+    Some(AircraftMessage::OperationalStatus {
+        icao,
+        timestamp: None,
+        version: small(bits(me, 40, 3)),
+        nic_supplement_a: bits(me, 43, 1) == 1,
+        nac_p: small(bits(me, 44, 4)),
+        sil: small(bits(me, 50, 2)),
+        is_on_ground,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mode_s::{hex_to_bytes, Decoder};
+    use crate::protocol::{
+        AircraftMessage, AutopilotModes, EmergencyState, SelectedAltitudeSource,
+    };
+
+    fn decode_hex(hex: &str) -> AircraftMessage {
+        Decoder::new().decode(&hex_to_bytes(hex)).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_decode_target_state() {
+        match decode_hex("8DA05629EA21485CBF3F8CADAEEB") {
+            AircraftMessage::TargetState {
+                icao,
+                timestamp: None,
+                selected_altitude,
+                selected_altitude_source,
+                selected_heading,
+                baro_setting,
+                autopilot_modes,
+            } => {
+                assert_eq!(icao, "A05629");
+                assert_eq!(selected_altitude, Some(16992));
+                assert_eq!(selected_altitude_source, Some(SelectedAltitudeSource::Mcp));
+                assert!((selected_heading.unwrap() - 66.796_875).abs() < 1e-6);
+                assert!((baro_setting.unwrap() - 1012.8).abs() < 1e-6);
+                let modes = autopilot_modes.unwrap();
+                assert_eq!(
+                    modes,
+                    AutopilotModes {
+                        autopilot: true,
+                        vnav: true,
+                        altitude_hold: false,
+                        approach: false,
+                        lnav: true,
+                        tcas: true,
+                    }
+                );
+                assert_eq!(modes.to_string(), "AP VNAV LNAV TCAS");
+            }
+            other => panic!("Expected TargetState, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_decode_emergency_status() {
+        match decode_hex("8D4840D6E12AAA000000003CF5CE") {
+            AircraftMessage::AircraftStatus {
+                emergency_state,
+                squawk,
+                resolution_advisory,
+                ..
+            } => {
+                assert_eq!(emergency_state, Some(EmergencyState::General));
+                assert_eq!(squawk.as_deref(), Some("7700"));
+                assert!(resolution_advisory.is_none());
+            }
+            other => panic!("Expected AircraftStatus, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_decode_resolution_advisory() {
+        match decode_hex("8D4840D6E2C00006AF37BC89DCA5") {
+            AircraftMessage::AircraftStatus {
+                emergency_state,
+                resolution_advisory,
+                ..
+            } => {
+                assert_eq!(emergency_state, None);
+                let ra = resolution_advisory.unwrap();
+                assert_eq!(ra.active_advisories, 0x3000);
+                assert!(ra.is_corrective());
+                assert!(!ra.is_downward());
+                assert!(!ra.terminated);
+                assert!(!ra.multiple_threats);
+                assert_eq!(ra.threat_icao.as_deref(), Some("ABCDEF"));
+            }
+            other => panic!("Expected AircraftStatus, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_decode_operational_status() {
+        assert_eq!(
+            decode_hex("8D4840D6F80000000049B8DD76BC"),
+            AircraftMessage::OperationalStatus {
+                icao: "4840D6".to_string(),
+                timestamp: None,
+                version: 2,
+                nic_supplement_a: false,
+                nac_p: 9,
+                sil: 3,
+                is_on_ground: false,
+            }
+        );
+    }
+}
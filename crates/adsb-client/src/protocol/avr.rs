@@ -0,0 +1,239 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! AVR raw hex protocol parser.
+//!
+//! Parses the line-based hex format served by dump1090 on port 30002 and
+//! emitted by many SDR tools.
+//!
+//! Message format:
+//! ```text
+//! *8D4840D6202CC371C32CE0576098;
+//! @0123456789AB8D4840D6202CC371C32CE0576098;
+//! ```
+//!
+//! The `@` form carries a 48-bit MLAT timestamp (12 hex digits, 12 MHz
+//! ticks) before the frame.
+
+use chrono::{DateTime, Utc};
+
+use super::{decode_hex, AircraftMessage, ParseError, Protocol, Signal};
+use crate::mode_s::{Decoder, DecoderStats, ErrorCorrection};
+
+/// Number of hex digits in an MLAT timestamp.
+const TIMESTAMP_DIGITS: usize = 12;
+/// Receiver clock frequency of the MLAT timestamp.
+const TIMESTAMP_HZ: f64 = 12_000_000.0;
+
+/// A single validated AVR frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AvrFrame {
+    /// 48-bit MLAT timestamp in 12 MHz ticks, for `@` frames.
+    pub timestamp: Option<u64>,
+    /// Mode-A/C or Mode S payload.
+    pub payload: Vec<u8>,
+}
+
+impl AvrFrame {
+    /// Receiver timestamp in seconds, for `@` frames.
+    #[must_use]
+    #[allow(
+        clippy::cast_precision_loss,
+        reason = "48-bit timestamp fits in f64 mantissa"
+    )]
+    pub fn timestamp_secs(&self) -> Option<f64> {
+        self.timestamp.map(|ticks| ticks as f64 / TIMESTAMP_HZ)
+    }
+}
+
+/// Parser for AVR raw hex frames.
+#[derive(Debug, Default)]
+pub struct AvrParser {
+    decoder: Decoder,
+    /// Receiver time of the last parsed frame.
+    signal: Option<Signal>,
+}
+
+impl AvrParser {
+    /// Create a new AVR parser.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the error correction applied to frames with a bad parity.
+    pub fn set_error_correction(&mut self, mode: ErrorCorrection) {
+        self.decoder.set_error_correction(mode);
+    }
+
+    /// Parse and validate a single line without decoding its payload.
+    ///
+    /// Returns `Ok(None)` for blank lines.
+    pub fn parse_frame(input: &[u8]) -> Result<Option<AvrFrame>, ParseError> {
+        let line = std::str::from_utf8(input)
+            .map_err(|e| ParseError::InvalidFormat(format!("invalid UTF-8: {e}")))?
+            .trim();
+
+        if line.is_empty() {
+            return Ok(None);
+        }
+
+        let body = line
+            .strip_suffix(';')
+            .ok_or(ParseError::MissingField("terminator"))?;
+
+        let (timestamp, hex) = if let Some(hex) = body.strip_prefix('*') {
+            (None, hex)
+        } else if let Some(rest) = body.strip_prefix('@') {
+            if rest.len() < TIMESTAMP_DIGITS {
+                return Err(ParseError::MissingField("timestamp"));
+            }
+            // Lines come off the network, so don't split inside a character
+            if !rest.is_char_boundary(TIMESTAMP_DIGITS) {
+                return Err(ParseError::InvalidFormat(format!(
+                    "timestamp is not hex: {line}"
+                )));
+            }
+            let (timestamp, hex) = rest.split_at(TIMESTAMP_DIGITS);
+            let timestamp =
+                u64::from_str_radix(timestamp, 16).map_err(|e| ParseError::InvalidValue {
+                    field: "timestamp",
+                    value: format!("{timestamp} ({e})"),
+                })?;
+            (Some(timestamp), hex)
+        } else {
+            return Err(ParseError::InvalidFormat(format!(
+                "expected '*' or '@' prefix: {line}"
+            )));
+        };
+
+        let payload = decode_hex(hex).ok_or_else(|| ParseError::InvalidValue {
+            field: "frame",
+            value: hex.to_string(),
+        })?;
+
+        Ok(Some(AvrFrame { timestamp, payload }))
+    }
+}
+
+impl Protocol for AvrParser {
+    type Message = AircraftMessage;
+    type Error = ParseError;
+
+    fn parse(&mut self, input: &[u8]) -> Result<Option<AircraftMessage>, ParseError> {
+        self.parse_at(input, Utc::now())
+    }
+
+    fn parse_at(
+        &mut self,
+        input: &[u8],
+        now: DateTime<Utc>,
+    ) -> Result<Option<AircraftMessage>, ParseError> {
+        let Some(frame) = Self::parse_frame(input)? else {
+            return Ok(None);
+        };
+        self.signal = Some(Signal {
+            rssi: None,
+            receiver_time: frame.timestamp_secs(),
+        });
+
+        // Mode-A/C replies carry no address, so they cannot be attributed
+        if frame.payload.len() == 2 {
+            return Ok(None);
+        }
+
+        self.decoder.decode_at(&frame.payload, now)
+    }
+
+    fn set_reference(&mut self, reference: Option<(f64, f64)>) {
+        self.decoder.set_reference(reference);
+    }
+
+    fn set_receiver_range(&mut self, range_nm: Option<f64>) {
+        self.decoder.set_receiver_range(range_nm);
+    }
+
+    fn decoder_stats(&self) -> Option<DecoderStats> {
+        Some(self.decoder.stats())
+    }
+
+    fn signal(&self) -> Option<Signal> {
+        self.signal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plain_frame() {
+        let msg = AvrParser::new()
+            .parse(b"*8D4840D6202CC371C32CE0576098;")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            msg,
+            AircraftMessage::Identification {
+                icao: "4840D6".to_string(),
+                timestamp: None,
+                callsign: "KLM1023".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_mlat_timestamp() {
+        let frame = AvrParser::parse_frame(b"@0123456789AB8D4840D6202CC371C32CE0576098;\r\n")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(frame.timestamp, Some(0x0123_4567_89AB));
+        assert_eq!(frame.payload.len(), 14);
+        assert_eq!(frame.payload[0], 0x8D);
+    }
+
+    #[test]
+    fn test_mode_ac_and_blank_lines() {
+        let mut parser = AvrParser::new();
+        assert_eq!(parser.parse(b"*2000;").unwrap(), None);
+        assert_eq!(parser.parse(b"").unwrap(), None);
+    }
+
+    #[test]
+    fn test_invalid_frames() {
+        let mut parser = AvrParser::new();
+        // Missing terminator
+        assert!(parser.parse(b"*8D4840D6202CC371C32CE0576098").is_err());
+        // Unknown prefix
+        assert!(parser.parse(b"#8D4840D6202CC371C32CE0576098;").is_err());
+        // Not hex
+        assert!(parser.parse(b"*8D4840D6202CC371C32CE05760ZZ;").is_err());
+        // Odd number of digits
+        assert!(parser.parse(b"*8D4840D6202CC371C32CE057609;").is_err());
+        // Wrong length for the downlink format
+        assert!(parser.parse(b"*8D4840D6202CC3;").is_err());
+        // Truncated timestamp
+        assert!(parser.parse(b"@01234;").is_err());
+    }
+
+    #[test]
+    fn test_multibyte_timestamp_is_rejected() {
+        // 'é' straddles the end of the timestamp digits
+        let line = "@aéééééééé8D4840D6202CC371C32CE0576098;";
+        assert!(AvrParser::parse_frame(line.as_bytes()).is_err());
+        assert!(AvrParser::new().parse(line.as_bytes()).is_err());
+    }
+}
@@ -12,27 +12,48 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! BaseStation/SBS-1 protocol parser.
+//! BaseStation/SBS-1 protocol parser and encoder.
 //!
 //! Parses the CSV-based BaseStation protocol format commonly used by
-//! dump1090 and similar ADS-B decoders.
+//! dump1090 and similar ADS-B decoders, and writes it back out with
+//! [`BaseStationEncoder`].
 //!
 //! Message format:
 //! ```text
 //! MSG,<type>,<session>,<aircraft>,<icao>,<flight>,<date>,<time>,<date>,<time>,<fields...>
 //! ```
+//!
+//! Fields 6-9 hold the date and time the message was generated and logged.
+//! The other record types (`SEL`, `ID`, `AIR`, `STA` and `CLK`) share the same
+//! header and carry at most one field after it.
+
+use std::collections::HashMap;
+use std::fmt::Display;
 
-use super::{AircraftMessage, ParseError, Protocol};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+use super::{AircraftMessage, ParseError, Protocol, TrackStatus};
+use crate::tracker::Aircraft;
 
 /// Parser for BaseStation/SBS-1 protocol messages.
 #[derive(Debug, Default)]
-pub struct BaseStationParser;
+pub struct BaseStationParser {
+    utc_timestamps: bool,
+}
 
 impl BaseStationParser {
     /// Create a new BaseStation parser.
     #[must_use]
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Interpret message timestamps as UTC instead of local time.
+    ///
+    /// `BaseStation` and dump1090 write local time; some feeders and recorders
+    /// are configured to write UTC.
+    pub fn set_utc_timestamps(&mut self, utc: bool) {
+        self.utc_timestamps = utc;
     }
 }
 
@@ -44,10 +65,34 @@ impl Protocol for BaseStationParser {
         let line = std::str::from_utf8(input)
             .map_err(|_| ParseError::InvalidFormat("invalid UTF-8".to_string()))?;
 
-        parse_basestation_line(line)
+        parse_basestation_line(line, self.utc_timestamps)
+    }
+}
+
+/// Parse a SBS-1 date ("2024/01/01") and time ("12:00:00.000") pair.
+fn parse_datetime(date: &str, time: &str, utc: bool) -> Option<DateTime<Utc>> {
+    let date = NaiveDate::parse_from_str(date.trim(), "%Y/%m/%d").ok()?;
+    let time = NaiveTime::parse_from_str(time.trim(), "%H:%M:%S%.f").ok()?;
+    let naive = NaiveDateTime::new(date, time);
+
+    if utc {
+        Some(Utc.from_utc_datetime(&naive))
+    } else {
+        Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|local| local.with_timezone(&Utc))
     }
 }
 
+/// Time the message was generated (fields 6-7), falling back to the time it
+/// was logged (fields 8-9).
+fn parse_timestamp(parts: &[&str], utc: bool) -> Option<DateTime<Utc>> {
+    let field = |index: usize| parts.get(index).copied().unwrap_or_default();
+
+    parse_datetime(field(6), field(7), utc).or_else(|| parse_datetime(field(8), field(9), utc))
+}
+
 /// Parse a SBS-1 boolean flag field ("0"/"1"/"-1") to Option<bool>.
 fn parse_bool_flag(field: &str) -> Option<bool> {
     match field.trim() {
@@ -58,18 +103,21 @@ fn parse_bool_flag(field: &str) -> Option<bool> {
 }
 
 /// Parse a single BaseStation message line.
-fn parse_basestation_line(line: &str) -> Result<Option<AircraftMessage>, ParseError> {
-    let parts: Vec<&str> = line.split(',').collect();
+fn parse_basestation_line(line: &str, utc: bool) -> Result<Option<AircraftMessage>, ParseError> {
+    let parts: Vec<&str> = line.trim_end().split(',').collect();
 
     if parts.is_empty() {
         return Ok(None);
     }
 
     let msg_type = parts[0];
+    let timestamp = parse_timestamp(&parts, utc);
 
-    // We only handle MSG type messages
-    if msg_type != "MSG" {
-        return Ok(None);
+    // Clock messages carry no aircraft
+    if msg_type == "CLK" {
+        return Ok(timestamp.map(|timestamp| AircraftMessage::Clock {
+            timestamp: Some(timestamp),
+        }));
     }
 
     // We need at least the ICAO field (index 4)
@@ -82,6 +130,10 @@ fn parse_basestation_line(line: &str) -> Result<Option<AircraftMessage>, ParseEr
         return Ok(None);
     }
 
+    if msg_type != "MSG" {
+        return Ok(parse_record(msg_type, icao, &parts, timestamp));
+    }
+
     // Need at least 11 fields to determine transmission type
     if parts.len() < 11 {
         return Ok(None);
@@ -95,6 +147,7 @@ fn parse_basestation_line(line: &str) -> Result<Option<AircraftMessage>, ParseEr
             if parts.len() > 10 && !parts[10].is_empty() {
                 return Ok(Some(AircraftMessage::Identification {
                     icao: icao.to_string(),
+                    timestamp,
                     callsign: parts[10].trim().to_string(),
                 }));
             }
@@ -136,6 +189,7 @@ fn parse_basestation_line(line: &str) -> Result<Option<AircraftMessage>, ParseEr
 
                     return Ok(Some(AircraftMessage::Position {
                         icao: icao.to_string(),
+                        timestamp,
                         latitude: lat,
                         longitude: lon,
                         altitude,
@@ -173,6 +227,7 @@ fn parse_basestation_line(line: &str) -> Result<Option<AircraftMessage>, ParseEr
 
                     return Ok(Some(AircraftMessage::Position {
                         icao: icao.to_string(),
+                        timestamp,
                         latitude: lat,
                         longitude: lon,
                         altitude,
@@ -211,6 +266,7 @@ fn parse_basestation_line(line: &str) -> Result<Option<AircraftMessage>, ParseEr
                 if let (Some(speed), Some(track)) = (speed, track) {
                     return Ok(Some(AircraftMessage::Velocity {
                         icao: icao.to_string(),
+                        timestamp,
                         speed,
                         track,
                         vertical_rate,
@@ -262,6 +318,7 @@ fn parse_basestation_line(line: &str) -> Result<Option<AircraftMessage>, ParseEr
                     {
                         return Ok(Some(AircraftMessage::Position {
                             icao: icao.to_string(),
+                            timestamp,
                             latitude: lat,
                             longitude: lon,
                             altitude,
@@ -279,6 +336,7 @@ fn parse_basestation_line(line: &str) -> Result<Option<AircraftMessage>, ParseEr
             {
                 return Ok(Some(AircraftMessage::Altitude {
                     icao: icao.to_string(),
+                    timestamp,
                     altitude,
                     squawk,
                     alert,
@@ -293,6 +351,377 @@ fn parse_basestation_line(line: &str) -> Result<Option<AircraftMessage>, ParseEr
     }
 }
 
+/// Parse a non-MSG record (`SEL`, `ID`, `AIR` or `STA`).
+fn parse_record(
+    msg_type: &str,
+    icao: &str,
+    parts: &[&str],
+    timestamp: Option<DateTime<Utc>>,
+) -> Option<AircraftMessage> {
+    let icao = icao.to_string();
+    let value = parts
+        .get(10)
+        .map(|field| field.trim())
+        .filter(|field| !field.is_empty());
+
+    match msg_type {
+        "SEL" => Some(AircraftMessage::Selection {
+            icao,
+            timestamp,
+            callsign: value.map(str::to_string),
+        }),
+        "ID" => Some(AircraftMessage::CallsignChanged {
+            icao,
+            timestamp,
+            callsign: value?.to_string(),
+        }),
+        "AIR" => Some(AircraftMessage::NewAircraft { icao, timestamp }),
+        "STA" => Some(AircraftMessage::StatusChanged {
+            icao,
+            timestamp,
+            status: TrackStatus::from_code(value?),
+        }),
+        _ => None,
+    }
+}
+
+/// Encoder for BaseStation/SBS-1 `MSG` lines, the inverse of [`BaseStationParser`].
+///
+/// Besides encoding single messages, the encoder remembers what it has written
+/// for each aircraft so a tracker's state can be streamed as the changes since
+/// the last lines, the way dump1090 serves port 30003.
+#[derive(Debug, Default)]
+pub struct BaseStationEncoder {
+    utc_timestamps: bool,
+    written: HashMap<String, Written>,
+}
+
+impl BaseStationEncoder {
+    /// Create a new `BaseStation` encoder.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write message timestamps in UTC instead of local time.
+    pub fn set_utc_timestamps(&mut self, utc: bool) {
+        self.utc_timestamps = utc;
+    }
+
+    /// Encode a message as a line, without the line ending.
+    ///
+    /// Identification is written as `MSG,1`, surface positions as `MSG,2`,
+    /// airborne positions as `MSG,3` and velocities as `MSG,4`. Surveillance
+    /// updates are written as `MSG,6` when they carry a squawk, `MSG,5` when
+    /// they carry an altitude and `MSG,8` otherwise; air-to-air (`MSG,7`)
+    /// replies can't be told apart from `MSG,5` once parsed. The generated time
+    /// is the message's timestamp, falling back to `logged`.
+    ///
+    /// Returns `None` for messages the format can't carry.
+    #[must_use]
+    pub fn encode(&self, message: &AircraftMessage, logged: DateTime<Utc>) -> Option<String> {
+        let mut fields = vec![String::new(); 22];
+        let transmission = match message {
+            AircraftMessage::Identification { callsign, .. } => {
+                fields[10].clone_from(callsign);
+                1
+            }
+            AircraftMessage::Position {
+                latitude,
+                longitude,
+                altitude,
+                ground_speed,
+                track,
+                is_on_ground,
+                ..
+            } => {
+                fields[11] = format_field(*altitude);
+                fields[12] = format_field(ground_speed.map(f64::round));
+                fields[13] = format_field(track.map(f64::round));
+                fields[14] = format!("{latitude:.5}");
+                fields[15] = format!("{longitude:.5}");
+                fields[21] = format_bool_flag(*is_on_ground);
+                if ground_speed.is_some() || track.is_some() || *is_on_ground == Some(true) {
+                    2
+                } else {
+                    3
+                }
+            }
+            AircraftMessage::Velocity {
+                speed,
+                track,
+                vertical_rate,
+                is_on_ground,
+                ..
+            } => {
+                fields[12] = format_field(Some(speed.round()));
+                fields[13] = format_field(Some(track.round()));
+                fields[16] = format_field(*vertical_rate);
+                fields[21] = format_bool_flag(*is_on_ground);
+                4
+            }
+            AircraftMessage::Altitude {
+                altitude,
+                squawk,
+                alert,
+                emergency,
+                spi,
+                is_on_ground,
+                ..
+            } => {
+                fields[11] = format_field(*altitude);
+                fields[17] = squawk.clone().unwrap_or_default();
+                fields[18] = format_bool_flag(*alert);
+                fields[19] = format_bool_flag(*emergency);
+                fields[20] = format_bool_flag(*spi);
+                fields[21] = format_bool_flag(*is_on_ground);
+                if squawk.is_some() {
+                    6
+                } else if altitude.is_some() {
+                    5
+                } else {
+                    8
+                }
+            }
+            _ => return None,
+        };
+
+        let (generated_date, generated_time) =
+            self.format_datetime(message.timestamp().unwrap_or(logged));
+        let (logged_date, logged_time) = self.format_datetime(logged);
+        fields[0] = "MSG".to_string();
+        fields[1] = transmission.to_string();
+        fields[2] = "1".to_string();
+        fields[3] = "1".to_string();
+        fields[4] = message.icao().to_string();
+        fields[5] = "1".to_string();
+        fields[6] = generated_date;
+        fields[7] = generated_time;
+        fields[8] = logged_date;
+        fields[9] = logged_time;
+        Some(fields.join(","))
+    }
+
+    /// Encode what changed about an aircraft since it was last passed in.
+    ///
+    /// The first call for an aircraft writes everything known about it. Values
+    /// the aircraft doesn't report are left as written before, so a feed merged
+    /// from several receivers doesn't repeat what only one of them decoded.
+    pub fn encode_changes(&mut self, aircraft: &Aircraft, logged: DateTime<Utc>) -> Vec<String> {
+        let messages = self
+            .written
+            .entry(aircraft.icao.clone())
+            .or_default()
+            .update(aircraft);
+        messages
+            .iter()
+            .filter_map(|message| self.encode(message, logged))
+            .collect()
+    }
+
+    /// Encode everything written so far about every aircraft, for example to
+    /// bring a newly connected client up to date.
+    #[must_use]
+    pub fn snapshot(&self, logged: DateTime<Utc>) -> Vec<String> {
+        self.written
+            .iter()
+            .flat_map(|(icao, written)| written.messages(icao))
+            .filter_map(|message| self.encode(&message, logged))
+            .collect()
+    }
+
+    /// Forget aircraft for which `keep` returns `false`, so they are written in
+    /// full if they come back.
+    pub fn retain(&mut self, mut keep: impl FnMut(&str) -> bool) {
+        self.written.retain(|icao, _| keep(icao));
+    }
+
+    /// Format a SBS-1 date ("2024/01/01") and time ("12:00:00.000") pair.
+    fn format_datetime(&self, time: DateTime<Utc>) -> (String, String) {
+        const DATE: &str = "%Y/%m/%d";
+        const TIME: &str = "%H:%M:%S%.3f";
+
+        if self.utc_timestamps {
+            (time.format(DATE).to_string(), time.format(TIME).to_string())
+        } else {
+            let local = time.with_timezone(&Local);
+            (
+                local.format(DATE).to_string(),
+                local.format(TIME).to_string(),
+            )
+        }
+    }
+}
+
+/// Format an optional field, leaving it empty when absent.
+fn format_field(value: Option<impl Display>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Format a SBS-1 boolean flag field, writing `-1` for true like `BaseStation`.
+fn format_bool_flag(value: Option<bool>) -> String {
+    match value {
+        Some(true) => "-1".to_string(),
+        Some(false) => "0".to_string(),
+        None => String::new(),
+    }
+}
+
+/// Replace `written` with `value` if the aircraft reports a different one.
+fn changed<T: PartialEq>(written: &mut Option<T>, value: Option<T>) -> bool {
+    match value {
+        Some(value) if written.as_ref() != Some(&value) => {
+            *written = Some(value);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// What an encoder has written about an aircraft.
+#[derive(Debug, Default)]
+struct Written {
+    last_seen: Option<DateTime<Utc>>,
+    callsign: Option<String>,
+    position_time: Option<DateTime<Utc>>,
+    position: Option<(f64, f64)>,
+    altitude: Option<i32>,
+    /// Ground speed, track and vertical rate.
+    velocity: Option<(f64, f64, Option<i32>)>,
+    squawk: Option<String>,
+    alert: Option<bool>,
+    emergency: Option<bool>,
+    spi: Option<bool>,
+    is_on_ground: Option<bool>,
+}
+
+impl Written {
+    /// Take in an aircraft's state, returning the messages describing what changed.
+    fn update(&mut self, aircraft: &Aircraft) -> Vec<AircraftMessage> {
+        let icao = &aircraft.icao;
+        self.last_seen = Some(aircraft.last_seen);
+
+        let callsign_changed = changed(&mut self.callsign, aircraft.callsign.clone());
+        let altitude_changed = changed(&mut self.altitude, aircraft.altitude);
+        let ground_changed = changed(&mut self.is_on_ground, aircraft.is_on_ground);
+        let position = aircraft.latitude.zip(aircraft.longitude);
+        let position_changed = position.is_some() && aircraft.position_time > self.position_time;
+        if position_changed {
+            self.position = position;
+            self.position_time = aircraft.position_time;
+        }
+        let velocity = aircraft
+            .velocity
+            .zip(aircraft.track)
+            .map(|(speed, track)| (speed, track, aircraft.vertical_rate));
+        let velocity_changed = changed(&mut self.velocity, velocity);
+        let surveillance_changed = changed(&mut self.squawk, aircraft.squawk.clone())
+            | changed(&mut self.alert, aircraft.alert)
+            | changed(&mut self.emergency, aircraft.emergency)
+            | changed(&mut self.spi, aircraft.spi);
+
+        let mut messages = Vec::new();
+        if callsign_changed {
+            messages.extend(self.identification(icao));
+        }
+        if position_changed {
+            messages.extend(self.position(icao));
+        }
+        if velocity_changed {
+            messages.extend(self.velocity(icao));
+        }
+        if surveillance_changed {
+            messages.extend(self.surveillance(icao));
+        } else if (altitude_changed || ground_changed) && !position_changed {
+            messages.push(AircraftMessage::Altitude {
+                icao: icao.clone(),
+                timestamp: self.last_seen,
+                altitude: self.altitude,
+                squawk: None,
+                alert: None,
+                emergency: None,
+                spi: None,
+                is_on_ground: self.is_on_ground,
+            });
+        }
+        messages
+    }
+
+    /// Messages restating everything written.
+    fn messages(&self, icao: &str) -> Vec<AircraftMessage> {
+        [
+            self.identification(icao),
+            self.position(icao),
+            self.velocity(icao),
+            self.surveillance(icao),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    fn identification(&self, icao: &str) -> Option<AircraftMessage> {
+        Some(AircraftMessage::Identification {
+            icao: icao.to_string(),
+            timestamp: self.last_seen,
+            callsign: self.callsign.clone()?,
+        })
+    }
+
+    /// Position, with speed and track for surface positions.
+    fn position(&self, icao: &str) -> Option<AircraftMessage> {
+        let (latitude, longitude) = self.position?;
+        let surface = self.is_on_ground == Some(true);
+        let velocity = self.velocity.filter(|_| surface);
+        Some(AircraftMessage::Position {
+            icao: icao.to_string(),
+            timestamp: self.position_time,
+            latitude,
+            longitude,
+            altitude: self.altitude,
+            ground_speed: velocity.map(|(speed, _, _)| speed),
+            track: velocity.map(|(_, track, _)| track),
+            is_on_ground: self.is_on_ground,
+        })
+    }
+
+    /// Airborne velocity; surface positions carry their own.
+    fn velocity(&self, icao: &str) -> Option<AircraftMessage> {
+        if self.is_on_ground == Some(true) {
+            return None;
+        }
+        let (speed, track, vertical_rate) = self.velocity?;
+        Some(AircraftMessage::Velocity {
+            icao: icao.to_string(),
+            timestamp: self.last_seen,
+            speed,
+            track,
+            vertical_rate,
+            is_on_ground: self.is_on_ground,
+        })
+    }
+
+    /// Squawk, altitude and status flags.
+    fn surveillance(&self, icao: &str) -> Option<AircraftMessage> {
+        let any = self.altitude.is_some()
+            || self.squawk.is_some()
+            || self.alert.is_some()
+            || self.emergency.is_some()
+            || self.spi.is_some()
+            || self.is_on_ground.is_some();
+        any.then(|| AircraftMessage::Altitude {
+            icao: icao.to_string(),
+            timestamp: self.last_seen,
+            altitude: self.altitude,
+            squawk: self.squawk.clone(),
+            alert: self.alert,
+            emergency: self.emergency,
+            spi: self.spi,
+            is_on_ground: self.is_on_ground,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -304,7 +733,7 @@ mod tests {
         let result = parser.parse(line).unwrap();
         assert!(matches!(
             result,
-            Some(AircraftMessage::Identification { icao, callsign })
+            Some(AircraftMessage::Identification { icao, callsign, .. })
             if icao == "A1B2C3" && callsign == "UAL123"
         ));
     }
@@ -359,7 +788,7 @@ mod tests {
         let result = parser.parse(line).unwrap();
         assert!(matches!(
             result,
-            Some(AircraftMessage::Position { icao, latitude, longitude, altitude, ground_speed, track, is_on_ground })
+            Some(AircraftMessage::Position { icao, latitude, longitude, altitude, ground_speed, track, is_on_ground, .. })
             if icao == "A1B2C3"
                 && (latitude - 33.9425).abs() < 0.0001
                 && (longitude - (-118.4081)).abs() < 0.0001
@@ -389,7 +818,7 @@ mod tests {
         let result = parser.parse(line).unwrap();
         assert!(matches!(
             result,
-            Some(AircraftMessage::Altitude { icao, altitude, squawk, alert, emergency, spi, is_on_ground })
+            Some(AircraftMessage::Altitude { icao, altitude, squawk, alert, emergency, spi, is_on_ground, .. })
             if icao == "A1B2C3"
                 && altitude == Some(30000)
                 && squawk.as_deref() == Some("1200")
@@ -432,7 +861,207 @@ mod tests {
     #[test]
     fn test_parse_non_msg_type() {
         let mut parser = BaseStationParser::new();
-        let result = parser.parse(b"STA,1,1,1,A1B2C3").unwrap();
-        assert!(result.is_none());
+        parser.set_utc_timestamps(true);
+        let timestamp = Some(Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap());
+
+        let result = parser.parse(b"STA,,1,1,A1B2C3,1,2024/01/01,12:00:00.000,2024/01/01,12:00:00.000,RM\r\n").unwrap();
+        assert_eq!(
+            result,
+            Some(AircraftMessage::StatusChanged {
+                icao: "A1B2C3".to_string(),
+                timestamp,
+                status: TrackStatus::Removed,
+            })
+        );
+
+        let result = parser.parse(b"ID,,1,1,A1B2C3,1,2024/01/01,12:00:00.000,2024/01/01,12:00:00.000,UAL456").unwrap();
+        assert_eq!(
+            result,
+            Some(AircraftMessage::CallsignChanged {
+                icao: "A1B2C3".to_string(),
+                timestamp,
+                callsign: "UAL456".to_string(),
+            })
+        );
+
+        let result = parser.parse(b"SEL,,1,1,A1B2C3,1,2024/01/01,12:00:00.000,2024/01/01,12:00:00.000,UAL456").unwrap();
+        assert!(matches!(
+            result,
+            Some(AircraftMessage::Selection { callsign, .. }) if callsign.as_deref() == Some("UAL456")
+        ));
+
+        let result = parser.parse(b"AIR,,1,1,A1B2C3,1,2024/01/01,12:00:00.000,2024/01/01,12:00:00.000").unwrap();
+        assert!(matches!(result, Some(AircraftMessage::NewAircraft { .. })));
+
+        let result = parser.parse(b"CLK,,1,-1,,-1,2024/01/01,12:00:00.000,2024/01/01,12:00:00.000").unwrap();
+        assert_eq!(result, Some(AircraftMessage::Clock { timestamp }));
+        assert_eq!(result.unwrap().icao(), "");
+
+        // Records without their value field produce nothing
+        assert!(parser.parse(b"STA,1,1,1,A1B2C3").unwrap().is_none());
+        assert!(parser.parse(b"ID,,1,1,A1B2C3,1,2024/01/01,12:00:00.000,2024/01/01,12:00:00.000,").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_parse_timestamps() {
+        let line = b"MSG,3,1,1,A1B2C3,1,2024/03/15,08:30:45.250,2024/03/15,08:30:46.000,,35000,,,33.9425,-118.4081,";
+
+        let mut parser = BaseStationParser::new();
+        parser.set_utc_timestamps(true);
+        let expected = Utc.with_ymd_and_hms(2024, 3, 15, 8, 30, 45).unwrap()
+            + chrono::Duration::milliseconds(250);
+        assert_eq!(parser.parse(line).unwrap().unwrap().timestamp(), Some(expected));
+
+        // Local time by default
+        let mut parser = BaseStationParser::new();
+        let local = Local
+            .with_ymd_and_hms(2024, 3, 15, 8, 30, 45)
+            .earliest()
+            .unwrap()
+            .with_timezone(&Utc)
+            + chrono::Duration::milliseconds(250);
+        assert_eq!(parser.parse(line).unwrap().unwrap().timestamp(), Some(local));
+
+        // Falls back to the logged time, and tolerates missing times
+        assert_eq!(
+            parse_timestamp(&["MSG", "3", "", "", "", "", "", "", "2024/03/15", "08:30:46"], true),
+            Some(Utc.with_ymd_and_hms(2024, 3, 15, 8, 30, 46).unwrap())
+        );
+        assert_eq!(parse_timestamp(&["MSG", "3", "", "", "A1B2C3"], true), None);
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let mut encoder = BaseStationEncoder::new();
+        encoder.set_utc_timestamps(true);
+        let mut parser = BaseStationParser::new();
+        parser.set_utc_timestamps(true);
+
+        let icao = "A1B2C3".to_string();
+        let timestamp = Some(
+            Utc.with_ymd_and_hms(2024, 3, 15, 8, 30, 45).unwrap()
+                + chrono::Duration::milliseconds(250),
+        );
+        let logged = Utc.with_ymd_and_hms(2024, 3, 15, 8, 30, 46).unwrap();
+        let messages = [
+            ("MSG,1,", AircraftMessage::Identification {
+                icao: icao.clone(),
+                timestamp,
+                callsign: "UAL123".to_string(),
+            }),
+            ("MSG,2,", AircraftMessage::Position {
+                icao: icao.clone(),
+                timestamp,
+                latitude: 33.9425,
+                longitude: -118.4081,
+                altitude: None,
+                ground_speed: Some(12.0),
+                track: Some(90.0),
+                is_on_ground: Some(true),
+            }),
+            ("MSG,3,", AircraftMessage::Position {
+                icao: icao.clone(),
+                timestamp,
+                latitude: 33.9425,
+                longitude: -118.4081,
+                altitude: Some(35000),
+                ground_speed: None,
+                track: None,
+                is_on_ground: Some(false),
+            }),
+            ("MSG,4,", AircraftMessage::Velocity {
+                icao: icao.clone(),
+                timestamp,
+                speed: 450.0,
+                track: 270.0,
+                vertical_rate: Some(-1500),
+                is_on_ground: None,
+            }),
+            ("MSG,5,", AircraftMessage::Altitude {
+                icao: icao.clone(),
+                timestamp,
+                altitude: Some(35000),
+                squawk: None,
+                alert: None,
+                emergency: None,
+                spi: None,
+                is_on_ground: Some(false),
+            }),
+            ("MSG,6,", AircraftMessage::Altitude {
+                icao: icao.clone(),
+                timestamp,
+                altitude: Some(35000),
+                squawk: Some("7700".to_string()),
+                alert: Some(true),
+                emergency: Some(true),
+                spi: Some(false),
+                is_on_ground: Some(false),
+            }),
+            ("MSG,8,", AircraftMessage::Altitude {
+                icao,
+                timestamp,
+                altitude: None,
+                squawk: None,
+                alert: None,
+                emergency: None,
+                spi: None,
+                is_on_ground: Some(true),
+            }),
+        ];
+
+        for (prefix, message) in messages {
+            let line = encoder.encode(&message, logged).unwrap();
+            assert!(line.starts_with(prefix), "{line}");
+            assert_eq!(line.split(',').count(), 22, "{line}");
+            assert_eq!(parser.parse(line.as_bytes()).unwrap(), Some(message));
+        }
+
+        assert!(encoder
+            .encode(&AircraftMessage::Clock { timestamp }, logged)
+            .is_none());
+    }
+
+    #[test]
+    fn test_encode_changes() {
+        let transmission_types = |lines: Vec<String>| -> Vec<String> {
+            lines
+                .iter()
+                .map(|line| line.split(',').nth(1).unwrap().to_string())
+                .collect()
+        };
+        let now = Utc::now();
+        let mut encoder = BaseStationEncoder::new();
+        let mut aircraft = Aircraft::new("A1B2C3".to_string());
+        aircraft.callsign = Some("UAL123".to_string());
+        aircraft.latitude = Some(33.9425);
+        aircraft.longitude = Some(-118.4081);
+        aircraft.altitude = Some(35000);
+        aircraft.position_time = Some(now);
+        aircraft.velocity = Some(450.0);
+        aircraft.track = Some(270.0);
+        aircraft.squawk = Some("1200".to_string());
+
+        // Everything is written the first time, then only changes
+        let all = ["1", "3", "4", "6"];
+        assert_eq!(transmission_types(encoder.encode_changes(&aircraft, now)), all);
+        assert!(encoder.encode_changes(&aircraft, now).is_empty());
+
+        // A receiver that hasn't decoded the callsign doesn't clear it
+        aircraft.callsign = None;
+        aircraft.latitude = Some(33.95);
+        aircraft.position_time = Some(now + chrono::Duration::seconds(1));
+        assert_eq!(transmission_types(encoder.encode_changes(&aircraft, now)), ["3"]);
+        aircraft.altitude = Some(34900);
+        assert_eq!(transmission_types(encoder.encode_changes(&aircraft, now)), ["5"]);
+
+        // The snapshot restates the latest values
+        let snapshot = encoder.snapshot(now);
+        assert!(snapshot[1].contains(",34900,,,33.95000,-118.40810,"));
+        assert_eq!(transmission_types(snapshot), all);
+
+        // Forgotten aircraft are written in full again
+        encoder.retain(|icao| icao != "A1B2C3");
+        assert!(encoder.snapshot(now).is_empty());
+        assert_eq!(transmission_types(encoder.encode_changes(&aircraft, now)), ["3", "4", "6"]);
     }
 }
@@ -0,0 +1,388 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! BEAST binary protocol parser.
+//!
+//! Parses the binary format served by dump1090/readsb on port 30005.
+//!
+//! Frame format:
+//! ```text
+//! <0x1A> <type> <6-byte timestamp> <1-byte signal> <payload>
+//! ```
+//!
+//! The type byte is `'1'` for a Mode-A/C reply (2 bytes), `'2'` for a short
+//! Mode S frame (7 bytes) or `'3'` for a long Mode S frame (14 bytes). Any
+//! `0x1A` byte after the type byte is doubled on the wire.
+
+use chrono::{DateTime, Utc};
+
+use super::{AircraftMessage, ParseError, Protocol, Signal};
+use crate::mode_s::{self, Decoder, DecoderStats, ErrorCorrection};
+
+/// Escape byte that introduces every BEAST frame.
+const ESCAPE: u8 = 0x1A;
+/// Length of the timestamp and signal header following the type byte.
+const HEADER_LEN: usize = 7;
+/// Receiver clock frequency of the 48-bit timestamp.
+const TIMESTAMP_HZ: f64 = 12_000_000.0;
+
+/// Type of a BEAST frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BeastFrameType {
+    /// Mode-A/C reply.
+    ModeAc,
+    /// Short (56-bit) Mode S frame.
+    ModeSShort,
+    /// Long (112-bit) Mode S frame.
+    ModeSLong,
+}
+
+impl BeastFrameType {
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            b'1' => Some(Self::ModeAc),
+            b'2' => Some(Self::ModeSShort),
+            b'3' => Some(Self::ModeSLong),
+            _ => None,
+        }
+    }
+
+    /// Payload length in bytes.
+    #[must_use]
+    pub fn payload_len(self) -> usize {
+        match self {
+            Self::ModeAc => 2,
+            Self::ModeSShort => mode_s::SHORT_FRAME_LEN,
+            Self::ModeSLong => mode_s::LONG_FRAME_LEN,
+        }
+    }
+}
+
+/// A single de-stuffed BEAST frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BeastFrame {
+    /// Frame type.
+    pub frame_type: BeastFrameType,
+    /// 48-bit receiver timestamp in 12 MHz ticks.
+    pub timestamp: u64,
+    /// Raw signal level byte (0-255).
+    pub signal: u8,
+    /// Mode-A/C or Mode S payload.
+    pub payload: Vec<u8>,
+}
+
+impl BeastFrame {
+    /// Receiver timestamp in seconds.
+    #[must_use]
+    #[allow(
+        clippy::cast_precision_loss,
+        reason = "48-bit timestamp fits in f64 mantissa"
+    )]
+    pub fn timestamp_secs(&self) -> f64 {
+        self.timestamp as f64 / TIMESTAMP_HZ
+    }
+
+    /// Signal level in dBFS, or `None` if the receiver did not report one.
+    #[must_use]
+    pub fn rssi_dbfs(&self) -> Option<f64> {
+        (self.signal > 0).then(|| 20.0 * (f64::from(self.signal) / 255.0).log10())
+    }
+
+    /// Signal level and receiver time of the frame.
+    #[must_use]
+    pub fn signal(&self) -> Signal {
+        Signal {
+            rssi: self.rssi_dbfs(),
+            receiver_time: Some(self.timestamp_secs()),
+        }
+    }
+}
+
+/// Parser for BEAST binary protocol frames.
+///
+/// Each call to [`Protocol::parse`] expects exactly one raw (still escaped)
+/// frame, as delimited by [`crate::tcp::Framing::Beast`].
+#[derive(Debug, Default)]
+pub struct BeastParser {
+    decoder: Decoder,
+    /// Signal of the last parsed frame.
+    signal: Option<Signal>,
+}
+
+impl BeastParser {
+    /// Create a new BEAST parser.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the error correction applied to frames with a bad parity.
+    pub fn set_error_correction(&mut self, mode: ErrorCorrection) {
+        self.decoder.set_error_correction(mode);
+    }
+
+    /// Parse and de-stuff a single raw frame without decoding its payload.
+    pub fn parse_frame(input: &[u8]) -> Result<BeastFrame, ParseError> {
+        if input.len() < 2 || input[0] != ESCAPE {
+            return Err(ParseError::InvalidFormat(
+                "missing 0x1A frame marker".to_string(),
+            ));
+        }
+
+        let frame_type =
+            BeastFrameType::from_byte(input[1]).ok_or_else(|| ParseError::InvalidValue {
+                field: "frame type",
+                value: format!("0x{:02X}", input[1]),
+            })?;
+
+        let expected_len = HEADER_LEN + frame_type.payload_len();
+        let mut body = Vec::with_capacity(expected_len);
+        let mut bytes = input[2..].iter();
+        while let Some(&byte) = bytes.next() {
+            if byte == ESCAPE && bytes.next() != Some(&ESCAPE) {
+                return Err(ParseError::InvalidFormat(
+                    "unescaped 0x1A inside frame".to_string(),
+                ));
+            }
+            body.push(byte);
+        }
+
+        if body.len() != expected_len {
+            return Err(ParseError::InvalidFormat(format!(
+                "expected {expected_len} bytes after frame type, got {}",
+                body.len()
+            )));
+        }
+
+        let timestamp = body[..6]
+            .iter()
+            .fold(0u64, |acc, &b| (acc << 8) | u64::from(b));
+
+        Ok(BeastFrame {
+            frame_type,
+            timestamp,
+            signal: body[6],
+            payload: body[HEADER_LEN..].to_vec(),
+        })
+    }
+}
+
+impl Protocol for BeastParser {
+    type Message = AircraftMessage;
+    type Error = ParseError;
+
+    fn parse(&mut self, input: &[u8]) -> Result<Option<AircraftMessage>, ParseError> {
+        self.parse_at(input, Utc::now())
+    }
+
+    fn parse_at(
+        &mut self,
+        input: &[u8],
+        now: DateTime<Utc>,
+    ) -> Result<Option<AircraftMessage>, ParseError> {
+        let frame = Self::parse_frame(input)?;
+        self.signal = Some(frame.signal());
+
+        match frame.frame_type {
+            // Mode-A/C replies carry no address, so they cannot be attributed
+            BeastFrameType::ModeAc => Ok(None),
+            BeastFrameType::ModeSShort | BeastFrameType::ModeSLong => {
+                self.decoder.decode_at(&frame.payload, now)
+            }
+        }
+    }
+
+    fn set_reference(&mut self, reference: Option<(f64, f64)>) {
+        self.decoder.set_reference(reference);
+    }
+
+    fn set_receiver_range(&mut self, range_nm: Option<f64>) {
+        self.decoder.set_receiver_range(range_nm);
+    }
+
+    fn decoder_stats(&self) -> Option<DecoderStats> {
+        Some(self.decoder.stats())
+    }
+
+    fn signal(&self) -> Option<Signal> {
+        self.signal
+    }
+}
+
+/// Result of scanning a byte buffer for the next BEAST frame.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum FrameScan {
+    /// The buffer holds an incomplete frame; read more data.
+    NeedMore,
+    /// The first `n` bytes are not part of a valid frame and should be dropped.
+    Discard(usize),
+    /// The first `n` bytes form one complete raw frame.
+    Frame(usize),
+}
+
+/// Find the boundary of the next raw frame at the start of `buf`.
+pub(crate) fn scan_frame(buf: &[u8]) -> FrameScan {
+    match buf.first() {
+        None => return FrameScan::NeedMore,
+        Some(&ESCAPE) => {}
+        Some(_) => {
+            let skip = buf.iter().position(|&b| b == ESCAPE).unwrap_or(buf.len());
+            return FrameScan::Discard(skip);
+        }
+    }
+
+    let Some(&type_byte) = buf.get(1) else {
+        return FrameScan::NeedMore;
+    };
+    let Some(frame_type) = BeastFrameType::from_byte(type_byte) else {
+        return FrameScan::Discard(1);
+    };
+
+    let body_len = HEADER_LEN + frame_type.payload_len();
+    let mut pos = 2;
+    for _ in 0..body_len {
+        match buf.get(pos) {
+            None => return FrameScan::NeedMore,
+            Some(&ESCAPE) => match buf.get(pos + 1) {
+                None => return FrameScan::NeedMore,
+                Some(&ESCAPE) => pos += 2,
+                // A lone escape starts a new frame; the current one is truncated
+                Some(_) => return FrameScan::Discard(pos),
+            },
+            Some(_) => pos += 1,
+        }
+    }
+
+    FrameScan::Frame(pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mode_s::hex_to_bytes;
+
+    /// Build an escaped BEAST frame from its parts.
+    fn beast_frame(frame_type: u8, timestamp: u64, signal: u8, payload: &[u8]) -> Vec<u8> {
+        let mut body = timestamp.to_be_bytes()[2..].to_vec();
+        body.push(signal);
+        body.extend_from_slice(payload);
+
+        let mut frame = vec![ESCAPE, frame_type];
+        for byte in body {
+            frame.push(byte);
+            if byte == ESCAPE {
+                frame.push(ESCAPE);
+            }
+        }
+        frame
+    }
+
+    #[test]
+    fn test_parse_long_frame() {
+        let payload = hex_to_bytes("8D4840D6202CC371C32CE0576098");
+        let raw = beast_frame(b'3', 0x0012_3456_789A, 0x80, &payload);
+
+        let frame = BeastParser::parse_frame(&raw).unwrap();
+        assert_eq!(frame.frame_type, BeastFrameType::ModeSLong);
+        assert_eq!(frame.timestamp, 0x0012_3456_789A);
+        assert_eq!(frame.signal, 0x80);
+        assert_eq!(frame.payload, payload);
+
+        let msg = BeastParser::new().parse(&raw).unwrap().unwrap();
+        assert_eq!(
+            msg,
+            AircraftMessage::Identification {
+                icao: "4840D6".to_string(),
+                timestamp: None,
+                callsign: "KLM1023".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_destuff_escaped_bytes() {
+        // Timestamp and signal both contain 0x1A and must be de-stuffed
+        let payload = hex_to_bytes("5D4840D6ABCDEF");
+        let raw = beast_frame(b'2', 0x1A1A_0000_001A, ESCAPE, &payload);
+        assert_eq!(raw.len(), 2 + HEADER_LEN + payload.len() + 4);
+
+        let frame = BeastParser::parse_frame(&raw).unwrap();
+        assert_eq!(frame.frame_type, BeastFrameType::ModeSShort);
+        assert_eq!(frame.timestamp, 0x1A1A_0000_001A);
+        assert_eq!(frame.signal, ESCAPE);
+        assert_eq!(frame.payload, payload);
+    }
+
+    #[test]
+    fn test_mode_ac_frame_produces_no_message() {
+        let raw = beast_frame(b'1', 1, 10, &[0x12, 0x34]);
+        assert_eq!(BeastParser::new().parse(&raw).unwrap(), None);
+    }
+
+    #[test]
+    fn test_invalid_frames() {
+        assert!(BeastParser::parse_frame(&[0x00, b'3']).is_err());
+        assert!(BeastParser::parse_frame(&[ESCAPE, b'9', 0, 0]).is_err());
+        // Truncated payload
+        assert!(BeastParser::parse_frame(&[ESCAPE, b'2', 0, 0, 0, 0, 0, 0, 0, 0x5D]).is_err());
+    }
+
+    #[test]
+    fn test_timestamp_and_rssi() {
+        let frame = BeastFrame {
+            frame_type: BeastFrameType::ModeSShort,
+            timestamp: 12_000_000,
+            signal: 255,
+            payload: Vec::new(),
+        };
+        assert!((frame.timestamp_secs() - 1.0).abs() < f64::EPSILON);
+        assert!(frame.rssi_dbfs().unwrap().abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_signal_reaches_tracked_aircraft() {
+        let raw = beast_frame(
+            b'3',
+            24_000_000,
+            255,
+            &hex_to_bytes("8D4840D6202CC371C32CE0576098"),
+        );
+        let mut parser = BeastParser::new();
+        let msg = parser.parse(&raw).unwrap().unwrap();
+        let signal = parser.signal().unwrap();
+        assert!((signal.receiver_time.unwrap() - 2.0).abs() < f64::EPSILON);
+
+        let mut tracker = crate::AircraftTracker::new(crate::TrackerConfig::default());
+        tracker.process_message_with_signal(msg, Some(signal), chrono::Utc::now());
+        let aircraft = tracker.get_by_icao("4840D6").unwrap();
+        assert!(aircraft.rssi.unwrap().abs() < f64::EPSILON);
+        assert_eq!(aircraft.receiver_time, signal.receiver_time);
+    }
+
+    #[test]
+    fn test_scan_frame() {
+        let raw = beast_frame(b'2', 0x1A, 0x1A, &hex_to_bytes("5D4840D6ABCDEF"));
+
+        let mut stream = vec![0xFF, 0x00];
+        stream.extend_from_slice(&raw);
+        assert_eq!(scan_frame(&stream), FrameScan::Discard(2));
+        assert_eq!(scan_frame(&raw), FrameScan::Frame(raw.len()));
+        assert_eq!(scan_frame(&raw[..raw.len() - 1]), FrameScan::NeedMore);
+
+        // A new frame marker inside a truncated frame resynchronises
+        let mut truncated = raw[..5].to_vec();
+        truncated.extend_from_slice(&raw);
+        assert_eq!(scan_frame(&truncated), FrameScan::Discard(5));
+    }
+}
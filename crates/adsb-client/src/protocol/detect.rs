@@ -0,0 +1,310 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Feed format detection.
+//!
+//! Classifies a feed from its first bytes, so a connection can pick the right
+//! framing and parser without being told which port it was pointed at:
+//!
+//! - BEAST: binary frames introduced by a `0x1A` escape and a type byte
+//! - SBS: CSV lines starting with a record type (`MSG,`, `AIR,`, ...)
+//! - AVR: hex lines like `*8D4840D6...;` or `@0123456789AB8D48...;`
+//! - UAT raw: dump978 hex lines starting with `-` or `+`
+//! - JSON: one object per line, as served by dump978-fa
+//!
+//! Text feeds never contain the BEAST escape byte, so a single escape
+//! followed by a valid type byte is enough to recognise a binary feed. Text
+//! feeds are classified from their first complete line that matches a
+//! known format; a partial line at the start of the buffer is skipped.
+
+use chrono::{DateTime, Utc};
+
+use super::{AircraftMessage, ParseError, Protocol, Signal};
+use crate::mode_s::DecoderStats;
+use crate::tcp::Framing;
+use crate::{BoxedParser, ProtocolType};
+
+/// BEAST escape byte.
+const ESCAPE: u8 = 0x1A;
+/// SBS record types that start a line.
+const SBS_RECORD_TYPES: [&str; 6] = ["MSG", "SEL", "ID", "AIR", "STA", "CLK"];
+
+/// Wire format of a feed, as detected from its first bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DetectedFormat {
+    /// BaseStation/SBS-1 CSV.
+    Sbs,
+    /// AVR raw hex frames.
+    Avr,
+    /// BEAST binary frames.
+    Beast,
+    /// dump978 raw UAT frames.
+    UatRaw,
+    /// JSON objects, one per line (dump978-fa).
+    Json,
+}
+
+impl DetectedFormat {
+    /// Protocol whose parser handles this format.
+    #[must_use]
+    pub fn protocol(self) -> ProtocolType {
+        match self {
+            Self::Sbs => ProtocolType::BaseStation,
+            Self::Avr => ProtocolType::Avr,
+            Self::Beast => ProtocolType::Beast,
+            Self::UatRaw | Self::Json => ProtocolType::Uat,
+        }
+    }
+
+    /// Stream framing for this format.
+    #[must_use]
+    pub fn framing(self) -> Framing {
+        match self {
+            Self::Beast => Framing::Beast,
+            Self::Sbs | Self::Avr | Self::UatRaw | Self::Json => Framing::Lines,
+        }
+    }
+
+    /// Human-readable format name.
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Sbs => "SBS CSV",
+            Self::Avr => "AVR hex",
+            Self::Beast => "BEAST binary",
+            Self::UatRaw => "UAT raw",
+            Self::Json => "JSON",
+        }
+    }
+}
+
+/// Classify a feed from the bytes received at the start of the stream.
+///
+/// Returns `None` while the buffer does not yet hold enough data to decide,
+/// or when nothing in it matches a known format. Callers decide how many
+/// bytes to wait for before giving up.
+#[must_use]
+pub fn detect_format(data: &[u8]) -> Option<DetectedFormat> {
+    if data
+        .windows(2)
+        .any(|pair| pair[0] == ESCAPE && matches!(pair[1], b'1'..=b'3'))
+    {
+        return Some(DetectedFormat::Beast);
+    }
+
+    // Only complete lines can be classified
+    let end = data.iter().rposition(|&b| b == b'\n')?;
+    data[..end].split(|&b| b == b'\n').find_map(classify_line)
+}
+
+/// Classify a single line of a text feed, without its terminator.
+#[must_use]
+pub fn classify_line(line: &[u8]) -> Option<DetectedFormat> {
+    let line = std::str::from_utf8(line).ok()?.trim();
+
+    if let Some((record_type, _)) = line.split_once(',') {
+        if SBS_RECORD_TYPES.contains(&record_type) {
+            return Some(DetectedFormat::Sbs);
+        }
+    }
+
+    let mut chars = line.chars();
+    let first = chars.next()?;
+    let rest = chars.as_str();
+    match first {
+        '*' | '@' => {
+            let hex = rest.strip_suffix(';')?;
+            is_hex(hex).then_some(DetectedFormat::Avr)
+        }
+        '-' | '+' => {
+            // Metadata follows the frame after the first ';'
+            let hex = rest.split(';').next()?;
+            is_hex(hex).then_some(DetectedFormat::UatRaw)
+        }
+        '{' => line.ends_with('}').then_some(DetectedFormat::Json),
+        _ => None,
+    }
+}
+
+fn is_hex(text: &str) -> bool {
+    !text.is_empty() && text.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Parser that detects the format from the first recognisable message and
+/// delegates to that protocol's parser from then on.
+///
+/// Messages received before the format is known are skipped. Each frame must
+/// already be split with the right [`Framing`], which a connection using
+/// [`Framing::Detect`] takes care of.
+#[derive(Default)]
+pub struct AutoParser {
+    inner: Option<(DetectedFormat, BoxedParser)>,
+    reference: Option<(f64, f64)>,
+    receiver_range_nm: Option<f64>,
+}
+
+impl std::fmt::Debug for AutoParser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AutoParser")
+            .field("format", &self.format())
+            .field("reference", &self.reference)
+            .field("receiver_range_nm", &self.receiver_range_nm)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AutoParser {
+    /// Create a new auto-detecting parser.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The detected format, once a message has been recognised.
+    #[must_use]
+    pub fn format(&self) -> Option<DetectedFormat> {
+        self.inner.as_ref().map(|(format, _)| *format)
+    }
+}
+
+impl Protocol for AutoParser {
+    type Message = AircraftMessage;
+    type Error = ParseError;
+
+    fn parse(&mut self, input: &[u8]) -> Result<Option<AircraftMessage>, ParseError> {
+        self.parse_at(input, Utc::now())
+    }
+
+    fn parse_at(
+        &mut self,
+        input: &[u8],
+        now: DateTime<Utc>,
+    ) -> Result<Option<AircraftMessage>, ParseError> {
+        if self.inner.is_none() {
+            let format = if input.first() == Some(&ESCAPE) {
+                Some(DetectedFormat::Beast)
+            } else {
+                classify_line(input)
+            };
+            let Some(format) = format else {
+                return Ok(None);
+            };
+
+            let mut parser = format.protocol().parser();
+            parser.set_reference(self.reference);
+            parser.set_receiver_range(self.receiver_range_nm);
+            self.inner = Some((format, parser));
+        }
+
+        match &mut self.inner {
+            Some((_, parser)) => parser.parse_at(input, now),
+            None => Ok(None),
+        }
+    }
+
+    fn set_reference(&mut self, reference: Option<(f64, f64)>) {
+        self.reference = reference;
+        if let Some((_, parser)) = &mut self.inner {
+            parser.set_reference(reference);
+        }
+    }
+
+    fn set_receiver_range(&mut self, range_nm: Option<f64>) {
+        self.receiver_range_nm = range_nm;
+        if let Some((_, parser)) = &mut self.inner {
+            parser.set_receiver_range(range_nm);
+        }
+    }
+
+    fn decoder_stats(&self) -> Option<DecoderStats> {
+        self.inner
+            .as_ref()
+            .and_then(|(_, parser)| parser.decoder_stats())
+    }
+
+    fn signal(&self) -> Option<Signal> {
+        self.inner.as_ref().and_then(|(_, parser)| parser.signal())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_text_formats() {
+        assert_eq!(
+            detect_format(
+                b"MSG,3,1,1,A1B2C3,1,2024/01/01,12:00:00.000,2024/01/01,12:00:00.000,,35000\r\n"
+            ),
+            Some(DetectedFormat::Sbs)
+        );
+        assert_eq!(
+            detect_format(b"*8D4840D6202CC371C32CE0576098;\n"),
+            Some(DetectedFormat::Avr)
+        );
+        assert_eq!(
+            detect_format(b"@0123456789AB8D4840D6202CC371C32CE0576098;\n"),
+            Some(DetectedFormat::Avr)
+        );
+        assert_eq!(
+            detect_format(b"-0B28C5A4;rs=1;rssi=-12.3;\n"),
+            Some(DetectedFormat::UatRaw)
+        );
+        assert_eq!(
+            detect_format(b"{\"address\":\"a1b2c3\",\"address_qualifier\":\"adsb_icao\"}\n"),
+            Some(DetectedFormat::Json)
+        );
+    }
+
+    #[test]
+    fn test_detect_needs_complete_line() {
+        assert_eq!(detect_format(b""), None);
+        assert_eq!(detect_format(b"MSG,3,1,1,A1B2"), None);
+        // A partial first line is skipped in favour of the next complete one
+        assert_eq!(
+            detect_format(b"0576098;\n*8D4840D6202CC371C32CE0576098;\n"),
+            Some(DetectedFormat::Avr)
+        );
+        assert_eq!(detect_format(b"HTTP/1.1 400 Bad Request\r\n"), None);
+    }
+
+    #[test]
+    fn test_detect_beast() {
+        let frame = [0x1A, b'3', 0, 0, 0, 0, 0, 1, 0x40, 0x8D, 0x48, 0x40];
+        assert_eq!(detect_format(&frame), Some(DetectedFormat::Beast));
+        // The tail of a frame holds no escape, so nothing is recognised yet
+        assert_eq!(detect_format(&frame[5..]), None);
+        // Bytes before the next escape are skipped
+        let mut data = vec![0x12, 0x34];
+        data.extend_from_slice(&frame);
+        assert_eq!(detect_format(&data), Some(DetectedFormat::Beast));
+    }
+
+    #[test]
+    fn test_auto_parser_delegates() {
+        let mut parser = AutoParser::new();
+        assert_eq!(parser.parse(b"garbage").unwrap(), None);
+        assert_eq!(parser.format(), None);
+
+        let msg = parser
+            .parse(b"MSG,1,1,1,A1B2C3,1,2024/01/01,12:00:00.000,2024/01/01,12:00:00.000,UAL123")
+            .unwrap()
+            .unwrap();
+        assert_eq!(msg.icao(), "A1B2C3");
+        assert_eq!(parser.format(), Some(DetectedFormat::Sbs));
+        assert_eq!(DetectedFormat::Sbs.protocol(), ProtocolType::BaseStation);
+    }
+}
@@ -15,17 +15,31 @@
 //! Protocol layer for ADS-B message parsing.
 //!
 //! This module provides a trait-based abstraction for extensible protocol support.
-//! Currently implements BaseStation/SBS-1 protocol, with future support planned
-//! for BEAST, AVR, and other formats.
+//! Currently implements the BaseStation/SBS-1 text, BEAST binary and AVR raw
+//! hex protocols for 1090 MHz, and dump978 raw and JSON output for UAT.
+//! [`AutoParser`] detects which of these a feed carries from its first
+//! messages.
 
+mod avr;
 mod basestation;
+pub(crate) mod beast;
+mod detect;
+mod uat;
 
-pub use basestation::BaseStationParser;
+pub use avr::{AvrFrame, AvrParser};
+pub use basestation::{BaseStationEncoder, BaseStationParser};
+pub use beast::{BeastFrame, BeastFrameType, BeastParser};
+pub use detect::{classify_line, detect_format, AutoParser, DetectedFormat};
+pub use uat::UatParser;
 
+use chrono::{DateTime, Utc};
 use thiserror::Error;
 
+use crate::mode_s::comm_b::{CommBRegister, Inferred};
+use crate::mode_s::DecoderStats;
+
 /// Errors that can occur during message parsing.
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, Error)]
 pub enum ParseError {
     #[error("invalid message format: {0}")]
     InvalidFormat(String),
@@ -47,6 +61,8 @@ pub enum AircraftMessage {
     Identification {
         /// ICAO 24-bit address (hex string, e.g., "A1B2C3").
         icao: String,
+        /// Time the message was generated, when the feed reports it.
+        timestamp: Option<DateTime<Utc>>,
         /// Aircraft callsign (e.g., "UAL123").
         callsign: String,
     },
@@ -55,6 +71,8 @@ pub enum AircraftMessage {
     Position {
         /// ICAO 24-bit address.
         icao: String,
+        /// Time the message was generated, when the feed reports it.
+        timestamp: Option<DateTime<Utc>>,
         /// Latitude in degrees.
         latitude: f64,
         /// Longitude in degrees.
@@ -73,6 +91,8 @@ pub enum AircraftMessage {
     Velocity {
         /// ICAO 24-bit address.
         icao: String,
+        /// Time the message was generated, when the feed reports it.
+        timestamp: Option<DateTime<Utc>>,
         /// Ground speed in knots.
         speed: f64,
         /// Track angle in degrees (0-360, north = 0).
@@ -87,6 +107,8 @@ pub enum AircraftMessage {
     Altitude {
         /// ICAO 24-bit address.
         icao: String,
+        /// Time the message was generated, when the feed reports it.
+        timestamp: Option<DateTime<Utc>>,
         /// Altitude in feet (absent in some MSG types like MSG,8).
         altitude: Option<i32>,
         /// Squawk code (transponder code).
@@ -100,17 +122,413 @@ pub enum AircraftMessage {
         /// Whether the aircraft is on the ground.
         is_on_ground: Option<bool>,
     },
+
+    /// Emergency/priority status or TCAS resolution advisory (ADS-B TC 28).
+    AircraftStatus {
+        /// ICAO 24-bit address.
+        icao: String,
+        /// Time the message was generated, when the feed reports it.
+        timestamp: Option<DateTime<Utc>>,
+        /// Emergency/priority state (subtype 1).
+        emergency_state: Option<EmergencyState>,
+        /// Squawk code broadcast with the emergency state (subtype 1).
+        squawk: Option<String>,
+        /// Active resolution advisory (subtype 2).
+        resolution_advisory: Option<ResolutionAdvisory>,
+    },
+
+    /// Target state and status (ADS-B TC 29, version 2).
+    TargetState {
+        /// ICAO 24-bit address.
+        icao: String,
+        /// Time the message was generated, when the feed reports it.
+        timestamp: Option<DateTime<Utc>>,
+        /// Selected altitude in feet.
+        selected_altitude: Option<i32>,
+        /// Whether the selected altitude comes from the MCP/FCU or the FMS.
+        selected_altitude_source: Option<SelectedAltitudeSource>,
+        /// Selected heading in degrees.
+        selected_heading: Option<f64>,
+        /// Barometric pressure setting (QNH) in hPa.
+        baro_setting: Option<f64>,
+        /// Engaged autopilot modes, if the aircraft reports them.
+        autopilot_modes: Option<AutopilotModes>,
+    },
+
+    /// Aircraft operational status (ADS-B TC 31).
+    OperationalStatus {
+        /// ICAO 24-bit address.
+        icao: String,
+        /// Time the message was generated, when the feed reports it.
+        timestamp: Option<DateTime<Utc>>,
+        /// ADS-B version number (0, 1 or 2).
+        version: u8,
+        /// NIC supplement A.
+        nic_supplement_a: bool,
+        /// Navigation accuracy category for position (`NACp`).
+        nac_p: u8,
+        /// Source integrity level (SIL).
+        sil: u8,
+        /// Whether the aircraft is on the ground (surface subtype).
+        is_on_ground: bool,
+    },
+
+    /// Comm-B reply (DF20/21) with an inferred Enhanced Surveillance register.
+    CommB {
+        /// ICAO 24-bit address.
+        icao: String,
+        /// Time the message was generated, when the feed reports it.
+        timestamp: Option<DateTime<Utc>>,
+        /// Altitude in feet (DF20 only).
+        altitude: Option<i32>,
+        /// Squawk code (DF21 only).
+        squawk: Option<String>,
+        /// Decoded register and the confidence of its inference.
+        register: Inferred<CommBRegister>,
+    },
+
+    /// Complete state report from a link that sends all fields at once (UAT).
+    StateVector {
+        /// ICAO 24-bit address, prefixed with `~` for non-ICAO addresses.
+        icao: String,
+        /// Time the message was generated, when the feed reports it.
+        timestamp: Option<DateTime<Utc>>,
+        /// Link the report was received on.
+        source: DataSource,
+        /// Latitude in degrees.
+        latitude: Option<f64>,
+        /// Longitude in degrees.
+        longitude: Option<f64>,
+        /// Altitude in feet.
+        altitude: Option<i32>,
+        /// Ground speed in knots.
+        ground_speed: Option<f64>,
+        /// Track angle in degrees (0-360, north = 0).
+        track: Option<f64>,
+        /// Vertical rate in feet per minute.
+        vertical_rate: Option<i32>,
+        /// Whether the aircraft is on the ground.
+        is_on_ground: Option<bool>,
+        /// Aircraft callsign.
+        callsign: Option<String>,
+        /// Squawk code (UAT flight plan ID).
+        squawk: Option<String>,
+        /// Emergency/priority state.
+        emergency_state: Option<EmergencyState>,
+    },
+
+    /// Aircraft selected by the user of the sending application (SBS `SEL`).
+    Selection {
+        /// ICAO 24-bit address.
+        icao: String,
+        /// Time the message was generated, when the feed reports it.
+        timestamp: Option<DateTime<Utc>>,
+        /// Callsign of the selected aircraft.
+        callsign: Option<String>,
+    },
+
+    /// Callsign change (SBS `ID`).
+    CallsignChanged {
+        /// ICAO 24-bit address.
+        icao: String,
+        /// Time the message was generated, when the feed reports it.
+        timestamp: Option<DateTime<Utc>>,
+        /// New callsign.
+        callsign: String,
+    },
+
+    /// First message from a new aircraft (SBS `AIR`).
+    NewAircraft {
+        /// ICAO 24-bit address.
+        icao: String,
+        /// Time the message was generated, when the feed reports it.
+        timestamp: Option<DateTime<Utc>>,
+    },
+
+    /// Change in the sender's tracking status of an aircraft (SBS `STA`).
+    StatusChanged {
+        /// ICAO 24-bit address.
+        icao: String,
+        /// Time the message was generated, when the feed reports it.
+        timestamp: Option<DateTime<Utc>>,
+        /// New tracking status.
+        status: TrackStatus,
+    },
+
+    /// Time signal from the sender, not tied to an aircraft (SBS `CLK`).
+    ///
+    /// [`AircraftMessage::icao`] returns an empty string for this variant.
+    Clock {
+        /// Time the message was generated, when the feed reports it.
+        timestamp: Option<DateTime<Utc>>,
+    },
+}
+
+/// Tracking status reported by an SBS `STA` message.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TrackStatus {
+    /// Position lost (`PL`).
+    PositionLost,
+    /// Signal lost (`SL`).
+    SignalLost,
+    /// Aircraft removed from tracking (`RM`).
+    Removed,
+    /// Aircraft deleted by the user (`AD`).
+    Deleted,
+    /// Tracking resumed (`OK`).
+    Ok,
+    /// Any other status code.
+    Other(String),
+}
+
+impl TrackStatus {
+    /// Parse a two-letter status code.
+    #[must_use]
+    pub fn from_code(code: &str) -> Self {
+        match code {
+            "PL" => Self::PositionLost,
+            "SL" => Self::SignalLost,
+            "RM" => Self::Removed,
+            "AD" => Self::Deleted,
+            "OK" => Self::Ok,
+            other => Self::Other(other.to_string()),
+        }
+    }
+
+    /// Whether the sender has stopped tracking the aircraft.
+    #[must_use]
+    pub fn is_removal(&self) -> bool {
+        matches!(self, Self::Removed | Self::Deleted)
+    }
+}
+
+/// Radio link an aircraft's data was received on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DataSource {
+    /// 1090 MHz Mode S and extended squitter (default).
+    #[default]
+    ModeS,
+    /// 978 MHz Universal Access Transceiver.
+    Uat,
+}
+
+impl DataSource {
+    /// Short label for display.
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::ModeS => "1090",
+            Self::Uat => "UAT",
+        }
+    }
+}
+
+/// Receiver measurements of a raw frame, for protocols that report them.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Signal {
+    /// Signal level in dBFS.
+    pub rssi: Option<f64>,
+    /// Receiver clock at reception in seconds (48-bit 12 MHz MLAT counter).
+    pub receiver_time: Option<f64>,
+}
+
+/// Emergency/priority state broadcast in ADS-B TC 28 subtype 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EmergencyState {
+    /// No emergency.
+    None,
+    /// General emergency (squawk 7700).
+    General,
+    /// Lifeguard/medical emergency.
+    Medical,
+    /// Minimum fuel.
+    MinimumFuel,
+    /// No communications (squawk 7600).
+    NoCommunications,
+    /// Unlawful interference (squawk 7500).
+    UnlawfulInterference,
+    /// Downed aircraft.
+    Downed,
+    /// Reserved value.
+    Reserved,
+}
+
+impl EmergencyState {
+    /// Decode the 3-bit emergency state field.
+    #[must_use]
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            0 => Self::None,
+            1 => Self::General,
+            2 => Self::Medical,
+            3 => Self::MinimumFuel,
+            4 => Self::NoCommunications,
+            5 => Self::UnlawfulInterference,
+            6 => Self::Downed,
+            _ => Self::Reserved,
+        }
+    }
+
+    /// Whether this state indicates an emergency.
+    #[must_use]
+    pub fn is_emergency(self) -> bool {
+        !matches!(self, Self::None | Self::Reserved)
+    }
+
+    /// Short human-readable description.
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::None => "No emergency",
+            Self::General => "General emergency",
+            Self::Medical => "Lifeguard/medical",
+            Self::MinimumFuel => "Minimum fuel",
+            Self::NoCommunications => "No communications",
+            Self::UnlawfulInterference => "Unlawful interference",
+            Self::Downed => "Downed aircraft",
+            Self::Reserved => "Reserved",
+        }
+    }
+}
+
+/// TCAS resolution advisory broadcast in ADS-B TC 28 subtype 2.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolutionAdvisory {
+    /// Active resolution advisories (14-bit ARA field).
+    pub active_advisories: u16,
+    /// Resolution advisory complements (4-bit RAC field).
+    pub complements: u8,
+    /// Whether the advisory has been terminated.
+    pub terminated: bool,
+    /// Whether more than one threat is being resolved.
+    pub multiple_threats: bool,
+    /// ICAO address of the threat aircraft, if identified.
+    pub threat_icao: Option<String>,
+}
+
+impl ResolutionAdvisory {
+    /// Whether a single-threat advisory is corrective (as opposed to preventive).
+    #[must_use]
+    pub fn is_corrective(&self) -> bool {
+        self.active_advisories & 0x2000 != 0 && self.active_advisories & 0x1000 != 0
+    }
+
+    /// Whether a single-threat advisory has a downward sense.
+    #[must_use]
+    pub fn is_downward(&self) -> bool {
+        self.active_advisories & 0x2000 != 0 && self.active_advisories & 0x0800 != 0
+    }
+}
+
+/// Source of the selected altitude in ADS-B TC 29.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SelectedAltitudeSource {
+    /// Mode control panel / flight control unit.
+    Mcp,
+    /// Flight management system.
+    Fms,
+}
+
+impl SelectedAltitudeSource {
+    /// Short label for display.
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Mcp => "MCP",
+            Self::Fms => "FMS",
+        }
+    }
+}
+
+/// Autopilot and navigation modes reported in ADS-B TC 29.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[allow(
+    clippy::struct_excessive_bools,
+    reason = "mirrors the independent mode flags of the message"
+)]
+pub struct AutopilotModes {
+    /// Autopilot engaged.
+    pub autopilot: bool,
+    /// Vertical navigation mode engaged.
+    pub vnav: bool,
+    /// Altitude hold mode engaged.
+    pub altitude_hold: bool,
+    /// Approach mode engaged.
+    pub approach: bool,
+    /// Lateral navigation mode engaged.
+    pub lnav: bool,
+    /// TCAS operational.
+    pub tcas: bool,
+}
+
+impl std::fmt::Display for AutopilotModes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let modes = [
+            (self.autopilot, "AP"),
+            (self.vnav, "VNAV"),
+            (self.altitude_hold, "ALT"),
+            (self.approach, "APP"),
+            (self.lnav, "LNAV"),
+            (self.tcas, "TCAS"),
+        ];
+        let active: Vec<&str> = modes
+            .iter()
+            .filter(|(engaged, _)| *engaged)
+            .map(|(_, label)| *label)
+            .collect();
+
+        if active.is_empty() {
+            f.write_str("None")
+        } else {
+            f.write_str(&active.join(" "))
+        }
+    }
 }
 
 impl AircraftMessage {
     /// Get the ICAO address from any message variant.
+    ///
+    /// Returns an empty string for [`AircraftMessage::Clock`].
     #[must_use]
     pub fn icao(&self) -> &str {
         match self {
             Self::Identification { icao, .. }
             | Self::Position { icao, .. }
             | Self::Velocity { icao, .. }
-            | Self::Altitude { icao, .. } => icao,
+            | Self::Altitude { icao, .. }
+            | Self::AircraftStatus { icao, .. }
+            | Self::TargetState { icao, .. }
+            | Self::OperationalStatus { icao, .. }
+            | Self::CommB { icao, .. }
+            | Self::StateVector { icao, .. }
+            | Self::Selection { icao, .. }
+            | Self::CallsignChanged { icao, .. }
+            | Self::NewAircraft { icao, .. }
+            | Self::StatusChanged { icao, .. } => icao,
+            Self::Clock { .. } => "",
+        }
+    }
+
+    /// Get the time the message was generated, when the feed reports it.
+    #[must_use]
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Identification { timestamp, .. }
+            | Self::Position { timestamp, .. }
+            | Self::Velocity { timestamp, .. }
+            | Self::Altitude { timestamp, .. }
+            | Self::AircraftStatus { timestamp, .. }
+            | Self::TargetState { timestamp, .. }
+            | Self::OperationalStatus { timestamp, .. }
+            | Self::CommB { timestamp, .. }
+            | Self::StateVector { timestamp, .. }
+            | Self::Selection { timestamp, .. }
+            | Self::CallsignChanged { timestamp, .. }
+            | Self::NewAircraft { timestamp, .. }
+            | Self::StatusChanged { timestamp, .. }
+            | Self::Clock { timestamp } => *timestamp,
         }
     }
 }
@@ -130,4 +548,55 @@ pub trait Protocol {
     /// `Ok(None)` if the input is valid but doesn't produce a message,
     /// or `Err(error)` if parsing failed.
     fn parse(&mut self, input: &[u8]) -> Result<Option<Self::Message>, Self::Error>;
+
+    /// Parse input bytes received at `now` on the feed clock.
+    ///
+    /// Protocols that decode raw Mode S frames measure frame ages (CPR
+    /// pairing, known addresses) on this clock, so recorded feeds decode the
+    /// same as they did live. The default ignores the time and calls
+    /// [`parse`](Self::parse).
+    fn parse_at(
+        &mut self,
+        input: &[u8],
+        _now: DateTime<Utc>,
+    ) -> Result<Option<Self::Message>, Self::Error> {
+        self.parse(input)
+    }
+
+    /// Set the receiver position (lat, lon) used to resolve positions, or
+    /// clear it when the receiver location is unknown.
+    ///
+    /// Only protocols that carry raw Mode S frames need a reference (for
+    /// local and surface CPR decoding). The default does nothing.
+    fn set_reference(&mut self, _reference: Option<(f64, f64)>) {}
+
+    /// Set the receiver's range in nm, within which aircraft may be decoded
+    /// against the reference before a global decode, or clear it.
+    ///
+    /// Only protocols that carry raw Mode S frames use it. The default does
+    /// nothing.
+    fn set_receiver_range(&mut self, _range_nm: Option<f64>) {}
+
+    /// Frame counters for protocols that decode raw Mode S frames.
+    fn decoder_stats(&self) -> Option<DecoderStats> {
+        None
+    }
+
+    /// Receiver measurements of the frame behind the last parsed message,
+    /// for protocols whose frames carry them.
+    fn signal(&self) -> Option<Signal> {
+        None
+    }
+}
+
+/// Decode an even-length hex string into bytes.
+pub(crate) fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.is_empty() || !hex.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
 }
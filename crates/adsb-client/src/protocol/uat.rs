@@ -0,0 +1,512 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! UAT (978 MHz) protocol parser.
+//!
+//! Parses the two line-based outputs of dump978: raw frames (port 30978) and
+//! decoded JSON messages (dump978-fa, port 30979). Both may be mixed on one
+//! connection.
+//!
+//! Raw message format:
+//! ```text
+//! -08A1B2C338E38F6AAAAA105810F428E0B009D90CFC25040B00900200000000000000;rs=1;rssi=-21.3;
+//! +3514C4B3A8...;rs=4;
+//! ```
+//!
+//! `-` prefixes a downlink (aircraft) frame and `+` an uplink (ground
+//! station FIS-B) frame. Frames are already error corrected; the optional
+//! `;key=value` fields after the hex carry receiver metadata.
+//!
+//! Only downlink ADS-B is decoded. Each message yields one
+//! [`AircraftMessage::StateVector`] combining the state vector with the
+//! callsign, squawk and emergency state of a mode status element.
+
+use serde_json::Value;
+
+use super::{decode_hex, AircraftMessage, DataSource, EmergencyState, ParseError, Protocol};
+use crate::mode_s::{bits, format_icao};
+
+/// Length of a basic (state vector only) downlink frame.
+const BASIC_FRAME_BYTES: usize = 18;
+/// Length of a long downlink frame.
+const LONG_FRAME_BYTES: usize = 34;
+/// Length of an uplink frame.
+const UPLINK_FRAME_BYTES: usize = 432;
+
+/// Characters of the base-40 alphabet used for UAT callsigns.
+const BASE40_ALPHABET: &[u8; 40] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ  ..";
+
+/// Address qualifiers whose address is a 24-bit ICAO address
+/// (ADS-B and TIS-B with ICAO address).
+const ICAO_ADDRESS_QUALIFIERS: [u32; 2] = [0, 2];
+
+/// Air/ground state values.
+const AIRBORNE_SUBSONIC: u32 = 0;
+const AIRBORNE_SUPERSONIC: u32 = 1;
+const ON_GROUND: u32 = 2;
+
+/// Parser for dump978 raw and JSON output.
+#[derive(Debug, Default)]
+pub struct UatParser;
+
+impl UatParser {
+    /// Create a new UAT parser.
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Decode an error-corrected downlink frame.
+    ///
+    /// Returns `Ok(None)` for frames without a usable address or state
+    /// vector.
+    pub fn decode_downlink(frame: &[u8]) -> Result<Option<AircraftMessage>, ParseError> {
+        let mdb_type = match frame.len() {
+            BASIC_FRAME_BYTES | LONG_FRAME_BYTES => bits(frame, 0, 5),
+            len => {
+                return Err(ParseError::InvalidValue {
+                    field: "frame length",
+                    value: len.to_string(),
+                })
+            }
+        };
+
+        // Basic frames are always type 0 and long frames never are
+        if (mdb_type == 0) != (frame.len() == BASIC_FRAME_BYTES) {
+            return Err(ParseError::InvalidValue {
+                field: "payload type",
+                value: mdb_type.to_string(),
+            });
+        }
+
+        // Types 11 and up carry no state vector
+        if mdb_type > 10 {
+            return Ok(None);
+        }
+
+        let address_qualifier = bits(frame, 5, 3);
+        let address = format_icao(bits(frame, 8, 24));
+        let icao = if ICAO_ADDRESS_QUALIFIERS.contains(&address_qualifier) {
+            address
+        } else {
+            // Non-ICAO addresses are marked like dump978 and readsb do
+            format!("~{address}")
+        };
+
+        let mut message = decode_state_vector(icao, frame);
+
+        // Types 1 and 3 carry a mode status element
+        if matches!(mdb_type, 1 | 3) {
+            decode_mode_status(&mut message, frame);
+        }
+
+        Ok(Some(message))
+    }
+
+    /// Decode one dump978-fa JSON message.
+    pub fn decode_json(line: &str) -> Result<Option<AircraftMessage>, ParseError> {
+        let json: Value = serde_json::from_str(line)
+            .map_err(|e| ParseError::InvalidFormat(format!("invalid JSON: {e}")))?;
+
+        let address = json
+            .get("address")
+            .and_then(Value::as_str)
+            .ok_or(ParseError::MissingField("address"))?
+            .to_ascii_uppercase();
+        let is_icao = json
+            .get("address_qualifier")
+            .and_then(Value::as_str)
+            .is_none_or(|qualifier| qualifier.ends_with("_icao"));
+        let icao = if is_icao {
+            address
+        } else {
+            format!("~{address}")
+        };
+
+        let position = json.get("position");
+        let float = |value: Option<&Value>| value.and_then(Value::as_f64);
+        let int = |key: &str| {
+            json.get(key)
+                .and_then(Value::as_i64)
+                .and_then(|value| i32::try_from(value).ok())
+        };
+        let text = |key: &str| {
+            json.get(key)
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+
+        let emergency_state = json
+            .get("emergency")
+            .and_then(Value::as_str)
+            .map(|emergency| match emergency {
+                "none" => EmergencyState::None,
+                "general" => EmergencyState::General,
+                "medical" => EmergencyState::Medical,
+                "minfuel" => EmergencyState::MinimumFuel,
+                "nordo" => EmergencyState::NoCommunications,
+                "unlawful" => EmergencyState::UnlawfulInterference,
+                "downed" => EmergencyState::Downed,
+                _ => EmergencyState::Reserved,
+            });
+
+        Ok(Some(AircraftMessage::StateVector {
+            icao,
+            timestamp: None,
+            source: DataSource::Uat,
+            latitude: float(position.and_then(|p| p.get("lat"))),
+            longitude: float(position.and_then(|p| p.get("lon"))),
+            altitude: int("pressure_altitude").or_else(|| int("geometric_altitude")),
+            ground_speed: float(json.get("ground_speed")),
+            track: float(json.get("true_track")),
+            vertical_rate: int("vertical_velocity_barometric")
+                .or_else(|| int("vertical_velocity_geometric")),
+            is_on_ground: json
+                .get("airground_state")
+                .and_then(Value::as_str)
+                .map(|state| state == "ground"),
+            callsign: text("callsign"),
+            squawk: text("flightplan_id"),
+            emergency_state,
+        }))
+    }
+}
+
+impl Protocol for UatParser {
+    type Message = AircraftMessage;
+    type Error = ParseError;
+
+    fn parse(&mut self, input: &[u8]) -> Result<Option<AircraftMessage>, ParseError> {
+        let line = std::str::from_utf8(input)
+            .map_err(|e| ParseError::InvalidFormat(format!("invalid UTF-8: {e}")))?
+            .trim();
+
+        if line.is_empty() {
+            return Ok(None);
+        }
+        if line.starts_with('{') {
+            return Self::decode_json(line);
+        }
+
+        let (is_downlink, body) = if let Some(body) = line.strip_prefix('-') {
+            (true, body)
+        } else if let Some(body) = line.strip_prefix('+') {
+            (false, body)
+        } else {
+            return Err(ParseError::InvalidFormat(format!(
+                "expected '-', '+' or '{{' prefix: {line}"
+            )));
+        };
+
+        // Metadata follows the hex after the first ';'
+        let hex = body.split(';').next().unwrap_or_default();
+        let frame = decode_hex(hex).ok_or_else(|| ParseError::InvalidValue {
+            field: "frame",
+            value: hex.to_string(),
+        })?;
+
+        if is_downlink {
+            Self::decode_downlink(&frame)
+        } else if frame.len() == UPLINK_FRAME_BYTES {
+            // FIS-B uplinks carry weather products, not traffic
+            Ok(None)
+        } else {
+            Err(ParseError::InvalidValue {
+                field: "frame length",
+                value: frame.len().to_string(),
+            })
+        }
+    }
+}
+
+/// Decode the state vector common to all downlink payload types 0-10.
+fn decode_state_vector(icao: String, frame: &[u8]) -> AircraftMessage {
+    let raw_lat = bits(frame, 32, 23);
+    let raw_lon = bits(frame, 55, 24);
+    let (latitude, longitude) = if raw_lat == 0 && raw_lon == 0 {
+        (None, None)
+    } else {
+        let mut lat = f64::from(raw_lat) * 360.0 / 16_777_216.0;
+        if lat > 90.0 {
+            lat -= 180.0;
+        }
+        let mut lon = f64::from(raw_lon) * 360.0 / 16_777_216.0;
+        if lon > 180.0 {
+            lon -= 360.0;
+        }
+        (Some(lat), Some(lon))
+    };
+
+    // Pressure or geometric altitude, depending on bit 79
+    let altitude = match bits(frame, 80, 12) {
+        0 => None,
+        raw => i32::try_from(raw).ok().map(|raw| (raw - 1) * 25 - 1000),
+    };
+
+    let air_ground_state = bits(frame, 96, 2);
+    let (ground_speed, track, vertical_rate) = match air_ground_state {
+        AIRBORNE_SUBSONIC | AIRBORNE_SUPERSONIC => {
+            let scale = if air_ground_state == AIRBORNE_SUPERSONIC {
+                4.0
+            } else {
+                1.0
+            };
+            let north = signed_velocity(bits(frame, 99, 11)).map(|v| v * scale);
+            let east = signed_velocity(bits(frame, 110, 11)).map(|v| v * scale);
+            let (speed, track) = match (north, east) {
+                (Some(north), Some(east)) => (
+                    Some(north.hypot(east)),
+                    Some(east.atan2(north).to_degrees().rem_euclid(360.0)),
+                ),
+                _ => (None, None),
+            };
+
+            let raw_vertical = bits(frame, 121, 11);
+            let vertical_rate = match raw_vertical & 0x1FF {
+                0 => None,
+                magnitude => i32::try_from(magnitude - 1).ok().map(|rate| {
+                    if raw_vertical & 0x200 == 0 {
+                        rate * 64
+                    } else {
+                        -rate * 64
+                    }
+                }),
+            };
+
+            (speed, track, vertical_rate)
+        }
+        ON_GROUND => {
+            let speed = match bits(frame, 99, 11) & 0x3FF {
+                0 => None,
+                raw => Some(f64::from(raw - 1)),
+            };
+            let raw_track = bits(frame, 110, 11);
+            // Track type 0 means no track or heading is available
+            let track =
+                (raw_track & 0x600 != 0).then(|| f64::from(raw_track & 0x1FF) * 360.0 / 512.0);
+            (speed, track, None)
+        }
+        _ => (None, None, None),
+    };
+
+    AircraftMessage::StateVector {
+        icao,
+        timestamp: None,
+        source: DataSource::Uat,
+        latitude,
+        longitude,
+        altitude,
+        ground_speed,
+        track,
+        vertical_rate,
+        is_on_ground: (air_ground_state <= ON_GROUND).then_some(air_ground_state == ON_GROUND),
+        callsign: None,
+        squawk: None,
+        emergency_state: None,
+    }
+}
+
+/// Decode an 11-bit sign/magnitude velocity component in knots.
+fn signed_velocity(raw: u32) -> Option<f64> {
+    match raw & 0x3FF {
+        0 => None,
+        magnitude => {
+            let speed = f64::from(magnitude - 1);
+            Some(if raw & 0x400 == 0 { speed } else { -speed })
+        }
+    }
+}
+
+/// Add the callsign or squawk and emergency state of a mode status element.
+fn decode_mode_status(message: &mut AircraftMessage, frame: &[u8]) {
+    let AircraftMessage::StateVector {
+        callsign,
+        squawk,
+        emergency_state,
+        ..
+    } = message
+    else {
+        return;
+    };
+
+    // Three 16-bit words of three base-40 digits; the first digit is the
+    // emitter category
+    let chars: String = [136, 152, 168]
+        .iter()
+        .flat_map(|&start| {
+            let word = bits(frame, start, 16);
+            [word / 1600 % 40, word / 40 % 40, word % 40]
+        })
+        .skip(1)
+        .map(|digit| char::from(BASE40_ALPHABET[digit as usize]))
+        .collect();
+    let id = chars.trim_end();
+
+    if !id.is_empty() {
+        // The CSID bit tells a callsign from a flight plan (squawk) ID
+        if bits(frame, 214, 1) == 1 {
+            *callsign = Some(id.to_string());
+        } else {
+            *squawk = id.get(..4).map(str::to_string);
+        }
+    }
+
+    let emergency = u8::try_from(bits(frame, 184, 3)).unwrap_or_default();
+    *emergency_state = Some(EmergencyState::from_code(emergency));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> AircraftMessage {
+        UatParser::new().parse(line.as_bytes()).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_basic_state_vector() {
+        match parse("-00A1B2C338E38F6AAAAA105810F428E0B000;rs=1;rssi=-21.3;") {
+            AircraftMessage::StateVector {
+                icao,
+                timestamp: None,
+                source,
+                latitude,
+                longitude,
+                altitude,
+                ground_speed,
+                track,
+                vertical_rate,
+                is_on_ground,
+                callsign,
+                ..
+            } => {
+                assert_eq!(icao, "A1B2C3");
+                assert_eq!(source, DataSource::Uat);
+                assert!((latitude.unwrap() - 40.0).abs() < 1e-4);
+                assert!((longitude.unwrap() + 105.0).abs() < 1e-4);
+                assert_eq!(altitude, Some(5500));
+                assert!((ground_speed.unwrap() - 100.0).abs() < 1e-9);
+                assert!((track.unwrap() - 126.869_9).abs() < 1e-3);
+                assert_eq!(vertical_rate, Some(-640));
+                assert_eq!(is_on_ground, Some(false));
+                assert_eq!(callsign, None);
+            }
+            other => panic!("Expected StateVector, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_mode_status_callsign_and_squawk() {
+        match parse("-08A1B2C338E38F6AAAAA105810F428E0B009D90CFC25040B00900200000000000000;") {
+            AircraftMessage::StateVector {
+                callsign,
+                squawk,
+                emergency_state,
+                ..
+            } => {
+                assert_eq!(callsign.as_deref(), Some("N12345"));
+                assert_eq!(squawk, None);
+                assert_eq!(emergency_state, Some(EmergencyState::None));
+            }
+            other => panic!("Expected StateVector, got {other:?}"),
+        }
+
+        // Self-assigned address with a flight plan ID and an emergency
+        match parse("-09123456382D836B05B00C980194008000075F0024E6C42B00900000000000000000;") {
+            AircraftMessage::StateVector {
+                icao,
+                timestamp: None,
+                callsign,
+                squawk,
+                emergency_state,
+                vertical_rate,
+                ..
+            } => {
+                assert_eq!(icao, "~123456");
+                assert_eq!(callsign, None);
+                assert_eq!(squawk.as_deref(), Some("7700"));
+                assert_eq!(emergency_state, Some(EmergencyState::General));
+                assert_eq!(vertical_rate, None);
+            }
+            other => panic!("Expected StateVector, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_surface_state_vector() {
+        match parse("-00ABCDEF38B0976B22D01018804140000000;") {
+            AircraftMessage::StateVector {
+                altitude,
+                ground_speed,
+                track,
+                is_on_ground,
+                ..
+            } => {
+                assert_eq!(altitude, Some(5400));
+                assert_eq!(ground_speed, Some(15.0));
+                assert_eq!(track, Some(90.0));
+                assert_eq!(is_on_ground, Some(true));
+            }
+            other => panic!("Expected StateVector, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_json_message() {
+        let line = r#"{"address":"a1b2c3","address_qualifier":"adsb_icao","airground_state":"airborne","callsign":"N12345","emergency":"none","flightplan_id":"1200","ground_speed":112,"position":{"lat":39.9,"lon":-105.1},"pressure_altitude":6500,"true_track":271.5,"vertical_velocity_barometric":-448,"metadata":{"rssi":-18.2}}"#;
+        assert_eq!(
+            parse(line),
+            AircraftMessage::StateVector {
+                icao: "A1B2C3".to_string(),
+                timestamp: None,
+                source: DataSource::Uat,
+                latitude: Some(39.9),
+                longitude: Some(-105.1),
+                altitude: Some(6500),
+                ground_speed: Some(112.0),
+                track: Some(271.5),
+                vertical_rate: Some(-448),
+                is_on_ground: Some(false),
+                callsign: Some("N12345".to_string()),
+                squawk: Some("1200".to_string()),
+                emergency_state: Some(EmergencyState::None),
+            }
+        );
+
+        let line = r#"{"address":"00ab12","address_qualifier":"tisb_trackfile","airground_state":"ground"}"#;
+        assert_eq!(parse(line).icao(), "~00AB12");
+    }
+
+    #[test]
+    fn test_uplink_and_invalid_lines() {
+        let mut parser = UatParser::new();
+        let uplink = format!("+{};rs=4;", "00".repeat(UPLINK_FRAME_BYTES));
+        assert_eq!(parser.parse(uplink.as_bytes()).unwrap(), None);
+        assert_eq!(parser.parse(b"\r\n").unwrap(), None);
+
+        // Unknown prefix
+        assert!(parser.parse(b"*8D4840D6202CC371C32CE0576098;").is_err());
+        // Wrong frame length
+        assert!(parser.parse(b"-00A1B2C3;").is_err());
+        // Long frame claiming to be basic
+        assert!(parser
+            .parse(b"-00A1B2C338E38F6AAAAA105810F428E0B009D90CFC25040B00900200000000000000;")
+            .is_err());
+        // Broken JSON and missing address
+        assert!(parser.parse(b"{\"address\":").is_err());
+        assert!(parser.parse(b"{\"callsign\":\"N1\"}").is_err());
+    }
+}
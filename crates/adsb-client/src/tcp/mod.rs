@@ -16,16 +16,74 @@
 //!
 //! Provides a connection handle that manages TCP connections to ADS-B feeds
 //! with automatic reconnection, address hot-reload, and graceful shutdown.
+//! With [`Framing::Detect`] the feed format is sniffed from the first bytes
+//! received and reported through [`ConnectionState::Connected`].
 
 use std::time::Duration;
 
 use log::{error, info, warn};
-use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::io::AsyncReadExt;
 use tokio::net::TcpStream;
 use tokio::sync::{mpsc, watch};
 use tokio::time::sleep;
 use tokio_util::sync::CancellationToken;
 
+use crate::protocol::beast::{scan_frame, FrameScan};
+use crate::protocol::{detect_format, DetectedFormat};
+
+/// Initial capacity of the read buffer.
+const READ_BUFFER_CAPACITY: usize = 8192;
+/// Longest line accepted before the buffer is discarded.
+const MAX_LINE_LENGTH: usize = 64 * 1024;
+/// Bytes read without recognising the feed format before giving up.
+const MAX_DETECTION_BYTES: usize = 16 * 1024;
+
+/// How the incoming byte stream is split into messages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Framing {
+    /// Newline-delimited text (`BaseStation`).
+    #[default]
+    Lines,
+    /// BEAST binary frames introduced by `0x1A` escapes.
+    Beast,
+    /// Detect the framing from the start of the stream.
+    Detect,
+}
+
+impl Framing {
+    /// Remove and return the next complete message from `buf`.
+    ///
+    /// Lines are returned without their trailing `\n`/`\r\n`. BEAST frames are
+    /// returned still escaped, exactly as received. Returns `None` when
+    /// more data is needed.
+    ///
+    /// [`Framing::Detect`] classifies the buffer on every call; a connection
+    /// resolves it once and then uses the detected framing.
+    pub fn next_frame(self, buf: &mut Vec<u8>) -> Option<Vec<u8>> {
+        match self {
+            Self::Lines => {
+                let end = buf.iter().position(|&b| b == b'\n')?;
+                let mut line: Vec<u8> = buf.drain(..=end).collect();
+                line.pop();
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+                Some(line)
+            }
+            Self::Beast => loop {
+                match scan_frame(buf) {
+                    FrameScan::NeedMore => return None,
+                    FrameScan::Discard(n) => {
+                        buf.drain(..n);
+                    }
+                    FrameScan::Frame(n) => return Some(buf.drain(..n).collect()),
+                }
+            },
+            Self::Detect => detect_format(buf)?.framing().next_frame(buf),
+        }
+    }
+}
+
 /// Configuration for TCP connections.
 #[derive(Debug, Clone)]
 pub struct ConnectionConfig {
@@ -37,6 +95,8 @@ pub struct ConnectionConfig {
     pub read_timeout: Option<Duration>,
     /// Channel buffer size for received data.
     pub buffer_size: usize,
+    /// How the byte stream is split into messages.
+    pub framing: Framing,
 }
 
 impl Default for ConnectionConfig {
@@ -46,6 +106,7 @@ impl Default for ConnectionConfig {
             reconnect_delay: Duration::from_secs(5),
             read_timeout: None,
             buffer_size: 1024,
+            framing: Framing::Lines,
         }
     }
 }
@@ -56,7 +117,11 @@ pub enum ConnectionState {
     /// Attempting to connect.
     Connecting,
     /// Successfully connected.
-    Connected,
+    Connected {
+        /// Feed format detected from the first bytes received. `None` until
+        /// detection completes, and always `None` with a fixed [`Framing`].
+        format: Option<DetectedFormat>,
+    },
     /// Disconnected (will attempt reconnect).
     Disconnected,
     /// Connection error occurred.
@@ -68,7 +133,7 @@ pub enum ConnectionState {
 pub enum ConnectionEvent {
     /// Connection state changed.
     StateChanged(ConnectionState),
-    /// Data received (one line).
+    /// Data received (one line or one binary frame, depending on [`Framing`]).
     DataReceived(Vec<u8>),
 }
 
@@ -104,9 +169,10 @@ impl Connection {
 
         let task_cancel = cancel_token.clone();
         let reconnect_delay = config.reconnect_delay;
+        let framing = config.framing;
 
         tokio::spawn(async move {
-            connection_loop(event_tx, address_rx, task_cancel, reconnect_delay).await;
+            connection_loop(event_tx, address_rx, task_cancel, reconnect_delay, framing).await;
         });
 
         Self {
@@ -153,6 +219,7 @@ async fn connection_loop(
     mut address_rx: watch::Receiver<String>,
     cancel_token: CancellationToken,
     reconnect_delay: Duration,
+    framing: Framing,
 ) {
     loop {
         if cancel_token.is_cancelled() {
@@ -178,6 +245,7 @@ async fn connection_loop(
             &event_tx,
             &mut address_rx,
             &cancel_token,
+            framing,
         )
         .await
         {
@@ -230,38 +298,77 @@ async fn connect_and_process(
     event_tx: &mpsc::Sender<ConnectionEvent>,
     address_rx: &mut watch::Receiver<String>,
     cancel_token: &CancellationToken,
+    framing: Framing,
 ) -> Result<ReconnectReason, Box<dyn std::error::Error + Send + Sync>> {
-    let stream = TcpStream::connect(address).await?;
+    let mut stream = TcpStream::connect(address).await?;
     info!("Connected to {}", address);
 
     if event_tx
-        .send(ConnectionEvent::StateChanged(ConnectionState::Connected))
+        .send(ConnectionEvent::StateChanged(ConnectionState::Connected {
+            format: None,
+        }))
         .await
         .is_err()
     {
         return Ok(ReconnectReason::Cancelled);
     }
 
-    let reader = BufReader::new(stream);
-    let mut lines = reader.lines();
+    let mut buf = Vec::with_capacity(READ_BUFFER_CAPACITY);
+    let mut framing = framing;
 
     loop {
         tokio::select! {
-            line_result = lines.next_line() => {
-                match line_result {
-                    Ok(Some(line)) => {
-                        if event_tx
-                            .send(ConnectionEvent::DataReceived(line.into_bytes()))
-                            .await
-                            .is_err()
-                        {
-                            return Ok(ReconnectReason::Cancelled);
+            read_result = stream.read_buf(&mut buf) => {
+                match read_result {
+                    Ok(0) => {
+                        // Flush a final unterminated line
+                        if framing == Framing::Lines && !buf.is_empty() {
+                            let _ = event_tx
+                                .send(ConnectionEvent::DataReceived(std::mem::take(&mut buf)))
+                                .await;
                         }
-                    }
-                    Ok(None) => {
                         info!("Connection closed by server");
                         return Ok(ReconnectReason::ConnectionClosed);
                     }
+                    Ok(_) => {
+                        if framing == Framing::Detect {
+                            let Some(format) = detect_format(&buf) else {
+                                if buf.len() > MAX_DETECTION_BYTES {
+                                    return Err(format!(
+                                        "unrecognised feed format after {} bytes",
+                                        buf.len()
+                                    )
+                                    .into());
+                                }
+                                continue;
+                            };
+                            info!("Detected {} feed at {}", format.label(), address);
+                            framing = format.framing();
+                            if event_tx
+                                .send(ConnectionEvent::StateChanged(ConnectionState::Connected {
+                                    format: Some(format),
+                                }))
+                                .await
+                                .is_err()
+                            {
+                                return Ok(ReconnectReason::Cancelled);
+                            }
+                        }
+
+                        while let Some(frame) = framing.next_frame(&mut buf) {
+                            if event_tx
+                                .send(ConnectionEvent::DataReceived(frame))
+                                .await
+                                .is_err()
+                            {
+                                return Ok(ReconnectReason::Cancelled);
+                            }
+                        }
+                        if buf.len() > MAX_LINE_LENGTH {
+                            warn!("Discarding {} bytes without a message delimiter", buf.len());
+                            buf.clear();
+                        }
+                    }
                     Err(e) => {
                         return Err(Box::new(e));
                     }
@@ -282,3 +389,40 @@ async fn connect_and_process(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_framing() {
+        let mut buf = b"MSG,1\r\nMSG,3\nMSG".to_vec();
+        assert_eq!(Framing::Lines.next_frame(&mut buf), Some(b"MSG,1".to_vec()));
+        assert_eq!(Framing::Lines.next_frame(&mut buf), Some(b"MSG,3".to_vec()));
+        assert_eq!(Framing::Lines.next_frame(&mut buf), None);
+        assert_eq!(buf, b"MSG");
+    }
+
+    #[test]
+    fn test_beast_framing_skips_garbage() {
+        let frame = [0x1A, b'1', 0, 0, 0, 0, 0, 1, 0x40, 0x12, 0x34];
+        let mut buf = vec![0x00, 0xFF];
+        buf.extend_from_slice(&frame);
+        buf.extend_from_slice(&frame[..4]);
+
+        assert_eq!(Framing::Beast.next_frame(&mut buf), Some(frame.to_vec()));
+        assert_eq!(Framing::Beast.next_frame(&mut buf), None);
+        assert_eq!(buf, frame[..4]);
+    }
+
+    #[test]
+    fn test_detect_framing() {
+        let mut buf = b"*8D4840D6202CC371C32CE0576098;\n*8D48".to_vec();
+        assert_eq!(
+            Framing::Detect.next_frame(&mut buf),
+            Some(b"*8D4840D6202CC371C32CE0576098;".to_vec())
+        );
+        assert_eq!(Framing::Detect.next_frame(&mut buf), None);
+        assert_eq!(buf, b"*8D48");
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Emergency detection.
+//!
+//! An aircraft can signal an emergency in three ways, from most to least
+//! specific:
+//!
+//! - Squawking 7500 (unlawful interference), 7600 (radio failure) or 7700
+//!   (general emergency)
+//! - Broadcasting an emergency/priority state in ADS-B TC 28
+//! - Having the emergency flag set by the feed (SBS), which carries no reason
+//!
+//! [`Aircraft::active_emergency`] picks the most specific signal, and the
+//! tracker emits [`TrackerEvent::Emergency`](super::TrackerEvent::Emergency)
+//! whenever it changes to a new emergency.
+
+use super::Aircraft;
+use crate::protocol::EmergencyState;
+
+/// Why an aircraft is considered to be in an emergency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Emergency {
+    /// Squawk 7500, unlawful interference.
+    Hijack,
+    /// Squawk 7600, radio failure.
+    RadioFailure,
+    /// Squawk 7700, general emergency.
+    General,
+    /// Emergency/priority state broadcast in ADS-B TC 28.
+    Status(EmergencyState),
+    /// Emergency flag set by the feed without a reason.
+    Flag,
+}
+
+impl Emergency {
+    /// Emergency signalled by a squawk code, if any.
+    #[must_use]
+    pub fn from_squawk(squawk: &str) -> Option<Self> {
+        match squawk.trim() {
+            "7500" => Some(Self::Hijack),
+            "7600" => Some(Self::RadioFailure),
+            "7700" => Some(Self::General),
+            _ => None,
+        }
+    }
+
+    /// Squawk code of an emergency signalled by squawk.
+    #[must_use]
+    pub fn squawk(self) -> Option<&'static str> {
+        match self {
+            Self::Hijack => Some("7500"),
+            Self::RadioFailure => Some("7600"),
+            Self::General => Some("7700"),
+            Self::Status(_) | Self::Flag => None,
+        }
+    }
+
+    /// Short human-readable description.
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Hijack => "Hijack (7500)",
+            Self::RadioFailure => "Radio failure (7600)",
+            Self::General => "Emergency (7700)",
+            Self::Status(state) => state.label(),
+            Self::Flag => "Emergency",
+        }
+    }
+}
+
+impl Aircraft {
+    /// The emergency this aircraft currently signals, if any.
+    ///
+    /// Emergency squawks take precedence over the TC 28 state, which takes
+    /// precedence over the feed's emergency flag.
+    #[must_use]
+    pub fn active_emergency(&self) -> Option<Emergency> {
+        self.squawk
+            .as_deref()
+            .and_then(Emergency::from_squawk)
+            .or_else(|| {
+                self.emergency_state
+                    .filter(|state| state.is_emergency())
+                    .map(Emergency::Status)
+            })
+            .or_else(|| (self.emergency == Some(true)).then_some(Emergency::Flag))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_active_emergency_precedence() {
+        let mut aircraft = Aircraft::new("A1B2C3".to_string());
+        assert_eq!(aircraft.active_emergency(), None);
+
+        aircraft.emergency = Some(true);
+        assert_eq!(aircraft.active_emergency(), Some(Emergency::Flag));
+
+        aircraft.emergency_state = Some(EmergencyState::MinimumFuel);
+        assert_eq!(
+            aircraft.active_emergency(),
+            Some(Emergency::Status(EmergencyState::MinimumFuel))
+        );
+
+        aircraft.squawk = Some("7500".to_string());
+        assert_eq!(aircraft.active_emergency(), Some(Emergency::Hijack));
+        assert_eq!(Emergency::Hijack.squawk(), Some("7500"));
+
+        aircraft.squawk = Some("1200".to_string());
+        aircraft.emergency_state = Some(EmergencyState::None);
+        aircraft.emergency = Some(false);
+        assert_eq!(aircraft.active_emergency(), None);
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Application data attached to tracked aircraft.
+//!
+//! Applications often know more about an aircraft than the feed does, such as
+//! its registration or a photo. [`Extensions`] holds one value per type, so
+//! each application can keep its own data on the tracker's aircraft without
+//! the library knowing about it.
+//!
+//! Values are shared between clones and copied on write, so snapshots of an
+//! aircraft stay cheap.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Type-keyed map of application data.
+#[derive(Clone, Default)]
+pub struct Extensions {
+    map: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl std::fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Extensions")
+            .field("len", &self.map.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Extensions {
+    /// Create an empty map.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a value, replacing any previous value of the same type.
+    pub fn insert<T: Any + Send + Sync>(&mut self, value: T) {
+        self.map.insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    /// Get the value of a type.
+    #[must_use]
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.map.get(&TypeId::of::<T>())?.downcast_ref()
+    }
+
+    /// Get mutable access to the value of a type.
+    ///
+    /// A value shared with a snapshot is copied first, so the snapshot does
+    /// not change.
+    pub fn get_mut<T: Any + Send + Sync + Clone>(&mut self) -> Option<&mut T> {
+        let slot = self.map.get_mut(&TypeId::of::<T>())?;
+        if Arc::get_mut(slot).is_none() {
+            let value: T = slot.downcast_ref::<T>()?.clone();
+            *slot = Arc::new(value);
+        }
+        Arc::get_mut(slot)?.downcast_mut()
+    }
+
+    /// Get mutable access to the value of a type, inserting the default
+    /// first if there is none.
+    pub fn get_or_default<T: Any + Send + Sync + Clone + Default>(&mut self) -> &mut T {
+        if !self.contains::<T>() {
+            self.insert(T::default());
+        }
+        self.get_mut()
+            .expect("value was inserted above with the same type")
+    }
+
+    /// Remove the value of a type.
+    pub fn remove<T: Any + Send + Sync>(&mut self) -> bool {
+        self.map.remove(&TypeId::of::<T>()).is_some()
+    }
+
+    /// Check whether a value of a type is stored.
+    #[must_use]
+    pub fn contains<T: Any + Send + Sync>(&self) -> bool {
+        self.map.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Number of stored values.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Check whether no values are stored.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Default, PartialEq)]
+    struct Registration(String);
+
+    #[test]
+    fn test_insert_get_and_copy_on_write() {
+        let mut extensions = Extensions::new();
+        assert!(extensions.get::<Registration>().is_none());
+
+        extensions.insert(Registration("N12345".to_string()));
+        extensions.insert(42_u32);
+        assert_eq!(extensions.len(), 2);
+        assert_eq!(extensions.get::<u32>(), Some(&42));
+
+        let snapshot = extensions.clone();
+        extensions.get_mut::<Registration>().unwrap().0 = "N54321".to_string();
+        assert_eq!(extensions.get::<Registration>().unwrap().0, "N54321");
+        assert_eq!(snapshot.get::<Registration>().unwrap().0, "N12345");
+
+        assert!(extensions.remove::<u32>());
+        assert!(!extensions.contains::<u32>());
+        *extensions.get_or_default::<u32>() += 1;
+        assert_eq!(extensions.get::<u32>(), Some(&1));
+    }
+}
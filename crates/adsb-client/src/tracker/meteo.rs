@@ -0,0 +1,267 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Meteorological data derived from aircraft air data.
+//!
+//! The wind is the difference between the ground vector (ADS-B ground speed
+//! and track) and the air vector (true airspeed from Comm-B BDS 5,0 and
+//! heading from BDS 6,0). Static air temperature follows from true airspeed
+//! and Mach, since the speed of sound depends only on temperature. Aircraft
+//! that answer BDS 4,4 or 4,5 report wind and temperature directly, and those
+//! reports take precedence.
+//!
+//! BDS 6,0 reports magnetic heading, which is used as-is; derived winds carry
+//! the local magnetic variation as a small direction error.
+
+use chrono::{DateTime, Utc};
+
+use crate::mode_s::comm_b::{CommBRegister, Inferred};
+
+/// Air data older than this is not combined with newer values.
+const MAX_AIR_DATA_AGE_SECS: i64 = 10;
+/// Directly reported wind and temperature stay valid this long.
+const MAX_REPORT_AGE_SECS: i64 = 60;
+/// Below this Mach number the temperature estimate is too noisy to use.
+const MIN_MACH_FOR_TEMPERATURE: f64 = 0.2;
+/// Speed of sound in knots per square root kelvin (sqrt(gamma * R)).
+const SPEED_OF_SOUND_COEFFICIENT: f64 = 38.967_854;
+/// Zero Celsius in kelvin.
+const ZERO_CELSIUS: f64 = 273.15;
+
+/// A wind vector.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Wind {
+    /// Direction the wind is blowing from, in degrees (0-360, north = 0).
+    pub direction: f64,
+    /// Wind speed in knots.
+    pub speed: f64,
+}
+
+/// Solve the wind triangle for the wind that turns the air vector into the
+/// ground vector.
+///
+/// Speeds are in knots, angles in degrees.
+#[must_use]
+pub fn wind_triangle(ground_speed: f64, track: f64, true_airspeed: f64, heading: f64) -> Wind {
+    let (track_sin, track_cos) = track.to_radians().sin_cos();
+    let (heading_sin, heading_cos) = heading.to_radians().sin_cos();
+
+    let east = ground_speed * track_sin - true_airspeed * heading_sin;
+    let north = ground_speed * track_cos - true_airspeed * heading_cos;
+
+    Wind {
+        // The wind blows towards (east, north); report where it comes from
+        direction: (-east).atan2(-north).to_degrees().rem_euclid(360.0),
+        speed: east.hypot(north),
+    }
+}
+
+/// Static air temperature in degrees Celsius from true airspeed (knots) and
+/// Mach number.
+#[must_use]
+pub fn temperature_from_mach(true_airspeed: f64, mach: f64) -> Option<f64> {
+    if mach < MIN_MACH_FOR_TEMPERATURE {
+        return None;
+    }
+    let speed_of_sound = true_airspeed / mach;
+    Some((speed_of_sound / SPEED_OF_SOUND_COEFFICIENT).powi(2) - ZERO_CELSIUS)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample<T> {
+    value: Inferred<T>,
+    at: DateTime<Utc>,
+}
+
+impl<T: Copy> Sample<T> {
+    fn fresh(slot: Option<Self>, now: DateTime<Utc>, max_age_secs: i64) -> Option<Inferred<T>> {
+        slot.filter(|sample| (now - sample.at).num_seconds() <= max_age_secs)
+            .map(|sample| sample.value)
+    }
+}
+
+/// Recent air data for one aircraft, used to derive wind and temperature.
+#[derive(Debug, Clone, Default)]
+pub struct AirData {
+    true_airspeed: Option<Sample<f64>>,
+    heading: Option<Sample<f64>>,
+    mach: Option<Sample<f64>>,
+    reported_wind: Option<Sample<Wind>>,
+    reported_temperature: Option<Sample<f64>>,
+}
+
+impl AirData {
+    /// Record the air data carried by a Comm-B register.
+    pub fn update(&mut self, register: &Inferred<CommBRegister>, now: DateTime<Utc>) {
+        let confidence = register.confidence;
+        let sample = |value: Option<f64>| {
+            value.map(|value| Sample {
+                value: Inferred { value, confidence },
+                at: now,
+            })
+        };
+
+        match register.value {
+            CommBRegister::TrackAndTurn { true_airspeed, .. } => {
+                self.true_airspeed = sample(true_airspeed).or(self.true_airspeed);
+            }
+            CommBRegister::HeadingAndSpeed {
+                magnetic_heading,
+                mach,
+                ..
+            } => {
+                self.heading = sample(magnetic_heading).or(self.heading);
+                self.mach = sample(mach).or(self.mach);
+            }
+            CommBRegister::MeteorologicalRoutine {
+                wind_speed,
+                wind_direction,
+                static_air_temperature,
+                ..
+            } => {
+                if let (Some(speed), Some(direction)) = (wind_speed, wind_direction) {
+                    self.reported_wind = Some(Sample {
+                        value: Inferred {
+                            value: Wind { direction, speed },
+                            confidence,
+                        },
+                        at: now,
+                    });
+                }
+                self.reported_temperature = sample(Some(static_air_temperature));
+            }
+            CommBRegister::MeteorologicalHazard {
+                static_air_temperature,
+                ..
+            } => {
+                self.reported_temperature =
+                    sample(static_air_temperature).or(self.reported_temperature);
+            }
+            CommBRegister::SelectedVerticalIntention { .. } => {}
+        }
+    }
+
+    /// Current wind, given the aircraft's ground speed and track.
+    ///
+    /// The confidence is the lower of the contributing registers'.
+    #[must_use]
+    pub fn wind(
+        &self,
+        ground_speed: f64,
+        track: f64,
+        now: DateTime<Utc>,
+    ) -> Option<Inferred<Wind>> {
+        if let Some(wind) = Sample::fresh(self.reported_wind, now, MAX_REPORT_AGE_SECS) {
+            return Some(wind);
+        }
+
+        let tas = Sample::fresh(self.true_airspeed, now, MAX_AIR_DATA_AGE_SECS)?;
+        let heading = Sample::fresh(self.heading, now, MAX_AIR_DATA_AGE_SECS)?;
+        Some(Inferred {
+            value: wind_triangle(ground_speed, track, tas.value, heading.value),
+            confidence: tas.confidence.min(heading.confidence),
+        })
+    }
+
+    /// Current static air temperature in degrees Celsius.
+    #[must_use]
+    pub fn static_air_temperature(&self, now: DateTime<Utc>) -> Option<Inferred<f64>> {
+        if let Some(temperature) =
+            Sample::fresh(self.reported_temperature, now, MAX_REPORT_AGE_SECS)
+        {
+            return Some(temperature);
+        }
+
+        let tas = Sample::fresh(self.true_airspeed, now, MAX_AIR_DATA_AGE_SECS)?;
+        let mach = Sample::fresh(self.mach, now, MAX_AIR_DATA_AGE_SECS)?;
+        Some(Inferred {
+            value: temperature_from_mach(tas.value, mach.value)?,
+            confidence: tas.confidence.min(mach.confidence),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wind_triangle() {
+        // Heading north at 400 kt TAS, drifting east at 50 kt: the wind comes
+        // from the west
+        let wind = wind_triangle(
+            400.0_f64.hypot(50.0),
+            50.0_f64.atan2(400.0).to_degrees(),
+            400.0,
+            0.0,
+        );
+        assert!((wind.speed - 50.0).abs() < 1e-6);
+        assert!((wind.direction - 270.0).abs() < 1e-6);
+
+        // Pure headwind from the north
+        let wind = wind_triangle(380.0, 0.0, 400.0, 0.0);
+        assert!((wind.speed - 20.0).abs() < 1e-6);
+        assert!(wind.direction.abs() < 1e-6 || (wind.direction - 360.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_temperature_from_mach() {
+        // ISA at FL350: -54.3 C, where Mach 0.78 is about 450 kt TAS
+        let temperature = temperature_from_mach(450.0, 0.78).unwrap();
+        assert!((temperature + 54.3).abs() < 1.5);
+        assert!(temperature_from_mach(100.0, 0.1).is_none());
+    }
+
+    #[test]
+    fn test_derived_wind_needs_fresh_air_data() {
+        let now = Utc::now();
+        let mut air_data = AirData::default();
+
+        air_data.update(
+            &Inferred {
+                value: CommBRegister::TrackAndTurn {
+                    roll: None,
+                    track_rate: None,
+                    true_airspeed: Some(450.0),
+                },
+                confidence: 0.9,
+            },
+            now,
+        );
+        assert!(air_data.wind(430.0, 90.0, now).is_none());
+
+        air_data.update(
+            &Inferred {
+                value: CommBRegister::HeadingAndSpeed {
+                    magnetic_heading: Some(90.0),
+                    indicated_airspeed: None,
+                    mach: Some(0.78),
+                    baro_vertical_rate: None,
+                    inertial_vertical_rate: None,
+                },
+                confidence: 0.7,
+            },
+            now,
+        );
+
+        let wind = air_data.wind(430.0, 90.0, now).unwrap();
+        assert!((wind.value.speed - 20.0).abs() < 1e-6);
+        assert!((wind.value.direction - 90.0).abs() < 1e-6);
+        assert!((wind.confidence - 0.7).abs() < f32::EPSILON);
+        assert!(air_data.static_air_temperature(now).is_some());
+
+        let later = now + chrono::Duration::seconds(MAX_AIR_DATA_AGE_SECS + 1);
+        assert!(air_data.wind(430.0, 90.0, later).is_none());
+    }
+}
@@ -16,20 +16,37 @@
 //!
 //! This module maintains aircraft state from ADS-B messages and emits change events.
 //! It provides position validation, history tracking, and spatial filtering.
+//! Aircraft are stored behind [`Arc`]s, so snapshots taken with
+//! [`AircraftTracker::snapshot`] are cheap and never block the tracker.
+
+mod emergency;
+mod extensions;
+pub mod meteo;
+mod prediction;
+mod validation;
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
-use log::{info, warn};
+use log::warn;
 use tokio::sync::broadcast;
 
-use crate::protocol::AircraftMessage;
+use crate::mode_s::comm_b::{CommBRegister, Inferred};
+use crate::protocol::{
+    AircraftMessage, AutopilotModes, DataSource, EmergencyState, ResolutionAdvisory,
+    SelectedAltitudeSource, Signal,
+};
+pub use emergency::Emergency;
+pub use extensions::Extensions;
+use meteo::{AirData, Wind};
+pub use prediction::{AlphaBeta, PredictedPosition, TrackEstimate};
+pub use validation::{
+    KalmanValidator, PositionReport, PositionStats, PositionValidator, Rejection, ValidatorConfig,
+};
 
 // Constants for position validation and tracking
 const NAUTICAL_MILE_CONVERSION: f64 = 1.15078; // 1 nautical mile = 1.15078 statute miles
-const JUMP_DETECTION_TIME_WINDOW_SECONDS: i64 = 20;
-const JUMP_DETECTION_THRESHOLD_MILES: f64 = 10.0;
-const MAX_CONSECUTIVE_REJECTIONS: u32 = 3;
 const POSITION_CHANGE_THRESHOLD_DEGREES: f64 = 0.001; // ~100 meters at mid-latitudes
 
 /// Calculate distance between two lat/lon points using Haversine formula (in miles).
@@ -55,6 +72,9 @@ pub fn haversine_distance_nm(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64
     statute_miles / NAUTICAL_MILE_CONVERSION
 }
 
+/// Nautical miles per degree of latitude.
+pub(crate) const NM_PER_DEGREE: f64 = 60.0;
+
 /// A single position sample with timestamp and altitude.
 #[derive(Debug, Clone)]
 pub struct PositionPoint {
@@ -69,6 +89,8 @@ pub struct PositionPoint {
 pub struct Aircraft {
     /// ICAO 24-bit address (hex string).
     pub icao: String,
+    /// Link the aircraft is received on.
+    pub source: DataSource,
     /// Aircraft callsign.
     pub callsign: Option<String>,
     /// Current latitude in degrees.
@@ -93,18 +115,83 @@ pub struct Aircraft {
     pub emergency: Option<bool>,
     /// SPI (Special Position Identification) flag.
     pub spi: Option<bool>,
+    /// Emergency/priority state from ADS-B aircraft status.
+    pub emergency_state: Option<EmergencyState>,
+    /// Most recent TCAS resolution advisory.
+    pub resolution_advisory: Option<ResolutionAdvisory>,
+    /// Selected altitude in feet.
+    pub selected_altitude: Option<i32>,
+    /// Whether the selected altitude comes from the MCP/FCU or the FMS.
+    pub selected_altitude_source: Option<SelectedAltitudeSource>,
+    /// Selected heading in degrees.
+    pub selected_heading: Option<f64>,
+    /// Barometric pressure setting (QNH) in hPa.
+    pub baro_setting: Option<f64>,
+    /// Engaged autopilot modes.
+    pub autopilot_modes: Option<AutopilotModes>,
+    /// ADS-B version number.
+    pub adsb_version: Option<u8>,
+    /// Navigation accuracy category for position (`NACp`).
+    pub nac_p: Option<u8>,
+    /// Source integrity level (SIL).
+    pub sil: Option<u8>,
+    /// Selected altitude in feet from Comm-B BDS 4,0 (MCP, else FMS).
+    pub ehs_selected_altitude: Option<Inferred<i32>>,
+    /// Barometric pressure setting in hPa from Comm-B BDS 4,0.
+    pub ehs_baro_setting: Option<Inferred<f64>>,
+    /// Roll angle in degrees from Comm-B BDS 5,0.
+    pub roll: Option<Inferred<f64>>,
+    /// True track rate in degrees per second from Comm-B BDS 5,0.
+    pub track_rate: Option<Inferred<f64>>,
+    /// True airspeed in knots from Comm-B BDS 5,0.
+    pub true_airspeed: Option<Inferred<f64>>,
+    /// Magnetic heading in degrees from Comm-B BDS 6,0.
+    pub magnetic_heading: Option<Inferred<f64>>,
+    /// Indicated airspeed in knots from Comm-B BDS 6,0.
+    pub indicated_airspeed: Option<Inferred<f64>>,
+    /// Mach number from Comm-B BDS 6,0.
+    pub mach: Option<Inferred<f64>>,
+    /// Barometric altitude rate in feet per minute from Comm-B BDS 6,0.
+    pub baro_vertical_rate: Option<Inferred<i32>>,
+    /// Inertial vertical velocity in feet per minute from Comm-B BDS 6,0.
+    pub inertial_vertical_rate: Option<Inferred<i32>>,
+    /// Wind at the aircraft, reported (BDS 4,4) or derived from air data.
+    pub wind: Option<Inferred<Wind>>,
+    /// Time the current wind was reported or derived.
+    pub wind_time: Option<DateTime<Utc>>,
+    /// Static air temperature in degrees Celsius, reported (BDS 4,4/4,5) or
+    /// derived from true airspeed and Mach.
+    pub static_air_temperature: Option<Inferred<f64>>,
+    /// Signal level of the last frame in dBFS, for feeds that report it.
+    pub rssi: Option<f64>,
+    /// Receiver clock at the last frame in seconds (12 MHz MLAT counter),
+    /// for feeds that report it.
+    pub receiver_time: Option<f64>,
+    /// Messages received from the aircraft.
+    pub messages: u64,
     /// Timestamp of last received message.
     pub last_seen: DateTime<Utc>,
+    /// Time the current position was accepted.
+    pub position_time: Option<DateTime<Utc>>,
+    /// Smoothed position and velocity, when track smoothing is enabled.
+    pub track_estimate: Option<TrackEstimate>,
+    /// Counts of positions accepted and rejected by the tracker's validator.
+    pub position_stats: PositionStats,
     /// Position history for trail rendering.
     pub position_history: Vec<PositionPoint>,
-    /// Counter for consecutive position rejections (internal use).
-    consecutive_rejections: u32,
+    /// Application data attached to the aircraft.
+    pub extensions: Extensions,
+    /// Recent air data used to derive wind and temperature (internal use).
+    air_data: AirData,
 }
 
 impl Aircraft {
-    fn new(icao: String) -> Self {
+    /// Create an aircraft with nothing decoded yet.
+    #[must_use]
+    pub fn new(icao: String) -> Self {
         Self {
             icao,
+            source: DataSource::default(),
             callsign: None,
             latitude: None,
             longitude: None,
@@ -117,9 +204,39 @@ impl Aircraft {
             alert: None,
             emergency: None,
             spi: None,
+            emergency_state: None,
+            resolution_advisory: None,
+            selected_altitude: None,
+            selected_altitude_source: None,
+            selected_heading: None,
+            baro_setting: None,
+            autopilot_modes: None,
+            adsb_version: None,
+            nac_p: None,
+            sil: None,
+            ehs_selected_altitude: None,
+            ehs_baro_setting: None,
+            roll: None,
+            track_rate: None,
+            true_airspeed: None,
+            magnetic_heading: None,
+            indicated_airspeed: None,
+            mach: None,
+            baro_vertical_rate: None,
+            inertial_vertical_rate: None,
+            wind: None,
+            wind_time: None,
+            static_air_temperature: None,
+            rssi: None,
+            receiver_time: None,
+            messages: 0,
             last_seen: Utc::now(),
+            position_time: None,
+            track_estimate: None,
+            position_stats: PositionStats::default(),
             position_history: Vec::new(),
-            consecutive_rejections: 0,
+            extensions: Extensions::new(),
+            air_data: AirData::default(),
         }
     }
 
@@ -133,44 +250,40 @@ impl Aircraft {
         }
     }
 
+    /// Store the receiver measurements of a frame from the aircraft.
+    fn record_signal(&mut self, signal: Signal) {
+        if let Some(rssi) = signal.rssi {
+            self.rssi = Some(rssi);
+        }
+        if let Some(time) = signal.receiver_time {
+            self.receiver_time = Some(time);
+        }
+    }
+
     /// Update position with validation.
-    fn update_position(
-        &mut self,
-        lat: f64,
-        lon: f64,
-        center_lat: f64,
-        center_lon: f64,
-        max_distance: f64,
-    ) -> bool {
+    fn update_position(&mut self, report: PositionReport, filter: &mut PositionFilter<'_>) -> bool {
+        let PositionReport {
+            latitude: lat,
+            longitude: lon,
+            timestamp: now,
+        } = report;
+
         // Check if position is within max distance from center
-        let distance_from_center = haversine_distance(center_lat, center_lon, lat, lon);
-        if distance_from_center > max_distance {
-            return false;
+        if let Some((center_lat, center_lon)) = filter.center {
+            if haversine_distance(center_lat, center_lon, lat, lon) > filter.max_distance {
+                return false;
+            }
         }
 
-        // Check if position is within threshold of previous position (only if recent update)
-        if let (Some(last_lat), Some(last_lon)) = (self.latitude, self.longitude) {
-            let time_since_last_update = (Utc::now() - self.last_seen).num_seconds();
-
-            if time_since_last_update <= JUMP_DETECTION_TIME_WINDOW_SECONDS {
-                let distance_from_last = haversine_distance(last_lat, last_lon, lat, lon);
-                if distance_from_last > JUMP_DETECTION_THRESHOLD_MILES {
-                    if self.consecutive_rejections >= MAX_CONSECUTIVE_REJECTIONS {
-                        info!(
-                            "Accepting position for {} after {} consecutive rejections (jumped {:.1} miles)",
-                            self.icao, self.consecutive_rejections, distance_from_last
-                        );
-                        self.consecutive_rejections = 0;
-                    } else {
-                        self.consecutive_rejections += 1;
-                        warn!(
-                            "Rejected position for {}: jumped {:.1} miles (rejection {} of 3)",
-                            self.icao, distance_from_last, self.consecutive_rejections
-                        );
-                        return false;
-                    }
-                }
-            }
+        if let Err(rejection) = filter.validator.validate(self, &report) {
+            self.position_stats.rejected += 1;
+            self.position_stats.consecutive_rejections += 1;
+            self.position_stats.last_rejection = Some(rejection);
+            warn!(
+                "Rejected position for {}: {} (rejection {} in a row)",
+                self.icao, rejection, self.position_stats.consecutive_rejections
+            );
+            return false;
         }
 
         // Only add to history if position has changed significantly
@@ -186,24 +299,326 @@ impl Aircraft {
                 lat,
                 lon,
                 altitude: self.altitude,
-                timestamp: Utc::now(),
+                timestamp: now,
             });
         }
 
         self.latitude = Some(lat);
         self.longitude = Some(lon);
-        self.consecutive_rejections = 0;
+        self.position_time = Some(now);
+        self.position_stats.accepted += 1;
+        self.position_stats.consecutive_rejections = 0;
+        if let Some(gains) = filter.smoothing {
+            self.smooth_position(gains, now);
+        }
 
         true
     }
 
-    fn cleanup_old_history(&mut self, max_age_seconds: i64) {
-        let now = Utc::now();
+    /// Apply a message to the aircraft, returning whether its position was
+    /// updated.
+    fn apply_message(
+        &mut self,
+        msg: AircraftMessage,
+        now: DateTime<Utc>,
+        filter: &mut PositionFilter<'_>,
+    ) -> bool {
+        match msg {
+            AircraftMessage::Identification { callsign, .. }
+            | AircraftMessage::CallsignChanged { callsign, .. } => {
+                self.callsign = Some(callsign);
+            }
+            AircraftMessage::Position { .. } | AircraftMessage::StateVector { .. } => {
+                return self.apply_position_message(msg, now, filter);
+            }
+            AircraftMessage::Velocity {
+                speed,
+                track,
+                vertical_rate,
+                is_on_ground,
+                ..
+            } => {
+                self.update_motion(None, Some(speed), Some(track), is_on_ground);
+                self.vertical_rate = vertical_rate;
+            }
+            AircraftMessage::Altitude {
+                altitude,
+                squawk,
+                alert,
+                emergency,
+                spi,
+                is_on_ground,
+                ..
+            } => {
+                self.update_motion(altitude, None, None, is_on_ground);
+                self.update_flight_status(squawk, alert, emergency, spi);
+            }
+            AircraftMessage::AircraftStatus {
+                emergency_state,
+                squawk,
+                resolution_advisory,
+                ..
+            } => self.update_status(emergency_state, squawk, resolution_advisory),
+            AircraftMessage::TargetState {
+                selected_altitude,
+                selected_altitude_source,
+                selected_heading,
+                baro_setting,
+                autopilot_modes,
+                ..
+            } => {
+                self.selected_altitude = selected_altitude;
+                self.selected_altitude_source = selected_altitude_source;
+                self.selected_heading = selected_heading;
+                if let Some(qnh) = baro_setting {
+                    self.baro_setting = Some(qnh);
+                }
+                if let Some(modes) = autopilot_modes {
+                    self.autopilot_modes = Some(modes);
+                }
+            }
+            AircraftMessage::OperationalStatus {
+                version,
+                nac_p,
+                sil,
+                is_on_ground,
+                ..
+            } => {
+                self.adsb_version = Some(version);
+                self.nac_p = Some(nac_p);
+                self.sil = Some(sil);
+                if is_on_ground {
+                    self.is_on_ground = Some(true);
+                }
+            }
+            AircraftMessage::CommB {
+                altitude,
+                squawk,
+                register,
+                ..
+            } => {
+                self.update_motion(altitude, None, None, None);
+                self.update_flight_status(squawk, None, None, None);
+                self.update_comm_b(&register, now);
+            }
+            AircraftMessage::Selection { callsign, .. } => {
+                if let Some(cs) = callsign {
+                    self.callsign = Some(cs);
+                }
+            }
+            AircraftMessage::NewAircraft { .. }
+            | AircraftMessage::StatusChanged { .. }
+            | AircraftMessage::Clock { .. } => {}
+        }
+        false
+    }
+
+    /// Apply a position report or UAT state vector, returning whether the
+    /// position was updated.
+    fn apply_position_message(
+        &mut self,
+        msg: AircraftMessage,
+        now: DateTime<Utc>,
+        filter: &mut PositionFilter<'_>,
+    ) -> bool {
+        let (latitude, longitude) = match msg {
+            AircraftMessage::Position {
+                latitude,
+                longitude,
+                altitude,
+                ground_speed,
+                track,
+                is_on_ground,
+                ..
+            } => {
+                self.update_motion(altitude, ground_speed, track, is_on_ground);
+                (Some(latitude), Some(longitude))
+            }
+            AircraftMessage::StateVector {
+                source,
+                latitude,
+                longitude,
+                altitude,
+                ground_speed,
+                track,
+                vertical_rate,
+                is_on_ground,
+                callsign,
+                squawk,
+                emergency_state,
+                ..
+            } => {
+                self.source = source;
+                self.update_motion(altitude, ground_speed, track, is_on_ground);
+                self.vertical_rate = vertical_rate;
+                if let Some(cs) = callsign {
+                    self.callsign = Some(cs);
+                }
+                self.update_status(emergency_state, squawk, None);
+                (latitude, longitude)
+            }
+            _ => return false,
+        };
+
+        let (Some(latitude), Some(longitude)) = (latitude, longitude) else {
+            return false;
+        };
+        let report = PositionReport {
+            latitude,
+            longitude,
+            timestamp: now,
+        };
+        self.update_position(report, filter)
+    }
+
+    /// Store the altitude, ground speed, track and ground state a message
+    /// reports.
+    fn update_motion(
+        &mut self,
+        altitude: Option<i32>,
+        ground_speed: Option<f64>,
+        track: Option<f64>,
+        is_on_ground: Option<bool>,
+    ) {
+        if let Some(alt) = altitude {
+            self.altitude = Some(alt);
+        }
+        if let Some(gs) = ground_speed {
+            self.velocity = Some(gs);
+        }
+        if let Some(trk) = track {
+            self.track = Some(trk);
+        }
+        if let Some(on_ground) = is_on_ground {
+            self.is_on_ground = Some(on_ground);
+        }
+    }
+
+    /// Store the squawk and flight status flags of a surveillance reply.
+    fn update_flight_status(
+        &mut self,
+        squawk: Option<String>,
+        alert: Option<bool>,
+        emergency: Option<bool>,
+        spi: Option<bool>,
+    ) {
+        if let Some(sq) = squawk {
+            self.squawk = Some(sq);
+        }
+        if let Some(a) = alert {
+            self.alert = Some(a);
+        }
+        if let Some(e) = emergency {
+            self.emergency = Some(e);
+        }
+        if let Some(s) = spi {
+            self.spi = Some(s);
+        }
+    }
+
+    /// Store the emergency state, squawk and resolution advisory of an
+    /// aircraft status report.
+    fn update_status(
+        &mut self,
+        emergency_state: Option<EmergencyState>,
+        squawk: Option<String>,
+        resolution_advisory: Option<ResolutionAdvisory>,
+    ) {
+        if let Some(state) = emergency_state {
+            self.emergency_state = Some(state);
+            self.emergency = Some(state.is_emergency());
+        }
+        if let Some(sq) = squawk {
+            self.squawk = Some(sq);
+        }
+        if let Some(ra) = resolution_advisory {
+            self.resolution_advisory = Some(ra);
+        }
+    }
+
+    /// Store the values of an inferred Comm-B register received at `now`.
+    fn update_comm_b(&mut self, register: &Inferred<CommBRegister>, now: DateTime<Utc>) {
+        let confidence = register.confidence;
+
+        self.air_data.update(register, now);
+        if let (Some(speed), Some(track)) = (self.velocity, self.track) {
+            if let Some(wind) = self.air_data.wind(speed, track, now) {
+                self.wind = Some(wind);
+                self.wind_time = Some(now);
+            }
+        }
+        if let Some(temperature) = self.air_data.static_air_temperature(now) {
+            self.static_air_temperature = Some(temperature);
+        }
+
+        match register.value {
+            CommBRegister::SelectedVerticalIntention {
+                mcp_altitude,
+                fms_altitude,
+                baro_setting,
+            } => {
+                if let Some(alt) = with_confidence(mcp_altitude.or(fms_altitude), confidence) {
+                    self.ehs_selected_altitude = Some(alt);
+                }
+                if let Some(qnh) = with_confidence(baro_setting, confidence) {
+                    self.ehs_baro_setting = Some(qnh);
+                }
+            }
+            // Wind and temperature are taken from the air data above
+            CommBRegister::MeteorologicalRoutine { .. }
+            | CommBRegister::MeteorologicalHazard { .. } => {}
+            CommBRegister::TrackAndTurn {
+                roll,
+                track_rate,
+                true_airspeed,
+            } => {
+                self.roll = with_confidence(roll, confidence).or(self.roll);
+                self.track_rate = with_confidence(track_rate, confidence).or(self.track_rate);
+                self.true_airspeed =
+                    with_confidence(true_airspeed, confidence).or(self.true_airspeed);
+            }
+            CommBRegister::HeadingAndSpeed {
+                magnetic_heading,
+                indicated_airspeed,
+                mach,
+                baro_vertical_rate,
+                inertial_vertical_rate,
+            } => {
+                self.magnetic_heading =
+                    with_confidence(magnetic_heading, confidence).or(self.magnetic_heading);
+                self.indicated_airspeed =
+                    with_confidence(indicated_airspeed, confidence).or(self.indicated_airspeed);
+                self.mach = with_confidence(mach, confidence).or(self.mach);
+                self.baro_vertical_rate =
+                    with_confidence(baro_vertical_rate, confidence).or(self.baro_vertical_rate);
+                self.inertial_vertical_rate = with_confidence(inertial_vertical_rate, confidence)
+                    .or(self.inertial_vertical_rate);
+            }
+        }
+    }
+
+    fn cleanup_old_history(&mut self, max_age_seconds: i64, now: DateTime<Utc>) {
         self.position_history
             .retain(|point| (now - point.timestamp).num_seconds() < max_age_seconds);
     }
 }
 
+/// Attach an inference confidence to an optional Comm-B value.
+fn with_confidence<T>(value: Option<T>, confidence: f32) -> Option<Inferred<T>> {
+    value.map(|value| Inferred { value, confidence })
+}
+
+/// Tracker settings a new position is checked and smoothed with.
+struct PositionFilter<'a> {
+    /// Center point for distance filtering (lat, lon), if any.
+    center: Option<(f64, f64)>,
+    /// Maximum distance from center in miles.
+    max_distance: f64,
+    /// Alpha-beta filter gains, when smoothing is enabled.
+    smoothing: Option<AlphaBeta>,
+    validator: &'a mut dyn PositionValidator,
+}
+
 /// Events emitted by the tracker when aircraft state changes.
 #[derive(Debug, Clone)]
 pub enum TrackerEvent {
@@ -213,12 +628,20 @@ pub enum TrackerEvent {
     PositionUpdated(String),
     /// An aircraft was removed due to timeout.
     AircraftRemoved(String),
+    /// An aircraft started signalling an emergency, or a different one.
+    Emergency {
+        /// ICAO address of the aircraft.
+        icao: String,
+        /// The emergency now signalled.
+        emergency: Emergency,
+    },
 }
 
 /// Configuration for the aircraft tracker.
 #[derive(Debug, Clone)]
 pub struct TrackerConfig {
-    /// Center point for distance filtering (lat, lon).
+    /// Center point for distance filtering (lat, lon), or `None` to accept
+    /// positions at any distance.
     pub center: Option<(f64, f64)>,
     /// Maximum distance from center in miles.
     pub max_distance_miles: f64,
@@ -228,6 +651,11 @@ pub struct TrackerConfig {
     pub position_history_secs: i64,
     /// Broadcast channel capacity for events.
     pub event_channel_capacity: usize,
+    /// Alpha-beta filter gains for smoothing positions, or `None` to keep
+    /// positions as reported.
+    pub track_smoothing: Option<AlphaBeta>,
+    /// Settings of the default position validator.
+    pub position_validation: ValidatorConfig,
 }
 
 impl Default for TrackerConfig {
@@ -238,28 +666,35 @@ impl Default for TrackerConfig {
             aircraft_timeout_secs: 180,
             position_history_secs: 300,
             event_channel_capacity: 256,
+            track_smoothing: None,
+            position_validation: ValidatorConfig::default(),
         }
     }
 }
 
 /// Aircraft tracker that maintains state and emits events.
 pub struct AircraftTracker {
-    aircraft: HashMap<String, Aircraft>,
-    center_lat: f64,
-    center_lon: f64,
+    aircraft: HashMap<String, Arc<Aircraft>>,
+    center: Option<(f64, f64)>,
     max_distance_miles: f64,
     aircraft_timeout_secs: i64,
-    position_history_secs: i64,
+    position_history_secs: Option<i64>,
+    track_smoothing: Option<AlphaBeta>,
+    validator: Box<dyn PositionValidator>,
     event_tx: broadcast::Sender<TrackerEvent>,
+    event_channel_capacity: usize,
 }
 
 impl std::fmt::Debug for AircraftTracker {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("AircraftTracker")
             .field("aircraft_count", &self.aircraft.len())
-            .field("center", &(self.center_lat, self.center_lon))
+            .field("center", &self.center)
             .field("max_distance_miles", &self.max_distance_miles)
-            .finish()
+            .field("aircraft_timeout_secs", &self.aircraft_timeout_secs)
+            .field("position_history_secs", &self.position_history_secs)
+            .field("track_smoothing", &self.track_smoothing)
+            .finish_non_exhaustive()
     }
 }
 
@@ -267,125 +702,138 @@ impl AircraftTracker {
     /// Create a new tracker with the given configuration.
     #[must_use]
     pub fn new(config: TrackerConfig) -> Self {
-        let (center_lat, center_lon) = config.center.unwrap_or((0.0, 0.0));
         let (event_tx, _) = broadcast::channel(config.event_channel_capacity);
 
         Self {
             aircraft: HashMap::new(),
-            center_lat,
-            center_lon,
+            center: config.center,
             max_distance_miles: config.max_distance_miles,
             aircraft_timeout_secs: config.aircraft_timeout_secs,
-            position_history_secs: config.position_history_secs,
+            position_history_secs: Some(config.position_history_secs),
+            track_smoothing: config.track_smoothing,
+            validator: Box::new(KalmanValidator::new(config.position_validation)),
             event_tx,
+            event_channel_capacity: config.event_channel_capacity,
         }
     }
 
     /// Set the center point for distance filtering.
     pub fn set_center(&mut self, lat: f64, lon: f64) {
-        self.center_lat = lat;
-        self.center_lon = lon;
+        self.center = Some((lat, lon));
+    }
+
+    /// Enable or disable track smoothing for subsequent positions.
+    ///
+    /// Disabling it drops existing estimates so positions are shown as
+    /// reported straight away.
+    pub fn set_track_smoothing(&mut self, smoothing: Option<AlphaBeta>) {
+        if smoothing.is_none() {
+            for aircraft in self.aircraft.values_mut() {
+                if aircraft.track_estimate.is_some() {
+                    Arc::make_mut(aircraft).track_estimate = None;
+                }
+            }
+        }
+        self.track_smoothing = smoothing;
+    }
+
+    /// Replace the position validator.
+    ///
+    /// The new validator starts without per-aircraft state; positions of
+    /// aircraft already tracked are checked against their last position.
+    pub fn set_position_validator(&mut self, validator: Box<dyn PositionValidator>) {
+        self.validator = validator;
+    }
+
+    /// Get the current center point, if one is set.
+    #[must_use]
+    pub fn center(&self) -> Option<(f64, f64)> {
+        self.center
     }
 
-    /// Get the current center point.
+    /// Get the maximum distance from the center in nautical miles.
     #[must_use]
-    pub fn center(&self) -> (f64, f64) {
-        (self.center_lat, self.center_lon)
+    pub fn max_distance_nm(&self) -> f64 {
+        self.max_distance_miles / NAUTICAL_MILE_CONVERSION
     }
 
     /// Process an incoming aircraft message.
     pub fn process_message(&mut self, msg: AircraftMessage) {
+        self.process_message_at(msg, Utc::now());
+    }
+
+    /// Process an aircraft message received at `now`.
+    ///
+    /// Replayed feeds pass the time the message was originally received, so
+    /// last-seen times, position history and position validation follow the
+    /// feed's clock instead of the wall clock. Pair it with
+    /// [`cleanup_stale_at`](Self::cleanup_stale_at) on the same clock.
+    pub fn process_message_at(&mut self, msg: AircraftMessage, now: DateTime<Utc>) {
+        self.process_message_with_signal(msg, None, now);
+    }
+
+    /// Process an aircraft message received at `now`, with the receiver
+    /// measurements of the frame it was decoded from.
+    pub fn process_message_with_signal(
+        &mut self,
+        msg: AircraftMessage,
+        signal: Option<Signal>,
+        now: DateTime<Utc>,
+    ) {
         let icao = msg.icao().to_string();
+
+        match &msg {
+            // Clock messages carry no aircraft
+            AircraftMessage::Clock { .. } => return,
+            // The sender stopped tracking the aircraft, so stop tracking it too
+            AircraftMessage::StatusChanged { status, .. } if status.is_removal() => {
+                if self.aircraft.remove(&icao).is_some() {
+                    self.validator.forget(&icao);
+                    let _ = self.event_tx.send(TrackerEvent::AircraftRemoved(icao));
+                }
+                return;
+            }
+            _ => {}
+        }
+
         let is_new = !self.aircraft.contains_key(&icao);
 
-        let aircraft = self
-            .aircraft
-            .entry(icao.clone())
-            .or_insert_with(|| Aircraft::new(icao.clone()));
+        let aircraft = Arc::make_mut(
+            self.aircraft
+                .entry(icao.clone())
+                .or_insert_with(|| Arc::new(Aircraft::new(icao.clone()))),
+        );
 
-        aircraft.last_seen = Utc::now();
+        aircraft.last_seen = now;
+        aircraft.messages += 1;
+        if let Some(signal) = signal {
+            aircraft.record_signal(signal);
+        }
+        let previous_emergency = aircraft.active_emergency();
 
         if is_new {
-            let _ = self.event_tx.send(TrackerEvent::AircraftAdded(icao.clone()));
+            let _ = self
+                .event_tx
+                .send(TrackerEvent::AircraftAdded(icao.clone()));
         }
 
-        match msg {
-            AircraftMessage::Identification { callsign, .. } => {
-                aircraft.callsign = Some(callsign);
-            }
-            AircraftMessage::Position {
-                latitude,
-                longitude,
-                altitude,
-                ground_speed,
-                track,
-                is_on_ground,
-                ..
-            } => {
-                if let Some(alt) = altitude {
-                    aircraft.altitude = Some(alt);
-                }
-                if let Some(gs) = ground_speed {
-                    aircraft.velocity = Some(gs);
-                }
-                if let Some(trk) = track {
-                    aircraft.track = Some(trk);
-                }
-                if let Some(on_ground) = is_on_ground {
-                    aircraft.is_on_ground = Some(on_ground);
-                }
-                let updated = aircraft.update_position(
-                    latitude,
-                    longitude,
-                    self.center_lat,
-                    self.center_lon,
-                    self.max_distance_miles,
-                );
-                if updated {
-                    let _ = self.event_tx.send(TrackerEvent::PositionUpdated(icao));
-                }
-            }
-            AircraftMessage::Velocity {
-                speed,
-                track,
-                vertical_rate,
-                is_on_ground,
-                ..
-            } => {
-                aircraft.velocity = Some(speed);
-                aircraft.track = Some(track);
-                aircraft.vertical_rate = vertical_rate;
-                if let Some(on_ground) = is_on_ground {
-                    aircraft.is_on_ground = Some(on_ground);
-                }
-            }
-            AircraftMessage::Altitude {
-                altitude,
-                squawk,
-                alert,
-                emergency,
-                spi,
-                is_on_ground,
-                ..
-            } => {
-                if let Some(alt) = altitude {
-                    aircraft.altitude = Some(alt);
-                }
-                if let Some(sq) = squawk {
-                    aircraft.squawk = Some(sq);
-                }
-                if let Some(a) = alert {
-                    aircraft.alert = Some(a);
-                }
-                if let Some(e) = emergency {
-                    aircraft.emergency = Some(e);
-                }
-                if let Some(s) = spi {
-                    aircraft.spi = Some(s);
-                }
-                if let Some(on_ground) = is_on_ground {
-                    aircraft.is_on_ground = Some(on_ground);
-                }
+        let mut filter = PositionFilter {
+            center: self.center,
+            max_distance: self.max_distance_miles,
+            smoothing: self.track_smoothing,
+            validator: self.validator.as_mut(),
+        };
+        if aircraft.apply_message(msg, now, &mut filter) {
+            let _ = self.event_tx.send(TrackerEvent::PositionUpdated(icao));
+        }
+
+        if let Some(emergency) = aircraft.active_emergency() {
+            if previous_emergency != Some(emergency) {
+                warn!("Aircraft {} signals {}", aircraft.icao, emergency.label());
+                let _ = self.event_tx.send(TrackerEvent::Emergency {
+                    icao: aircraft.icao.clone(),
+                    emergency,
+                });
             }
         }
     }
@@ -393,13 +841,52 @@ impl AircraftTracker {
     /// Get all tracked aircraft.
     #[must_use]
     pub fn get_aircraft(&self) -> Vec<&Aircraft> {
-        self.aircraft.values().collect()
+        self.aircraft.values().map(Arc::as_ref).collect()
     }
 
     /// Get a specific aircraft by ICAO address.
     #[must_use]
     pub fn get_by_icao(&self, icao: &str) -> Option<&Aircraft> {
-        self.aircraft.get(icao)
+        self.aircraft.get(icao).map(Arc::as_ref)
+    }
+
+    /// Get shared snapshots of all tracked aircraft.
+    ///
+    /// Snapshots are cheap to take and are not affected by later updates.
+    #[must_use]
+    pub fn snapshot(&self) -> Vec<Arc<Aircraft>> {
+        self.aircraft.values().cloned().collect()
+    }
+
+    /// Get a shared snapshot of a specific aircraft.
+    #[must_use]
+    pub fn get_shared(&self, icao: &str) -> Option<Arc<Aircraft>> {
+        self.aircraft.get(icao).cloned()
+    }
+
+    /// Modify a tracked aircraft, typically to update its [`Extensions`].
+    ///
+    /// Returns `None` if the aircraft is not tracked.
+    pub fn update_aircraft<R>(
+        &mut self,
+        icao: &str,
+        f: impl FnOnce(&mut Aircraft) -> R,
+    ) -> Option<R> {
+        self.aircraft
+            .get_mut(icao)
+            .map(|aircraft| f(Arc::make_mut(aircraft)))
+    }
+
+    /// Set how long position history is kept, or `None` to keep the full
+    /// history for as long as the aircraft is tracked.
+    pub fn set_position_history_secs(&mut self, secs: Option<i64>) {
+        self.position_history_secs = secs;
+    }
+
+    /// Get how long position history is kept.
+    #[must_use]
+    pub fn position_history_secs(&self) -> Option<i64> {
+        self.position_history_secs
     }
 
     /// Get the number of tracked aircraft.
@@ -420,13 +907,33 @@ impl AircraftTracker {
         self.event_tx.subscribe()
     }
 
+    /// End every current subscription to tracker events.
+    ///
+    /// Subscribers see their channel close, as if the tracker had been
+    /// dropped. Later subscriptions receive events as usual.
+    pub fn close_events(&mut self) {
+        self.event_tx = broadcast::channel(self.event_channel_capacity).0;
+    }
+
     /// Remove stale aircraft and clean up old position history.
     pub fn cleanup_stale(&mut self) {
-        let now = Utc::now();
+        self.cleanup_stale_at(Utc::now());
+    }
 
-        // Clean up old position history
-        for aircraft in self.aircraft.values_mut() {
-            aircraft.cleanup_old_history(self.position_history_secs);
+    /// Remove aircraft not seen for the timeout before `now`, and position
+    /// history older than the retention period.
+    pub fn cleanup_stale_at(&mut self, now: DateTime<Utc>) {
+        // Clean up old position history, copying only aircraft that change
+        if let Some(max_age_secs) = self.position_history_secs {
+            for aircraft in self.aircraft.values_mut() {
+                let expired = aircraft
+                    .position_history
+                    .first()
+                    .is_some_and(|point| (now - point.timestamp).num_seconds() >= max_age_secs);
+                if expired {
+                    Arc::make_mut(aircraft).cleanup_old_history(max_age_secs, now);
+                }
+            }
         }
 
         // Remove aircraft that haven't been seen recently
@@ -439,6 +946,16 @@ impl AircraftTracker {
 
         for icao in removed {
             self.aircraft.remove(&icao);
+            self.validator.forget(&icao);
+            let _ = self.event_tx.send(TrackerEvent::AircraftRemoved(icao));
+        }
+    }
+
+    /// Stop tracking every aircraft, e.g. before replaying a feed from a
+    /// different point in time.
+    pub fn clear(&mut self) {
+        for (icao, _) in self.aircraft.drain() {
+            self.validator.forget(&icao);
             let _ = self.event_tx.send(TrackerEvent::AircraftRemoved(icao));
         }
     }
@@ -447,6 +964,7 @@ impl AircraftTracker {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::protocol::TrackStatus;
 
     #[test]
     fn test_haversine_distance() {
@@ -461,12 +979,14 @@ mod tests {
 
         tracker.process_message(AircraftMessage::Identification {
             icao: "A1B2C3".to_string(),
+            timestamp: None,
             callsign: "UAL123".to_string(),
         });
 
         assert_eq!(tracker.len(), 1);
         let aircraft = tracker.get_by_icao("A1B2C3").unwrap();
         assert_eq!(aircraft.callsign.as_deref(), Some("UAL123"));
+        assert_eq!(aircraft.messages, 1);
     }
 
     #[test]
@@ -478,6 +998,7 @@ mod tests {
 
         tracker.process_message(AircraftMessage::Position {
             icao: "A1B2C3".to_string(),
+            timestamp: None,
             latitude: 34.0,
             longitude: -118.5,
             altitude: Some(35000),
@@ -492,6 +1013,59 @@ mod tests {
         assert_eq!(aircraft.altitude, Some(35000));
     }
 
+    #[test]
+    fn test_replayed_clock() {
+        let mut tracker = AircraftTracker::new(TrackerConfig {
+            center: Some((33.9425, -118.4081)),
+            ..Default::default()
+        });
+        let recorded = Utc::now() - chrono::Duration::days(30);
+
+        for (secs, latitude) in [(0, 34.0), (10, 34.01)] {
+            tracker.process_message_at(
+                AircraftMessage::Position {
+                    icao: "A1B2C3".to_string(),
+                    timestamp: None,
+                    latitude,
+                    longitude: -118.5,
+                    altitude: Some(35000),
+                    ground_speed: None,
+                    track: None,
+                    is_on_ground: None,
+                },
+                recorded + chrono::Duration::seconds(secs),
+            );
+        }
+
+        // A month-old position 10 s after the last is neither a jump nor stale
+        let aircraft = tracker.get_by_icao("A1B2C3").unwrap();
+        assert_eq!(aircraft.latitude, Some(34.01));
+        assert_eq!(aircraft.position_stats.rejected, 0);
+        assert_eq!(aircraft.last_seen, recorded + chrono::Duration::seconds(10));
+        assert_eq!(aircraft.position_history[0].timestamp, recorded);
+
+        tracker.cleanup_stale_at(recorded + chrono::Duration::seconds(60));
+        assert_eq!(tracker.len(), 1);
+        tracker.cleanup_stale_at(recorded + chrono::Duration::seconds(600));
+        assert!(tracker.is_empty());
+
+        tracker.process_message_at(
+            AircraftMessage::CallsignChanged {
+                icao: "A1B2C3".to_string(),
+                timestamp: None,
+                callsign: "UAL456".to_string(),
+            },
+            recorded,
+        );
+        let mut events = tracker.subscribe();
+        tracker.clear();
+        assert!(tracker.is_empty());
+        assert!(matches!(
+            events.try_recv(),
+            Ok(TrackerEvent::AircraftRemoved(icao)) if icao == "A1B2C3"
+        ));
+    }
+
     #[test]
     fn test_position_rejected_too_far() {
         let mut tracker = AircraftTracker::new(TrackerConfig {
@@ -503,6 +1077,7 @@ mod tests {
         // Position far from center should be rejected (LAX to NYC)
         tracker.process_message(AircraftMessage::Position {
             icao: "A1B2C3".to_string(),
+            timestamp: None,
             latitude: 40.6413,
             longitude: -73.7781,
             altitude: Some(35000),
@@ -514,4 +1089,224 @@ mod tests {
         let aircraft = tracker.get_by_icao("A1B2C3").unwrap();
         assert!(aircraft.latitude.is_none());
     }
+
+    #[test]
+    fn test_no_center_accepts_any_distance() {
+        let mut tracker = AircraftTracker::new(TrackerConfig {
+            max_distance_miles: 100.0,
+            ..Default::default()
+        });
+        assert_eq!(tracker.center(), None);
+
+        tracker.process_message(AircraftMessage::Position {
+            icao: "A1B2C3".to_string(),
+            timestamp: None,
+            latitude: 40.6413,
+            longitude: -73.7781,
+            altitude: Some(35000),
+            ground_speed: None,
+            track: None,
+            is_on_ground: None,
+        });
+
+        let aircraft = tracker.get_by_icao("A1B2C3").unwrap();
+        assert_eq!(aircraft.latitude, Some(40.6413));
+    }
+
+    #[test]
+    fn test_comm_b_values_keep_confidence() {
+        let mut tracker = AircraftTracker::new(TrackerConfig::default());
+
+        tracker.process_message(AircraftMessage::CommB {
+            icao: "A1B2C3".to_string(),
+            timestamp: None,
+            altitude: Some(24000),
+            squawk: None,
+            register: Inferred {
+                value: CommBRegister::HeadingAndSpeed {
+                    magnetic_heading: Some(90.0),
+                    indicated_airspeed: Some(280.0),
+                    mach: None,
+                    baro_vertical_rate: None,
+                    inertial_vertical_rate: None,
+                },
+                confidence: 0.8,
+            },
+        });
+
+        let aircraft = tracker.get_by_icao("A1B2C3").unwrap();
+        assert_eq!(aircraft.altitude, Some(24000));
+        assert_eq!(
+            aircraft.indicated_airspeed,
+            Some(Inferred {
+                value: 280.0,
+                confidence: 0.8
+            })
+        );
+        assert!(aircraft.mach.is_none());
+    }
+
+    #[test]
+    fn test_target_state_and_emergency_status() {
+        let mut tracker = AircraftTracker::new(TrackerConfig::default());
+
+        tracker.process_message(AircraftMessage::TargetState {
+            icao: "A1B2C3".to_string(),
+            timestamp: None,
+            selected_altitude: Some(36000),
+            selected_altitude_source: Some(SelectedAltitudeSource::Fms),
+            selected_heading: Some(270.0),
+            baro_setting: Some(1013.6),
+            autopilot_modes: Some(AutopilotModes {
+                autopilot: true,
+                lnav: true,
+                ..Default::default()
+            }),
+        });
+        tracker.process_message(AircraftMessage::AircraftStatus {
+            icao: "A1B2C3".to_string(),
+            timestamp: None,
+            emergency_state: Some(EmergencyState::NoCommunications),
+            squawk: Some("7600".to_string()),
+            resolution_advisory: None,
+        });
+
+        let aircraft = tracker.get_by_icao("A1B2C3").unwrap();
+        assert_eq!(aircraft.selected_altitude, Some(36000));
+        assert_eq!(
+            aircraft.selected_altitude_source,
+            Some(SelectedAltitudeSource::Fms)
+        );
+        assert_eq!(aircraft.autopilot_modes.unwrap().to_string(), "AP LNAV");
+        assert_eq!(aircraft.squawk.as_deref(), Some("7600"));
+        assert_eq!(aircraft.emergency, Some(true));
+    }
+
+    #[test]
+    fn test_emergency_events() {
+        let mut tracker = AircraftTracker::new(TrackerConfig::default());
+        let mut events = tracker.subscribe();
+        let status = |squawk: &str, emergency_state| AircraftMessage::AircraftStatus {
+            icao: "A1B2C3".to_string(),
+            timestamp: None,
+            emergency_state: Some(emergency_state),
+            squawk: Some(squawk.to_string()),
+            resolution_advisory: None,
+        };
+
+        tracker.process_message(status("1200", EmergencyState::None));
+        tracker.process_message(status("7600", EmergencyState::NoCommunications));
+        // Repeated reports of the same emergency are not events
+        tracker.process_message(status("7600", EmergencyState::NoCommunications));
+        tracker.process_message(status("7700", EmergencyState::General));
+
+        let emergencies: Vec<_> = std::iter::from_fn(|| events.try_recv().ok())
+            .filter_map(|event| match event {
+                TrackerEvent::Emergency { icao, emergency } => Some((icao, emergency)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            emergencies,
+            vec![
+                ("A1B2C3".to_string(), Emergency::RadioFailure),
+                ("A1B2C3".to_string(), Emergency::General),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_callsign_change_and_removal() {
+        let mut tracker = AircraftTracker::new(TrackerConfig::default());
+        let mut events = tracker.subscribe();
+
+        tracker.process_message(AircraftMessage::CallsignChanged {
+            icao: "A1B2C3".to_string(),
+            timestamp: None,
+            callsign: "UAL456".to_string(),
+        });
+        assert_eq!(
+            tracker.get_by_icao("A1B2C3").unwrap().callsign.as_deref(),
+            Some("UAL456")
+        );
+
+        // Clock messages never create an aircraft
+        tracker.process_message(AircraftMessage::Clock { timestamp: None });
+        assert_eq!(tracker.len(), 1);
+
+        tracker.process_message(AircraftMessage::StatusChanged {
+            icao: "A1B2C3".to_string(),
+            timestamp: None,
+            status: TrackStatus::Removed,
+        });
+        assert!(tracker.is_empty());
+
+        assert!(matches!(
+            events.try_recv(),
+            Ok(TrackerEvent::AircraftAdded(_))
+        ));
+        assert!(matches!(
+            events.try_recv(),
+            Ok(TrackerEvent::AircraftRemoved(icao)) if icao == "A1B2C3"
+        ));
+    }
+
+    #[test]
+    fn test_snapshots_and_extensions() {
+        #[derive(Debug, Clone, PartialEq)]
+        struct Registration(&'static str);
+
+        let mut tracker = AircraftTracker::new(TrackerConfig::default());
+        tracker.process_message(AircraftMessage::CallsignChanged {
+            icao: "A1B2C3".to_string(),
+            timestamp: None,
+            callsign: "UAL456".to_string(),
+        });
+
+        let before = tracker.get_shared("A1B2C3").unwrap();
+        tracker.update_aircraft("A1B2C3", |aircraft| {
+            aircraft.extensions.insert(Registration("N12345"));
+        });
+        assert!(tracker.update_aircraft("FFFFFF", |_| ()).is_none());
+
+        // Earlier snapshots are unaffected by later updates
+        assert!(before.extensions.is_empty());
+        let after = tracker.snapshot();
+        assert_eq!(
+            after[0].extensions.get::<Registration>(),
+            Some(&Registration("N12345"))
+        );
+        assert_eq!(after[0].callsign.as_deref(), Some("UAL456"));
+    }
+
+    #[test]
+    fn test_uat_state_vector() {
+        let mut tracker = AircraftTracker::new(TrackerConfig {
+            center: Some((40.0, -105.0)),
+            ..Default::default()
+        });
+
+        tracker.process_message(AircraftMessage::StateVector {
+            icao: "A1B2C3".to_string(),
+            timestamp: None,
+            source: DataSource::Uat,
+            latitude: Some(40.1),
+            longitude: Some(-105.1),
+            altitude: Some(7500),
+            ground_speed: Some(110.0),
+            track: Some(90.0),
+            vertical_rate: Some(-320),
+            is_on_ground: Some(false),
+            callsign: Some("N12345".to_string()),
+            squawk: None,
+            emergency_state: None,
+        });
+
+        let aircraft = tracker.get_by_icao("A1B2C3").unwrap();
+        assert_eq!(aircraft.source, DataSource::Uat);
+        assert_eq!(aircraft.callsign.as_deref(), Some("N12345"));
+        assert_eq!(aircraft.latitude, Some(40.1));
+        assert_eq!(aircraft.velocity, Some(110.0));
+        assert_eq!(aircraft.vertical_rate, Some(-320));
+    }
 }
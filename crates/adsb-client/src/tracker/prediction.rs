@@ -0,0 +1,319 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Dead reckoning and track smoothing.
+//!
+//! Positions arrive a few times per second at best, and not at all while an
+//! aircraft is out of coverage. [`Aircraft::predicted_position`] moves the
+//! last known position forward along the aircraft's velocity so displays can
+//! animate smoothly between updates.
+//!
+//! With [`TrackerConfig::track_smoothing`](super::TrackerConfig::track_smoothing)
+//! set, every accepted position also updates an alpha-beta filter. The filter
+//! trades a little lag for much less jitter on noisy sources (MLAT, low `NACp`),
+//! and provides a velocity estimate for feeds that never report one.
+
+use chrono::{DateTime, Utc};
+
+use super::{Aircraft, NM_PER_DEGREE};
+
+/// Positions are not extrapolated further than this, in seconds.
+const MAX_EXTRAPOLATION_SECS: f64 = 60.0;
+/// After a gap longer than this (seconds) the filter restarts from the new
+/// position instead of blending it in.
+const MAX_FILTER_GAP_SECS: f64 = 30.0;
+
+/// Gains of the alpha-beta filter that smooths noisy positions.
+///
+/// `alpha` is the share of each position error applied to the position
+/// estimate, `beta` the share applied to the velocity estimate. Lower values
+/// smooth more but follow turns more slowly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlphaBeta {
+    /// Position gain (0-1).
+    pub alpha: f64,
+    /// Velocity gain (0-1).
+    pub beta: f64,
+}
+
+impl Default for AlphaBeta {
+    fn default() -> Self {
+        Self {
+            alpha: 0.6,
+            beta: 0.2,
+        }
+    }
+}
+
+/// Smoothed position and velocity of an aircraft.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrackEstimate {
+    /// Estimated latitude in degrees.
+    pub latitude: f64,
+    /// Estimated longitude in degrees.
+    pub longitude: f64,
+    /// Estimated ground velocity east, in knots.
+    pub east_kt: f64,
+    /// Estimated ground velocity north, in knots.
+    pub north_kt: f64,
+    /// Time of the last position folded into the estimate.
+    pub timestamp: DateTime<Utc>,
+}
+
+impl TrackEstimate {
+    fn new(
+        latitude: f64,
+        longitude: f64,
+        velocity: Option<(f64, f64)>,
+        timestamp: DateTime<Utc>,
+    ) -> Self {
+        let (east_kt, north_kt) = velocity.unwrap_or_default();
+        Self {
+            latitude,
+            longitude,
+            east_kt,
+            north_kt,
+            timestamp,
+        }
+    }
+
+    /// Fold a measured position into the estimate.
+    fn update(
+        &mut self,
+        latitude: f64,
+        longitude: f64,
+        velocity: Option<(f64, f64)>,
+        timestamp: DateTime<Utc>,
+        gains: AlphaBeta,
+    ) {
+        let elapsed = seconds_between(self.timestamp, timestamp);
+        if elapsed <= 0.0 {
+            return;
+        }
+        if elapsed > MAX_FILTER_GAP_SECS {
+            *self = Self::new(latitude, longitude, velocity, timestamp);
+            return;
+        }
+
+        let (predicted_latitude, predicted_longitude) = move_by(
+            self.latitude,
+            self.longitude,
+            self.east_kt,
+            self.north_kt,
+            elapsed,
+        );
+        let (error_east_nm, error_north_nm) =
+            offset_nm(predicted_latitude, predicted_longitude, latitude, longitude);
+
+        let (latitude, longitude) = offset_position(
+            predicted_latitude,
+            predicted_longitude,
+            gains.alpha * error_east_nm,
+            gains.alpha * error_north_nm,
+        );
+        self.latitude = latitude;
+        self.longitude = longitude;
+        self.east_kt += gains.beta * error_east_nm / elapsed * 3600.0;
+        self.north_kt += gains.beta * error_north_nm / elapsed * 3600.0;
+        self.timestamp = timestamp;
+    }
+}
+
+/// A position extrapolated from the last known position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PredictedPosition {
+    /// Predicted latitude in degrees.
+    pub latitude: f64,
+    /// Predicted longitude in degrees.
+    pub longitude: f64,
+    /// Seconds since the position it was extrapolated from.
+    pub age_secs: f64,
+    /// Whether the position was moved forward, rather than held because the
+    /// velocity is unknown.
+    pub extrapolated: bool,
+}
+
+impl Aircraft {
+    /// Estimate the aircraft's position at `now` by dead reckoning.
+    ///
+    /// Starts from the smoothed position if track smoothing is enabled, else
+    /// the last reported one, and moves it along the reported ground speed
+    /// and track (or the filter's velocity estimate when none is reported).
+    /// Extrapolation stops after a minute; beyond that the position is held.
+    #[must_use]
+    pub fn predicted_position(&self, now: DateTime<Utc>) -> Option<PredictedPosition> {
+        let (latitude, longitude, timestamp) = match self.track_estimate {
+            Some(estimate) => (estimate.latitude, estimate.longitude, estimate.timestamp),
+            None => (self.latitude?, self.longitude?, self.position_time?),
+        };
+        let age_secs = seconds_between(timestamp, now).max(0.0);
+
+        let velocity = self.reported_velocity().or_else(|| {
+            self.track_estimate
+                .map(|estimate| (estimate.east_kt, estimate.north_kt))
+        });
+        let Some((east_kt, north_kt)) = velocity.filter(|_| age_secs > 0.0) else {
+            return Some(PredictedPosition {
+                latitude,
+                longitude,
+                age_secs,
+                extrapolated: false,
+            });
+        };
+
+        let (latitude, longitude) = move_by(
+            latitude,
+            longitude,
+            east_kt,
+            north_kt,
+            age_secs.min(MAX_EXTRAPOLATION_SECS),
+        );
+        Some(PredictedPosition {
+            latitude,
+            longitude,
+            age_secs,
+            extrapolated: true,
+        })
+    }
+
+    /// Reported ground velocity as (east, north) knots.
+    fn reported_velocity(&self) -> Option<(f64, f64)> {
+        let (sin, cos) = self.track?.to_radians().sin_cos();
+        let speed = self.velocity?;
+        Some((speed * sin, speed * cos))
+    }
+
+    /// Update the track filter with the position just accepted.
+    pub(super) fn smooth_position(&mut self, gains: AlphaBeta, timestamp: DateTime<Utc>) {
+        let (Some(latitude), Some(longitude)) = (self.latitude, self.longitude) else {
+            return;
+        };
+        let velocity = self.reported_velocity();
+        match &mut self.track_estimate {
+            Some(estimate) => estimate.update(latitude, longitude, velocity, timestamp, gains),
+            None => {
+                self.track_estimate =
+                    Some(TrackEstimate::new(latitude, longitude, velocity, timestamp));
+            }
+        }
+    }
+}
+
+/// Seconds from `from` to `to`.
+pub(super) fn seconds_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    (to - from).as_seconds_f64()
+}
+
+/// Move a position along a velocity (knots) for a number of seconds.
+fn move_by(latitude: f64, longitude: f64, east_kt: f64, north_kt: f64, secs: f64) -> (f64, f64) {
+    offset_position(
+        latitude,
+        longitude,
+        east_kt * secs / 3600.0,
+        north_kt * secs / 3600.0,
+    )
+}
+
+/// Move a position by an offset (east, north) in nautical miles.
+pub(super) fn offset_position(
+    latitude: f64,
+    longitude: f64,
+    east_nm: f64,
+    north_nm: f64,
+) -> (f64, f64) {
+    let latitude_after = latitude + north_nm / NM_PER_DEGREE;
+    let longitude_after = longitude + east_nm / (NM_PER_DEGREE * latitude.to_radians().cos());
+    (
+        latitude_after,
+        (longitude_after + 180.0).rem_euclid(360.0) - 180.0,
+    )
+}
+
+/// Offset (east, north) in nautical miles from one position to another.
+pub(super) fn offset_nm(
+    from_latitude: f64,
+    from_longitude: f64,
+    to_latitude: f64,
+    to_longitude: f64,
+) -> (f64, f64) {
+    let delta_longitude = (to_longitude - from_longitude + 180.0).rem_euclid(360.0) - 180.0;
+    (
+        delta_longitude * NM_PER_DEGREE * from_latitude.to_radians().cos(),
+        (to_latitude - from_latitude) * NM_PER_DEGREE,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_dead_reckoning() {
+        let now = Utc::now();
+        let mut aircraft = Aircraft::new("A1B2C3".to_string());
+        assert_eq!(aircraft.predicted_position(now), None);
+
+        aircraft.latitude = Some(37.0);
+        aircraft.longitude = Some(-122.0);
+        aircraft.position_time = Some(now - Duration::seconds(30));
+
+        // Without a velocity the position is held
+        let held = aircraft.predicted_position(now).unwrap();
+        assert!(!held.extrapolated);
+        assert!((held.age_secs - 30.0).abs() < 0.01);
+        assert!((held.latitude - 37.0).abs() < f64::EPSILON);
+
+        // 360 kt north for 30 s is 3 nm, or 0.05 degrees
+        aircraft.velocity = Some(360.0);
+        aircraft.track = Some(0.0);
+        let moved = aircraft.predicted_position(now).unwrap();
+        assert!(moved.extrapolated);
+        assert!((moved.latitude - 37.05).abs() < 1e-6);
+        assert!((moved.longitude + 122.0).abs() < 1e-6);
+
+        // Extrapolation stops after a minute
+        aircraft.position_time = Some(now - Duration::seconds(600));
+        let capped = aircraft.predicted_position(now).unwrap();
+        assert!((capped.latitude - 37.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_alpha_beta_smoothing() {
+        let start = Utc::now();
+        let mut aircraft = Aircraft::new("A1B2C3".to_string());
+        let gains = AlphaBeta::default();
+
+        // Due east at 360 kt (0.1 nm/s) with alternating 0.2 nm north/south noise
+        for step in 0..20 {
+            let secs = step * 2;
+            let noise = if step % 2 == 0 { 0.2 } else { -0.2 };
+            aircraft.latitude = Some(37.0 + noise / NM_PER_DEGREE);
+            aircraft.longitude = Some(
+                -122.0 + 0.1 * f64::from(secs) / (NM_PER_DEGREE * 37.0_f64.to_radians().cos()),
+            );
+            aircraft.smooth_position(gains, start + Duration::seconds(secs.into()));
+        }
+
+        let estimate = aircraft.track_estimate.unwrap();
+        // The velocity is learned without a reported one, and the noise is
+        // damped: raw position differences swing 360 kt north and south
+        assert!((estimate.latitude - 37.0).abs() * NM_PER_DEGREE < 0.2);
+        assert!((estimate.east_kt - 360.0).abs() < 10.0);
+        assert!(estimate.north_kt.abs() < 90.0);
+    }
+}
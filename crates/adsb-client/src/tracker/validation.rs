@@ -0,0 +1,554 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Position validation.
+//!
+//! Every decoded position is checked by the tracker's [`PositionValidator`]
+//! before it is stored. Bad CPR decodes and garbled MLAT results would
+//! otherwise draw spikes across the map and corrupt trails.
+//!
+//! The default [`KalmanValidator`] applies two gates:
+//! - a speed gate: the aircraft cannot have moved further than its reported
+//!   ground speed (or [`ValidatorConfig::max_speed_kt`]) allows in the time
+//!   since its last position;
+//! - a track gate: a constant-velocity Kalman filter follows each aircraft,
+//!   and positions too far from its prediction for their accuracy (`NACp`) are
+//!   rejected.
+//!
+//! When an aircraft's track itself is wrong, for example because the first
+//! position was bad, every later position would be rejected. After
+//! [`ValidatorConfig::reacquire_after`] rejections in a row, a position that
+//! agrees with the previous rejected one is accepted and the track restarts.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+
+use super::prediction::{offset_nm, offset_position, seconds_between};
+use super::{haversine_distance_nm, Aircraft};
+
+/// The filter restarts instead of predicting across gaps longer than this
+/// (seconds).
+const MAX_FILTER_GAP_SECS: f64 = 60.0;
+/// Position accuracy assumed when no `NACp` is known, in nm (one sigma).
+const DEFAULT_POSITION_SIGMA_NM: f64 = 0.1;
+/// Smallest position accuracy used, in nm, so precise sources still allow
+/// for encoding and timing error.
+const MIN_POSITION_SIGMA_NM: f64 = 0.01;
+/// Uncertainty of a reported ground velocity, in knots.
+const REPORTED_VELOCITY_SIGMA_KT: f64 = 20.0;
+
+/// A decoded position to validate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionReport {
+    /// Latitude in degrees.
+    pub latitude: f64,
+    /// Longitude in degrees.
+    pub longitude: f64,
+    /// Time the position was received.
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Why a position was rejected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rejection {
+    /// The aircraft could not have flown this far since its last position.
+    TooFar {
+        /// Distance from the last position in nm.
+        distance_nm: f64,
+        /// Largest plausible distance in nm.
+        max_nm: f64,
+        /// Seconds since the last position.
+        elapsed_secs: f64,
+    },
+    /// The position is too far from the aircraft's predicted track.
+    OffTrack {
+        /// Distance from the predicted position in nm.
+        distance_nm: f64,
+        /// Normalised innovation squared of the position.
+        score: f64,
+    },
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooFar {
+                distance_nm,
+                max_nm,
+                elapsed_secs,
+            } => write!(
+                f,
+                "moved {distance_nm:.1} nm in {elapsed_secs:.0} s, at most {max_nm:.1} nm is plausible"
+            ),
+            Self::OffTrack { distance_nm, score } => write!(
+                f,
+                "{distance_nm:.1} nm from the predicted track (score {score:.1})"
+            ),
+        }
+    }
+}
+
+/// Position validation counters for one aircraft.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PositionStats {
+    /// Positions accepted.
+    pub accepted: u64,
+    /// Positions rejected by the validator.
+    pub rejected: u64,
+    /// Positions rejected since the last accepted one.
+    pub consecutive_rejections: u32,
+    /// Reason for the most recent rejection.
+    pub last_rejection: Option<Rejection>,
+}
+
+impl PositionStats {
+    /// Share of validated positions that were rejected (0-1).
+    #[must_use]
+    #[allow(clippy::cast_precision_loss, reason = "counts stay far below 2^52")]
+    pub fn rejection_rate(&self) -> f64 {
+        let total = self.accepted + self.rejected;
+        if total == 0 {
+            0.0
+        } else {
+            self.rejected as f64 / total as f64
+        }
+    }
+}
+
+/// Decides whether decoded positions are plausible.
+///
+/// The tracker calls the validator for every position inside its range,
+/// before the aircraft is updated, so `aircraft` still holds the previous
+/// position ([`Aircraft::position_time`] is when it was accepted) and the
+/// current [`Aircraft::position_stats`].
+pub trait PositionValidator: Send + Sync {
+    /// Check a position, returning why it was rejected if it is implausible.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`Rejection`] reason when the position should be dropped.
+    fn validate(&mut self, aircraft: &Aircraft, report: &PositionReport) -> Result<(), Rejection>;
+
+    /// Forget any state kept for an aircraft the tracker no longer tracks.
+    fn forget(&mut self, _icao: &str) {}
+}
+
+/// Settings of the default [`KalmanValidator`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidatorConfig {
+    /// Speed assumed for aircraft without a reported ground speed, in knots.
+    pub max_speed_kt: f64,
+    /// Factor applied to the reported ground speed for the speed gate.
+    pub speed_margin: f64,
+    /// Distance always allowed by the speed gate, in nm.
+    pub distance_slack_nm: f64,
+    /// Normalised innovation squared beyond which a position is off track.
+    ///
+    /// The score has two degrees of freedom, so 16 rejects about 1 in 3000
+    /// good positions.
+    pub track_gate: f64,
+    /// Acceleration the motion model allows for, in knots per second.
+    pub maneuver_kt_per_sec: f64,
+    /// Rejections in a row after which positions agreeing with each other
+    /// restart the track.
+    pub reacquire_after: u32,
+}
+
+impl Default for ValidatorConfig {
+    fn default() -> Self {
+        Self {
+            max_speed_kt: 1000.0,
+            speed_margin: 1.5,
+            distance_slack_nm: 0.5,
+            track_gate: 16.0,
+            maneuver_kt_per_sec: 15.0,
+            reacquire_after: 3,
+        }
+    }
+}
+
+/// Position and velocity along one axis, in nm and nm per second.
+#[derive(Debug, Clone, Copy)]
+struct Axis {
+    /// Offset from the track position in nm.
+    position: f64,
+    velocity: f64,
+    covariance: [[f64; 2]; 2],
+}
+
+impl Axis {
+    fn new(velocity: f64, position_variance: f64, velocity_variance: f64) -> Self {
+        Self {
+            position: 0.0,
+            velocity,
+            covariance: [[position_variance, 0.0], [0.0, velocity_variance]],
+        }
+    }
+
+    /// Move the state forward with acceleration noise `q` (nm²/s⁴).
+    fn predict(&self, dt: f64, q: f64) -> Self {
+        let [[p00, p01], [p10, p11]] = self.covariance;
+        Self {
+            position: self.position + self.velocity * dt,
+            velocity: self.velocity,
+            covariance: [
+                [
+                    p00 + dt * (p01 + p10) + dt * dt * p11 + q * dt.powi(4) / 4.0,
+                    p01 + dt * p11 + q * dt.powi(3) / 2.0,
+                ],
+                [p10 + dt * p11 + q * dt.powi(3) / 2.0, p11 + q * dt * dt],
+            ],
+        }
+    }
+
+    /// Innovation and its variance for a measured position.
+    fn innovation(&self, measured: f64, variance: f64) -> (f64, f64) {
+        (measured - self.position, self.covariance[0][0] + variance)
+    }
+
+    /// Fold a measured position in.
+    fn update(&mut self, measured: f64, variance: f64) {
+        let (innovation, innovation_variance) = self.innovation(measured, variance);
+        let [[p00, p01], [p10, p11]] = self.covariance;
+        let gain_position = p00 / innovation_variance;
+        let gain_velocity = p10 / innovation_variance;
+        self.position += gain_position * innovation;
+        self.velocity += gain_velocity * innovation;
+        self.covariance = [
+            [(1.0 - gain_position) * p00, (1.0 - gain_position) * p01],
+            [p10 - gain_velocity * p00, p11 - gain_velocity * p01],
+        ];
+    }
+}
+
+/// Kalman filter state of one aircraft.
+#[derive(Debug, Clone, Copy)]
+struct Track {
+    latitude: f64,
+    longitude: f64,
+    east: Axis,
+    north: Axis,
+    timestamp: DateTime<Utc>,
+    /// Last rejected position, to recognise a consistent run of them.
+    last_rejected: Option<PositionReport>,
+}
+
+impl Track {
+    fn new(aircraft: &Aircraft, report: &PositionReport, config: &ValidatorConfig) -> Self {
+        let position_variance = position_sigma_nm(aircraft).powi(2);
+        let ((east, north), velocity_sigma_kt) = match (aircraft.velocity, aircraft.track) {
+            (Some(speed), Some(track)) => {
+                let (sin, cos) = track.to_radians().sin_cos();
+                ((speed * sin, speed * cos), REPORTED_VELOCITY_SIGMA_KT)
+            }
+            _ => ((0.0, 0.0), config.max_speed_kt),
+        };
+        let velocity_variance = (velocity_sigma_kt / 3600.0).powi(2);
+        Self {
+            latitude: report.latitude,
+            longitude: report.longitude,
+            east: Axis::new(east / 3600.0, position_variance, velocity_variance),
+            north: Axis::new(north / 3600.0, position_variance, velocity_variance),
+            timestamp: report.timestamp,
+            last_rejected: None,
+        }
+    }
+}
+
+/// Speed-gated Kalman filter validator, the tracker's default.
+#[derive(Debug, Default)]
+pub struct KalmanValidator {
+    config: ValidatorConfig,
+    tracks: HashMap<String, Track>,
+}
+
+impl KalmanValidator {
+    /// Create a validator with the given settings.
+    #[must_use]
+    pub fn new(config: ValidatorConfig) -> Self {
+        Self {
+            config,
+            tracks: HashMap::new(),
+        }
+    }
+
+    /// Current settings.
+    #[must_use]
+    pub fn config(&self) -> &ValidatorConfig {
+        &self.config
+    }
+
+    /// Speed gate against the aircraft's last accepted position.
+    fn check_speed(&self, aircraft: &Aircraft, report: &PositionReport) -> Result<(), Rejection> {
+        let (Some(latitude), Some(longitude), Some(position_time)) = (
+            aircraft.latitude,
+            aircraft.longitude,
+            aircraft.position_time,
+        ) else {
+            return Ok(());
+        };
+        let elapsed_secs = seconds_between(position_time, report.timestamp).max(0.0);
+        let speed_kt = aircraft.velocity.map_or(self.config.max_speed_kt, |speed| {
+            speed * self.config.speed_margin
+        });
+        let max_nm = speed_kt * elapsed_secs / 3600.0 + self.config.distance_slack_nm;
+        let distance_nm =
+            haversine_distance_nm(latitude, longitude, report.latitude, report.longitude);
+        if distance_nm > max_nm {
+            return Err(Rejection::TooFar {
+                distance_nm,
+                max_nm,
+                elapsed_secs,
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether two positions could both belong to the same aircraft.
+    fn consistent(&self, earlier: &PositionReport, later: &PositionReport) -> bool {
+        let elapsed_secs = seconds_between(earlier.timestamp, later.timestamp).max(0.0);
+        let max_nm =
+            self.config.max_speed_kt * elapsed_secs / 3600.0 + self.config.distance_slack_nm;
+        haversine_distance_nm(
+            earlier.latitude,
+            earlier.longitude,
+            later.latitude,
+            later.longitude,
+        ) <= max_nm
+    }
+}
+
+impl PositionValidator for KalmanValidator {
+    fn validate(&mut self, aircraft: &Aircraft, report: &PositionReport) -> Result<(), Rejection> {
+        let q = (self.config.maneuver_kt_per_sec / 3600.0).powi(2);
+        let measurement_variance = position_sigma_nm(aircraft).powi(2);
+
+        let result = self.check_speed(aircraft, report).and_then(|()| {
+            let Some(track) = self.tracks.get(&aircraft.icao) else {
+                return Ok(None);
+            };
+            let dt = seconds_between(track.timestamp, report.timestamp).max(0.0);
+            if dt > MAX_FILTER_GAP_SECS {
+                return Ok(None);
+            }
+
+            let (east_nm, north_nm) = offset_nm(
+                track.latitude,
+                track.longitude,
+                report.latitude,
+                report.longitude,
+            );
+            let east = track.east.predict(dt, q);
+            let north = track.north.predict(dt, q);
+            let (east_innovation, east_variance) = east.innovation(east_nm, measurement_variance);
+            let (north_innovation, north_variance) =
+                north.innovation(north_nm, measurement_variance);
+            let score =
+                east_innovation.powi(2) / east_variance + north_innovation.powi(2) / north_variance;
+            if score > self.config.track_gate {
+                return Err(Rejection::OffTrack {
+                    distance_nm: east_innovation.hypot(north_innovation),
+                    score,
+                });
+            }
+            Ok(Some((east, north, east_nm, north_nm)))
+        });
+
+        match result {
+            Ok(Some((mut east, mut north, east_nm, north_nm))) => {
+                east.update(east_nm, measurement_variance);
+                north.update(north_nm, measurement_variance);
+                let track = self
+                    .tracks
+                    .get_mut(&aircraft.icao)
+                    .expect("track checked above");
+                // Re-centre the filter on its new position estimate
+                (track.latitude, track.longitude) = offset_position(
+                    track.latitude,
+                    track.longitude,
+                    east.position,
+                    north.position,
+                );
+                east.position = 0.0;
+                north.position = 0.0;
+                track.east = east;
+                track.north = north;
+                track.timestamp = report.timestamp;
+                track.last_rejected = None;
+                Ok(())
+            }
+            Ok(None) => {
+                self.tracks.insert(
+                    aircraft.icao.clone(),
+                    Track::new(aircraft, report, &self.config),
+                );
+                Ok(())
+            }
+            Err(rejection) => {
+                let track = self
+                    .tracks
+                    .entry(aircraft.icao.clone())
+                    .or_insert_with(|| Track::new(aircraft, report, &self.config));
+                let previous = track.last_rejected.replace(*report);
+
+                // A run of rejected positions that agree with each other means
+                // the track is wrong, not the positions
+                let reacquire = aircraft.position_stats.consecutive_rejections
+                    >= self.config.reacquire_after
+                    && previous.is_some_and(|previous| self.consistent(&previous, report));
+                if reacquire {
+                    self.tracks.insert(
+                        aircraft.icao.clone(),
+                        Track::new(aircraft, report, &self.config),
+                    );
+                    return Ok(());
+                }
+                Err(rejection)
+            }
+        }
+    }
+
+    fn forget(&mut self, icao: &str) {
+        self.tracks.remove(icao);
+    }
+}
+
+/// One-sigma position accuracy in nm, from the aircraft's `NACp` when known.
+fn position_sigma_nm(aircraft: &Aircraft) -> f64 {
+    // Estimated position uncertainty (95% bound) in nm for each NACp
+    let epu_nm: f64 = match aircraft.nac_p {
+        Some(11) => 0.0016,
+        Some(10) => 0.0054,
+        Some(9) => 0.016,
+        Some(8) => 0.05,
+        Some(7) => 0.1,
+        Some(6) => 0.3,
+        Some(5) => 0.5,
+        Some(4) => 1.0,
+        Some(3) => 2.0,
+        Some(2) => 4.0,
+        Some(1) => 10.0,
+        _ => return DEFAULT_POSITION_SIGMA_NM,
+    };
+    (epu_nm / 2.0).max(MIN_POSITION_SIGMA_NM)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    /// Feed a report through the validator and apply it as the tracker does.
+    fn apply(
+        validator: &mut KalmanValidator,
+        aircraft: &mut Aircraft,
+        report: PositionReport,
+    ) -> Result<(), Rejection> {
+        let result = validator.validate(aircraft, &report);
+        match result {
+            Ok(()) => {
+                aircraft.latitude = Some(report.latitude);
+                aircraft.longitude = Some(report.longitude);
+                aircraft.position_time = Some(report.timestamp);
+                aircraft.position_stats.consecutive_rejections = 0;
+            }
+            Err(_) => aircraft.position_stats.consecutive_rejections += 1,
+        }
+        result
+    }
+
+    fn report(start: DateTime<Utc>, secs: i64, east_nm: f64) -> PositionReport {
+        let (latitude, longitude) = offset_position(37.0, -122.0, east_nm, 0.0);
+        PositionReport {
+            latitude,
+            longitude,
+            timestamp: start + Duration::seconds(secs),
+        }
+    }
+
+    #[test]
+    fn test_speed_gate() {
+        let start = Utc::now();
+        let mut validator = KalmanValidator::default();
+        let mut aircraft = Aircraft::new("A1B2C3".to_string());
+        aircraft.velocity = Some(480.0);
+        aircraft.track = Some(90.0);
+
+        // 480 kt is 8 nm a minute: 10 nm after 60 s is plausible...
+        assert!(apply(&mut validator, &mut aircraft, report(start, 0, 0.0)).is_ok());
+        assert!(apply(&mut validator, &mut aircraft, report(start, 60, 8.0)).is_ok());
+
+        // ...but 40 nm after 2 s is not
+        let rejection = apply(&mut validator, &mut aircraft, report(start, 62, 48.0)).unwrap_err();
+        assert!(matches!(rejection, Rejection::TooFar { .. }));
+    }
+
+    #[test]
+    fn test_track_gate_and_reacquire() {
+        let start = Utc::now();
+        let mut validator = KalmanValidator::default();
+        let mut aircraft = Aircraft::new("A1B2C3".to_string());
+        aircraft.velocity = Some(360.0);
+        aircraft.track = Some(90.0);
+        aircraft.nac_p = Some(9);
+
+        // A steady track east at 0.1 nm/s
+        for step in 0..10 {
+            let secs: i32 = step * 2;
+            assert!(apply(
+                &mut validator,
+                &mut aircraft,
+                report(start, secs.into(), 0.1 * f64::from(secs))
+            )
+            .is_ok());
+        }
+
+        // A fast aircraft jumping 0.6 nm sideways passes the speed gate but
+        // not the track gate
+        let (latitude, longitude) = offset_position(37.0, -122.0, 2.0, 0.6);
+        let jump = PositionReport {
+            latitude,
+            longitude,
+            timestamp: start + Duration::seconds(20),
+        };
+        let rejection = apply(&mut validator, &mut aircraft, jump).unwrap_err();
+        assert!(matches!(rejection, Rejection::OffTrack { .. }));
+
+        // Positions continuing the track are still accepted
+        assert!(apply(&mut validator, &mut aircraft, report(start, 22, 2.2)).is_ok());
+
+        // A consistent run of off-track positions restarts the track
+        let mut accepted = false;
+        for step in 0..6 {
+            let (latitude, longitude) =
+                offset_position(37.0, -122.0, 2.4 + 0.2 * f64::from(step), 0.6);
+            let moved = PositionReport {
+                latitude,
+                longitude,
+                timestamp: start + Duration::seconds(24 + 2 * i64::from(step)),
+            };
+            if apply(&mut validator, &mut aircraft, moved).is_ok() {
+                accepted = true;
+                break;
+            }
+        }
+        assert!(accepted);
+        assert_eq!(aircraft.position_stats.consecutive_rejections, 0);
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Current traffic conflicts.
+//!
+//! `adsb_client::analysis::detect_conflicts` is stateless; [`ConflictMonitor`]
+//! keeps the latest result for the map and reports which conflicts are new,
+//! so each one is recorded once rather than on every check.
+
+use adsb_client::analysis::Conflict;
+use std::collections::HashSet;
+
+/// Latest predicted conflicts
+#[derive(Debug, Default)]
+pub struct ConflictMonitor {
+    conflicts: Vec<Conflict>,
+    /// Pairs in the latest result, with the lower ICAO first
+    active: HashSet<(String, String)>,
+}
+
+impl ConflictMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the current conflicts, returning those that were not present before
+    pub fn update(&mut self, conflicts: Vec<Conflict>) -> Vec<Conflict> {
+        let active: HashSet<(String, String)> = conflicts.iter().map(pair_key).collect();
+        let new_conflicts = conflicts.iter()
+            .filter(|conflict| !self.active.contains(&pair_key(conflict)))
+            .cloned()
+            .collect();
+        self.active = active;
+        self.conflicts = conflicts;
+        new_conflicts
+    }
+
+    /// Conflicts from the latest update, soonest first
+    pub fn conflicts(&self) -> &[Conflict] {
+        &self.conflicts
+    }
+}
+
+fn pair_key(conflict: &Conflict) -> (String, String) {
+    if conflict.icao_a <= conflict.icao_b {
+        (conflict.icao_a.clone(), conflict.icao_b.clone())
+    } else {
+        (conflict.icao_b.clone(), conflict.icao_a.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adsb_client::analysis::ClosestApproach;
+
+    fn conflict(icao_a: &str, icao_b: &str) -> Conflict {
+        Conflict {
+            icao_a: icao_a.to_string(),
+            icao_b: icao_b.to_string(),
+            approach: ClosestApproach {
+                time_secs: 30.0,
+                lateral_nm: 0.5,
+                vertical_ft: Some(200.0),
+            },
+            time_to_loss_secs: 10.0,
+        }
+    }
+
+    #[test]
+    fn test_new_conflicts_reported_once() {
+        let mut monitor = ConflictMonitor::new();
+        assert_eq!(monitor.update(vec![conflict("A00001", "A00002")]).len(), 1);
+
+        // The same pair in either order is not new
+        assert!(monitor.update(vec![conflict("A00002", "A00001")]).is_empty());
+        assert_eq!(monitor.conflicts().len(), 1);
+
+        // A pair that cleared and came back is new again
+        assert!(monitor.update(Vec::new()).is_empty());
+        assert_eq!(monitor.update(vec![conflict("A00001", "A00002")]).len(), 1);
+    }
+}
@@ -35,6 +35,9 @@ pub struct AircraftInfo {
     #[allow(dead_code)]
     pub manufacturer: Option<String>,
     pub model: Option<String>,
+    /// Operated by a military
+    #[serde(default)]
+    pub mil: bool,
 }
 
 pub struct AircraftDatabase {
@@ -129,6 +132,11 @@ impl AircraftDatabase {
             .and_then(|info| info.reg.clone())
     }
 
+    /// Whether the database flags an ICAO hex code as a military aircraft
+    pub fn is_military(&self, icao_hex: &str) -> bool {
+        self.lookup(icao_hex).is_some_and(|info| info.mil)
+    }
+
     /// Get aircraft type for an ICAO hex code
     pub fn get_aircraft_type(&self, icao_hex: &str) -> Option<String> {
         self.lookup(icao_hex).and_then(|info| {
@@ -1,15 +1,16 @@
 //! Aircraft tracking and data management.
 //!
-//! This module provides aircraft tracking, ADS-B message parsing, aircraft databases,
-//! metadata services, and type information.
+//! This module provides the UI's view of aircraft tracked by `adsb_client`, aircraft
+//! databases, metadata services, and type information.
 
 pub mod tracker;
-pub mod adsb;
+pub mod conflicts;
 pub mod database;
 pub mod metadata;
 pub mod types;
 
-pub use tracker::{Aircraft, AircraftTracker};
+pub use tracker::{display_name, unique_aircraft, Aircraft, AircraftDetails};
+pub use conflicts::ConflictMonitor;
 pub use database::AircraftDatabase;
 pub use metadata::MetadataService;
 pub use types::AircraftTypeDatabase;
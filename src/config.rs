@@ -18,7 +18,17 @@
 //! It supports multi-server configurations, UI preferences, GPS location overrides,
 //! and automatic migration from legacy single-server configs.
 
+use adsb_client::analysis::ConflictConfig;
+use adsb_client::ProtocolType;
+use crate::geofence::GeofenceConfig;
+use crate::history::HistoryConfig;
+use crate::network::SbsOutputConfig;
+use crate::recording::RecordingConfig;
+use crate::watchlist::WatchlistConfig;
+use crate::web::WebConfig;
+use crate::weather::AltitudeBand;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use uuid::Uuid;
 
 /// Default server address for ADS-B feed
@@ -38,6 +48,14 @@ pub struct ServerConfig {
 
     /// Whether this server should auto-connect on startup
     pub enabled: bool,
+
+    /// Feed protocol served at this address (SBS, BEAST, AVR, UAT, or auto-detect)
+    #[serde(default)]
+    pub protocol: ProtocolType,
+
+    /// Capture or SBS/BEAST log replayed instead of connecting to the address
+    #[serde(default)]
+    pub replay: Option<PathBuf>,
 }
 
 impl ServerConfig {
@@ -48,6 +66,19 @@ impl ServerConfig {
             name,
             address,
             enabled,
+            protocol: ProtocolType::Auto,
+            replay: None,
+        }
+    }
+
+    /// Create a virtual server replaying a capture or log file
+    pub fn new_replay(path: PathBuf) -> Self {
+        let name = path.file_name()
+            .map_or_else(|| "Replay".to_string(), |name| format!("Replay: {}", name.to_string_lossy()));
+        Self {
+            address: path.display().to_string(),
+            replay: Some(path),
+            ..Self::new(name, String::new(), true)
         }
     }
 
@@ -106,6 +137,18 @@ pub struct AppConfig {
     #[serde(default)]
     pub time_limited_trails: bool,
 
+    /// Move aircraft along their track between position updates
+    #[serde(default = "default_true")]
+    pub dead_reckoning: bool,
+
+    /// Seconds without a position before an aircraft is drawn as predicted
+    #[serde(default = "default_stale_position_secs")]
+    pub stale_position_secs: f64,
+
+    /// Smooth noisy positions (MLAT, low accuracy) with an alpha-beta filter
+    #[serde(default)]
+    pub smooth_tracks: bool,
+
     /// Airport filter mode: "All", "FrequentlyUsed", or "MajorOnly"
     #[serde(default = "default_airport_filter")]
     pub airport_filter: String,
@@ -142,6 +185,14 @@ pub struct AppConfig {
     #[serde(default)]
     pub show_weather_wind: bool,
 
+    /// Show winds aloft measured by aircraft (no API key needed)
+    #[serde(default)]
+    pub show_winds_aloft: bool,
+
+    /// Altitude band shown by the winds aloft overlay
+    #[serde(default)]
+    pub winds_aloft_band: AltitudeBand,
+
     /// Weather layer opacity (0.0 - 1.0)
     #[serde(default = "default_weather_opacity")]
     pub weather_opacity: f32,
@@ -149,6 +200,42 @@ pub struct AppConfig {
     /// OpenWeatherMap API key (optional, env var takes precedence)
     #[serde(default)]
     pub openweathermap_api_key: Option<String>,
+
+    /// Select (and pan to) aircraft as soon as they signal an emergency
+    #[serde(default)]
+    pub auto_select_emergencies: bool,
+
+    /// Watchlist rules and outputs
+    #[serde(default)]
+    pub watchlist: WatchlistConfig,
+
+    /// Geofence zones and their rules
+    #[serde(default)]
+    pub geofences: GeofenceConfig,
+
+    /// Predict conflicts between aircraft and draw them on the map
+    #[serde(default = "default_true")]
+    pub show_conflicts: bool,
+
+    /// Separation minima and look-ahead for conflict detection
+    #[serde(default)]
+    pub conflict_detection: ConflictConfig,
+
+    /// Where and how server feeds are recorded
+    #[serde(default)]
+    pub recording: RecordingConfig,
+
+    /// Flight history database
+    #[serde(default)]
+    pub history: HistoryConfig,
+
+    /// Re-broadcast of the merged traffic as an SBS feed
+    #[serde(default)]
+    pub sbs_output: SbsOutputConfig,
+
+    /// Embedded HTTP API serving readsb-style JSON
+    #[serde(default)]
+    pub web: WebConfig,
 }
 
 // Default value functions for serde
@@ -168,6 +255,10 @@ fn default_zoom() -> f32 {
     7.0
 }
 
+fn default_stale_position_secs() -> f64 {
+    5.0
+}
+
 fn default_airport_filter() -> String {
     "FrequentlyUsed".to_string()
 }
@@ -194,6 +285,9 @@ impl Default for AppConfig {
             show_navaids: false,
             default_zoom: 7.0,
             time_limited_trails: false,
+            dead_reckoning: true,
+            stale_position_secs: default_stale_position_secs(),
+            smooth_tracks: false,
             airport_filter: "FrequentlyUsed".to_string(),
             aircraft_list_expanded: true,
             aircraft_list_width: 350.0,
@@ -203,8 +297,19 @@ impl Default for AppConfig {
             show_weather_precipitation: false,
             show_weather_clouds: false,
             show_weather_wind: false,
+            show_winds_aloft: false,
+            winds_aloft_band: AltitudeBand::default(),
             weather_opacity: default_weather_opacity(),
             openweathermap_api_key: None,
+            auto_select_emergencies: false,
+            watchlist: WatchlistConfig::default(),
+            geofences: GeofenceConfig::default(),
+            show_conflicts: true,
+            conflict_detection: ConflictConfig::default(),
+            recording: RecordingConfig::default(),
+            history: HistoryConfig::default(),
+            sbs_output: SbsOutputConfig::default(),
+            web: WebConfig::default(),
         }
     }
 }
@@ -259,6 +364,9 @@ impl AppConfig {
             show_navaids: legacy.show_navaids.unwrap_or(false),
             default_zoom: legacy.default_zoom.unwrap_or(7.0),
             time_limited_trails: legacy.time_limited_trails.unwrap_or(false),
+            dead_reckoning: true,
+            stale_position_secs: default_stale_position_secs(),
+            smooth_tracks: false,
             airport_filter: legacy.airport_filter.unwrap_or_else(|| "FrequentlyUsed".to_string()),
             aircraft_list_expanded: legacy.aircraft_list_expanded.unwrap_or(true),
             aircraft_list_width: legacy.aircraft_list_width.unwrap_or(350.0),
@@ -268,8 +376,19 @@ impl AppConfig {
             show_weather_precipitation: false,
             show_weather_clouds: false,
             show_weather_wind: false,
+            show_winds_aloft: false,
+            winds_aloft_band: AltitudeBand::default(),
             weather_opacity: default_weather_opacity(),
             openweathermap_api_key: None,
+            auto_select_emergencies: false,
+            watchlist: WatchlistConfig::default(),
+            geofences: GeofenceConfig::default(),
+            show_conflicts: true,
+            conflict_detection: ConflictConfig::default(),
+            recording: RecordingConfig::default(),
+            history: HistoryConfig::default(),
+            sbs_output: SbsOutputConfig::default(),
+            web: WebConfig::default(),
         }
     }
 
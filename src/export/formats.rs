@@ -0,0 +1,367 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Writers for each export format.
+
+use adsb_client::tracker::PositionPoint;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use super::FlightTrack;
+use crate::map::altitude_to_color;
+
+const FEET_TO_METERS: f64 = 0.3048;
+
+/// Height of the altitude bands KML lines are split into and colored by
+const KML_BAND_FEET: i32 = 1000;
+
+/// File formats tracks can be exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Kml,
+    Gpx,
+    GeoJson,
+    Csv,
+}
+
+impl ExportFormat {
+    pub const ALL: [Self; 4] = [Self::Kml, Self::Gpx, Self::GeoJson, Self::Csv];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Kml => "KML (Google Earth)",
+            Self::Gpx => "GPX",
+            Self::GeoJson => "GeoJSON",
+            Self::Csv => "CSV",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Kml => "kml",
+            Self::Gpx => "gpx",
+            Self::GeoJson => "geojson",
+            Self::Csv => "csv",
+        }
+    }
+}
+
+/// Write tracks to a file
+pub fn export_tracks(path: &Path, format: ExportFormat, tracks: &[FlightTrack]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_tracks(&mut writer, format, tracks)?;
+    writer.flush()
+}
+
+/// Write tracks in a format
+pub fn write_tracks(writer: &mut impl Write, format: ExportFormat, tracks: &[FlightTrack]) -> io::Result<()> {
+    match format {
+        ExportFormat::Kml => write_kml(writer, tracks),
+        ExportFormat::Gpx => write_gpx(writer, tracks),
+        ExportFormat::GeoJson => write_geojson(writer, tracks),
+        ExportFormat::Csv => write_csv(writer, tracks),
+    }
+}
+
+/// One folder per track, holding a line for each run of positions in the
+/// same altitude band, extruded to the ground
+fn write_kml(w: &mut impl Write, tracks: &[FlightTrack]) -> io::Result<()> {
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(w, r#"<kml xmlns="http://www.opengis.net/kml/2.2">"#)?;
+    writeln!(w, "<Document>")?;
+    writeln!(w, "  <name>AirJedi tracks</name>")?;
+
+    // A style for each band in use, with translucent walls below the line
+    let bands: BTreeSet<Option<i32>> = tracks
+        .iter()
+        .flat_map(|track| track.points.iter().map(|point| kml_band(point.altitude)))
+        .collect();
+    for band in bands {
+        let (r, g, b) = altitude_to_color(band.map(|band| band * KML_BAND_FEET + KML_BAND_FEET / 2));
+        writeln!(w, r#"  <Style id="{}">"#, kml_style_id(band))?;
+        writeln!(w, "    <LineStyle><color>ff{:02x}{:02x}{:02x}</color><width>3</width></LineStyle>", b, g, r)?;
+        writeln!(w, "    <PolyStyle><color>60{:02x}{:02x}{:02x}</color></PolyStyle>", b, g, r)?;
+        writeln!(w, "  </Style>")?;
+    }
+
+    for track in tracks {
+        writeln!(w, "  <Folder>")?;
+        writeln!(w, "    <name>{}</name>", xml_escape(&track.name()))?;
+        writeln!(w, "    <description>{}</description>", xml_escape(&describe(track)))?;
+        if let Some((start, end)) = track.span() {
+            writeln!(w, "    <TimeSpan><begin>{}</begin><end>{}</end></TimeSpan>", timestamp(start), timestamp(end))?;
+        }
+        for (band, points) in kml_segments(&track.points) {
+            // Positions without an altitude are drawn on the ground
+            let (extrude, altitude_mode) = if band.is_some() { (1, "absolute") } else { (0, "clampToGround") };
+            let band_floor = band.map_or(0.0, |band| f64::from(band * KML_BAND_FEET) * FEET_TO_METERS);
+            let coordinates: Vec<String> = points
+                .iter()
+                .map(|point| {
+                    let elevation = altitude_meters(point).unwrap_or(band_floor);
+                    format!("{:.6},{:.6},{:.1}", point.lon, point.lat, elevation)
+                })
+                .collect();
+            writeln!(w, "    <Placemark>")?;
+            writeln!(w, "      <styleUrl>#{}</styleUrl>", kml_style_id(band))?;
+            writeln!(w, "      <LineString>")?;
+            writeln!(w, "        <extrude>{}</extrude>", extrude)?;
+            writeln!(w, "        <tessellate>1</tessellate>")?;
+            writeln!(w, "        <altitudeMode>{}</altitudeMode>", altitude_mode)?;
+            writeln!(w, "        <coordinates>{}</coordinates>", coordinates.join(" "))?;
+            writeln!(w, "      </LineString>")?;
+            writeln!(w, "    </Placemark>")?;
+        }
+        writeln!(w, "  </Folder>")?;
+    }
+
+    writeln!(w, "</Document>")?;
+    writeln!(w, "</kml>")
+}
+
+/// Altitude band of a position, `None` without an altitude
+fn kml_band(altitude: Option<i32>) -> Option<i32> {
+    altitude.map(|altitude| altitude.max(0) / KML_BAND_FEET)
+}
+
+fn kml_style_id(band: Option<i32>) -> String {
+    match band {
+        Some(band) => format!("band-{}", band),
+        None => "band-unknown".to_string(),
+    }
+}
+
+/// Split positions into runs in the same altitude band. Each run starts with
+/// the last position of the one before, so the lines join up.
+fn kml_segments(points: &[PositionPoint]) -> Vec<(Option<i32>, Vec<&PositionPoint>)> {
+    let mut segments: Vec<(Option<i32>, Vec<&PositionPoint>)> = Vec::new();
+    for point in points {
+        let band = kml_band(point.altitude);
+        match segments.last_mut() {
+            Some((current, run)) if *current == band => run.push(point),
+            Some((_, run)) => {
+                let joint = run[run.len() - 1];
+                segments.push((band, vec![joint, point]));
+            }
+            None => segments.push((band, vec![point])),
+        }
+    }
+    // A lone first position is already the start of the next run
+    segments.retain(|(_, run)| run.len() >= 2);
+    segments
+}
+
+/// One track per aircraft, with elevations and times
+fn write_gpx(w: &mut impl Write, tracks: &[FlightTrack]) -> io::Result<()> {
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(w, r#"<gpx version="1.1" creator="AirJedi Desktop" xmlns="http://www.topografix.com/GPX/1/1">"#)?;
+    for track in tracks {
+        writeln!(w, "  <trk>")?;
+        writeln!(w, "    <name>{}</name>", xml_escape(&track.name()))?;
+        writeln!(w, "    <desc>{}</desc>", xml_escape(&describe(track)))?;
+        writeln!(w, "    <trkseg>")?;
+        for point in &track.points {
+            write!(w, r#"      <trkpt lat="{:.6}" lon="{:.6}">"#, point.lat, point.lon)?;
+            if let Some(elevation) = altitude_meters(point) {
+                write!(w, "<ele>{:.1}</ele>", elevation)?;
+            }
+            writeln!(w, "<time>{}</time></trkpt>", timestamp(point.timestamp))?;
+        }
+        writeln!(w, "    </trkseg>")?;
+        writeln!(w, "  </trk>")?;
+    }
+    writeln!(w, "</gpx>")
+}
+
+/// A `LineString` feature per aircraft. GeoJSON has no per-vertex properties,
+/// so times and altitudes are arrays matching the coordinates.
+fn write_geojson(w: &mut impl Write, tracks: &[FlightTrack]) -> io::Result<()> {
+    let features: Vec<serde_json::Value> = tracks
+        .iter()
+        .map(|track| {
+            let coordinates: Vec<Vec<f64>> = track
+                .points
+                .iter()
+                .map(|point| match altitude_meters(point) {
+                    Some(elevation) => vec![point.lon, point.lat, elevation],
+                    None => vec![point.lon, point.lat],
+                })
+                .collect();
+            let times: Vec<String> = track.points.iter().map(|point| timestamp(point.timestamp)).collect();
+            let altitudes: Vec<Option<i32>> = track.points.iter().map(|point| point.altitude).collect();
+            serde_json::json!({
+                "type": "Feature",
+                "geometry": {
+                    "type": "LineString",
+                    "coordinates": coordinates,
+                },
+                "properties": {
+                    "icao": track.icao,
+                    "callsign": track.callsign,
+                    "registration": track.registration,
+                    "aircraft_type": track.aircraft_type,
+                    "times": times,
+                    "altitudes_ft": altitudes,
+                },
+            })
+        })
+        .collect();
+
+    let collection = serde_json::json!({
+        "type": "FeatureCollection",
+        "features": features,
+    });
+    serde_json::to_writer_pretty(&mut *w, &collection)?;
+    writeln!(w)
+}
+
+/// A CSV row per position
+#[derive(Serialize)]
+struct CsvRow<'a> {
+    icao: &'a str,
+    callsign: Option<&'a str>,
+    registration: Option<&'a str>,
+    timestamp: String,
+    latitude: f64,
+    longitude: f64,
+    altitude_ft: Option<i32>,
+}
+
+fn write_csv(w: &mut impl Write, tracks: &[FlightTrack]) -> io::Result<()> {
+    let mut writer = csv::Writer::from_writer(w);
+    for track in tracks {
+        for point in &track.points {
+            writer.serialize(CsvRow {
+                icao: &track.icao,
+                callsign: track.callsign.as_deref().map(str::trim),
+                registration: track.registration.as_deref(),
+                timestamp: timestamp(point.timestamp),
+                latitude: point.lat,
+                longitude: point.lon,
+                altitude_ft: point.altitude,
+            })?;
+        }
+    }
+    writer.flush()
+}
+
+/// ICAO address, registration and type, for descriptions
+fn describe(track: &FlightTrack) -> String {
+    let mut parts = vec![format!("ICAO {}", track.icao.to_uppercase())];
+    parts.extend(track.registration.clone());
+    parts.extend(track.aircraft_type.clone());
+    parts.join(", ")
+}
+
+fn altitude_meters(point: &PositionPoint) -> Option<f64> {
+    point.altitude.map(|altitude| f64::from(altitude) * FEET_TO_METERS)
+}
+
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn xml_escape(text: &str) -> Cow<'_, str> {
+    if !text.contains(['&', '<', '>', '"', '\'']) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+            .replace('\'', "&apos;"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn track() -> FlightTrack {
+        let start = Utc::now();
+        let point = |seconds: i64, altitude: Option<i32>| PositionPoint {
+            lat: 37.0 + seconds as f64 * 0.001,
+            lon: -122.0,
+            altitude,
+            timestamp: start + Duration::seconds(seconds),
+        };
+        FlightTrack {
+            icao: "a1b2c3".to_string(),
+            callsign: Some("UAL123  ".to_string()),
+            registration: Some("N12345".to_string()),
+            aircraft_type: None,
+            points: vec![
+                point(0, Some(4_200)),
+                point(10, Some(4_800)),
+                point(20, Some(5_100)),
+                point(30, None),
+                point(40, Some(5_300)),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_kml_segments() {
+        let track = track();
+        let segments = kml_segments(&track.points);
+        let bands: Vec<Option<i32>> = segments.iter().map(|(band, _)| *band).collect();
+        assert_eq!(bands, vec![Some(4), Some(5), None, Some(5)]);
+        // Runs join at their shared positions
+        assert_eq!(segments[1].1.len(), 2);
+        assert_eq!(segments[1].1[0].timestamp, track.points[1].timestamp);
+
+        let mut kml = Vec::new();
+        write_tracks(&mut kml, ExportFormat::Kml, &[track]).unwrap();
+        let kml = String::from_utf8(kml).unwrap();
+        assert_eq!(kml.matches("<Placemark>").count(), 4);
+        assert_eq!(kml.matches("<Style id=").count(), 3);
+        assert!(kml.contains("<name>UAL123</name>"));
+        assert!(kml.contains("<altitudeMode>clampToGround</altitudeMode>"));
+    }
+
+    #[test]
+    fn test_gpx_geojson_csv() {
+        let tracks = [track()];
+
+        let mut gpx = Vec::new();
+        write_tracks(&mut gpx, ExportFormat::Gpx, &tracks).unwrap();
+        let gpx = String::from_utf8(gpx).unwrap();
+        assert_eq!(gpx.matches("<trkpt").count(), 5);
+        assert_eq!(gpx.matches("<ele>").count(), 4);
+        assert!(gpx.contains(&format!("<time>{}</time>", timestamp(tracks[0].points[0].timestamp))));
+
+        let mut geojson = Vec::new();
+        write_tracks(&mut geojson, ExportFormat::GeoJson, &tracks).unwrap();
+        let geojson: serde_json::Value = serde_json::from_slice(&geojson).unwrap();
+        let feature = &geojson["features"][0];
+        assert_eq!(feature["geometry"]["coordinates"].as_array().unwrap().len(), 5);
+        assert_eq!(feature["properties"]["times"].as_array().unwrap().len(), 5);
+        assert_eq!(feature["properties"]["altitudes_ft"][3], serde_json::Value::Null);
+
+        let mut csv = Vec::new();
+        write_tracks(&mut csv, ExportFormat::Csv, &tracks).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[0], "icao,callsign,registration,timestamp,latitude,longitude,altitude_ft");
+        assert!(lines[1].starts_with("a1b2c3,UAL123,N12345,"));
+    }
+}
@@ -0,0 +1,11 @@
+//! Flight track export.
+//!
+//! Writes the trails of the selected aircraft, or of every tracked aircraft,
+//! to files other tools can open: KML for Google Earth with altitude-extruded
+//! lines colored like the map's trails, GPX with timestamps, GeoJSON, and CSV.
+
+pub mod formats;
+pub mod track;
+
+pub use formats::{export_tracks, ExportFormat};
+pub use track::FlightTrack;
@@ -0,0 +1,62 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Aircraft trails gathered for export.
+
+use adsb_client::tracker::PositionPoint;
+use chrono::{DateTime, Utc};
+
+use crate::aircraft::Aircraft;
+
+/// An aircraft's trail and the names it is known by
+#[derive(Debug, Clone)]
+pub struct FlightTrack {
+    pub icao: String,
+    pub callsign: Option<String>,
+    pub registration: Option<String>,
+    pub aircraft_type: Option<String>,
+    /// Positions, oldest first
+    pub points: Vec<PositionPoint>,
+}
+
+impl FlightTrack {
+    /// The trail of an aircraft, or `None` until it has at least two positions
+    pub fn from_aircraft(aircraft: &Aircraft) -> Option<Self> {
+        let points = aircraft.position_history();
+        if points.len() < 2 {
+            return None;
+        }
+        Some(Self {
+            icao: aircraft.icao(),
+            callsign: aircraft.callsign(),
+            registration: aircraft.registration(),
+            aircraft_type: aircraft.aircraft_type(),
+            points,
+        })
+    }
+
+    /// Callsign if known, else the ICAO address
+    pub fn name(&self) -> String {
+        self.callsign
+            .as_deref()
+            .map(str::trim)
+            .filter(|callsign| !callsign.is_empty())
+            .map_or_else(|| self.icao.to_uppercase(), str::to_string)
+    }
+
+    /// Times of the first and last positions
+    pub fn span(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        Some((self.points.first()?.timestamp, self.points.last()?.timestamp))
+    }
+}
@@ -0,0 +1,11 @@
+//! Geofences and zone alerts.
+//!
+//! Users draw named polygons or circles on the map and attach rules to them
+//! (enter, exit, below an altitude). Every position update from the trackers
+//! is checked against the zones, and hits are logged and highlighted on the map.
+
+pub mod zone;
+pub mod monitor;
+
+pub use zone::{GeofenceConfig, Zone, ZoneRule, ZoneShape};
+pub use monitor::{GeofenceMonitor, SharedGeofenceMonitor};
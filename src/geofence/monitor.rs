@@ -0,0 +1,343 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Zone rule evaluation.
+//!
+//! The connection manager feeds every `PositionUpdated` from each server's
+//! tracker into the shared [`GeofenceMonitor`], which remembers which zones
+//! each aircraft is inside and records a [`GeofenceHit`] whenever a rule
+//! fires. The UI reads the recent hits to highlight aircraft on the map.
+
+use adsb_client::Aircraft;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use crate::aircraft::display_name;
+use super::zone::{Zone, ZoneRule};
+
+const MAX_HITS: usize = 500;
+
+/// How long an aircraft stays highlighted on the map after a hit
+const HIGHLIGHT_SECONDS: i64 = 60;
+
+/// Zone state is forgotten for aircraft without a position for this long
+const STATE_TIMEOUT_SECONDS: i64 = 300;
+
+/// A zone rule fired for an aircraft
+#[derive(Debug, Clone)]
+pub struct GeofenceHit {
+    pub zone_name: String,
+    pub zone_color: [u8; 3],
+    pub rule: ZoneRule,
+    pub icao: String,
+    pub callsign: Option<String>,
+    pub squawk: Option<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: Option<i32>,
+    pub ground_speed: Option<f64>,
+    pub track: Option<f64>,
+    pub server_name: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl GeofenceHit {
+    /// Name to show for the aircraft
+    pub fn display_name(&self) -> String {
+        display_name(self.callsign.as_deref(), &self.icao)
+    }
+
+    /// One-line summary with the aircraft's details, for logs
+    pub fn describe(&self) -> String {
+        let mut description = format!(
+            "[{}] {} ({}) {}: {} at {:.4}, {:.4}",
+            self.server_name,
+            self.display_name(),
+            self.icao,
+            self.zone_name,
+            self.rule.describe(),
+            self.latitude,
+            self.longitude,
+        );
+        if let Some(altitude) = self.altitude {
+            description.push_str(&format!(", {} ft", altitude));
+        }
+        if let Some(speed) = self.ground_speed {
+            description.push_str(&format!(", {:.0} kt", speed));
+        }
+        if let Some(track) = self.track {
+            description.push_str(&format!(", track {:.0}°", track));
+        }
+        if let Some(ref squawk) = self.squawk {
+            description.push_str(&format!(", squawk {}", squawk));
+        }
+        description
+    }
+
+    /// Whether the hit is recent enough to highlight the aircraft, `now` being
+    /// the time on the aircraft's feed
+    pub fn is_recent(&self, now: DateTime<Utc>) -> bool {
+        now - self.timestamp <= Duration::seconds(HIGHLIGHT_SECONDS)
+    }
+}
+
+/// Per-aircraft zone state
+#[derive(Debug)]
+struct AircraftZoneState {
+    /// Server of the last position checked, on whose clock the state expires
+    server_name: String,
+    /// Feed time of the last position checked
+    last_update: DateTime<Utc>,
+    /// Zones the aircraft was inside at its last position
+    inside: HashSet<String>,
+    /// Zones whose altitude rule already fired (re-armed on climbing or leaving)
+    below: HashSet<String>,
+}
+
+/// Geofence monitor shared between the connection tasks and the UI
+pub type SharedGeofenceMonitor = Arc<Mutex<GeofenceMonitor>>;
+
+/// Replayed server (`None` for live ones) and ICAO address a zone state is kept for
+type StateKey = (Option<String>, String);
+
+/// Evaluates zone rules against aircraft positions
+#[derive(Debug, Default)]
+pub struct GeofenceMonitor {
+    zones: Vec<Zone>,
+    aircraft: HashMap<StateKey, AircraftZoneState>,
+    /// Most recent hits, oldest first
+    hits: VecDeque<GeofenceHit>,
+}
+
+impl GeofenceMonitor {
+    pub fn new(zones: Vec<Zone>) -> Self {
+        Self {
+            zones,
+            ..Default::default()
+        }
+    }
+
+    /// Replace the zones, forgetting state of removed or reshaped zones
+    pub fn set_zones(&mut self, zones: Vec<Zone>) {
+        let kept: HashSet<&str> = zones.iter()
+            .filter(|zone| {
+                self.zones.iter().any(|old| old.id == zone.id && old.shape == zone.shape)
+            })
+            .map(|zone| zone.id.as_str())
+            .collect();
+        for state in self.aircraft.values_mut() {
+            state.inside.retain(|id| kept.contains(id.as_str()));
+            state.below.retain(|id| kept.contains(id.as_str()));
+        }
+        self.zones = zones;
+    }
+
+    /// Check an aircraft's latest position against every zone
+    ///
+    /// Returns the hits raised by this position; they are also kept for
+    /// [`recent_hits`](Self::recent_hits). Hits are timestamped when the
+    /// aircraft was last seen, so replayed feeds keep their own clock.
+    ///
+    /// `replay` is the id of the replayed server the position comes from. Live
+    /// servers share the aircraft's zone state, while each replay keeps its own.
+    pub fn check(&mut self, aircraft: &Aircraft, server_name: &str, replay: Option<&str>) -> Vec<GeofenceHit> {
+        let (Some(latitude), Some(longitude)) = (aircraft.latitude, aircraft.longitude) else {
+            return Vec::new();
+        };
+
+        let now = aircraft.last_seen;
+        let key = (replay.map(str::to_string), aircraft.icao.clone());
+        let state = self.aircraft.entry(key).or_insert_with(|| AircraftZoneState {
+            server_name: server_name.to_string(),
+            last_update: now,
+            inside: HashSet::new(),
+            below: HashSet::new(),
+        });
+        if state.server_name != server_name {
+            state.server_name = server_name.to_string();
+        }
+        state.last_update = now;
+        let mut hits = Vec::new();
+
+        for zone in self.zones.iter().filter(|zone| zone.enabled) {
+            let inside = zone.shape.contains(latitude, longitude);
+            let was_inside = if inside {
+                !state.inside.insert(zone.id.clone())
+            } else {
+                state.inside.remove(&zone.id)
+            };
+
+            for &rule in &zone.rules {
+                let fired = match rule {
+                    ZoneRule::Enter => inside && !was_inside,
+                    ZoneRule::Exit => !inside && was_inside,
+                    ZoneRule::BelowAltitude { altitude } => {
+                        let below = inside && aircraft.altitude.is_some_and(|alt| alt < altitude);
+                        if below {
+                            state.below.insert(zone.id.clone())
+                        } else {
+                            state.below.remove(&zone.id);
+                            false
+                        }
+                    }
+                };
+
+                if fired {
+                    hits.push(GeofenceHit {
+                        zone_name: zone.name.clone(),
+                        zone_color: zone.color,
+                        rule,
+                        icao: aircraft.icao.clone(),
+                        callsign: aircraft.callsign.clone(),
+                        squawk: aircraft.squawk.clone(),
+                        latitude,
+                        longitude,
+                        altitude: aircraft.altitude,
+                        ground_speed: aircraft.velocity,
+                        track: aircraft.track,
+                        server_name: server_name.to_string(),
+                        timestamp: now,
+                    });
+                }
+            }
+        }
+
+        for hit in &hits {
+            self.hits.push_back(hit.clone());
+        }
+        while self.hits.len() > MAX_HITS {
+            self.hits.pop_front();
+        }
+        hits
+    }
+
+    /// Forget aircraft a server has not reported a position for recently,
+    /// `now` being the time on that server's feed
+    ///
+    /// Zone state is shared by all servers, so it is timed out here rather
+    /// than dropped when one server's tracker loses the aircraft. Each server
+    /// only expires the state it last updated, as replayed feeds run on their
+    /// own clock.
+    pub fn prune(&mut self, server_name: &str, now: DateTime<Utc>) {
+        let cutoff = now - Duration::seconds(STATE_TIMEOUT_SECONDS);
+        self.aircraft.retain(|_, state| state.server_name != server_name || state.last_update >= cutoff);
+    }
+
+    /// Most recent hits, oldest first
+    pub fn recent_hits(&self) -> &VecDeque<GeofenceHit> {
+        &self.hits
+    }
+
+    pub fn clear_hits(&mut self) {
+        self.hits.clear();
+    }
+
+    /// Latest hit per aircraft, for map highlights while
+    /// [`GeofenceHit::is_recent`]
+    pub fn latest_hits(&self) -> HashMap<String, GeofenceHit> {
+        self.hits.iter()
+            .map(|hit| (hit.icao.clone(), hit.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geofence::zone::ZoneShape;
+
+    #[test]
+    fn test_zone_rules() {
+        let mut zone = Zone::new(
+            "Heliport".to_string(),
+            ZoneShape::Circle { center: (37.0, -122.0), radius_nm: 5.0 },
+            0,
+        );
+        zone.rules = vec![ZoneRule::Enter, ZoneRule::Exit, ZoneRule::BelowAltitude { altitude: 1000 }];
+        let mut monitor = GeofenceMonitor::new(vec![zone]);
+
+        let mut aircraft = Aircraft::new("A1B2C3".to_string());
+        aircraft.latitude = Some(37.5);
+        aircraft.longitude = Some(-122.0);
+        aircraft.altitude = Some(3000);
+        assert!(monitor.check(&aircraft, "test", None).is_empty());
+
+        // Entering fires once
+        aircraft.latitude = Some(37.01);
+        let hits = monitor.check(&aircraft, "test", None);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].rule, ZoneRule::Enter);
+        assert!(monitor.check(&aircraft, "test", None).is_empty());
+
+        // Descending below the floor fires once
+        aircraft.altitude = Some(800);
+        let hits = monitor.check(&aircraft, "test", None);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].rule, ZoneRule::BelowAltitude { altitude: 1000 });
+        assert!(monitor.check(&aircraft, "test", None).is_empty());
+
+        // Leaving fires the exit rule
+        aircraft.latitude = Some(37.5);
+        let hits = monitor.check(&aircraft, "test", None);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].rule, ZoneRule::Exit);
+
+        assert_eq!(monitor.recent_hits().len(), 3);
+        let latest = &monitor.latest_hits()["A1B2C3"];
+        assert_eq!(latest.rule, ZoneRule::Exit);
+        assert!(latest.is_recent(aircraft.last_seen));
+        assert!(!latest.is_recent(aircraft.last_seen + Duration::seconds(HIGHLIGHT_SECONDS + 1)));
+
+        // State expires on the clock of the server that last reported the aircraft
+        let later = aircraft.last_seen + Duration::seconds(STATE_TIMEOUT_SECONDS + 1);
+        monitor.prune("other", later);
+        assert_eq!(monitor.aircraft.len(), 1);
+        monitor.prune("test", later);
+        assert!(monitor.aircraft.is_empty());
+    }
+
+    #[test]
+    fn test_replay_state_kept_apart_from_live() {
+        let mut zone = Zone::new(
+            "Heliport".to_string(),
+            ZoneShape::Circle { center: (37.0, -122.0), radius_nm: 5.0 },
+            0,
+        );
+        zone.rules = vec![ZoneRule::Enter, ZoneRule::Exit];
+        let mut monitor = GeofenceMonitor::new(vec![zone]);
+
+        let mut live = Aircraft::new("A1B2C3".to_string());
+        live.latitude = Some(37.01);
+        live.longitude = Some(-122.0);
+        let mut replayed = live.clone();
+        replayed.latitude = Some(37.5);
+
+        // Live servers share the state, so a second one doesn't enter again
+        assert_eq!(monitor.check(&live, "live 1", None).len(), 1);
+        assert!(monitor.check(&live, "live 2", None).is_empty());
+
+        // Interleaved positions of a replay neither exit nor re-enter the live track
+        for _ in 0..2 {
+            assert!(monitor.check(&replayed, "replay", Some("replay-id")).is_empty());
+            assert!(monitor.check(&live, "live 1", None).is_empty());
+        }
+
+        // The replay's state expires on its own
+        let later = replayed.last_seen + Duration::seconds(STATE_TIMEOUT_SECONDS + 1);
+        monitor.prune("replay", later);
+        assert!(monitor.aircraft.contains_key(&(None, "A1B2C3".to_string())));
+        assert!(!monitor.aircraft.contains_key(&(Some("replay-id".to_string()), "A1B2C3".to_string())));
+    }
+}
@@ -0,0 +1,200 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Zone geometry and rule configuration.
+//!
+//! Zones are stored in `AppConfig`. Each zone is a polygon or a circle drawn
+//! on the map, plus the rules that raise a hit when an aircraft enters,
+//! exits, or flies below an altitude inside it.
+
+use adsb_client::tracker::haversine_distance_nm;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Colors assigned to new zones, in order
+const ZONE_PALETTE: [[u8; 3]; 6] = [
+    [255, 140, 0],
+    [0, 200, 255],
+    [200, 80, 255],
+    [80, 220, 120],
+    [255, 90, 140],
+    [240, 220, 60],
+];
+
+/// Outline of a zone
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ZoneShape {
+    /// Closed polygon of (lat, lon) vertices
+    Polygon { points: Vec<(f64, f64)> },
+    /// Circle around a (lat, lon) center
+    Circle { center: (f64, f64), radius_nm: f64 },
+}
+
+impl ZoneShape {
+    /// Check whether a position lies inside the zone
+    pub fn contains(&self, lat: f64, lon: f64) -> bool {
+        match self {
+            ZoneShape::Polygon { points } => polygon_contains(points, lat, lon),
+            ZoneShape::Circle { center, radius_nm } => {
+                haversine_distance_nm(center.0, center.1, lat, lon) <= *radius_nm
+            }
+        }
+    }
+
+    /// Human-readable shape description for settings UI
+    pub fn describe(&self) -> String {
+        match self {
+            ZoneShape::Polygon { points } => format!("Polygon, {} points", points.len()),
+            ZoneShape::Circle { radius_nm, .. } => format!("Circle, {:.1} nm", radius_nm),
+        }
+    }
+}
+
+/// Ray casting, treating lat/lon as planar coordinates
+///
+/// Good enough for the approach corridors and heliport zones this is meant
+/// for; zones spanning the antimeridian are not supported.
+fn polygon_contains(points: &[(f64, f64)], lat: f64, lon: f64) -> bool {
+    if points.len() < 3 {
+        return false;
+    }
+
+    let mut inside = false;
+    let mut previous = points[points.len() - 1];
+    for &point in points {
+        let (lat_a, lon_a) = point;
+        let (lat_b, lon_b) = previous;
+        if (lat_a > lat) != (lat_b > lat) {
+            let crossing_lon = lon_a + (lat - lat_a) / (lat_b - lat_a) * (lon_b - lon_a);
+            if lon < crossing_lon {
+                inside = !inside;
+            }
+        }
+        previous = point;
+    }
+    inside
+}
+
+/// When a zone raises a hit
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ZoneRule {
+    /// An aircraft moves into the zone
+    Enter,
+    /// An aircraft moves out of the zone
+    Exit,
+    /// An aircraft inside the zone descends below an altitude (feet)
+    BelowAltitude { altitude: i32 },
+}
+
+impl ZoneRule {
+    /// Short description, e.g. "Below 1500 ft"
+    pub fn describe(self) -> String {
+        match self {
+            ZoneRule::Enter => "Enter".to_string(),
+            ZoneRule::Exit => "Exit".to_string(),
+            ZoneRule::BelowAltitude { altitude } => format!("Below {} ft", altitude),
+        }
+    }
+}
+
+/// A named zone with its rules
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Zone {
+    /// Unique identifier (UUID)
+    pub id: String,
+
+    /// Display name shown on the map and in hits
+    pub name: String,
+
+    /// Whether the zone's rules are evaluated
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Outline and highlight color (RGB)
+    pub color: [u8; 3],
+
+    pub shape: ZoneShape,
+
+    /// Rules evaluated for every position update
+    #[serde(default)]
+    pub rules: Vec<ZoneRule>,
+}
+
+impl Zone {
+    /// Create an enabled zone with a generated UUID and an "Enter" rule
+    ///
+    /// `index` picks the zone's color from a fixed palette.
+    pub fn new(name: String, shape: ZoneShape, index: usize) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            enabled: true,
+            color: ZONE_PALETTE[index % ZONE_PALETTE.len()],
+            shape,
+            rules: vec![ZoneRule::Enter],
+        }
+    }
+}
+
+/// Geofence configuration stored in `AppConfig`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeofenceConfig {
+    /// Zones, drawn in order
+    #[serde(default)]
+    pub zones: Vec<Zone>,
+
+    /// Draw zone outlines on the map
+    #[serde(default = "default_true")]
+    pub show_zones: bool,
+}
+
+impl Default for GeofenceConfig {
+    fn default() -> Self {
+        Self {
+            zones: Vec::new(),
+            show_zones: true,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zone_contains() {
+        // Square around (37.5, -122.5)
+        let square = ZoneShape::Polygon {
+            points: vec![(37.0, -123.0), (38.0, -123.0), (38.0, -122.0), (37.0, -122.0)],
+        };
+        assert!(square.contains(37.5, -122.5));
+        assert!(!square.contains(38.5, -122.5));
+        assert!(!square.contains(37.5, -121.5));
+
+        // Too few points never contain anything
+        let line = ZoneShape::Polygon { points: vec![(37.0, -123.0), (38.0, -122.0)] };
+        assert!(!line.contains(37.5, -122.5));
+
+        // One degree of latitude is 60 nm
+        let circle = ZoneShape::Circle { center: (37.0, -122.0), radius_nm: 10.0 };
+        assert!(circle.contains(37.1, -122.0));
+        assert!(!circle.contains(37.2, -122.0));
+    }
+}
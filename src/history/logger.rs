@@ -0,0 +1,377 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Turning position updates into flight sessions.
+//!
+//! The connection manager feeds every `PositionUpdated` from each live
+//! server's tracker into the shared [`FlightLogger`]. It keeps a session open
+//! per aircraft, keeps a track point every few seconds (and on turns and
+//! climbs), and periodically writes changed sessions to the [`HistoryStore`].
+
+use adsb_client::tracker::PositionPoint;
+use adsb_client::Aircraft;
+use chrono::{DateTime, Duration, Utc};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use super::store::{FlightSession, HistoryStore};
+use crate::aircraft::AircraftDetails;
+
+/// A session closes once its aircraft has not been seen for this long
+const SESSION_GAP_SECONDS: i64 = 600;
+
+/// Track points are kept at least this far apart
+const TRACK_POINT_SECONDS: i64 = 15;
+
+/// Points are also kept sooner (but not within this many seconds) when the
+/// aircraft turns or climbs
+const TRACK_POINT_MIN_SECONDS: i64 = 2;
+const TURN_DEGREES: f64 = 10.0;
+const CLIMB_FEET: i32 = 500;
+
+/// Changes are written to the database this often
+const FLUSH_SECONDS: i64 = 10;
+
+/// Sessions past the retention period are deleted this often
+const PRUNE_HOURS: i64 = 1;
+
+/// A flight logger shared by every server's status task
+pub type SharedFlightLogger = Arc<Mutex<FlightLogger>>;
+
+/// Whether and where flight history is kept
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    /// Log flight sessions
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// SQLite database file
+    #[serde(default = "default_database")]
+    pub database: PathBuf,
+
+    /// Delete sessions older than this many days
+    #[serde(default = "default_retention_days")]
+    pub retention_days: Option<u32>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_database() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("airjedi-desktop")
+        .join("history.sqlite")
+}
+
+fn default_retention_days() -> Option<u32> {
+    Some(90)
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            database: default_database(),
+            retention_days: default_retention_days(),
+        }
+    }
+}
+
+/// A session being logged
+struct OpenSession {
+    session: FlightSession,
+    /// Track points not yet written
+    pending_points: Vec<PositionPoint>,
+    /// Last track point kept, with the aircraft's track then
+    last_point: Option<(PositionPoint, Option<f64>)>,
+    /// Whether the session changed since it was last written
+    dirty: bool,
+}
+
+/// Logs flight sessions to the history database
+pub struct FlightLogger {
+    store: Option<HistoryStore>,
+    retention_days: Option<u32>,
+    sessions: HashMap<String, OpenSession>,
+    /// Sessions closed since the last flush, still to be written
+    closed: Vec<OpenSession>,
+    last_flush: Option<DateTime<Utc>>,
+    last_prune: Option<DateTime<Utc>>,
+}
+
+impl FlightLogger {
+    /// Log to the configured database, or nowhere if history is disabled or
+    /// the database cannot be opened
+    pub fn new(config: &HistoryConfig) -> Self {
+        let store = if config.enabled {
+            match HistoryStore::open(&config.database) {
+                Ok(store) => {
+                    info!("Logging flight history to {}", config.database.display());
+                    Some(store)
+                }
+                Err(e) => {
+                    warn!("Cannot open flight history {}: {}", config.database.display(), e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        Self::with_store(store, config.retention_days)
+    }
+
+    fn with_store(store: Option<HistoryStore>, retention_days: Option<u32>) -> Self {
+        Self {
+            store,
+            retention_days,
+            sessions: HashMap::new(),
+            closed: Vec::new(),
+            last_flush: None,
+            last_prune: None,
+        }
+    }
+
+    /// Log an aircraft's latest position, received by `server_name` with the
+    /// receiver at `receiver` (latitude, longitude) when its location is known
+    pub fn observe(&mut self, aircraft: &Aircraft, server_name: &str, receiver: Option<(f64, f64)>) {
+        if self.store.is_none() {
+            return;
+        }
+        let (Some(lat), Some(lon)) = (aircraft.latitude, aircraft.longitude) else {
+            return;
+        };
+        let time = aircraft.position_time.unwrap_or(aircraft.last_seen);
+
+        // An aircraft back after a long gap starts a new session
+        let gone_quiet = self.sessions.get(&aircraft.icao)
+            .is_some_and(|open| time - open.session.last_seen > Duration::seconds(SESSION_GAP_SECONDS));
+        if gone_quiet {
+            if let Some(open) = self.sessions.remove(&aircraft.icao) {
+                self.closed.push(open);
+            }
+        }
+
+        let open = self.sessions.entry(aircraft.icao.clone()).or_insert_with(|| OpenSession {
+            session: FlightSession::new(aircraft.icao.clone(), time),
+            pending_points: Vec::new(),
+            last_point: None,
+            dirty: true,
+        });
+        let session = &mut open.session;
+        open.dirty = true;
+        session.last_seen = session.last_seen.max(time);
+
+        if let Some(callsign) = aircraft.callsign.as_deref().map(str::trim).filter(|callsign| !callsign.is_empty()) {
+            if !session.callsigns.iter().any(|known| known == callsign) {
+                session.callsigns.push(callsign.to_string());
+            }
+        }
+        if let Some(details) = aircraft.extensions.get::<AircraftDetails>() {
+            if details.registration.is_some() {
+                session.registration.clone_from(&details.registration);
+            }
+            if details.aircraft_type.is_some() {
+                session.aircraft_type.clone_from(&details.aircraft_type);
+            }
+        }
+        if !session.servers.iter().any(|known| known == server_name) {
+            session.servers.push(server_name.to_string());
+        }
+        if let Some(altitude) = aircraft.altitude {
+            session.min_altitude = Some(session.min_altitude.map_or(altitude, |min| min.min(altitude)));
+            session.max_altitude = Some(session.max_altitude.map_or(altitude, |max| max.max(altitude)));
+        }
+        if let Some(distance) = receiver.and_then(|(lat, lon)| aircraft.distance_from_nm(lat, lon)) {
+            if session.closest_approach.is_none_or(|(closest, _)| distance < closest) {
+                session.closest_approach = Some((distance, time));
+            }
+        }
+
+        let point = PositionPoint {
+            lat,
+            lon,
+            altitude: aircraft.altitude,
+            timestamp: time,
+        };
+        if keep_point(open.last_point.as_ref(), &point, aircraft.track) {
+            open.pending_points.push(point.clone());
+            open.last_point = Some((point, aircraft.track));
+        }
+    }
+
+    /// Write changes if the last write was long enough ago
+    pub fn flush_if_due(&mut self, now: DateTime<Utc>) {
+        let due = self.last_flush.is_none_or(|last| now - last >= Duration::seconds(FLUSH_SECONDS));
+        if due && self.store.is_some() {
+            self.last_flush = Some(now);
+            self.flush(now);
+        }
+    }
+
+    /// Write changed sessions and close those not seen for a while
+    pub fn flush(&mut self, now: DateTime<Utc>) {
+        let Some(store) = self.store.as_mut() else {
+            return;
+        };
+
+        let quiet: Vec<String> = self.sessions.iter()
+            .filter(|(_, open)| now - open.session.last_seen > Duration::seconds(SESSION_GAP_SECONDS))
+            .map(|(icao, _)| icao.clone())
+            .collect();
+        for icao in quiet {
+            if let Some(open) = self.sessions.remove(&icao) {
+                self.closed.push(open);
+            }
+        }
+
+        let changed = self.sessions.values_mut()
+            .chain(self.closed.iter_mut())
+            .filter(|open| open.dirty)
+            .map(|open| (&mut open.session, open.pending_points.as_slice()));
+        if let Err(e) = store.save(changed) {
+            // Keep the changes and try again at the next flush
+            warn!("Failed to write flight history: {}", e);
+            return;
+        }
+        for open in self.sessions.values_mut() {
+            open.dirty = false;
+            open.pending_points.clear();
+        }
+        self.closed.clear();
+
+        if let Some(days) = self.retention_days {
+            if self.last_prune.is_none_or(|last| now - last >= Duration::hours(PRUNE_HOURS)) {
+                self.last_prune = Some(now);
+                match store.prune(now - Duration::days(i64::from(days))) {
+                    Ok(0) => {}
+                    Ok(count) => info!("Deleted {} flight sessions older than {} days", count, days),
+                    Err(e) => warn!("Failed to prune flight history: {}", e),
+                }
+            }
+        }
+    }
+}
+
+impl Drop for FlightLogger {
+    fn drop(&mut self) {
+        let now = Utc::now();
+        self.flush(now);
+    }
+}
+
+/// Whether a position is kept in the stored track: every
+/// `TRACK_POINT_SECONDS`, or sooner on a turn or climb
+fn keep_point(last: Option<&(PositionPoint, Option<f64>)>, point: &PositionPoint, track: Option<f64>) -> bool {
+    let Some((last, last_track)) = last else {
+        return true;
+    };
+    let elapsed = point.timestamp - last.timestamp;
+    if elapsed >= Duration::seconds(TRACK_POINT_SECONDS) {
+        return true;
+    }
+    if elapsed < Duration::seconds(TRACK_POINT_MIN_SECONDS) {
+        return false;
+    }
+    let turned = track.zip(*last_track).is_some_and(|(track, last_track)| {
+        let difference = (track - last_track).rem_euclid(360.0);
+        difference.min(360.0 - difference) >= TURN_DEGREES
+    });
+    let climbed = point.altitude.zip(last.altitude)
+        .is_some_and(|(altitude, last_altitude)| (altitude - last_altitude).abs() >= CLIMB_FEET);
+    turned || climbed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::HistoryQuery;
+    use chrono::TimeZone;
+
+    fn aircraft(icao: &str, time: DateTime<Utc>, lat: f64, altitude: i32, callsign: &str) -> Aircraft {
+        let mut aircraft = Aircraft::new(icao.to_string());
+        aircraft.latitude = Some(lat);
+        aircraft.longitude = Some(-122.0);
+        aircraft.altitude = Some(altitude);
+        aircraft.track = Some(0.0);
+        aircraft.callsign = Some(callsign.to_string());
+        aircraft.last_seen = time;
+        aircraft.position_time = Some(time);
+        aircraft
+    }
+
+    #[test]
+    fn test_sessions_and_queries() {
+        let mut logger = FlightLogger::with_store(Some(HistoryStore::open_in_memory().unwrap()), None);
+        let receiver = Some((37.0, -122.0));
+        let start = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+
+        // One minute of positions a second apart, with a callsign change
+        let details = AircraftDetails {
+            registration: Some("N12345".to_string()),
+            ..AircraftDetails::default()
+        };
+        for second in 0..60 {
+            let time = start + Duration::seconds(second);
+            let callsign = if second < 30 { "UAL123" } else { "UAL124" };
+            let lat = 37.5 - second as f64 * 0.005;
+            let mut aircraft = aircraft("a1b2c3", time, lat, 10_000 + second as i32 * 10, callsign);
+            aircraft.extensions.insert(details.clone());
+            logger.observe(&aircraft, "Local", receiver);
+        }
+        // The same aircraft an hour later is a new session, seen by another server
+        let later = start + Duration::hours(1);
+        logger.observe(&aircraft("a1b2c3", later, 37.2, 3_000, "UAL124"), "Remote", receiver);
+        logger.flush(later);
+
+        let store = logger.store.as_ref().unwrap();
+        let sightings = store.sightings_of_registration("n12345").unwrap();
+        assert_eq!(sightings.len(), 1, "registration only known in the first session");
+        let session = &sightings[0];
+        assert_eq!(session.callsigns, vec!["UAL123", "UAL124"]);
+        assert_eq!(session.first_seen, start);
+        assert_eq!(session.last_seen, start + Duration::seconds(59));
+        assert_eq!((session.min_altitude, session.max_altitude), (Some(10_000), Some(10_590)));
+        assert_eq!(session.servers, vec!["Local"]);
+        let (closest, closest_time) = session.closest_approach.unwrap();
+        assert!(closest < 13.0);
+        assert_eq!(closest_time, start + Duration::seconds(59));
+
+        // Decimated to one point every 15 seconds
+        let track = store.track(session.id.unwrap()).unwrap();
+        assert_eq!(track.len(), 4);
+        assert_eq!(track[1].timestamp, start + Duration::seconds(15));
+
+        let sessions = store.search(&HistoryQuery {
+            search: Some("ual124".to_string()),
+            ..HistoryQuery::default()
+        }).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].servers, vec!["Remote"]);
+
+        let recent = store.search(&HistoryQuery {
+            icao: Some("A1B2C3".to_string()),
+            since: Some(later - Duration::minutes(1)),
+            ..HistoryQuery::default()
+        }).unwrap();
+        assert_eq!(recent.len(), 1);
+
+        assert_eq!(store.prune(later).unwrap(), 1);
+        assert!(store.track(session.id.unwrap()).unwrap().is_empty());
+    }
+}
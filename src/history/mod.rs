@@ -0,0 +1,15 @@
+//! Flight history.
+//!
+//! Every aircraft with a position is logged to an SQLite database as flight
+//! sessions. A session opens when an aircraft is first seen and closes once it
+//! has not been heard from for a while; it holds the callsigns used, first and
+//! last seen times, altitude range, closest approach to the receiver, the
+//! servers that received it and a decimated track. The history window queries
+//! the same database, so "every time this tail number was seen" survives
+//! restarts and the tracker's timeout.
+
+pub mod logger;
+pub mod store;
+
+pub use logger::{FlightLogger, HistoryConfig, SharedFlightLogger};
+pub use store::{FlightSession, HistoryQuery, HistoryStore};
@@ -0,0 +1,304 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SQLite storage and queries for flight sessions.
+
+use adsb_client::tracker::PositionPoint;
+use chrono::{DateTime, Utc};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, Row};
+use std::path::Path;
+
+/// Schema version, kept in the database's `user_version`
+const SCHEMA_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS sessions (
+        id INTEGER PRIMARY KEY,
+        icao TEXT NOT NULL,
+        callsigns TEXT NOT NULL DEFAULT '',
+        registration TEXT,
+        aircraft_type TEXT,
+        first_seen INTEGER NOT NULL,
+        last_seen INTEGER NOT NULL,
+        min_altitude INTEGER,
+        max_altitude INTEGER,
+        closest_nm REAL,
+        closest_time INTEGER,
+        servers TEXT NOT NULL DEFAULT ''
+    );
+    CREATE INDEX IF NOT EXISTS sessions_icao ON sessions (icao, first_seen);
+    CREATE INDEX IF NOT EXISTS sessions_registration ON sessions (registration COLLATE NOCASE);
+    CREATE INDEX IF NOT EXISTS sessions_first_seen ON sessions (first_seen);
+
+    CREATE TABLE IF NOT EXISTS track_points (
+        session_id INTEGER NOT NULL REFERENCES sessions (id) ON DELETE CASCADE,
+        time INTEGER NOT NULL,
+        latitude REAL NOT NULL,
+        longitude REAL NOT NULL,
+        altitude INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS track_points_session ON track_points (session_id, time);
+";
+
+const SESSION_COLUMNS: &str = "id, icao, callsigns, registration, aircraft_type, first_seen, last_seen, \
+     min_altitude, max_altitude, closest_nm, closest_time, servers";
+
+/// One continuous sighting of an aircraft
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlightSession {
+    /// Database row, `None` until the session is first saved
+    pub id: Option<i64>,
+    pub icao: String,
+    /// Callsigns in the order they were first used
+    pub callsigns: Vec<String>,
+    pub registration: Option<String>,
+    pub aircraft_type: Option<String>,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub min_altitude: Option<i32>,
+    pub max_altitude: Option<i32>,
+    /// Closest distance to the receiver in nautical miles, and when
+    pub closest_approach: Option<(f64, DateTime<Utc>)>,
+    /// Servers the aircraft was received on
+    pub servers: Vec<String>,
+}
+
+impl FlightSession {
+    /// An empty session for an aircraft first seen at `time`
+    pub fn new(icao: String, time: DateTime<Utc>) -> Self {
+        Self {
+            id: None,
+            icao,
+            callsigns: Vec::new(),
+            registration: None,
+            aircraft_type: None,
+            first_seen: time,
+            last_seen: time,
+            min_altitude: None,
+            max_altitude: None,
+            closest_approach: None,
+            servers: Vec::new(),
+        }
+    }
+
+    /// Latest callsign if any, else the ICAO address
+    pub fn display_name(&self) -> String {
+        self.callsigns.last().cloned().unwrap_or_else(|| self.icao.to_uppercase())
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let closest_nm: Option<f64> = row.get(9)?;
+        let closest_time: Option<i64> = row.get(10)?;
+        Ok(Self {
+            id: Some(row.get(0)?),
+            icao: row.get(1)?,
+            callsigns: split_list(&row.get::<_, String>(2)?),
+            registration: row.get(3)?,
+            aircraft_type: row.get(4)?,
+            first_seen: from_millis(row.get(5)?),
+            last_seen: from_millis(row.get(6)?),
+            min_altitude: row.get(7)?,
+            max_altitude: row.get(8)?,
+            closest_approach: closest_nm.zip(closest_time.map(from_millis)),
+            servers: split_list(&row.get::<_, String>(11)?),
+        })
+    }
+}
+
+/// Which sessions to return, newest first. Unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryQuery {
+    /// ICAO address or registration (exact), or part of a callsign
+    pub search: Option<String>,
+    /// ICAO address
+    pub icao: Option<String>,
+    /// Registration (tail number)
+    pub registration: Option<String>,
+    /// Sessions still going at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Sessions started at or before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Most sessions to return
+    pub limit: Option<usize>,
+}
+
+/// The flight history database
+pub struct HistoryStore {
+    connection: Connection,
+}
+
+impl HistoryStore {
+    /// Open or create the database at `path`
+    pub fn open(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        let connection = Connection::open(path)?;
+        // Let the history window read while the logger writes
+        connection.pragma_update(None, "journal_mode", "WAL")?;
+        Ok(Self::init(connection)?)
+    }
+
+    /// A database held in memory, for tests
+    #[cfg(test)]
+    pub fn open_in_memory() -> rusqlite::Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(connection: Connection) -> rusqlite::Result<Self> {
+        connection.pragma_update(None, "foreign_keys", true)?;
+        let version: i64 = connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version < SCHEMA_VERSION {
+            connection.execute_batch(SCHEMA)?;
+            connection.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        }
+        Ok(Self { connection })
+    }
+
+    /// Insert new sessions and update saved ones, adding track points to
+    /// each, in one transaction. New sessions get their `id` set.
+    pub fn save<'a>(
+        &mut self,
+        sessions: impl IntoIterator<Item = (&'a mut FlightSession, &'a [PositionPoint])>,
+    ) -> rusqlite::Result<()> {
+        let transaction = self.connection.transaction()?;
+        {
+            let mut upsert = transaction.prepare_cached(
+                "INSERT INTO sessions (id, icao, callsigns, registration, aircraft_type, first_seen, last_seen, \
+                 min_altitude, max_altitude, closest_nm, closest_time, servers) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12) \
+                 ON CONFLICT (id) DO UPDATE SET callsigns = excluded.callsigns, registration = excluded.registration, \
+                 aircraft_type = excluded.aircraft_type, last_seen = excluded.last_seen, \
+                 min_altitude = excluded.min_altitude, max_altitude = excluded.max_altitude, \
+                 closest_nm = excluded.closest_nm, closest_time = excluded.closest_time, servers = excluded.servers",
+            )?;
+            let mut insert_point = transaction.prepare_cached(
+                "INSERT INTO track_points (session_id, time, latitude, longitude, altitude) VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+
+            for (session, points) in sessions {
+                upsert.execute(params![
+                    session.id,
+                    session.icao,
+                    session.callsigns.join(","),
+                    session.registration,
+                    session.aircraft_type,
+                    session.first_seen.timestamp_millis(),
+                    session.last_seen.timestamp_millis(),
+                    session.min_altitude,
+                    session.max_altitude,
+                    session.closest_approach.map(|(distance, _)| distance),
+                    session.closest_approach.map(|(_, time)| time.timestamp_millis()),
+                    session.servers.join(","),
+                ])?;
+                let id = *session.id.get_or_insert_with(|| transaction.last_insert_rowid());
+
+                for point in points {
+                    insert_point.execute(params![
+                        id,
+                        point.timestamp.timestamp_millis(),
+                        point.lat,
+                        point.lon,
+                        point.altitude,
+                    ])?;
+                }
+            }
+        }
+        transaction.commit()
+    }
+
+    /// Sessions matching a query, newest first
+    pub fn search(&self, query: &HistoryQuery) -> rusqlite::Result<Vec<FlightSession>> {
+        let mut conditions = Vec::new();
+        let mut values: Vec<Value> = Vec::new();
+
+        if let Some(search) = query.search.as_deref().map(str::trim).filter(|search| !search.is_empty()) {
+            values.push(Value::Text(search.to_string()));
+            values.push(Value::Text(format!("%{}%", search)));
+            conditions.push(format!(
+                "(icao = ?{0} COLLATE NOCASE OR registration = ?{0} COLLATE NOCASE OR callsigns LIKE ?{1})",
+                values.len() - 1,
+                values.len(),
+            ));
+        }
+        if let Some(icao) = &query.icao {
+            values.push(Value::Text(icao.clone()));
+            conditions.push(format!("icao = ?{} COLLATE NOCASE", values.len()));
+        }
+        if let Some(registration) = &query.registration {
+            values.push(Value::Text(registration.clone()));
+            conditions.push(format!("registration = ?{} COLLATE NOCASE", values.len()));
+        }
+        if let Some(since) = query.since {
+            values.push(Value::Integer(since.timestamp_millis()));
+            conditions.push(format!("last_seen >= ?{}", values.len()));
+        }
+        if let Some(until) = query.until {
+            values.push(Value::Integer(until.timestamp_millis()));
+            conditions.push(format!("first_seen <= ?{}", values.len()));
+        }
+
+        let mut sql = format!("SELECT {} FROM sessions", SESSION_COLUMNS);
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY first_seen DESC");
+        if let Some(limit) = query.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        let mut statement = self.connection.prepare(&sql)?;
+        let sessions = statement.query_map(params_from_iter(values), FlightSession::from_row)?;
+        sessions.collect()
+    }
+
+    /// Every time an aircraft with this registration was seen, newest first
+    pub fn sightings_of_registration(&self, registration: &str) -> rusqlite::Result<Vec<FlightSession>> {
+        self.search(&HistoryQuery {
+            registration: Some(registration.to_string()),
+            ..HistoryQuery::default()
+        })
+    }
+
+    /// Stored track of a session, oldest first
+    pub fn track(&self, session_id: i64) -> rusqlite::Result<Vec<PositionPoint>> {
+        let mut statement = self.connection.prepare_cached(
+            "SELECT time, latitude, longitude, altitude FROM track_points WHERE session_id = ?1 ORDER BY time",
+        )?;
+        let points = statement.query_map([session_id], |row| {
+            Ok(PositionPoint {
+                timestamp: from_millis(row.get(0)?),
+                lat: row.get(1)?,
+                lon: row.get(2)?,
+                altitude: row.get(3)?,
+            })
+        })?;
+        points.collect()
+    }
+
+    /// Delete sessions last seen before `time` with their tracks, returning how many
+    pub fn prune(&self, time: DateTime<Utc>) -> rusqlite::Result<usize> {
+        self.connection.execute("DELETE FROM sessions WHERE last_seen < ?1", [time.timestamp_millis()])
+    }
+}
+
+fn from_millis(millis: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(millis).unwrap_or_default()
+}
+
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',').filter(|item| !item.is_empty()).map(str::to_string).collect()
+}
@@ -21,34 +21,61 @@ static GLOBAL: MiMalloc = MiMalloc;
 mod aircraft;
 mod aviation;
 mod config;
+mod export;
+mod geofence;
+mod history;
 mod map;
 mod media;
 mod network;
+mod recording;
 mod sdr;
 mod status;
 mod ui;
 mod video;
+mod watchlist;
 mod weather;
+mod web;
 
-use aircraft::{AircraftDatabase, AircraftTypeDatabase, MetadataService, Aircraft};
+use aircraft::{AircraftDatabase, AircraftTypeDatabase, MetadataService, Aircraft, AircraftDetails, ConflictMonitor};
 use aviation::{AviationData, Airport, Navaid, AirportFilter};
 use clap::Parser;
 use eframe::egui;
 use media::PhotoTextureManager;
 use status::{SystemStatus, DiagnosticLevel, ServerStatus};
-use ui::StatusPane;
+use ui::{EmergencyBanner, GeofenceWindow, Notifications, StatusPane, WatchlistSettings};
 use std::sync::{Arc, Mutex};
 use serde::Deserialize;
 use map::{WebMercator, CartoTileSource};
 use config::DEFAULT_SERVER_ADDRESS;
 use walkers::{HttpTiles, MapMemory, HttpOptions, lat_lon, Tiles};
-use weather::{WeatherTiles, WeatherLayer};
+use weather::{AltitudeBand, WeatherTiles, WeatherLayer, WindsAloft};
+use weather::winds_aloft::{draw_wind_barb, WindBarb, WindSample};
+use adsb_client::DataSource;
+use watchlist::{WatchEvent, Watchlist};
+use geofence::{GeofenceMonitor, SharedGeofenceMonitor, Zone};
+use recording::RecordingConfig;
 
 // Trail display constants
 const TRAIL_MAX_AGE_SECONDS: f32 = 300.0;  // 5 minutes total
 const TRAIL_SOLID_DURATION_SECONDS: f32 = 225.0;  // First 75% solid (3.75 minutes)
 const TRAIL_FADE_DURATION_SECONDS: f32 = 75.0;  // Last 25% fade (1.25 minutes)
 
+// Badge color for aircraft received on 978 MHz UAT
+const UAT_BADGE_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 180, 80);
+
+// Emergency alert color
+const EMERGENCY_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 40, 40);
+
+// Highlight color for aircraft matching a watchlist rule
+const WATCHLIST_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 210, 60);
+
+// How often aircraft are checked against the watchlist
+const WATCHLIST_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+// Predicted conflicts: line color, and how often they are recomputed
+const CONFLICT_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 120, 30);
+const CONFLICT_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
 /// Validate server address format (host:port)
 fn validate_server_address(s: &str) -> Result<String, String> {
     let parts: Vec<&str> = s.split(':').collect();
@@ -63,6 +90,15 @@ fn validate_server_address(s: &str) -> Result<String, String> {
     Ok(s.to_string())
 }
 
+/// Ask for a capture or feed log to replay
+fn pick_replay_file() -> Option<std::path::PathBuf> {
+    rfd::FileDialog::new()
+        .set_title("Replay a recording")
+        .add_filter("Captures and feed logs", &[recording::recorder::CAPTURE_EXTENSION, "gz", "log", "txt", "sbs", "bin", "beast"])
+        .add_filter("All files", &["*"])
+        .pick_file()
+}
+
 /// AirJedi Desktop - Real-time ADS-B aircraft tracking application
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -75,6 +111,18 @@ struct CliArgs {
         value_parser = validate_server_address
     )]
     server: String,
+
+    /// Record every enabled server's feed from startup
+    #[arg(long)]
+    record: bool,
+
+    /// Directory for recordings (default: the configured directory)
+    #[arg(long, value_name = "DIR", requires = "record")]
+    record_dir: Option<std::path::PathBuf>,
+
+    /// Write recordings without gzip compression
+    #[arg(long, requires = "record")]
+    record_uncompressed: bool,
 }
 
 #[derive(Deserialize, Debug)]
@@ -229,6 +277,18 @@ fn main() -> Result<(), eframe::Error> {
         }
     }
 
+    // Recording from the command line applies to this session only
+    let record_on_start = args.record.then(|| {
+        let mut recording = config.recording.clone();
+        if let Some(directory) = args.record_dir.clone() {
+            recording.directory = directory;
+        }
+        if args.record_uncompressed {
+            recording.compress = false;
+        }
+        recording
+    });
+
     println!("Starting AirJedi Desktop...");
 
     // Display config file path
@@ -260,7 +320,7 @@ fn main() -> Result<(), eframe::Error> {
         options,
         Box::new(move |cc| {
             println!("Creating application...");
-            Ok(Box::new(AirjediApp::new(config, &cc.egui_ctx)))
+            Ok(Box::new(AirjediApp::new(config, record_on_start, &cc.egui_ctx)))
         }),
     )
 }
@@ -276,6 +336,7 @@ enum HoveredMapItem {
     Airport(Airport),
     Navaid(Navaid),
     Aircraft(Aircraft),
+    WindBarb(WindBarb),
 }
 
 // Implement popup rendering for Airport
@@ -408,6 +469,31 @@ impl MapItemPopup for Navaid {
     }
 }
 
+// Implement popup rendering for winds aloft barbs
+impl MapItemPopup for WindBarb {
+    fn render_popup(&self, ui: &mut egui::Ui, _receiver_lat: f64, _receiver_lon: f64, _aircraft_types: &Arc<Mutex<AircraftTypeDatabase>>) {
+        ui.set_min_width(160.0);
+
+        ui.label(egui::RichText::new(format!("{:03.0}° / {:.0} kt", self.direction, self.speed))
+            .color(egui::Color32::from_rgb(120, 200, 255))
+            .size(14.0)
+            .strong());
+
+        if let Some(temperature) = self.temperature {
+            ui.label(egui::RichText::new(format!("{:.0}°C", temperature))
+                .color(egui::Color32::from_rgb(200, 200, 200))
+                .size(10.0));
+        }
+
+        ui.add_space(2.0);
+
+        // Aircraft averaged into the cell
+        ui.label(egui::RichText::new(format!("{} aircraft reports", self.sample_count))
+            .color(egui::Color32::from_rgb(150, 150, 150))
+            .size(9.0));
+    }
+}
+
 // Implement popup rendering for Aircraft
 impl MapItemPopup for Aircraft {
     fn render_popup(&self, ui: &mut egui::Ui, receiver_lat: f64, receiver_lon: f64, aircraft_types: &Arc<Mutex<AircraftTypeDatabase>>) {
@@ -425,10 +511,18 @@ impl MapItemPopup for Aircraft {
                     .strong());
 
                 // ICAO as subtitle
-                ui.label(egui::RichText::new(&data.icao)
-                    .color(egui::Color32::from_rgb(180, 180, 180))
-                    .size(10.0)
-                    .monospace());
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(&data.icao)
+                        .color(egui::Color32::from_rgb(180, 180, 180))
+                        .size(10.0)
+                        .monospace());
+                    if data.source == DataSource::Uat {
+                        ui.label(egui::RichText::new("UAT 978")
+                            .color(UAT_BADGE_COLOR)
+                            .size(9.0)
+                            .strong());
+                    }
+                });
             } else {
                 // Just ICAO if no callsign
                 ui.label(egui::RichText::new(&data.icao)
@@ -436,13 +530,19 @@ impl MapItemPopup for Aircraft {
                     .size(16.0)
                     .strong()
                     .monospace());
+                if data.source == DataSource::Uat {
+                    ui.label(egui::RichText::new("UAT 978")
+                        .color(UAT_BADGE_COLOR)
+                        .size(9.0)
+                        .strong());
+                }
             }
 
             ui.add_space(4.0);
 
             // Altitude with color coding
             if let Some(alt) = data.altitude {
-                let (r, g, b) = AirjediApp::altitude_to_color(Some(alt));
+                let (r, g, b) = map::altitude_to_color(Some(alt));
                 let alt_color = egui::Color32::from_rgb(r, g, b);
 
                 ui.horizontal(|ui| {
@@ -520,13 +620,143 @@ impl MapItemPopup for Aircraft {
                 });
             }
 
+            // Squawk, highlighted when the aircraft reports an emergency
+            if let Some(ref squawk) = data.squawk {
+                let emergency = data.emergency_state.filter(|state| state.is_emergency());
+                let squawk_color = if emergency.is_some() {
+                    egui::Color32::from_rgb(255, 80, 80)
+                } else {
+                    egui::Color32::from_rgb(200, 200, 200)
+                };
+
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("Squawk:")
+                        .color(egui::Color32::from_rgb(150, 150, 150))
+                        .size(9.0));
+                    ui.label(egui::RichText::new(squawk)
+                        .color(squawk_color)
+                        .size(9.0)
+                        .monospace());
+                    if let Some(state) = emergency {
+                        ui.label(egui::RichText::new(state.label())
+                            .color(squawk_color)
+                            .size(9.0)
+                            .strong());
+                    }
+                });
+            }
+
+            // Surveillance status flags
+            let flags: Vec<&str> = [
+                (data.is_on_ground, "GND"),
+                (data.spi, "IDENT"),
+                (data.alert, "ALERT"),
+            ]
+            .into_iter()
+            .filter_map(|(flag, label)| (flag == Some(true)).then_some(label))
+            .collect();
+            if !flags.is_empty() {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("Status:")
+                        .color(egui::Color32::from_rgb(150, 150, 150))
+                        .size(9.0));
+                    ui.label(egui::RichText::new(flags.join(" "))
+                        .color(egui::Color32::from_rgb(255, 200, 100))
+                        .size(9.0)
+                        .monospace());
+                });
+            }
+
+            // Selected altitude from the MCP/FCU or FMS
+            if let Some(sel_alt) = data.selected_altitude {
+                let source = data.selected_altitude_source
+                    .map(|source| format!(" ({})", source.label()))
+                    .unwrap_or_default();
+                let sel_alt_text = if sel_alt >= 18000 {
+                    format!("FL{:03}{}", sel_alt / 100, source)
+                } else {
+                    format!("{} ft{}", sel_alt, source)
+                };
+
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("Sel Alt:")
+                        .color(egui::Color32::from_rgb(150, 150, 150))
+                        .size(9.0));
+                    ui.label(egui::RichText::new(sel_alt_text)
+                        .color(egui::Color32::from_rgb(255, 180, 255))
+                        .size(9.0)
+                        .monospace());
+                });
+            }
+
+            // Selected heading and altimeter setting
+            if data.selected_heading.is_some() || data.baro_setting.is_some() {
+                ui.horizontal(|ui| {
+                    if let Some(sel_hdg) = data.selected_heading {
+                        ui.label(egui::RichText::new("Sel Hdg:")
+                            .color(egui::Color32::from_rgb(150, 150, 150))
+                            .size(9.0));
+                        ui.label(egui::RichText::new(format!("{:03}°", sel_hdg.round() as i32 % 360))
+                            .color(egui::Color32::from_rgb(255, 180, 255))
+                            .size(9.0)
+                            .monospace());
+                    }
+                    if let Some(qnh) = data.baro_setting {
+                        ui.label(egui::RichText::new("QNH:")
+                            .color(egui::Color32::from_rgb(150, 150, 150))
+                            .size(9.0));
+                        ui.label(egui::RichText::new(format!("{:.1} hPa", qnh))
+                            .color(egui::Color32::from_rgb(200, 200, 200))
+                            .size(9.0)
+                            .monospace());
+                    }
+                });
+            }
+
+            // Autopilot modes
+            if let Some(modes) = data.autopilot_modes {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("Modes:")
+                        .color(egui::Color32::from_rgb(150, 150, 150))
+                        .size(9.0));
+                    ui.label(egui::RichText::new(modes.to_string())
+                        .color(egui::Color32::from_rgb(100, 255, 100))
+                        .size(9.0)
+                        .monospace());
+                });
+            }
+
+            // Wind and temperature from Comm-B air data
+            if data.wind.is_some() || data.static_air_temperature.is_some() {
+                ui.horizontal(|ui| {
+                    if let Some(wind) = data.wind {
+                        ui.label(egui::RichText::new("Wind:")
+                            .color(egui::Color32::from_rgb(150, 150, 150))
+                            .size(9.0));
+                        ui.label(egui::RichText::new(format!("{:03.0}°/{:.0} kt", wind.value.direction, wind.value.speed))
+                            .color(egui::Color32::from_rgb(120, 200, 255))
+                            .size(9.0)
+                            .monospace());
+                    }
+                    if let Some(temperature) = data.static_air_temperature {
+                        ui.label(egui::RichText::new("SAT:")
+                            .color(egui::Color32::from_rgb(150, 150, 150))
+                            .size(9.0));
+                        ui.label(egui::RichText::new(format!("{:.0}°C", temperature.value))
+                            .color(egui::Color32::from_rgb(200, 200, 200))
+                            .size(9.0)
+                            .monospace());
+                    }
+                });
+            }
+
             ui.add_space(2.0);
 
             // Aircraft type
-            if let Some(ref aircraft_type) = data.aircraft_type {
+            if let Some(aircraft_type) = self.aircraft_type() {
                 // Lookup full aircraft type name from type database
                 let type_display = if let Ok(type_db) = aircraft_types.lock() {
-                    type_db.lookup(aircraft_type)
+                    type_db.lookup(&aircraft_type)
                         .unwrap_or(aircraft_type.as_str())
                         .to_string()
                 } else {
@@ -563,8 +793,21 @@ impl MapItemPopup for Aircraft {
                     .size(8.0));
             }
 
+            // Positions dropped by the tracker's validator
+            let stats = &data.position_stats;
+            if stats.rejected > 0 {
+                let mut text = format!("{} positions rejected ({:.0}%)",
+                    stats.rejected, stats.rejection_rate() * 100.0);
+                if let Some(rejection) = stats.last_rejection {
+                    text.push_str(&format!(", last: {}", rejection));
+                }
+                ui.label(egui::RichText::new(text)
+                    .color(egui::Color32::from_rgb(200, 150, 100))
+                    .size(8.0));
+            }
+
             // Last seen
-            let seconds_ago = (chrono::Utc::now() - data.last_seen).num_seconds();
+            let seconds_ago = (self.feed_now() - data.last_seen).num_seconds();
             let time_color = if seconds_ago < 5 {
                 egui::Color32::from_rgb(100, 255, 100) // Recent - green
             } else if seconds_ago < 30 {
@@ -576,6 +819,12 @@ impl MapItemPopup for Aircraft {
             ui.label(egui::RichText::new(format!("Updated {}s ago", seconds_ago))
                 .color(time_color)
                 .size(8.0));
+
+            if data.position_history.len() >= 2 {
+                ui.label(egui::RichText::new("Right-click to export track")
+                    .color(egui::Color32::from_rgb(120, 120, 120))
+                    .size(8.0));
+            }
         });
     }
 }
@@ -608,6 +857,7 @@ struct AirjediApp {
     tile_error: Option<String>,
     selected_aircraft: Option<String>, // ICAO of selected aircraft
     previous_selected_aircraft: Option<String>, // Track selection changes for auto-scroll
+    export_menu_aircraft: Option<String>, // Aircraft right-clicked on the map, for the export menu
     aviation_data: Arc<Mutex<AviationData>>,
     aviation_data_loading: Arc<Mutex<bool>>,
     show_airports: bool,
@@ -630,6 +880,18 @@ struct AirjediApp {
     // System status and monitoring
     system_status: Arc<Mutex<SystemStatus>>,
     status_pane: StatusPane,
+    emergency_banner: EmergencyBanner,
+    // Watchlist rules and in-app notifications
+    watchlist: Watchlist,
+    watchlist_events: tokio::sync::broadcast::Receiver<WatchEvent>,
+    last_watchlist_check: std::time::Instant,
+    notifications: Notifications,
+    watchlist_settings: WatchlistSettings,
+    // Recording settings requested on the command line, applied once servers start
+    record_on_start: Option<RecordingConfig>,
+    // Predicted traffic conflicts
+    conflict_monitor: ConflictMonitor,
+    last_conflict_check: std::time::Instant,
     // Startup sequence tracking
     startup_state: StartupState,
     startup_frame_count: usize,
@@ -650,6 +912,9 @@ struct AirjediApp {
     following_aircraft: bool, // Whether we've auto-panned to an aircraft
     // Application configuration
     config: config::AppConfig,
+    // Geofence zones: monitor shared with the connection tasks, and window with drawing state
+    geofences: SharedGeofenceMonitor,
+    geofence_window: GeofenceWindow,
     // Server UI edit state (server_id -> (name, address))
     server_edit_state: std::collections::HashMap<String, (String, String)>,
     // GPS override UI state
@@ -670,8 +935,14 @@ struct AirjediApp {
     video_manager: video::VideoManager,
     // Waterfall/SDR visualization
     waterfall_window: Option<ui::WaterfallWindow>,
+    // Flight history browser
+    history_window: Option<ui::HistoryWindow>,
+    // HTTP API serving readsb-style JSON, while enabled
+    web_server: Option<web::WebServer>,
     // Weather overlay tiles
     weather_tiles: WeatherTiles,
+    // Winds aloft aggregated from aircraft air data
+    winds_aloft: WindsAloft,
 }
 
 // AirportFilter is now imported from aviation module
@@ -806,6 +1077,38 @@ impl AirjediApp {
         painter.add(egui::Shape::mesh(mesh));
     }
 
+    // Draw a hollow aircraft symbol, for positions predicted rather than received
+    fn draw_aircraft_outline(
+        painter: &egui::Painter,
+        pos: egui::Pos2,
+        track_degrees: f32,
+        color: egui::Color32,
+        size: f32,
+    ) {
+        let angle = track_degrees.to_radians();
+        let cos_a = angle.cos();
+        let sin_a = angle.sin();
+
+        // Arrowhead pointing in track direction, notched at the tail
+        let vertices = [
+            (0.0, -size * 1.5),
+            (size * 0.9, size),
+            (0.0, size * 0.4),
+            (-size * 0.9, size),
+        ];
+
+        let points: Vec<egui::Pos2> = vertices
+            .iter()
+            .map(|(x, y)| {
+                let rx = x * cos_a - y * sin_a;
+                let ry = x * sin_a + y * cos_a;
+                egui::pos2(pos.x + rx, pos.y + ry)
+            })
+            .collect();
+
+        painter.add(egui::Shape::closed_line(points, egui::Stroke::new(1.5, color)));
+    }
+
     // Load the AirJedi logo SVG for the loading screen
     fn load_logo_texture(ctx: &egui::Context) -> Option<egui::TextureHandle> {
         // Read SVG file from disk
@@ -932,46 +1235,6 @@ impl AirjediApp {
         }
     }
 
-    // Convert altitude to continuous color gradient
-    // Low altitude (cyan) -> High altitude (purple) with smooth blending
-    fn altitude_to_color(altitude_ft: Option<i32>) -> (u8, u8, u8) {
-        let alt = altitude_ft.unwrap_or(0) as f32;
-
-        // Clamp altitude to 0-45000 range for gradient calculation
-        let clamped_alt = alt.clamp(0.0, 45000.0);
-
-        // Define gradient stops with colors (in feet)
-        // Each stop is (altitude, (r, g, b))
-        let stops = [
-            (0.0, (0.0, 200.0, 200.0)),        // Cyan
-            (10000.0, (50.0, 150.0, 200.0)),   // Teal
-            (20000.0, (150.0, 200.0, 0.0)),    // Yellow
-            (30000.0, (255.0, 150.0, 0.0)),    // Orange
-            (40000.0, (255.0, 50.0, 150.0)),   // Red/Magenta
-            (45000.0, (150.0, 50.0, 255.0)),   // Purple
-        ];
-
-        // Find which two stops we're between
-        for i in 0..stops.len() - 1 {
-            let (alt1, color1) = stops[i];
-            let (alt2, color2) = stops[i + 1];
-
-            if clamped_alt >= alt1 && clamped_alt <= alt2 {
-                // Linear interpolation between the two colors
-                let t = (clamped_alt - alt1) / (alt2 - alt1);
-
-                let r = color1.0 + (color2.0 - color1.0) * t;
-                let g = color1.1 + (color2.1 - color1.1) * t;
-                let b = color1.2 + (color2.2 - color1.2) * t;
-
-                return (r as u8, g as u8, b as u8);
-            }
-        }
-
-        // Fallback to highest color if somehow we didn't match
-        (150, 50, 255)
-    }
-
     /// Convert HSL to RGB (hue 0-360, saturation 0-1, lightness 0-1)
     fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> (u8, u8, u8) {
         let c = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
@@ -994,7 +1257,7 @@ impl AirjediApp {
         )
     }
 
-    fn new(config: config::AppConfig, egui_ctx: &egui::Context) -> Self {
+    fn new(config: config::AppConfig, record_on_start: Option<RecordingConfig>, egui_ctx: &egui::Context) -> Self {
         println!("Initializing ADSB app...");
 
         // Load logo for loading screen
@@ -1005,10 +1268,12 @@ impl AirjediApp {
 
         // Initialize core structures
         let system_status = Arc::new(Mutex::new(SystemStatus::new()));
+        let geofences = Arc::new(Mutex::new(GeofenceMonitor::new(config.geofences.zones.clone())));
+        let flight_logger = Arc::new(Mutex::new(history::FlightLogger::new(&config.history)));
 
         // Initialize ConnectionManager (connections will be started in startup sequence)
         let connection_manager = Arc::new(Mutex::new(
-            network::ConnectionManager::new(system_status.clone(), 37.7749, -122.4194)
+            network::ConnectionManager::new(system_status.clone(), geofences.clone(), flight_logger, 37.7749, -122.4194)
         ));
         let aviation_data = Arc::new(Mutex::new(AviationData::new()));
         let aviation_data_loading = Arc::new(Mutex::new(true));
@@ -1016,6 +1281,8 @@ impl AirjediApp {
         let aircraft_types = Arc::new(Mutex::new(AircraftTypeDatabase::new()));
         let metadata_service = Arc::new(MetadataService::new());
         let photo_manager = PhotoTextureManager::new();
+        let watchlist = Watchlist::new(&config.watchlist);
+        let watchlist_events = watchlist.subscribe();
 
         // Initialize Walkers tile management
         let cache_dir = dirs::cache_dir()
@@ -1058,6 +1325,10 @@ impl AirjediApp {
             _ => AirportFilter::FrequentlyUsed, // Default fallback
         };
 
+        let web_server = config.web.enabled.then(|| {
+            web::WebServer::spawn(config.web.clone(), Arc::downgrade(&connection_manager), system_status.clone())
+        });
+
         println!("App structure initialized - startup will continue in first frames");
 
         Self {
@@ -1074,6 +1345,7 @@ impl AirjediApp {
             tile_error: None,
             selected_aircraft: None,
             previous_selected_aircraft: None,
+            export_menu_aircraft: None,
             aviation_data,
             aviation_data_loading,
             show_airports: config.show_airports,
@@ -1092,6 +1364,15 @@ impl AirjediApp {
             photo_manager,
             system_status,
             status_pane: StatusPane::new(),
+            emergency_banner: EmergencyBanner::new(),
+            watchlist,
+            watchlist_events,
+            last_watchlist_check: std::time::Instant::now(),
+            notifications: Notifications::new(),
+            watchlist_settings: WatchlistSettings::new(&config.watchlist),
+            record_on_start,
+            conflict_monitor: ConflictMonitor::new(),
+            last_conflict_check: std::time::Instant::now(),
             startup_state: StartupState::InitializingWindow,
             startup_frame_count: 0,
             // Initialize filtering and sorting with sensible defaults
@@ -1110,6 +1391,8 @@ impl AirjediApp {
             stored_map_center: None,
             following_aircraft: false,
             config: config.clone(),
+            geofences,
+            geofence_window: GeofenceWindow::new(),
             server_edit_state: std::collections::HashMap::new(),
             gps_override_lat_text: String::new(),
             gps_override_lon_text: String::new(),
@@ -1122,6 +1405,8 @@ impl AirjediApp {
             scroll_zoom_velocity: 0.0,
             video_manager: video::VideoManager::new(),
             waterfall_window: None,
+            history_window: None,
+            web_server,
             weather_tiles: {
                 let mut tiles = WeatherTiles::new();
                 let api_key = WeatherTiles::resolve_api_key(
@@ -1130,6 +1415,7 @@ impl AirjediApp {
                 tiles.set_api_key(api_key, egui_ctx);
                 tiles
             },
+            winds_aloft: WindsAloft::new(),
         }
     }
 
@@ -1166,20 +1452,17 @@ impl AirjediApp {
                     metadata_service.fetch_photo_by_icao(&icao).await
                 };
 
-                // Update aircraft with metadata using the new API
-                if let Some(aircraft) = connection_manager.lock().unwrap().get_aircraft_by_icao(&icao) {
-                    aircraft.with_data_mut(|data| {
-                        data.registration = registration;
-                        data.aircraft_type = aircraft_type;
-                        data.metadata_fetched = true;
-
-                        if let Some(metadata) = photo_metadata {
-                            data.photo_url = metadata.photo_url;
-                            data.photo_thumbnail_url = metadata.photo_thumbnail_url;
-                            data.photographer = metadata.photographer;
-                        }
-                    });
-                }
+                // Attach the metadata to the tracked aircraft, keeping any video links
+                connection_manager.lock().unwrap().update_aircraft(&icao, |aircraft| {
+                    let details = aircraft.extensions.get_or_default::<AircraftDetails>();
+                    details.registration = registration.clone();
+                    details.aircraft_type = aircraft_type.clone();
+                    if let Some(metadata) = &photo_metadata {
+                        details.photo_url = metadata.photo_url.clone();
+                        details.photo_thumbnail_url = metadata.photo_thumbnail_url.clone();
+                        details.photographer = metadata.photographer.clone();
+                    }
+                });
 
                 // Remove from pending
                 pending_metadata.lock().unwrap().remove(&icao);
@@ -1361,7 +1644,7 @@ impl AirjediApp {
                     }
 
                     // Determine status color based on altitude and recency
-                    let seconds_ago = (chrono::Utc::now() - aircraft.last_seen()).num_seconds();
+                    let seconds_ago = (aircraft.feed_now() - aircraft.last_seen()).num_seconds();
                     let (status_color, status_symbol) = if seconds_ago < 10 {
                         (egui::Color32::from_rgb(100, 255, 100), "●") // Active - green
                     } else if seconds_ago < 60 {
@@ -1383,6 +1666,9 @@ impl AirjediApp {
                     let icao = aircraft.icao();
                     let is_selected = self.selected_aircraft.as_ref() == Some(&icao);
 
+                    // Watchlist rules this aircraft currently matches
+                    let watched_rules = self.watchlist.matched_rules(&icao);
+
                     // Create a frame with background color if selected
                     let frame = if is_selected {
                         egui::Frame::group(ui.style())
@@ -1391,6 +1677,13 @@ impl AirjediApp {
                         egui::Frame::group(ui.style())
                     };
 
+                    // Outline watched aircraft
+                    let frame = if watched_rules.is_empty() {
+                        frame
+                    } else {
+                        frame.stroke(egui::Stroke::new(1.5, WATCHLIST_COLOR))
+                    };
+
                     let inner_response = frame.show(ui, |ui| {
                         let mut photo_double_clicked = false; // Track if photo was double-clicked
                         let mut photo_single_clicked = false; // Track if photo was single-clicked
@@ -1414,6 +1707,23 @@ impl AirjediApp {
                                         .monospace()
                                         .strong());
 
+                                    // Mark aircraft received on 978 MHz
+                                    if aircraft.source() == DataSource::Uat {
+                                        ui.label(egui::RichText::new("UAT")
+                                            .color(UAT_BADGE_COLOR)
+                                            .size(7.5)
+                                            .strong())
+                                            .on_hover_text("Received on 978 MHz UAT");
+                                    }
+
+                                    // Mark aircraft matching the watchlist
+                                    if !watched_rules.is_empty() {
+                                        ui.label(egui::RichText::new("★")
+                                            .color(WATCHLIST_COLOR)
+                                            .size(10.0))
+                                            .on_hover_text(format!("Watchlist: {}", watched_rules.join(", ")));
+                                    }
+
                                     if let Some(ref callsign) = aircraft.callsign() {
                                         let callsign_color = if is_selected {
                                             egui::Color32::from_rgb(255, 50, 50)
@@ -1705,6 +2015,109 @@ impl AirjediApp {
         );
     }
 
+    /// Save the config, reporting a failure in the diagnostics
+    ///
+    /// Returns whether the config was saved.
+    fn save_config(&self) -> bool {
+        match self.config.save() {
+            Ok(()) => true,
+            Err(e) => {
+                self.system_status.lock().unwrap().add_diagnostic(
+                    DiagnosticLevel::Error,
+                    format!("Failed to save config: {}", e),
+                );
+                false
+            }
+        }
+    }
+
+    /// Start, restart or stop the HTTP API to match its settings
+    fn update_web_server(&mut self) {
+        if !self.config.web.enabled {
+            self.web_server = None;
+            return;
+        }
+        if self.web_server.as_ref().is_some_and(|server| server.config() == &self.config.web) {
+            return;
+        }
+
+        // Stop the old server before starting one on the new port
+        self.web_server = None;
+        self.web_server = Some(web::WebServer::spawn(
+            self.config.web.clone(),
+            Arc::downgrade(&self.connection_manager),
+            self.system_status.clone(),
+        ));
+    }
+
+    /// Buttons exporting an aircraft's track, or every track, in each format
+    fn export_format_buttons(&mut self, ui: &mut egui::Ui, icao: Option<&str>) {
+        for format in export::ExportFormat::ALL {
+            if ui.button(format!("{}...", format.label())).clicked() {
+                ui.close();
+                self.export_tracks(icao, format);
+            }
+        }
+    }
+
+    /// Ask where to save an aircraft's track, or every track, and write it
+    fn export_tracks(&mut self, icao: Option<&str>, format: export::ExportFormat) {
+        let aircraft = {
+            let connection_manager = self.connection_manager.lock().unwrap();
+            match icao {
+                Some(icao) => connection_manager.get_aircraft_by_icao(icao).into_iter().collect(),
+                None => connection_manager.get_all_aircraft_unique(),
+            }
+        };
+        let tracks: Vec<export::FlightTrack> = aircraft.iter()
+            .filter_map(export::FlightTrack::from_aircraft)
+            .collect();
+
+        if tracks.is_empty() {
+            self.notifications.push(
+                "Nothing to export".to_string(),
+                "No trail has been recorded yet".to_string(),
+                egui::Color32::from_rgb(200, 200, 200),
+                None,
+            );
+            return;
+        }
+
+        let stamp = chrono::Utc::now().format("%Y%m%d-%H%M%S");
+        let file_name = match tracks.as_slice() {
+            [track] => format!("{}-{}.{}", track.name(), stamp, format.extension()),
+            _ => format!("tracks-{}.{}", stamp, format.extension()),
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Export tracks")
+            .set_file_name(file_name)
+            .add_filter(format.label(), &[format.extension()])
+            .save_file()
+        else {
+            return;
+        };
+
+        match export::export_tracks(&path, format, &tracks) {
+            Ok(()) => self.system_status.lock().unwrap().add_diagnostic(
+                DiagnosticLevel::Info,
+                format!("Exported {} track(s) to {}", tracks.len(), path.display()),
+            ),
+            Err(e) => {
+                self.system_status.lock().unwrap().add_diagnostic(
+                    DiagnosticLevel::Error,
+                    format!("Failed to export tracks to {}: {}", path.display(), e),
+                );
+                self.notifications.push(
+                    "Export failed".to_string(),
+                    e.to_string(),
+                    egui::Color32::from_rgb(255, 100, 100),
+                    None,
+                );
+            }
+        }
+    }
+
+    /// Draw a timeline bar with playback controls for each running replay
     fn draw_map(&mut self, ui: &mut egui::Ui) {
         // Check if pointer is over the aircraft list panel (using rect from previous frame)
         let pointer_over_panel = if let Some(panel_rect) = self.aircraft_list_rect {
@@ -1826,7 +2239,37 @@ impl AirjediApp {
         // Get aircraft list
         let aircraft_list: Vec<Aircraft> = {
             let connection_manager = self.connection_manager.lock().unwrap();
-            connection_manager.get_all_aircraft_merged()
+            connection_manager.get_all_aircraft_unique()
+        };
+
+        // Feed live aircraft wind reports into the winds aloft grid; replays
+        // run on their own clock, so they are left out
+        let now = chrono::Utc::now();
+        for aircraft in aircraft_list.iter().filter(|aircraft| !aircraft.is_replayed()) {
+            if let (Some(wind), Some(timestamp), Some(latitude), Some(longitude), Some(altitude)) = (
+                aircraft.wind(),
+                aircraft.wind_time(),
+                aircraft.latitude(),
+                aircraft.longitude(),
+                aircraft.altitude(),
+            ) {
+                self.winds_aloft.record(&aircraft.icao(), WindSample {
+                    latitude,
+                    longitude,
+                    altitude,
+                    direction: wind.value.direction,
+                    speed: wind.value.speed,
+                    temperature: aircraft.static_air_temperature().map(|t| t.value),
+                    confidence: wind.confidence,
+                    timestamp,
+                });
+            }
+        }
+        self.winds_aloft.prune(now);
+        let wind_barbs = if self.config.show_winds_aloft {
+            self.winds_aloft.barbs(self.config.winds_aloft_band)
+        } else {
+            Vec::new()
         };
 
         // Get trail settings
@@ -1838,9 +2281,37 @@ impl AirjediApp {
         let show_navaids = self.show_navaids;
         let airport_filter = self.airport_filter;
         let selected_aircraft = self.selected_aircraft.clone();
+        // Emergency symbols flash twice a second
+        let emergency_flash_on = (ui.input(|i| i.time) * 2.0).fract() < 0.5;
         let receiver_lat = self.receiver_lat;
         let receiver_lon = self.receiver_lon;
 
+        // Geofence zones, the zone being drawn, and aircraft highlighted by a recent hit
+        let zone_drawing = self.geofence_window.drawing().cloned();
+        let zones: &[Zone] = if self.config.geofences.show_zones || zone_drawing.is_some() {
+            &self.config.geofences.zones
+        } else {
+            &[]
+        };
+        let geofence_highlights = self.geofences.lock().unwrap().latest_hits();
+        let conflicts = self.conflict_monitor.conflicts();
+
+        // Aircraft are drawn where dead reckoning puts them now. Once the last position is
+        // older than the stale threshold, it is returned too for a leader to the prediction.
+        let dead_reckoning = self.config.dead_reckoning;
+        let stale_position_secs = self.config.stale_position_secs;
+        let display_position = |aircraft: &Aircraft| -> Option<((f64, f64), Option<(f64, f64)>)> {
+            let reported = (aircraft.latitude()?, aircraft.longitude()?);
+            if !dead_reckoning {
+                let smoothed = aircraft.data().track_estimate
+                    .map_or(reported, |estimate| (estimate.latitude, estimate.longitude));
+                return Some((smoothed, None));
+            }
+            let predicted = aircraft.predicted_position(aircraft.feed_now())?;
+            let stale = predicted.age_secs > stale_position_secs;
+            Some(((predicted.latitude, predicted.longitude), stale.then_some(reported)))
+        };
+
         // Handle scroll events: either for map zoom or for panel scrolling
         let scroll_delta;
         let (saved_smooth_scroll, saved_raw_scroll);
@@ -1899,6 +2370,8 @@ impl AirjediApp {
         let mut detected_hover: Option<HoveredMapItem> = None;
         // Variable to track clicked aircraft
         let mut clicked_aircraft_icao: Option<String> = None;
+        // Map position clicked while drawing a zone
+        let mut clicked_map_position: Option<(f64, f64)> = None;
 
         let map_response = Map::new(
             Some(&mut self.http_tiles),
@@ -2033,6 +2506,29 @@ impl AirjediApp {
                 }
             }
 
+            // Winds aloft from aircraft air data (no API key required)
+            let barb_color = egui::Color32::from_rgba_unmultiplied(
+                120, 200, 255, (weather_opacity.max(0.5) * 255.0) as u8
+            );
+            for barb in &wind_barbs {
+                let pos = to_screen(barb.latitude, barb.longitude);
+                if rect.contains(pos) {
+                    draw_wind_barb(painter, pos, barb, 28.0, barb_color);
+                    if hover_pos.is_some_and(|hover_pos_val| hover_pos_val.distance(pos) <= 14.0) {
+                        detected_hover = Some(HoveredMapItem::WindBarb(barb.clone()));
+                    }
+                    if let Some(temperature) = barb.temperature {
+                        painter.text(
+                            pos + egui::vec2(-4.0, 4.0),
+                            egui::Align2::RIGHT_TOP,
+                            format!("{:.0}°", temperature),
+                            egui::FontId::proportional(10.0),
+                            barb_color,
+                        );
+                    }
+                }
+            }
+
             // Draw aviation overlays
             // Runways (draw first, under airports)
             if show_runways && map_zoom_level >= 9.5 {
@@ -2202,6 +2698,37 @@ impl AirjediApp {
                 }
             }
 
+            // Geofence zones
+            for zone in zones {
+                let [r, g, b] = zone.color;
+                let alpha = if zone.enabled { 255 } else { 90 };
+                ui::geofence_window::draw_zone_shape(
+                    painter,
+                    &zone.shape,
+                    egui::Color32::from_rgba_unmultiplied(r, g, b, alpha),
+                    Some(&zone.name),
+                    &to_screen,
+                );
+            }
+
+            // Zone being drawn: placed points plus a preview to the pointer
+            if let Some(ref drawing) = zone_drawing {
+                let unproject = |pos: egui::Pos2| {
+                    let position = projector.unproject(pos.to_vec2());
+                    (position.y(), position.x())
+                };
+                let hover_lat_lon = hover_pos.filter(|pos| rect.contains(*pos)).map(unproject);
+                let preview_color = egui::Color32::from_rgb(255, 255, 255);
+                if let Some(preview) = drawing.preview(hover_lat_lon) {
+                    ui::geofence_window::draw_zone_shape(painter, &preview, preview_color, None, &to_screen);
+                }
+                for (lat, lon) in drawing.placed() {
+                    painter.circle_filled(to_screen(lat, lon), 4.0, preview_color);
+                }
+
+                clicked_map_position = click_pos.filter(|pos| rect.contains(*pos)).map(unproject);
+            }
+
             // Aircraft trails with LOD
             let trail_detail_level = if map_zoom_level >= 10.0 {
                 1
@@ -2227,7 +2754,7 @@ impl AirjediApp {
                         }
                     }
 
-                    let now = chrono::Utc::now();
+                    let now = aircraft.feed_now();
                     let mut points_drawn = 0;
 
                     for i in (0..data.position_history.len()).step_by(trail_detail_level) {
@@ -2259,7 +2786,7 @@ impl AirjediApp {
 
                             if !time_limited_trails || next_age <= TRAIL_MAX_AGE_SECONDS {
                                 let next_pos = to_screen(next_point.lat, next_point.lon);
-                                let (r, g, b) = Self::altitude_to_color(point.altitude);
+                                let (r, g, b) = map::altitude_to_color(point.altitude);
                                 let trail_color = egui::Color32::from_rgba_unmultiplied(r, g, b, alpha);
                                 painter.line_segment(
                                     [trail_pos, next_pos],
@@ -2279,7 +2806,7 @@ impl AirjediApp {
                         if let Some(last_point) = data.position_history.last() {
                             let last_pos = to_screen(last_point.lat, last_point.lon);
                             let current_pos = to_screen(lat, lon);
-                            let (r, g, b) = Self::altitude_to_color(data.altitude);
+                            let (r, g, b) = map::altitude_to_color(data.altitude);
                             let trail_color = egui::Color32::from_rgb(r, g, b);
                             painter.line_segment(
                                 [last_pos, current_pos],
@@ -2290,10 +2817,48 @@ impl AirjediApp {
                 });
             }
 
+            // Predicted conflicts: a dashed line between each pair, labelled with time to CPA
+            if !conflicts.is_empty() {
+                let positions: std::collections::HashMap<String, egui::Pos2> = aircraft_list.iter()
+                    .filter_map(|aircraft| {
+                        let ((lat, lon), _) = display_position(aircraft)?;
+                        Some((aircraft.icao(), to_screen(lat, lon)))
+                    })
+                    .collect();
+
+                for conflict in conflicts {
+                    let (Some(&pos_a), Some(&pos_b)) = (positions.get(&conflict.icao_a), positions.get(&conflict.icao_b)) else {
+                        continue;
+                    };
+                    if !rect.contains(pos_a) && !rect.contains(pos_b) {
+                        continue;
+                    }
+
+                    painter.extend(egui::Shape::dashed_line(
+                        &[pos_a, pos_b],
+                        egui::Stroke::new(2.0, CONFLICT_COLOR),
+                        8.0,
+                        4.0,
+                    ));
+
+                    let mut label = format!("CPA {:.0}s  {:.1} nm", conflict.approach.time_secs, conflict.approach.lateral_nm);
+                    if let Some(vertical) = conflict.approach.vertical_ft {
+                        label.push_str(&format!(" / {:.0} ft", vertical));
+                    }
+                    let galley = painter.layout_no_wrap(label, egui::FontId::monospace(10.0), CONFLICT_COLOR);
+                    let label_rect = egui::Rect::from_center_size(
+                        pos_a.lerp(pos_b, 0.5),
+                        galley.size() + egui::vec2(8.0, 4.0),
+                    );
+                    painter.rect_filled(label_rect, 3.0, egui::Color32::from_rgba_unmultiplied(20, 20, 20, 200));
+                    painter.galley(label_rect.min + egui::vec2(4.0, 2.0), galley, CONFLICT_COLOR);
+                }
+            }
+
             // Aircraft rendering - Two-pass approach for proper layering:
             // Pass 1: Draw label bubbles (callsign and altitude) first
             for aircraft in &aircraft_list {
-                if let (Some(lat), Some(lon)) = (aircraft.latitude(), aircraft.longitude()) {
+                if let Some(((lat, lon), _)) = display_position(aircraft) {
                     let pos = to_screen(lat, lon);
 
                     if rect.contains(pos) {
@@ -2364,7 +2929,7 @@ impl AirjediApp {
 
             // Pass 2: Draw aircraft icons on top of labels
             for aircraft in &aircraft_list {
-                if let (Some(lat), Some(lon)) = (aircraft.latitude(), aircraft.longitude()) {
+                if let Some(((lat, lon), stale_from)) = display_position(aircraft) {
                     let pos = to_screen(lat, lon);
 
                     if rect.contains(pos) {
@@ -2377,8 +2942,37 @@ impl AirjediApp {
                             (egui::Color32::WHITE, 5.75)  // Original colors for unselected
                         };
 
+                        // Aircraft signalling an emergency flash red with a pulsing ring
+                        let emergency = aircraft.emergency().is_some();
+                        let color = if emergency && emergency_flash_on {
+                            EMERGENCY_COLOR
+                        } else {
+                            color
+                        };
+
                         let track = aircraft.track().unwrap_or(0.0) as f32;
-                        Self::draw_aircraft_icon(airplane_texture, &painter, pos, track, color, size);
+                        if let Some((last_lat, last_lon)) = stale_from {
+                            // Stale positions: a hollow icon at the prediction, with a dashed
+                            // leader back to where the aircraft was last reported
+                            painter.extend(egui::Shape::dashed_line(
+                                &[to_screen(last_lat, last_lon), pos],
+                                egui::Stroke::new(1.5, color.gamma_multiply(0.6)),
+                                4.0,
+                                3.0,
+                            ));
+                            Self::draw_aircraft_outline(&painter, pos, track, color, size);
+                        } else {
+                            Self::draw_aircraft_icon(airplane_texture, &painter, pos, track, color, size);
+                        }
+
+                        if emergency {
+                            let ring_alpha = if emergency_flash_on { 1.0 } else { 0.35 };
+                            painter.circle_stroke(
+                                pos,
+                                size * 2.6,
+                                egui::Stroke::new(2.5, EMERGENCY_COLOR.gamma_multiply(ring_alpha)),
+                            );
+                        }
 
                         if is_selected {
                             painter.circle_stroke(
@@ -2388,6 +2982,16 @@ impl AirjediApp {
                             );
                         }
 
+                        // Aircraft that recently hit a zone rule get a ring in the zone's color
+                        if let Some(hit) = geofence_highlights.get(&icao).filter(|hit| hit.is_recent(aircraft.feed_now())) {
+                            let [r, g, b] = hit.zone_color;
+                            painter.circle_stroke(
+                                pos,
+                                size * 2.2,
+                                egui::Stroke::new(2.0, egui::Color32::from_rgb(r, g, b)),
+                            );
+                        }
+
                         // Check for hover on aircraft
                         if let Some(hover_pos_val) = hover_pos {
                             let distance = hover_pos_val.distance(pos);
@@ -2409,18 +3013,47 @@ impl AirjediApp {
                 }
             }
 
-            (detected_hover, clicked_aircraft_icao)
+            (detected_hover, clicked_aircraft_icao, clicked_map_position)
         });
 
         // Restore weather_tiles after Map closure
         self.weather_tiles = weather_tiles;
 
         // Update hover state and handle clicks from the map
-        let (hover_result, click_result) = map_response.inner;
+        let (hover_result, click_result, clicked_map_position) = map_response.inner;
         self.hovered_map_item = hover_result;
 
-        // Handle aircraft selection from map click; clear selection if map was clicked but no aircraft
-        if let Some(clicked_icao) = click_result {
+        // Right-click menu exporting the aircraft under the pointer, or every track
+        if map_response.response.secondary_clicked() {
+            self.export_menu_aircraft = match self.hovered_map_item {
+                Some(HoveredMapItem::Aircraft(ref aircraft)) => Some(aircraft.icao()),
+                _ => None,
+            };
+        }
+        map_response.response.context_menu(|ui| {
+            if let Some(icao) = self.export_menu_aircraft.clone() {
+                ui.menu_button(format!("Export {} Track", icao.to_uppercase()), |ui| {
+                    self.export_format_buttons(ui, Some(&icao));
+                });
+            }
+            ui.menu_button("Export All Tracks", |ui| {
+                self.export_format_buttons(ui, None);
+            });
+        });
+
+        // While drawing a zone, clicks place points instead of selecting aircraft
+        // (ignoring clicks on windows above the map)
+        if self.geofence_window.drawing().is_some() {
+            let clicked_map_position = clicked_map_position.filter(|_| map_response.response.hovered());
+            if self.geofence_window.handle_drawing_input(
+                ui.ctx(),
+                clicked_map_position,
+                &mut self.config.geofences,
+                &self.geofences,
+            ) {
+                self.save_config();
+            }
+        } else if let Some(clicked_icao) = click_result {
             self.selected_aircraft = Some(clicked_icao);
         } else {
             // Detect a primary click inside the map area that didn't hit an aircraft
@@ -2459,6 +3092,7 @@ impl AirjediApp {
                                     HoveredMapItem::Airport(airport) => airport.render_popup(ui, self.receiver_lat, self.receiver_lon, &self.aircraft_types),
                                     HoveredMapItem::Navaid(navaid) => navaid.render_popup(ui, self.receiver_lat, self.receiver_lon, &self.aircraft_types),
                                     HoveredMapItem::Aircraft(aircraft) => aircraft.render_popup(ui, self.receiver_lat, self.receiver_lon, &self.aircraft_types),
+                                    HoveredMapItem::WindBarb(barb) => barb.render_popup(ui, self.receiver_lat, self.receiver_lon, &self.aircraft_types),
                                 }
                             });
                     });
@@ -2466,14 +3100,18 @@ impl AirjediApp {
         }
 
         // Instructions text at the top
+        let (instructions, instructions_color) = match self.geofence_window.drawing() {
+            Some(drawing) => (drawing.instructions(), egui::Color32::from_rgb(255, 200, 100)),
+            None => ("Drag to pan | Scroll/pinch to zoom", egui::Color32::from_rgb(200, 200, 200)),
+        };
         egui::Area::new("map_instructions".into())
             .fixed_pos(egui::pos2(10.0, 35.0))
             .order(egui::Order::Foreground)
             .show(ui.ctx(), |ui| {
                 ui.label(
-                    egui::RichText::new("Drag to pan | Scroll/pinch to zoom")
+                    egui::RichText::new(instructions)
                         .size(12.0)
-                        .color(egui::Color32::from_rgb(200, 200, 200))
+                        .color(instructions_color)
                 );
             });
 
@@ -2538,6 +3176,10 @@ impl AirjediApp {
                     });
             });
 
+        // Replay timelines along the bottom of the map
+        let replays = self.connection_manager.lock().unwrap().replays();
+        ui::replay_timeline::show(ui.ctx(), ui.max_rect(), &replays);
+
         // Tile error/loading display at top-center
         if let Some(ref error_msg) = self.tile_error {
             let is_error = error_msg.contains("Failed");
@@ -2793,9 +3435,19 @@ impl eframe::App for AirjediApp {
 
                     // Add all configured servers to the ConnectionManager
                     let mut connection_manager = self.connection_manager.lock().unwrap();
+                    connection_manager.set_time_limited_trails(self.time_limited_trails);
+                    connection_manager.set_smooth_tracks(self.config.smooth_tracks);
                     for server in &self.config.servers {
                         connection_manager.add_server(server.clone());
                     }
+                    connection_manager.set_sbs_output(&self.config.sbs_output);
+                    if let Some(recording) = self.record_on_start.take() {
+                        connection_manager.start_recording_all(&recording);
+                        self.system_status.lock().unwrap().add_diagnostic(
+                            DiagnosticLevel::Info,
+                            format!("Recording feeds to {}", recording.directory.display())
+                        );
+                    }
 
                     self.startup_state = StartupState::LoadingAviationData;
                 }
@@ -2888,13 +3540,12 @@ impl eframe::App for AirjediApp {
         // Update system status with current aircraft stats
         {
             let connection_manager = self.connection_manager.lock().unwrap();
-            let aircraft_list = connection_manager.get_all_aircraft_merged();  // Cheap Arc clones from all servers
+            let aircraft_list = connection_manager.get_all_aircraft_unique();  // Cheap Arc clones, each aircraft once
             let total = aircraft_list.len();
 
-            // Capture time once to avoid re-evaluation in the filter closure
-            let now = chrono::Utc::now();
+            // Replayed aircraft are aged on their replay's clock
             let active = aircraft_list.iter().filter(|a| {
-                (now - a.last_seen()).num_seconds() < 60
+                (a.feed_now() - a.last_seen()).num_seconds() < 60
             }).count();
 
             // Update per-server aircraft counts
@@ -2902,6 +3553,77 @@ impl eframe::App for AirjediApp {
 
             self.system_status.lock().unwrap().update_aircraft_stats(total, active);
             self.system_status.lock().unwrap().update_uptime();
+
+            // Check aircraft against the watchlist (skipped while the database is busy loading)
+            if self.last_watchlist_check.elapsed() >= WATCHLIST_CHECK_INTERVAL {
+                if let Ok(db) = self.aircraft_db.try_lock() {
+                    self.watchlist.evaluate(&aircraft_list, &db, self.receiver_lat, self.receiver_lon);
+                    self.last_watchlist_check = std::time::Instant::now();
+                }
+            }
+
+            // Predict conflicts between aircraft, recording each new one
+            if self.last_conflict_check.elapsed() >= CONFLICT_CHECK_INTERVAL {
+                self.last_conflict_check = std::time::Instant::now();
+                let conflicts = if self.config.show_conflicts {
+                    // Aircraft are only compared on the same clock: live servers together, each
+                    // replay on its own
+                    let mut feeds = std::collections::HashMap::new();
+                    for aircraft in connection_manager.get_all_aircraft_unique() {
+                        let clock = aircraft.is_replayed().then(|| aircraft.source_server_id());
+                        feeds.entry(clock)
+                            .or_insert_with(|| (aircraft.feed_now(), Vec::new()))
+                            .1
+                            .push(aircraft);
+                    }
+                    let mut conflicts: Vec<_> = feeds.into_values()
+                        .flat_map(|(now, aircraft)| {
+                            adsb_client::analysis::detect_conflicts(aircraft.iter().map(Aircraft::data), now, &self.config.conflict_detection)
+                        })
+                        .collect();
+                    conflicts.sort_by(|a, b| a.time_to_loss_secs.total_cmp(&b.time_to_loss_secs));
+                    conflicts
+                } else {
+                    Vec::new()
+                };
+
+                let mut status = self.system_status.lock().unwrap();
+                for conflict in self.conflict_monitor.update(conflicts) {
+                    let vertical = conflict.approach.vertical_ft
+                        .map(|vertical| format!(", {:.0} ft", vertical))
+                        .unwrap_or_default();
+                    let message = format!(
+                        "Conflict predicted: {} / {} closest in {:.0}s ({:.1} nm{})",
+                        conflict.icao_a,
+                        conflict.icao_b,
+                        conflict.approach.time_secs,
+                        conflict.approach.lateral_nm,
+                        vertical,
+                    );
+                    log::warn!("{}", message);
+                    status.add_diagnostic(DiagnosticLevel::Warning, message);
+                }
+            }
+        }
+
+        // Raise notifications for new watchlist matches
+        while let Ok(event) = self.watchlist_events.try_recv() {
+            if let WatchEvent::Matched(watch_match) = event {
+                let mut message = format!("{} ({}) — {}", watch_match.display_name(), watch_match.icao, watch_match.rule);
+                if let Some(range) = watch_match.range_nm {
+                    message.push_str(&format!(", {:.1} nm", range));
+                }
+                self.system_status.lock().unwrap().add_diagnostic(
+                    DiagnosticLevel::Info,
+                    format!("Watchlist match [{}]: {}", watch_match.rule_name, message),
+                );
+                self.notifications.push(
+                    format!("★ Watchlist: {}", watch_match.rule_name),
+                    message,
+                    WATCHLIST_COLOR,
+                    Some(watch_match.icao),
+                );
+            }
         }
 
         // Request continuous repaints for smooth interaction
@@ -2930,6 +3652,16 @@ impl eframe::App for AirjediApp {
                     ui.add_enabled(false, egui::Button::new("Save"));
                     ui.add_enabled(false, egui::Button::new("Save As..."));
                     ui.separator();
+                    let selected_aircraft = self.selected_aircraft.clone();
+                    ui.add_enabled_ui(selected_aircraft.is_some(), |ui| {
+                        ui.menu_button("Export Selected Track", |ui| {
+                            self.export_format_buttons(ui, selected_aircraft.as_deref());
+                        });
+                    });
+                    ui.menu_button("Export All Tracks", |ui| {
+                        self.export_format_buttons(ui, None);
+                    });
+                    ui.separator();
                     if ui.add(egui::Button::new("Settings...")
                         .shortcut_text(ui.ctx().format_shortcut(&SETTINGS_SHORTCUT)))
                         .clicked()
@@ -2949,12 +3681,20 @@ impl eframe::App for AirjediApp {
                     if ui.button("Filters...").clicked() {
                         self.show_filters_window = true;
                     }
+                    if ui.button("Geofences...").clicked() {
+                        self.geofence_window.open();
+                    }
                     if ui.button("Waterfall...").clicked() {
                         // Create new waterfall window if not already open
                         if self.waterfall_window.is_none() {
                             self.waterfall_window = Some(ui::WaterfallWindow::new("waterfall_1"));
                         }
                     }
+                    if ui.button("Flight History...").clicked() {
+                        if self.history_window.is_none() {
+                            self.history_window = Some(ui::HistoryWindow::new(self.config.history.database.clone()));
+                        }
+                    }
                     ui.separator();
                     // Aircraft List with checkmark and keyboard shortcut
                     let aircraft_list_text = if self.aircraft_list_expanded {
@@ -2978,6 +3718,14 @@ impl eframe::App for AirjediApp {
             });
         });
 
+        // Emergency alerts stay on screen until dismissed
+        if self.startup_state == StartupState::Complete {
+            let auto_select = self.config.auto_select_emergencies;
+            if let Some(icao) = self.emergency_banner.render(ctx, &self.system_status, auto_select) {
+                self.selected_aircraft = Some(icao);
+            }
+        }
+
         // Map takes full width (or loading screen during startup)
         egui::CentralPanel::default()
             .frame(egui::Frame::NONE)
@@ -3166,10 +3914,11 @@ impl eframe::App for AirjediApp {
             });
 
         // Overlay controls window (only shown when opened from View menu)
+        let mut open = self.show_map_overlays_window;
         egui::Window::new("Map Overlays")
             .resizable(false)
             .collapsible(false)
-            .open(&mut self.show_map_overlays_window)
+            .open(&mut open)
             .show(ctx, |ui| {
                 // Check if data is still loading
                 let is_loading = *self.aviation_data_loading.lock().unwrap();
@@ -3239,6 +3988,31 @@ impl eframe::App for AirjediApp {
                             self.connection_manager.lock().unwrap().set_time_limited_trails(self.time_limited_trails);
                         }
                     });
+                    ui.horizontal(|ui| {
+                        ui.label("Dead Reckoning:");
+                        if ui.checkbox(&mut self.config.dead_reckoning, "").changed() {
+                            settings_changed = true;
+                        }
+                    });
+                    if self.config.dead_reckoning {
+                        ui.horizontal(|ui| {
+                            ui.add_space(10.0);
+                            ui.label("Predicted after:");
+                            if ui.add(egui::DragValue::new(&mut self.config.stale_position_secs)
+                                .range(1.0..=60.0)
+                                .speed(0.5)
+                                .suffix(" s")).changed() {
+                                settings_changed = true;
+                            }
+                        });
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("Smooth Tracks:");
+                        if ui.checkbox(&mut self.config.smooth_tracks, "").changed() {
+                            settings_changed = true;
+                            self.connection_manager.lock().unwrap().set_smooth_tracks(self.config.smooth_tracks);
+                        }
+                    });
 
                     ui.add_space(8.0);
                     ui.separator();
@@ -3283,12 +4057,46 @@ impl eframe::App for AirjediApp {
                         });
 
                         if weather_changed {
-                            if let Err(e) = self.config.save() {
-                                eprintln!("Failed to save config: {}", e);
-                            }
+                            self.save_config();
+                        }
+                    });
+
+                    // Winds aloft come from aircraft air data and need no API key
+                    let mut winds_aloft_changed = false;
+                    ui.horizontal(|ui| {
+                        ui.label("Winds aloft:");
+                        if ui.checkbox(&mut self.config.show_winds_aloft, "").changed() {
+                            winds_aloft_changed = true;
                         }
                     });
 
+                    if self.config.show_winds_aloft {
+                        ui.horizontal(|ui| {
+                            ui.label("Band:");
+                            let mut band = self.config.winds_aloft_band;
+                            egui::ComboBox::from_id_salt("winds_aloft_band")
+                                .selected_text(band.label())
+                                .width(110.0)
+                                .show_ui(ui, |ui| {
+                                    for option in AltitudeBand::ALL {
+                                        ui.selectable_value(&mut band, option, option.label());
+                                    }
+                                });
+
+                            if band != self.config.winds_aloft_band {
+                                self.config.winds_aloft_band = band;
+                                winds_aloft_changed = true;
+                            }
+                        });
+                        ui.label(egui::RichText::new(format!("{} aircraft reports", self.winds_aloft.sample_count()))
+                            .color(egui::Color32::GRAY)
+                            .size(9.0));
+                    }
+
+                    if winds_aloft_changed {
+                        self.save_config();
+                    }
+
                     // Auto-save settings if any changed
                     if settings_changed {
                         self.config.show_airports = self.show_airports;
@@ -3301,9 +4109,7 @@ impl eframe::App for AirjediApp {
                             AirportFilter::MajorOnly => "MajorOnly".to_string(),
                         };
 
-                        if let Err(e) = self.config.save() {
-                            eprintln!("Failed to save config: {}", e);
-                        }
+                        self.save_config();
                     }
                     ui.separator();
 
@@ -3324,12 +4130,14 @@ impl eframe::App for AirjediApp {
                     }
                 }
             });
+        self.show_map_overlays_window = open;
 
         // Settings window (only shown when opened from File menu or Cmd+,)
+        let mut open = self.show_settings_window;
         egui::Window::new("Settings")
             .resizable(false)
             .collapsible(false)
-            .open(&mut self.show_settings_window)
+            .open(&mut open)
             .show(ctx, |ui| {
                 ui.heading(egui::RichText::new("Server Configuration")
                     .size(12.0)
@@ -3390,28 +4198,81 @@ impl eframe::App for AirjediApp {
                                     }
                                 });
 
-                                // Server address editor
-                                ui.horizontal(|ui| {
-                                    ui.label("Address:");
-                                    let (_, address) = self.server_edit_state.get_mut(&server.id).unwrap();
-                                    if ui.add(egui::TextEdit::singleline(address)
-                                        .hint_text("host:port")
-                                        .desired_width(120.0)).changed() {
-                                        server.address = address.clone();
-                                        config_changed = true;
-
-                                        // Update SystemStatus immediately for live status pane update
-                                        self.system_status.lock().unwrap().update_server_info(
-                                            &server.id,
-                                            server.name.clone(),
-                                            server.address.clone()
-                                        );
-
-                                        // Hot-reload address via ConnectionManager
-                                        self.connection_manager.lock().unwrap()
-                                            .update_server(&server.id, server.clone());
-                                    }
-                                });
+                                // Replayed file, picked instead of an address and protocol
+                                if let Some(path) = server.replay.clone() {
+                                    ui.horizontal(|ui| {
+                                        ui.label("File:");
+                                        let file_name = path.file_name()
+                                            .map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned());
+                                        ui.label(egui::RichText::new(file_name)
+                                            .color(egui::Color32::from_rgb(200, 200, 200)))
+                                            .on_hover_text(path.display().to_string());
+
+                                        if ui.button("Browse...").clicked() {
+                                            if let Some(path) = pick_replay_file() {
+                                                server.address = path.display().to_string();
+                                                server.replay = Some(path);
+                                                config_changed = true;
+
+                                                // Restart the replay with the new file via ConnectionManager
+                                                self.connection_manager.lock().unwrap()
+                                                    .update_server(&server.id, server.clone());
+                                            }
+                                        }
+                                    });
+                                } else {
+                                    // Server address editor
+                                    ui.horizontal(|ui| {
+                                        ui.label("Address:");
+                                        let (_, address) = self.server_edit_state.get_mut(&server.id).unwrap();
+                                        if ui.add(egui::TextEdit::singleline(address)
+                                            .hint_text("host:port")
+                                            .desired_width(120.0)).changed() {
+                                            server.address = address.clone();
+                                            config_changed = true;
+
+                                            // Update SystemStatus immediately for live status pane update
+                                            self.system_status.lock().unwrap().update_server_info(
+                                                &server.id,
+                                                server.name.clone(),
+                                                server.address.clone()
+                                            );
+
+                                            // Hot-reload address via ConnectionManager
+                                            self.connection_manager.lock().unwrap()
+                                                .update_server(&server.id, server.clone());
+                                        }
+                                    });
+
+                                    // Feed protocol selector
+                                    ui.horizontal(|ui| {
+                                        ui.label("Protocol:");
+                                        let mut protocol = server.protocol;
+                                        egui::ComboBox::from_id_salt(("server_protocol", &server.id))
+                                            .selected_text(protocol.label())
+                                            .width(120.0)
+                                            .show_ui(ui, |ui| {
+                                                for option in adsb_client::ProtocolType::ALL {
+                                                    let hint = if option == adsb_client::ProtocolType::Auto {
+                                                        "Detect SBS, BEAST, AVR or JSON from the first bytes".to_string()
+                                                    } else {
+                                                        format!("Usually port {}", option.default_port())
+                                                    };
+                                                    ui.selectable_value(&mut protocol, option, option.label())
+                                                        .on_hover_text(hint);
+                                                }
+                                            });
+
+                                        if protocol != server.protocol {
+                                            server.protocol = protocol;
+                                            config_changed = true;
+
+                                            // Reconnect with the new parser via ConnectionManager
+                                            self.connection_manager.lock().unwrap()
+                                                .update_server(&server.id, server.clone());
+                                        }
+                                    });
+                                }
 
                                 // Show connection stats if available
                                 if let Some(server_status) = server_statuses.get(&server.id) {
@@ -3422,12 +4283,34 @@ impl eframe::App for AirjediApp {
                                         .size(8.0)
                                         .color(egui::Color32::from_rgb(120, 120, 120)));
 
+                                    if let Some(format) = server_status.detected_format {
+                                        ui.label(egui::RichText::new(format!("Detected: {}", format.label()))
+                                            .size(8.0)
+                                            .color(egui::Color32::from_rgb(120, 120, 120)));
+                                    }
+
+                                    if server_status.frames_corrected > 0 || server_status.frames_dropped > 0 {
+                                        ui.label(egui::RichText::new(
+                                            format!("CRC corrected: {} | Dropped: {}",
+                                                server_status.frames_corrected,
+                                                server_status.frames_dropped))
+                                            .size(8.0)
+                                            .color(egui::Color32::from_rgb(120, 120, 120)));
+                                    }
+
                                     if let Some(ref error) = server_status.last_error {
                                         ui.label(egui::RichText::new(format!("Error: {}", error))
                                             .size(8.0)
                                             .color(egui::Color32::from_rgb(255, 100, 100)));
                                     }
                                 }
+
+                                // Recording progress
+                                let recording_status = self.connection_manager.lock().unwrap()
+                                    .recording_status(&server.id);
+                                if let Some(recording) = recording_status {
+                                    ui::recording::progress_label(ui, &recording);
+                                }
                             });
 
                             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -3436,6 +4319,19 @@ impl eframe::App for AirjediApp {
                                     servers_to_remove.push(server.id.clone());
                                 }
 
+                                // Record toggle, for live feeds
+                                if server.replay.is_none() {
+                                    let recording = self.connection_manager.lock().unwrap().is_recording(&server.id);
+                                    if ui::recording::record_button(ui, recording) {
+                                        let mut connection_manager = self.connection_manager.lock().unwrap();
+                                        if recording {
+                                            connection_manager.stop_recording(&server.id);
+                                        } else {
+                                            connection_manager.start_recording(&server.id, self.config.recording.clone());
+                                        }
+                                    }
+                                }
+
                                 // Enabled checkbox
                                 let mut enabled = server.enabled;
                                 if ui.checkbox(&mut enabled, "Enabled").changed() {
@@ -3486,11 +4382,121 @@ impl eframe::App for AirjediApp {
                     config_changed = true;
                 }
 
+                // Add a virtual server replaying a recording
+                if ui.button("▶ Add Replay...")
+                    .on_hover_text("Replay a capture, SBS log or BEAST log as a server")
+                    .clicked()
+                {
+                    if let Some(path) = pick_replay_file() {
+                        let new_server = config::ServerConfig::new_replay(path);
+
+                        self.server_edit_state.insert(
+                            new_server.id.clone(),
+                            (new_server.name.clone(), new_server.address.clone())
+                        );
+
+                        self.connection_manager.lock().unwrap().add_server(new_server.clone());
+                        self.config.add_server(new_server);
+                        config_changed = true;
+                    }
+                }
+
                 // Auto-save configuration when changed
                 if config_changed {
-                    if let Err(e) = self.config.save() {
-                        eprintln!("Failed to save config: {}", e);
+                    self.save_config();
+                }
+
+                ui.add_space(8.0);
+                ui.separator();
+                ui.add_space(8.0);
+
+                // Recording section
+                if ui::recording::settings_section(ui, &mut self.config.recording) {
+                    self.save_config();
+                }
+
+                ui.add_space(8.0);
+                ui.separator();
+                ui.add_space(8.0);
+
+                // Flight history section
+                ui.heading(egui::RichText::new("Flight History")
+                    .size(12.0)
+                    .strong());
+
+                ui.add_space(4.0);
+
+                ui.label(egui::RichText::new("Every aircraft seen is logged to a database, browsable from View > Flight History. Changes apply after a restart.")
+                    .size(9.0)
+                    .color(egui::Color32::from_rgb(150, 150, 150)));
+
+                let mut history_changed = false;
+
+                if ui.checkbox(&mut self.config.history.enabled, "Log flight history").changed() {
+                    history_changed = true;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Database:");
+                    ui.label(egui::RichText::new(self.config.history.database.display().to_string())
+                        .color(egui::Color32::from_rgb(200, 200, 200)));
+
+                    if ui.button("Browse...").clicked() {
+                        let mut dialog = rfd::FileDialog::new()
+                            .add_filter("SQLite database", &["sqlite", "db"]);
+                        if let Some(directory) = self.config.history.database.parent() {
+                            dialog = dialog.set_directory(directory);
+                        }
+                        if let Some(path) = dialog.save_file() {
+                            self.config.history.database = path;
+                            history_changed = true;
+                        }
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    let mut retain = self.config.history.retention_days.is_some();
+                    if ui.checkbox(&mut retain, "Delete sessions older than").changed() {
+                        self.config.history.retention_days = retain.then_some(90);
+                        history_changed = true;
                     }
+                    if let Some(ref mut days) = self.config.history.retention_days {
+                        if ui.add(egui::DragValue::new(days).range(1..=3650).suffix(" days")).changed() {
+                            history_changed = true;
+                        }
+                    }
+                });
+
+                if history_changed {
+                    self.save_config();
+                }
+
+                ui.add_space(8.0);
+                ui.separator();
+                ui.add_space(8.0);
+
+                // SBS output section
+                let sbs_status = self.connection_manager.lock().unwrap().sbs_output_status();
+                let sbs_change = ui::output_settings::sbs_output_section(ui, &mut self.config.sbs_output, sbs_status.as_ref());
+                if sbs_change.apply {
+                    self.connection_manager.lock().unwrap().set_sbs_output(&self.config.sbs_output);
+                }
+                if sbs_change.changed {
+                    self.save_config();
+                }
+
+                ui.add_space(8.0);
+                ui.separator();
+                ui.add_space(8.0);
+
+                // HTTP API section
+                let web_status = self.web_server.as_ref().map(web::WebServer::status);
+                let web_change = ui::output_settings::http_api_section(ui, &mut self.config.web, web_status.as_ref());
+                if web_change.apply {
+                    self.update_web_server();
+                }
+                if web_change.changed {
+                    self.save_config();
                 }
 
                 ui.add_space(8.0);
@@ -3578,13 +4584,7 @@ impl eframe::App for AirjediApp {
                                 self.connection_manager.lock().unwrap().set_center(lat, lon);
 
                                 // Save config
-                                if let Err(e) = self.config.save() {
-                                    eprintln!("Failed to save config: {}", e);
-                                    self.system_status.lock().unwrap().add_diagnostic(
-                                        DiagnosticLevel::Error,
-                                        format!("Failed to save GPS override: {}", e)
-                                    );
-                                } else {
+                                if self.save_config() {
                                     self.system_status.lock().unwrap().add_diagnostic(
                                         DiagnosticLevel::Info,
                                         format!("GPS override set to {:.4}°, {:.4}°", lat, lon)
@@ -3623,9 +4623,7 @@ impl eframe::App for AirjediApp {
                         self.config.override_gps_longitude = None;
 
                         // Save config
-                        if let Err(e) = self.config.save() {
-                            eprintln!("Failed to save config: {}", e);
-                        } else {
+                        if self.save_config() {
                             self.system_status.lock().unwrap().add_diagnostic(
                                 DiagnosticLevel::Info,
                                 "GPS override cleared - will use automatic detection on next restart".to_string()
@@ -3664,6 +4662,88 @@ impl eframe::App for AirjediApp {
                 ui.separator();
                 ui.add_space(8.0);
 
+                // Emergency alert section
+                ui.heading(egui::RichText::new("Emergency Alerts")
+                    .size(12.0)
+                    .strong());
+
+                ui.add_space(8.0);
+
+                if ui.checkbox(&mut self.config.auto_select_emergencies,
+                    "Auto-select aircraft that declare an emergency")
+                    .on_hover_text("Squawk 7500/7600/7700, ADS-B emergency status or emergency flag")
+                    .changed()
+                {
+                    self.save_config();
+                }
+
+                ui.add_space(8.0);
+                ui.separator();
+                ui.add_space(8.0);
+
+                // Watchlist section
+                if let Some(watchlist) = self.watchlist_settings.render(ui, &self.config.watchlist) {
+                    self.watchlist.configure(&watchlist);
+                    self.config.watchlist = watchlist;
+                    self.save_config();
+                }
+
+                ui.add_space(8.0);
+                ui.separator();
+                ui.add_space(8.0);
+
+                // Conflict detection section
+                ui.heading(egui::RichText::new("Conflict Detection")
+                    .size(12.0)
+                    .strong());
+
+                ui.add_space(8.0);
+
+                let mut conflicts_changed = ui.checkbox(&mut self.config.show_conflicts,
+                    "Predict conflicts and draw them on the map")
+                    .on_hover_text("Extrapolates position, track, ground speed and vertical rate")
+                    .changed();
+
+                ui.add_enabled_ui(self.config.show_conflicts, |ui| {
+                    let detection = &mut self.config.conflict_detection;
+                    egui::Grid::new("conflict_detection")
+                        .num_columns(2)
+                        .spacing([8.0, 4.0])
+                        .show(ui, |ui| {
+                            ui.label("Lateral separation:");
+                            conflicts_changed |= ui.add(egui::DragValue::new(&mut detection.lateral_nm)
+                                .range(0.1..=20.0)
+                                .speed(0.1)
+                                .suffix(" nm"))
+                                .changed();
+                            ui.end_row();
+
+                            ui.label("Vertical separation:");
+                            conflicts_changed |= ui.add(egui::DragValue::new(&mut detection.vertical_ft)
+                                .range(100.0..=5000.0)
+                                .speed(50.0)
+                                .suffix(" ft"))
+                                .changed();
+                            ui.end_row();
+
+                            ui.label("Look-ahead:");
+                            conflicts_changed |= ui.add(egui::DragValue::new(&mut detection.look_ahead_secs)
+                                .range(10.0..=600.0)
+                                .speed(5.0)
+                                .suffix(" s"))
+                                .changed();
+                            ui.end_row();
+                        });
+                });
+
+                if conflicts_changed {
+                    self.save_config();
+                }
+
+                ui.add_space(8.0);
+                ui.separator();
+                ui.add_space(8.0);
+
                 // Video Streaming Test section
                 ui.heading(egui::RichText::new("Video Streaming Test")
                     .size(12.0)
@@ -3697,9 +4777,7 @@ impl eframe::App for AirjediApp {
 
                 // Auto-save when URL changes
                 if url_changed {
-                    if let Err(e) = self.config.save() {
-                        eprintln!("Failed to save config: {}", e);
-                    }
+                    self.save_config();
                 }
 
                 ui.add_space(4.0);
@@ -3812,9 +4890,7 @@ impl eframe::App for AirjediApp {
                         );
                         self.weather_tiles.set_api_key(resolved_key, ctx);
 
-                        if let Err(e) = self.config.save() {
-                            eprintln!("Failed to save config: {}", e);
-                        }
+                        self.save_config();
                     }
 
                     // Help button linking to OpenWeatherMap signup
@@ -3833,9 +4909,7 @@ impl eframe::App for AirjediApp {
                             .show_value(true)
                             .custom_formatter(|v, _| format!("{:.0}%", v * 100.0))
                     ).changed() {
-                        if let Err(e) = self.config.save() {
-                            eprintln!("Failed to save config: {}", e);
-                        }
+                        self.save_config();
                     }
                 });
 
@@ -3869,9 +4943,7 @@ impl eframe::App for AirjediApp {
                     });
 
                     if precipitation_changed || clouds_changed || wind_changed {
-                        if let Err(e) = self.config.save() {
-                            eprintln!("Failed to save config: {}", e);
-                        }
+                        self.save_config();
                     }
                 });
 
@@ -3880,7 +4952,20 @@ impl eframe::App for AirjediApp {
                         .size(8.0)
                         .color(egui::Color32::from_rgb(150, 150, 150)));
                 }
+
+                ui.add_space(8.0);
+
+                // Winds aloft from aircraft (always available)
+                ui.horizontal(|ui| {
+                    if ui.checkbox(
+                        &mut self.config.show_winds_aloft,
+                        "Winds Aloft (from aircraft reports)"
+                    ).changed() {
+                        self.save_config();
+                    }
+                });
             });
+        self.show_settings_window = open;
 
         // Filters window (only shown when opened from View menu)
         egui::Window::new("Filters")
@@ -4004,12 +5089,26 @@ impl eframe::App for AirjediApp {
                 }
             });
 
+        // Geofences window (only shown when opened from View menu or after drawing a zone)
+        let geofence_response = self.geofence_window.render(ctx, &mut self.config.geofences, &self.geofences);
+        if geofence_response.changed {
+            self.save_config();
+        }
+        if let Some(icao) = geofence_response.select_icao {
+            self.selected_aircraft = Some(icao);
+        }
+
         // Render status pane (bottom-left overlay)
         {
             let status = self.system_status.lock().unwrap();
             self.status_pane.render(ctx, &status);
         }
 
+        // Render watchlist notifications; clicking one selects the aircraft
+        if let Some(icao) = self.notifications.render(ctx) {
+            self.selected_aircraft = Some(icao);
+        }
+
         // Render video player windows
         self.video_manager.render(ctx);
 
@@ -4022,6 +5121,14 @@ impl eframe::App for AirjediApp {
             }
         }
 
+        // Render flight history window
+        if let Some(history_window) = &mut self.history_window {
+            history_window.render(ctx);
+            if !history_window.is_open() {
+                self.history_window = None;
+            }
+        }
+
         // Update frame time performance metrics
         let frame_duration = frame_start.elapsed().as_secs_f64() * 1000.0;
         self.system_status.lock().unwrap().update_performance(frame_duration);
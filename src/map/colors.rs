@@ -0,0 +1,55 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Colors shared by the map and exported tracks.
+
+/// Convert altitude to a continuous color gradient
+/// Low altitude (cyan) -> High altitude (purple) with smooth blending
+pub fn altitude_to_color(altitude_ft: Option<i32>) -> (u8, u8, u8) {
+    let alt = altitude_ft.unwrap_or(0) as f32;
+
+    // Clamp altitude to 0-45000 range for gradient calculation
+    let clamped_alt = alt.clamp(0.0, 45000.0);
+
+    // Define gradient stops with colors (in feet)
+    // Each stop is (altitude, (r, g, b))
+    let stops = [
+        (0.0, (0.0, 200.0, 200.0)),        // Cyan
+        (10000.0, (50.0, 150.0, 200.0)),   // Teal
+        (20000.0, (150.0, 200.0, 0.0)),    // Yellow
+        (30000.0, (255.0, 150.0, 0.0)),    // Orange
+        (40000.0, (255.0, 50.0, 150.0)),   // Red/Magenta
+        (45000.0, (150.0, 50.0, 255.0)),   // Purple
+    ];
+
+    // Find which two stops we're between
+    for i in 0..stops.len() - 1 {
+        let (alt1, color1) = stops[i];
+        let (alt2, color2) = stops[i + 1];
+
+        if clamped_alt >= alt1 && clamped_alt <= alt2 {
+            // Linear interpolation between the two colors
+            let t = (clamped_alt - alt1) / (alt2 - alt1);
+
+            let r = color1.0 + (color2.0 - color1.0) * t;
+            let g = color1.1 + (color2.1 - color1.1) * t;
+            let b = color1.2 + (color2.2 - color1.2) * t;
+
+            return (r as u8, g as u8, b as u8);
+        }
+    }
+
+    // Fallback to highest color if somehow we didn't match
+    (150, 50, 255)
+}
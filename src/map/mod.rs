@@ -4,7 +4,9 @@
 
 pub mod tiles;
 pub mod carto;
+pub mod colors;
 
 pub use tiles::WebMercator;
 pub use carto::CartoTileSource;
+pub use colors::altitude_to_color;
 
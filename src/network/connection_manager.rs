@@ -18,6 +18,19 @@
 //! each with its own lifecycle control. It provides hot-reload capabilities for
 //! server addresses and independent enable/disable control per server.
 //!
+//! Each server is an [`adsb_client::Client`], which connects, parses the
+//! configured (or auto-detected) protocol and tracks aircraft in the
+//! background. A server with a replay file is fed from that file instead, on
+//! the file's clock.
+//!
+//! A companion task per client reports what it sees to the rest of the app:
+//! - Connection state and counters go to the system status
+//! - Position updates are checked against the geofences and logged to the flight history
+//! - Emergencies are recorded in the system status
+//! - Tracker events are re-published on one channel, for consumers following every server
+//!
+//! An optional SBS output server re-broadcasts the aircraft of every live server.
+//!
 //! Key features:
 //! - Multiple concurrent server connections with independent aircraft trackers
 //! - Hot-reload of server addresses without restarting connections
@@ -25,120 +38,415 @@
 //! - Merged aircraft view across all active servers
 //! - Graceful shutdown with proper cleanup
 
+use adsb_client::{AlphaBeta, Client, ClientConfig, ClientStats, ConnectionConfig, ConnectionState, Emergency, TrackerConfig, TrackerEvent};
 use log::{info, warn};
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-use tokio::sync::watch;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::Duration;
 use tokio_util::sync::CancellationToken;
 
-use crate::aircraft::{Aircraft, AircraftTracker};
+use crate::aircraft::{unique_aircraft, Aircraft};
 use crate::config::ServerConfig;
-use crate::status::SharedSystemStatus;
-use super::tcp_client::connect_adsb_feed;
+use crate::geofence::SharedGeofenceMonitor;
+use crate::history::SharedFlightLogger;
+use crate::recording::{Recording, RecordingConfig, RecordingSource, RecordingStatus, Replay, ReplayControl, ReplayFormat};
+use crate::status::{ConnectionStatus, DiagnosticLevel, EmergencyAlert, SharedSystemStatus};
+use super::sbs_server::{SbsOutputConfig, SbsServer, SbsServerStatus};
+
+const TRAIL_HISTORY_SECONDS: i64 = 300; // Keep 5 minutes of position history when trails are time-limited
+const STATUS_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+const EVENT_CHANNEL_CAPACITY: usize = 1024; // Events of every server, re-published
+
+/// Shared state the companion task of every server reports into
+#[derive(Clone)]
+struct FeedContext {
+    /// System status tracker
+    status: SharedSystemStatus,
+
+    /// Geofence monitor checked on every position update
+    geofences: SharedGeofenceMonitor,
+
+    /// Flight history logging position updates (None for replayed feeds)
+    history: Option<SharedFlightLogger>,
+
+    /// Tracker events of every server
+    events: broadcast::Sender<TrackerEvent>,
+}
 
 /// Represents a single server connection with its own tracker and lifecycle management
 struct ServerConnection {
     /// Server configuration
     config: ServerConfig,
 
-    /// Dedicated aircraft tracker for this server
-    tracker: Arc<Mutex<AircraftTracker>>,
+    /// Running client for this server (None while stopped)
+    client: Option<Arc<Client>>,
 
-    /// Cancellation token for clean shutdown
+    /// Cancellation token for the status reporting task
     cancel_token: CancellationToken,
 
-    /// Watch sender for hot-reloading server address
-    address_tx: watch::Sender<String>,
+    /// Recording settings while the feed should be recorded, kept across restarts
+    record: Option<RecordingConfig>,
+
+    /// Recording of the running client's feed
+    recording: Option<Recording>,
+
+    /// Replay feeding the client, for servers replaying a file
+    replay: Option<Replay>,
 }
 
 impl ServerConnection {
     /// Create a new server connection
-    fn new(config: ServerConfig, center_lat: f64, center_lon: f64) -> Self {
-        // Create dedicated tracker for this server
-        let mut tracker = AircraftTracker::new();
-        tracker.set_center(center_lat, center_lon);
-        tracker.set_server_info(config.id.clone(), config.name.clone());
-
-        let tracker = Arc::new(Mutex::new(tracker));
-
-        // Create watch channel for address hot-reload
-        let (address_tx, _) = watch::channel(config.address.clone());
-
-        // Create cancellation token
-        let cancel_token = CancellationToken::new();
-
+    fn new(config: ServerConfig) -> Self {
         Self {
             config,
-            tracker,
-            cancel_token,
-            address_tx,
+            client: None,
+            cancel_token: CancellationToken::new(),
+            record: None,
+            recording: None,
+            replay: None,
         }
     }
 
-    /// Start the connection in a background task
-    fn start(&self, status: SharedSystemStatus) {
+    /// Start the connection in the background
+    fn start(
+        &mut self,
+        runtime: &Runtime,
+        mut context: FeedContext,
+        center: (f64, f64),
+        time_limited_trails: bool,
+        smooth_tracks: bool,
+    ) {
+        // A token cancelled by a previous stop() would end the new task immediately
+        if self.cancel_token.is_cancelled() {
+            self.cancel_token = CancellationToken::new();
+        }
+
         let server_id = self.config.id.clone();
         let server_name = self.config.name.clone();
-        let address_rx = self.address_tx.subscribe();
-        let tracker = self.tracker.clone();
-        let status_clone = status.clone();
-        let cancel_token = self.cancel_token.clone();
+        let protocol = self.config.protocol;
 
         // Register server in status tracking
-        status.lock().unwrap().register_server(
+        context.status.lock().unwrap().register_server(
             server_id.clone(),
             server_name.clone(),
             self.config.address.clone(),
         );
 
-        info!("Starting connection to server '{}' ({})", server_name, self.config.address);
+        let client_config = ClientConfig {
+            connection: ConnectionConfig {
+                address: self.config.address.clone(),
+                ..Default::default()
+            },
+            tracker: TrackerConfig {
+                center: Some(center),
+                ..Default::default()
+            },
+            protocol,
+            ..Default::default()
+        };
+
+        // The client spawns its tasks on the connection manager's runtime
+        let _guard = runtime.enter();
+        let client = match &self.config.replay {
+            Some(path) => match ReplayFormat::probe(path) {
+                Ok((format, protocol)) => {
+                    info!("Replaying {} {} as server '{}'", format.label(), path.display(), server_name);
+                    let (client, feed_tx) = Client::spawn_feed(ClientConfig { protocol, ..client_config });
+                    self.replay = Some(Replay::spawn(path.clone(), format, feed_tx));
+                    client
+                }
+                Err(e) => {
+                    warn!("Cannot replay {} for server '{}': {}", path.display(), server_name, e);
+                    context.status.lock().unwrap().update_server_error(&server_id, format!("Cannot replay file: {}", e));
+                    return;
+                }
+            },
+            None => {
+                info!("Starting {} connection to server '{}' ({})",
+                    protocol.label(), server_name, self.config.address);
+                Client::spawn(client_config)
+            }
+        };
+        let client = Arc::new(client);
+        set_trail_history(&client, time_limited_trails);
+        set_track_smoothing(&client, smooth_tracks);
+
+        // A restarted client gets a new recording (and file)
+        if let Some(record) = &self.record {
+            self.recording = Some(Recording::spawn(runtime, &client, self.recording_source(), record.clone()));
+        }
 
-        // Spawn connection task
-        std::thread::spawn(move || {
-            let rt = tokio::runtime::Runtime::new().unwrap();
-            rt.block_on(connect_adsb_feed(
-                server_id,
-                server_name,
-                address_rx,
-                tracker,
-                status_clone,
-                cancel_token,
-            ));
-        });
+        // Replayed flights are not logged to the history
+        if self.config.replay.is_some() {
+            context.history = None;
+        }
+        let reporter = FeedReporter {
+            client: client.clone(),
+            server_id,
+            server_name,
+            context,
+            replay: self.replay.as_ref().map(Replay::control),
+        };
+        runtime.spawn(reporter.run(self.cancel_token.clone()));
+        self.client = Some(client);
     }
 
     /// Stop the connection gracefully
-    fn stop(&self, status: SharedSystemStatus) {
+    fn stop(&mut self, status: SharedSystemStatus) {
         info!("Stopping connection to server '{}'", self.config.name);
         self.cancel_token.cancel();
+        self.recording = None;
+        self.replay = None;
+        if let Some(client) = self.client.take() {
+            client.shutdown();
+        }
 
         // Update status to disconnected
         status.lock().unwrap().update_server_status(
             &self.config.id,
-            crate::status::ConnectionStatus::Disconnected,
+            ConnectionStatus::Disconnected,
         );
     }
 
+    /// Feed details written to capture headers
+    fn recording_source(&self) -> RecordingSource {
+        RecordingSource {
+            server_name: self.config.name.clone(),
+            address: self.config.address.clone(),
+            protocol: self.config.protocol,
+        }
+    }
+
+    /// Start recording the feed, now and whenever the client restarts
+    fn start_recording(&mut self, runtime: &Runtime, config: RecordingConfig) {
+        if let Some(client) = &self.client {
+            self.recording = Some(Recording::spawn(runtime, client, self.recording_source(), config.clone()));
+        }
+        self.record = Some(config);
+    }
+
+    fn stop_recording(&mut self) {
+        self.record = None;
+        self.recording = None;
+    }
+
     /// Update the server address (hot-reload)
     fn update_address(&mut self, new_address: String) {
         info!("Updating address for server '{}': {} -> {}",
             self.config.name, self.config.address, new_address);
         self.config.address = new_address.clone();
-        let _ = self.address_tx.send(new_address);
+        if let Some(client) = &self.client {
+            client.set_address(new_address);
+        }
     }
 
     /// Get aircraft from this server
     fn get_aircraft(&self) -> Vec<Aircraft> {
-        self.tracker.lock().unwrap().get_aircraft()
+        let Some(client) = &self.client else {
+            return Vec::new();
+        };
+        let snapshot = client.tracker().read().unwrap().snapshot();
+        let replay_time = self.replay.as_ref().map(Replay::clock);
+        snapshot
+            .into_iter()
+            .map(|aircraft| {
+                Aircraft::new(aircraft, self.config.id.clone(), self.config.name.clone())
+                    .with_replay_time(replay_time)
+            })
+            .collect()
+    }
+
+    /// Find an aircraft on this server
+    fn get_aircraft_by_icao(&self, icao: &str) -> Option<Aircraft> {
+        let aircraft = self.client.as_ref()?.tracker().read().unwrap().get_shared(icao)?;
+        Some(Aircraft::new(aircraft, self.config.id.clone(), self.config.name.clone())
+            .with_replay_time(self.replay.as_ref().map(Replay::clock)))
     }
 
     /// Update aircraft count in status
     fn update_status_aircraft_count(&self, status: &SharedSystemStatus) {
-        let count = self.tracker.lock().unwrap().get_aircraft().len();
+        let count = self.client.as_ref().map_or(0, |client| client.aircraft_count());
         status.lock().unwrap().update_server_aircraft_count(&self.config.id, count);
     }
 }
 
+/// Keep the full trail history, or only the last few minutes
+fn set_trail_history(client: &Client, time_limited_trails: bool) {
+    let history_secs = time_limited_trails.then_some(TRAIL_HISTORY_SECONDS);
+    client.tracker().write().unwrap().set_position_history_secs(history_secs);
+}
+
+/// Smooth positions with the default filter gains, or keep them as reported
+fn set_track_smoothing(client: &Client, smooth_tracks: bool) {
+    let smoothing = smooth_tracks.then(AlphaBeta::default);
+    client.tracker().write().unwrap().set_track_smoothing(smoothing);
+}
+
+/// Companion task of a running client, reporting what it sees to the rest of the app
+struct FeedReporter {
+    client: Arc<Client>,
+    server_id: String,
+    server_name: String,
+    context: FeedContext,
+    /// Replay feeding the client, whose clock the feed runs on
+    replay: Option<ReplayControl>,
+}
+
+impl FeedReporter {
+    /// Report until the client stops or the token is cancelled
+    async fn run(self, cancel_token: CancellationToken) {
+        let mut state_rx = self.client.subscribe_state();
+        let mut events = self.client.subscribe();
+        let mut last_stats = ClientStats::default();
+        let mut stats_interval = tokio::time::interval(STATUS_UPDATE_INTERVAL);
+        stats_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            tokio::select! {
+                changed = state_rx.changed() => {
+                    if changed.is_err() {
+                        return; // Client stopped
+                    }
+                    let state = state_rx.borrow_and_update().clone();
+                    self.report_state(state);
+                }
+
+                event = events.recv() => {
+                    match event {
+                        Ok(event) => self.handle_event(event),
+                        Err(RecvError::Lagged(_)) => {}
+                        Err(RecvError::Closed) => return,
+                    }
+                }
+
+                _ = stats_interval.tick() => {
+                    self.maintain();
+                    last_stats = self.report_stats(last_stats);
+                }
+
+                _ = cancel_token.cancelled() => {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Mirror the client's connection state into the system status
+    fn report_state(&self, state: ConnectionState) {
+        let mut status = self.context.status.lock().unwrap();
+        match state {
+            ConnectionState::Connecting => {
+                status.update_server_status(&self.server_id, ConnectionStatus::Connecting);
+            }
+            ConnectionState::Connected { format } => {
+                // Quick successive changes may arrive as one, so only report the
+                // connection once
+                let connected = status.get_server_status(&self.server_id)
+                    .is_some_and(|server| server.status == ConnectionStatus::Connected);
+                if !connected {
+                    status.update_server_status(&self.server_id, ConnectionStatus::Connected);
+                }
+                if let Some(format) = format {
+                    status.set_server_detected_format(&self.server_id, format);
+                }
+            }
+            ConnectionState::Disconnected => {
+                status.update_server_status(&self.server_id, ConnectionStatus::Disconnected);
+            }
+            ConnectionState::Error(error) => {
+                status.update_server_error(&self.server_id, error);
+            }
+        }
+    }
+
+    /// Re-publish a tracker event and act on it
+    fn handle_event(&self, event: TrackerEvent) {
+        let _ = self.context.events.send(event.clone());
+        match event {
+            TrackerEvent::PositionUpdated(icao) => {
+                self.context.status.lock().unwrap().record_position_update();
+                let (aircraft, center) = {
+                    let tracker = self.client.tracker().read().unwrap();
+                    (tracker.get_shared(&icao), tracker.center())
+                };
+                if let Some(aircraft) = aircraft {
+                    self.check_geofences(&aircraft);
+                    self.log_history(&aircraft, center);
+                }
+            }
+            TrackerEvent::Emergency { icao, emergency } => self.record_emergency(icao, emergency),
+            TrackerEvent::AircraftAdded(_) | TrackerEvent::AircraftRemoved(_) => {}
+        }
+    }
+
+    /// Report the geofences an aircraft's new position enters or leaves
+    fn check_geofences(&self, aircraft: &adsb_client::Aircraft) {
+        let hits = self.context.geofences.lock().unwrap()
+            .check(aircraft, &self.server_name, self.replay.is_some().then_some(self.server_id.as_str()));
+        if hits.is_empty() {
+            return;
+        }
+        let mut status = self.context.status.lock().unwrap();
+        for hit in hits {
+            info!("Geofence hit: {}", hit.describe());
+            status.add_diagnostic(DiagnosticLevel::Warning, format!("Geofence {}", hit.describe()));
+        }
+    }
+
+    /// Log an aircraft's new position to the flight history
+    fn log_history(&self, aircraft: &adsb_client::Aircraft, receiver: Option<(f64, f64)>) {
+        if let Some(history) = &self.context.history {
+            history.lock().unwrap().observe(aircraft, &self.server_name, receiver);
+        }
+    }
+
+    /// Record an aircraft starting to signal an emergency
+    fn record_emergency(&self, icao: String, emergency: Emergency) {
+        let aircraft = self.client.tracker().read().unwrap().get_shared(&icao);
+        let alert = EmergencyAlert {
+            id: 0,
+            callsign: aircraft.as_ref().and_then(|a| a.callsign.clone()),
+            latitude: aircraft.as_ref().and_then(|a| a.latitude),
+            longitude: aircraft.as_ref().and_then(|a| a.longitude),
+            altitude: aircraft.as_ref().and_then(|a| a.altitude),
+            icao,
+            emergency,
+            server_name: self.server_name.clone(),
+            // On the feed's clock, so replayed emergencies keep their time
+            detected_at: aircraft.as_ref().map_or_else(chrono::Utc::now, |a| a.last_seen),
+        };
+        self.context.status.lock().unwrap().record_emergency(alert);
+    }
+
+    /// Expire geofence state and write pending flight history
+    fn maintain(&self) {
+        let feed_now = self.replay.as_ref().map_or_else(chrono::Utc::now, ReplayControl::clock);
+        self.context.geofences.lock().unwrap().prune(&self.server_name, feed_now);
+        if let Some(history) = &self.context.history {
+            history.lock().unwrap().flush_if_due(chrono::Utc::now());
+        }
+    }
+
+    /// Add the client's counters since `last_stats` to the system status,
+    /// returning the current counters
+    fn report_stats(&self, last_stats: ClientStats) -> ClientStats {
+        let stats = self.client.stats();
+        if stats != last_stats {
+            let mut status = self.context.status.lock().unwrap();
+            status.add_server_message_count(&self.server_id, stats.messages - last_stats.messages);
+            status.add_server_frame_stats(
+                &self.server_id,
+                stats.frames_corrected - last_stats.frames_corrected,
+                stats.frames_dropped - last_stats.frames_dropped,
+            );
+        }
+        stats
+    }
+}
+
 /// Manages multiple server connections with independent lifecycle control
 pub struct ConnectionManager {
     /// Active server connections (keyed by server_id)
@@ -147,19 +455,78 @@ pub struct ConnectionManager {
     /// System status tracker
     status: SharedSystemStatus,
 
+    /// Geofence monitor fed by every server's position updates
+    geofences: SharedGeofenceMonitor,
+
+    /// Flight history fed by every live server's position updates
+    history: SharedFlightLogger,
+
+    /// Runtime driving every client (None only while dropping)
+    runtime: Option<Runtime>,
+
     /// Center location for distance filtering (shared across all connections)
     center_lat: f64,
     center_lon: f64,
+
+    /// Whether trails only keep the last few minutes of positions
+    time_limited_trails: bool,
+
+    /// Whether trackers smooth noisy positions
+    smooth_tracks: bool,
+
+    /// Server re-broadcasting every live server's aircraft as SBS, while enabled
+    sbs_output: Option<SbsServer>,
+
+    /// Tracker events of every server
+    events: broadcast::Sender<TrackerEvent>,
 }
 
 impl ConnectionManager {
     /// Create a new connection manager
-    pub fn new(status: SharedSystemStatus, center_lat: f64, center_lon: f64) -> Self {
+    pub fn new(
+        status: SharedSystemStatus,
+        geofences: SharedGeofenceMonitor,
+        history: SharedFlightLogger,
+        center_lat: f64,
+        center_lon: f64,
+    ) -> Self {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("adsb-feeds")
+            .build()
+            .expect("Failed to create tokio runtime");
+
         Self {
             connections: HashMap::new(),
             status,
+            geofences,
+            history,
+            runtime: Some(runtime),
             center_lat,
             center_lon,
+            time_limited_trails: false,
+            smooth_tracks: false,
+            sbs_output: None,
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+        }
+    }
+
+    /// Start a connection with the manager's current settings
+    fn start_connection(&mut self, server_id: &str) {
+        let center = (self.center_lat, self.center_lon);
+        let runtime = self.runtime.as_ref().expect("runtime is only taken on drop");
+        let context = FeedContext {
+            status: self.status.clone(),
+            geofences: self.geofences.clone(),
+            history: Some(self.history.clone()),
+            events: self.events.clone(),
+        };
+        if let Some(connection) = self.connections.get_mut(server_id) {
+            connection.start(runtime, context, center, self.time_limited_trails, self.smooth_tracks);
+            // Replayed traffic is not re-broadcast as if it were live
+            if let (Some(server), Some(client), None) = (&self.sbs_output, &connection.client, &connection.config.replay) {
+                server.add_feed(client, &connection.config.id, &connection.config.name);
+            }
         }
     }
 
@@ -168,12 +535,24 @@ impl ConnectionManager {
         self.center_lat = lat;
         self.center_lon = lon;
 
-        // Update all existing trackers
-        for connection in self.connections.values() {
-            connection.tracker.lock().unwrap().set_center(lat, lon);
+        // Update all running clients
+        for client in self.connections.values().filter_map(|conn| conn.client.as_ref()) {
+            client.set_center(lat, lon);
         }
     }
 
+    /// Center location shared by every tracker (the receiver location)
+    pub fn center(&self) -> (f64, f64) {
+        (self.center_lat, self.center_lon)
+    }
+
+    /// Subscribe to the tracker events of every server
+    ///
+    /// An aircraft tracked by several servers gets events from each of them.
+    pub fn subscribe(&self) -> broadcast::Receiver<TrackerEvent> {
+        self.events.subscribe()
+    }
+
     /// Add and start a new server connection
     pub fn add_server(&mut self, config: ServerConfig) {
         let server_id = config.id.clone();
@@ -181,21 +560,16 @@ impl ConnectionManager {
 
         info!("Adding server '{}' ({}) - enabled: {}", config.name, config.address, enabled);
 
-        // Create connection
-        let connection = ServerConnection::new(config, self.center_lat, self.center_lon);
-
-        // Start if enabled
+        // Store connection, then start it if enabled
+        self.connections.insert(server_id.clone(), ServerConnection::new(config));
         if enabled {
-            connection.start(self.status.clone());
+            self.start_connection(&server_id);
         }
-
-        // Store connection
-        self.connections.insert(server_id, connection);
     }
 
     /// Remove a server connection
     pub fn remove_server(&mut self, server_id: &str) {
-        if let Some(connection) = self.connections.remove(server_id) {
+        if let Some(mut connection) = self.connections.remove(server_id) {
             info!("Removing server '{}'", connection.config.name);
 
             // Stop connection
@@ -214,7 +588,7 @@ impl ConnectionManager {
             if !connection.config.enabled {
                 info!("Enabling server '{}'", connection.config.name);
                 connection.config.enabled = true;
-                connection.start(self.status.clone());
+                self.start_connection(server_id);
             }
         } else {
             warn!("Attempted to enable non-existent server: {}", server_id);
@@ -234,33 +608,59 @@ impl ConnectionManager {
         }
     }
 
-    /// Update server configuration (hot-reload address)
+    /// Update server configuration (hot-reload address, restart on protocol change)
     pub fn update_server(&mut self, server_id: &str, new_config: ServerConfig) {
-        if let Some(connection) = self.connections.get_mut(server_id) {
-            info!("Updating server '{}' configuration", connection.config.name);
+        let Some(connection) = self.connections.get_mut(server_id) else {
+            warn!("Attempted to update non-existent server: {}", server_id);
+            return;
+        };
+        info!("Updating server '{}' configuration", connection.config.name);
+
+        // Update name if changed
+        if connection.config.name != new_config.name {
+            connection.config.name = new_config.name.clone();
+        }
+
+        // Update address if changed (hot-reload)
+        if connection.config.address != new_config.address {
+            connection.update_address(new_config.address.clone());
+        }
 
-            // Update name if changed
-            if connection.config.name != new_config.name {
-                connection.config.name = new_config.name.clone();
+        // Protocol changes need a fresh client with a new parser
+        let mut restart = false;
+        if connection.config.protocol != new_config.protocol {
+            info!("Switching server '{}' protocol: {} -> {}",
+                connection.config.name,
+                connection.config.protocol.label(),
+                new_config.protocol.label());
+            connection.config.protocol = new_config.protocol;
+            if connection.config.enabled && new_config.enabled {
+                connection.stop(self.status.clone());
+                restart = true;
             }
+        }
 
-            // Update address if changed (hot-reload)
-            if connection.config.address != new_config.address {
-                connection.update_address(new_config.address.clone());
+        // A different replay file starts a new replay
+        if connection.config.replay != new_config.replay {
+            connection.config.replay = new_config.replay.clone();
+            if connection.config.enabled && new_config.enabled && !restart {
+                connection.stop(self.status.clone());
+                restart = true;
             }
+        }
 
-            // Handle enabled state change
-            if connection.config.enabled != new_config.enabled {
-                if new_config.enabled {
-                    connection.config.enabled = true;
-                    connection.start(self.status.clone());
-                } else {
-                    connection.config.enabled = false;
-                    connection.stop(self.status.clone());
-                }
+        // Handle enabled state change
+        if connection.config.enabled != new_config.enabled {
+            connection.config.enabled = new_config.enabled;
+            if new_config.enabled {
+                restart = true;
+            } else {
+                connection.stop(self.status.clone());
             }
-        } else {
-            warn!("Attempted to update non-existent server: {}", server_id);
+        }
+
+        if restart {
+            self.start_connection(server_id);
         }
     }
 
@@ -272,12 +672,32 @@ impl ConnectionManager {
 
     /// Find a specific aircraft by ICAO across all servers
     pub fn get_aircraft_by_icao(&self, icao: &str) -> Option<Aircraft> {
-        for connection in self.connections.values() {
-            if let Some(aircraft) = connection.tracker.lock().unwrap().get_aircraft_by_icao(icao) {
-                return Some(aircraft);
-            }
+        self.connections
+            .values()
+            .find_map(|connection| connection.get_aircraft_by_icao(icao))
+    }
+
+    /// Find several aircraft by ICAO across all servers, keeping the most
+    /// recently seen copy of each (see [`unique_aircraft`])
+    pub fn get_aircraft_unique(&self, icaos: &[&str]) -> Vec<Aircraft> {
+        unique_aircraft(self.connections.values().flat_map(|connection| {
+            icaos.iter().filter_map(|icao| connection.get_aircraft_by_icao(icao))
+        }))
+    }
+
+    /// Modify an aircraft on every server that tracks it, e.g. to attach app data
+    /// to its extensions
+    ///
+    /// Returns whether any server tracks the aircraft.
+    pub fn update_aircraft<F>(&self, icao: &str, mut f: F) -> bool
+    where
+        F: FnMut(&mut adsb_client::Aircraft),
+    {
+        let mut found = false;
+        for client in self.connections.values().filter_map(|conn| conn.client.as_ref()) {
+            found |= client.tracker().write().unwrap().update_aircraft(icao, &mut f).is_some();
         }
-        None
+        found
     }
 
     /// Get all aircraft grouped by server
@@ -304,6 +724,12 @@ impl ConnectionManager {
         all_aircraft
     }
 
+    /// Get every aircraft once, keeping the most recently seen copy of aircraft
+    /// tracked by several servers (see [`unique_aircraft`])
+    pub fn get_all_aircraft_unique(&self) -> Vec<Aircraft> {
+        unique_aircraft(self.get_all_aircraft_merged())
+    }
+
     /// Get server configurations
     #[allow(dead_code)]
     pub fn get_server_configs(&self) -> Vec<ServerConfig> {
@@ -332,27 +758,108 @@ impl ConnectionManager {
     }
 
     /// Set time-limited trails for all trackers
-    pub fn set_time_limited_trails(&self, enabled: bool) {
-        for connection in self.connections.values() {
-            connection.tracker.lock().unwrap().set_time_limited_trails(enabled);
+    pub fn set_time_limited_trails(&mut self, enabled: bool) {
+        self.time_limited_trails = enabled;
+        for client in self.connections.values().filter_map(|conn| conn.client.as_ref()) {
+            set_trail_history(client, enabled);
         }
     }
 
-    /// Get time-limited trails setting (from first tracker, assumed same for all)
+    /// Get time-limited trails setting
     pub fn get_time_limited_trails(&self) -> bool {
-        self.connections.values()
-            .next()
-            .map(|conn| conn.tracker.lock().unwrap().get_time_limited_trails())
-            .unwrap_or(false)
+        self.time_limited_trails
     }
 
-    /// Cleanup old aircraft and position history for all trackers
-    #[allow(dead_code)]
-    pub fn cleanup_all(&self, max_age_seconds: i64) {
-        for connection in self.connections.values() {
-            connection.tracker.lock().unwrap().cleanup_old(max_age_seconds);
+    /// Set track smoothing for all trackers
+    pub fn set_smooth_tracks(&mut self, enabled: bool) {
+        self.smooth_tracks = enabled;
+        for client in self.connections.values().filter_map(|conn| conn.client.as_ref()) {
+            set_track_smoothing(client, enabled);
         }
     }
+
+    /// Record a server's feed to capture files until stopped
+    pub fn start_recording(&mut self, server_id: &str, config: RecordingConfig) {
+        let runtime = self.runtime.as_ref().expect("runtime is only taken on drop");
+        if let Some(connection) = self.connections.get_mut(server_id) {
+            info!("Starting recording of server '{}'", connection.config.name);
+            connection.start_recording(runtime, config);
+        } else {
+            warn!("Attempted to record non-existent server: {}", server_id);
+        }
+    }
+
+    /// Record every enabled server's live feed
+    pub fn start_recording_all(&mut self, config: &RecordingConfig) {
+        let enabled: Vec<String> = self.connections.values()
+            .filter(|conn| conn.config.enabled && conn.config.replay.is_none())
+            .map(|conn| conn.config.id.clone())
+            .collect();
+        for server_id in enabled {
+            self.start_recording(&server_id, config.clone());
+        }
+    }
+
+    /// Stop recording a server's feed, closing the current file
+    pub fn stop_recording(&mut self, server_id: &str) {
+        if let Some(connection) = self.connections.get_mut(server_id) {
+            if connection.record.is_some() {
+                info!("Stopping recording of server '{}'", connection.config.name);
+            }
+            connection.stop_recording();
+        }
+    }
+
+    /// Whether a server's feed is being recorded
+    pub fn is_recording(&self, server_id: &str) -> bool {
+        self.connections.get(server_id).is_some_and(|conn| conn.record.is_some())
+    }
+
+    /// Progress of a server's recording, while its client runs
+    pub fn recording_status(&self, server_id: &str) -> Option<RecordingStatus> {
+        self.connections.get(server_id)?.recording.as_ref().map(Recording::status)
+    }
+
+    /// Controls of every running replay with its server's name, ordered by name
+    pub fn replays(&self) -> Vec<(String, ReplayControl)> {
+        let mut replays: Vec<_> = self.connections.values()
+            .filter_map(|conn| Some((conn.config.name.clone(), conn.replay.as_ref()?.control())))
+            .collect();
+        replays.sort_by(|a, b| a.0.cmp(&b.0));
+        replays
+    }
+
+    /// Start, restart or stop the SBS output server to match its settings
+    pub fn set_sbs_output(&mut self, config: &SbsOutputConfig) {
+        if !config.enabled {
+            if self.sbs_output.take().is_some() {
+                info!("Stopping SBS output");
+            }
+            return;
+        }
+
+        // Only a new port needs a new listener
+        if let Some(server) = &mut self.sbs_output {
+            if server.config().port == config.port {
+                server.set_utc_timestamps(config.utc_timestamps);
+                return;
+            }
+        }
+
+        let runtime = self.runtime.as_ref().expect("runtime is only taken on drop");
+        let server = SbsServer::spawn(runtime, config.clone());
+        for conn in self.connections.values().filter(|conn| conn.config.replay.is_none()) {
+            if let Some(client) = &conn.client {
+                server.add_feed(client, &conn.config.id, &conn.config.name);
+            }
+        }
+        self.sbs_output = Some(server);
+    }
+
+    /// State of the SBS output server, while enabled
+    pub fn sbs_output_status(&self) -> Option<SbsServerStatus> {
+        self.sbs_output.as_ref().map(SbsServer::status)
+    }
 }
 
 impl Drop for ConnectionManager {
@@ -360,8 +867,16 @@ impl Drop for ConnectionManager {
         info!("Shutting down ConnectionManager - stopping all connections");
 
         // Stop all connections gracefully
-        for (_, connection) in &self.connections {
+        for connection in self.connections.values_mut() {
             connection.stop(self.status.clone());
         }
+
+        // Don't block the UI thread waiting for tasks to notice the cancellation
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+
+        // Write sessions still open so the last minutes aren't lost
+        self.history.lock().unwrap().flush(chrono::Utc::now());
     }
 }
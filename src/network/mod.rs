@@ -1,10 +1,12 @@
 //! Network connectivity and connection management.
 //!
-//! This module handles TCP connections to ADS-B feeds and manages multiple
-//! concurrent server connections.
+//! This module manages multiple concurrent server connections, each backed by
+//! an `adsb_client` client that handles the TCP feed, and can serve the merged
+//! traffic of all of them as an SBS feed.
 
-pub mod tcp_client;
 pub mod connection_manager;
+pub mod sbs_server;
 
 pub use connection_manager::ConnectionManager;
+pub use sbs_server::SbsOutputConfig;
 
@@ -0,0 +1,372 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SBS (BaseStation) output server.
+//!
+//! Re-broadcasts the traffic of every live server as one merged BaseStation
+//! feed, for tools such as Virtual Radar Server and PlanePlotter. Replays are
+//! left out, as their traffic is not happening now. A task sweeps
+//! the trackers a few times a second and encodes what changed since the last
+//! sweep as `MSG` lines, sent to every connected client as one batch.
+//!
+//! Each client is written by its own task from a bounded broadcast channel, so
+//! the feeds never wait on a client. A client that falls behind misses batches
+//! and is sent the current state of every aircraft instead, and one that stops
+//! reading altogether is disconnected.
+
+use adsb_client::{BaseStationEncoder, Client};
+use chrono::Utc;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, Weak};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::runtime::Runtime;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+use crate::aircraft::{unique_aircraft, Aircraft};
+
+/// How often the trackers are checked for changes
+const SWEEP_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Batches a client may fall behind by before it misses some
+const CLIENT_BACKLOG: usize = 64;
+
+/// Clients that accept no data for this long are disconnected
+const WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Whether and where the merged traffic is served
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SbsOutputConfig {
+    /// Serve the merged traffic
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// TCP port to listen on, on all interfaces
+    #[serde(default = "default_port")]
+    pub port: u16,
+
+    /// Write timestamps in UTC instead of local time
+    #[serde(default)]
+    pub utc_timestamps: bool,
+}
+
+/// Not the usual 30003, which is often taken by a local decoder feeding this app
+fn default_port() -> u16 {
+    30103
+}
+
+impl Default for SbsOutputConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_port(),
+            utc_timestamps: false,
+        }
+    }
+}
+
+/// State of the server, for the UI
+#[derive(Debug, Clone, Default)]
+pub struct SbsServerStatus {
+    /// Port listened on, once listening
+    pub port: Option<u16>,
+    /// Addresses of the connected clients
+    pub clients: Vec<SocketAddr>,
+    /// Lines encoded since the server started
+    pub lines: u64,
+    /// Batches missed by clients that fell behind
+    pub dropped: u64,
+    /// Error that stopped the server
+    pub error: Option<String>,
+}
+
+/// Lines encoded by one sweep, shared by every client
+type Batch = Arc<String>;
+
+/// A server's client whose aircraft are served
+struct Feed {
+    client: Weak<Client>,
+    server_id: String,
+    server_name: String,
+}
+
+/// A running SBS output server, stopped when dropped
+pub struct SbsServer {
+    config: SbsOutputConfig,
+    /// Stopped clients drop out on the next sweep
+    feeds: Arc<Mutex<Vec<Feed>>>,
+    encoder: Arc<Mutex<BaseStationEncoder>>,
+    status: Arc<Mutex<SbsServerStatus>>,
+    cancel_token: CancellationToken,
+}
+
+impl SbsServer {
+    /// Start listening, serving the aircraft of clients as they are added
+    pub fn spawn(runtime: &Runtime, config: SbsOutputConfig) -> Self {
+        let feeds = Arc::new(Mutex::new(Vec::new()));
+        let status = Arc::new(Mutex::new(SbsServerStatus::default()));
+        let cancel_token = CancellationToken::new();
+
+        let mut encoder = BaseStationEncoder::new();
+        encoder.set_utc_timestamps(config.utc_timestamps);
+        let encoder = Arc::new(Mutex::new(encoder));
+        let (batch_tx, _) = broadcast::channel(CLIENT_BACKLOG);
+
+        runtime.spawn(sweep(
+            feeds.clone(),
+            encoder.clone(),
+            batch_tx.clone(),
+            status.clone(),
+            cancel_token.clone(),
+        ));
+        runtime.spawn(serve(config.port, encoder.clone(), batch_tx, status.clone(), cancel_token.clone()));
+
+        Self { config, feeds, encoder, status, cancel_token }
+    }
+
+    pub fn config(&self) -> &SbsOutputConfig {
+        &self.config
+    }
+
+    /// Switch between UTC and local timestamps without dropping the clients
+    pub fn set_utc_timestamps(&mut self, utc: bool) {
+        self.config.utc_timestamps = utc;
+        self.encoder.lock().unwrap().set_utc_timestamps(utc);
+    }
+
+    /// Include the aircraft of a server's client in the output
+    pub fn add_feed(&self, client: &Arc<Client>, server_id: &str, server_name: &str) {
+        self.feeds.lock().unwrap().push(Feed {
+            client: Arc::downgrade(client),
+            server_id: server_id.to_string(),
+            server_name: server_name.to_string(),
+        });
+    }
+
+    pub fn status(&self) -> SbsServerStatus {
+        self.status.lock().unwrap().clone()
+    }
+}
+
+impl Drop for SbsServer {
+    fn drop(&mut self) {
+        self.cancel_token.cancel();
+    }
+}
+
+/// Encode the changes to the merged aircraft every sweep and broadcast them
+async fn sweep(
+    feeds: Arc<Mutex<Vec<Feed>>>,
+    encoder: Arc<Mutex<BaseStationEncoder>>,
+    batch_tx: broadcast::Sender<Batch>,
+    status: Arc<Mutex<SbsServerStatus>>,
+    cancel_token: CancellationToken,
+) {
+    let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        tokio::select! {
+            () = cancel_token.cancelled() => return,
+            _ = interval.tick() => {}
+        }
+
+        let aircraft = merged_aircraft(&feeds);
+        let lines = encode_changes(&mut encoder.lock().unwrap(), &aircraft);
+        if lines.is_empty() {
+            continue;
+        }
+        status.lock().unwrap().lines += lines.len() as u64;
+        // Sending only fails while no client is connected
+        let _ = batch_tx.send(Arc::new(join_lines(lines)));
+    }
+}
+
+/// The most recently updated state of every aircraft across the feeds
+fn merged_aircraft(feeds: &Mutex<Vec<Feed>>) -> Vec<Aircraft> {
+    let clients: Vec<(Arc<Client>, String, String)> = {
+        let mut feeds = feeds.lock().unwrap();
+        feeds.retain(|feed| feed.client.strong_count() > 0);
+        feeds
+            .iter()
+            .filter_map(|feed| Some((feed.client.upgrade()?, feed.server_id.clone(), feed.server_name.clone())))
+            .collect()
+    };
+
+    unique_aircraft(clients.into_iter().flat_map(|(client, server_id, server_name)| {
+        let snapshot = client.tracker().read().unwrap().snapshot();
+        snapshot
+            .into_iter()
+            .map(move |aircraft| Aircraft::new(aircraft, server_id.clone(), server_name.clone()))
+    }))
+}
+
+/// Lines for what changed since the last sweep, forgetting aircraft no feed tracks any more
+fn encode_changes(encoder: &mut BaseStationEncoder, aircraft: &[Aircraft]) -> Vec<String> {
+    let now = Utc::now();
+    let tracked: HashSet<String> = aircraft.iter().map(Aircraft::icao).collect();
+    encoder.retain(|icao| tracked.contains(icao));
+    aircraft
+        .iter()
+        .flat_map(|aircraft| encoder.encode_changes(aircraft.data(), now))
+        .collect()
+}
+
+/// Lines terminated with CRLF, like BaseStation writes them
+fn join_lines(lines: Vec<String>) -> String {
+    lines.into_iter().map(|line| line + "\r\n").collect()
+}
+
+/// Accept clients until cancelled
+async fn serve(
+    port: u16,
+    encoder: Arc<Mutex<BaseStationEncoder>>,
+    batch_tx: broadcast::Sender<Batch>,
+    status: Arc<Mutex<SbsServerStatus>>,
+    cancel_token: CancellationToken,
+) {
+    let listener = match TcpListener::bind(("0.0.0.0", port)).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!("SBS output cannot listen on port {}: {}", port, e);
+            status.lock().unwrap().error = Some(format!("Cannot listen on port {}: {}", port, e));
+            return;
+        }
+    };
+    let port = listener.local_addr().map_or(port, |address| address.port());
+    info!("Serving SBS output on port {}", port);
+    status.lock().unwrap().port = Some(port);
+
+    loop {
+        let accepted = tokio::select! {
+            () = cancel_token.cancelled() => return,
+            accepted = listener.accept() => accepted,
+        };
+        match accepted {
+            Ok((stream, address)) => {
+                // Subscribe before taking the snapshot, so no batch falls between the two
+                let batch_rx = batch_tx.subscribe();
+                tokio::spawn(write_client(
+                    stream,
+                    address,
+                    encoder.clone(),
+                    batch_rx,
+                    status.clone(),
+                    cancel_token.clone(),
+                ));
+            }
+            Err(e) => {
+                // Usually out of file descriptors, so give connections time to close
+                warn!("SBS output failed to accept a client: {}", e);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        }
+    }
+}
+
+/// Send a client the current state, then every batch until it disconnects
+async fn write_client(
+    mut stream: TcpStream,
+    address: SocketAddr,
+    encoder: Arc<Mutex<BaseStationEncoder>>,
+    mut batch_rx: broadcast::Receiver<Batch>,
+    status: Arc<Mutex<SbsServerStatus>>,
+    cancel_token: CancellationToken,
+) {
+    info!("SBS output client {} connected", address);
+    status.lock().unwrap().clients.push(address);
+    let snapshot = || -> Batch { Arc::new(join_lines(encoder.lock().unwrap().snapshot(Utc::now()))) };
+
+    let mut pending = snapshot();
+    let result = loop {
+        match tokio::time::timeout(WRITE_TIMEOUT, stream.write_all(pending.as_bytes())).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => break Err(e),
+            Err(_) => break Err(io::Error::new(io::ErrorKind::TimedOut, "client stopped reading")),
+        }
+
+        let batch = tokio::select! {
+            () = cancel_token.cancelled() => break Ok(()),
+            batch = batch_rx.recv() => batch,
+        };
+        pending = match batch {
+            Ok(batch) => batch,
+            Err(RecvError::Lagged(skipped)) => {
+                warn!("SBS output client {} fell behind, missed {} batches", address, skipped);
+                status.lock().unwrap().dropped += skipped;
+                snapshot()
+            }
+            Err(RecvError::Closed) => break Ok(()),
+        };
+    };
+
+    match result {
+        Ok(()) => info!("SBS output client {} disconnected", address),
+        Err(e) => info!("SBS output client {} disconnected: {}", address, e),
+    }
+    status.lock().unwrap().clients.retain(|client| *client != address);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adsb_client::{AircraftMessage, ClientConfig};
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    #[test]
+    fn test_serves_tracked_aircraft() {
+        let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build().unwrap();
+        let config = SbsOutputConfig {
+            enabled: true,
+            port: 0,
+            utc_timestamps: true,
+        };
+        let server = SbsServer::spawn(&runtime, config);
+
+        runtime.block_on(async {
+            let (client, _feed_tx) = Client::spawn_feed(ClientConfig::default());
+            let client = Arc::new(client);
+            client.tracker().write().unwrap().process_message(AircraftMessage::Identification {
+                icao: "A1B2C3".to_string(),
+                timestamp: None,
+                callsign: "UAL123".to_string(),
+            });
+            server.add_feed(&client, "server", "Server");
+
+            let port = loop {
+                if let Some(port) = server.status().port {
+                    break port;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            };
+            let stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+            let mut lines = BufReader::new(stream).lines();
+            let line = tokio::time::timeout(Duration::from_secs(5), lines.next_line())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            assert!(line.starts_with("MSG,1,1,1,A1B2C3,1,"), "{line}");
+            assert!(line.ends_with(",UAL123,,,,,,,,,,,"), "{line}");
+            assert_eq!(server.status().clients.len(), 1);
+        });
+    }
+}
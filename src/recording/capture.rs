@@ -0,0 +1,289 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Capture file format.
+//!
+//! A capture holds the lines or frames received from one feed, exactly as
+//! they were passed to the parser, with their receive times:
+//! - Magic `AJCAP` and a format version byte
+//! - Header: varint length, then a JSON [`CaptureHeader`]
+//! - Records until the end of the file: varint microseconds since the
+//!   previous record (the header's start time for the first), varint data
+//!   length, then the data
+//!
+//! The whole file may be gzip-compressed; [`CaptureReader::open`] detects
+//! this. A capture cut short by a crash reads up to the last whole record.
+
+use adsb_client::{ProtocolType, ReceivedData};
+use chrono::{DateTime, Duration, Utc};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 5] = b"AJCAP";
+const VERSION: u8 = 1;
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Largest record accepted when reading, to fail fast on corrupt files
+const MAX_RECORD_LEN: u64 = 64 * 1024;
+
+/// Describes the feed a capture was recorded from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaptureHeader {
+    pub server_name: String,
+    pub address: String,
+    /// Protocol the feed was parsed with, and so how to parse the records
+    pub protocol: ProtocolType,
+    /// Time the capture starts at
+    pub started: DateTime<Utc>,
+}
+
+/// Writes records to a capture
+pub struct CaptureWriter<W: Write> {
+    inner: BufWriter<W>,
+    last: DateTime<Utc>,
+    /// Bytes written so far, before any compression
+    bytes_written: u64,
+}
+
+impl<W: Write> CaptureWriter<W> {
+    /// Start a capture by writing its header
+    pub fn new(inner: W, header: &CaptureHeader) -> io::Result<Self> {
+        let mut writer = Self {
+            inner: BufWriter::new(inner),
+            last: header.started,
+            bytes_written: 0,
+        };
+        let json = serde_json::to_vec(header)?;
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        writer.write_varint(json.len() as u64)?;
+        writer.write_all(&json)?;
+        Ok(writer)
+    }
+
+    /// Append a line or frame with its receive time
+    pub fn write_record(&mut self, record: &ReceivedData) -> io::Result<()> {
+        let delta = (record.timestamp - self.last).num_microseconds().unwrap_or(0).max(0);
+        self.last = self.last.max(record.timestamp);
+        self.write_varint(delta as u64)?;
+        self.write_varint(record.data.len() as u64)?;
+        self.write_all(&record.data)
+    }
+
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Flush buffered records, returning the underlying writer
+    pub fn finish(self) -> io::Result<W> {
+        self.inner.into_inner().map_err(io::IntoInnerError::into_error)
+    }
+
+    fn write_all(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.inner.write_all(bytes)?;
+        self.bytes_written += bytes.len() as u64;
+        Ok(())
+    }
+
+    fn write_varint(&mut self, mut value: u64) -> io::Result<()> {
+        let mut bytes = Vec::with_capacity(10);
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                bytes.push(byte);
+                break;
+            }
+            bytes.push(byte | 0x80);
+        }
+        self.write_all(&bytes)
+    }
+}
+
+/// Reads records from a capture
+pub struct CaptureReader<R: Read> {
+    inner: BufReader<R>,
+    header: CaptureHeader,
+    time: DateTime<Utc>,
+}
+
+impl CaptureReader<Box<dyn Read + Send>> {
+    /// Open a capture file, compressed or not
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::new(open_decompressed(path)?)
+    }
+}
+
+/// Open a file for reading, decompressing it if it is gzipped
+pub fn open_decompressed(path: &Path) -> io::Result<Box<dyn Read + Send>> {
+    let mut file = BufReader::new(File::open(path)?);
+    let mut magic = [0u8; 2];
+    let compressed = file.read_exact(&mut magic).is_ok() && magic == GZIP_MAGIC;
+
+    // Start again from the first byte
+    let file = File::open(path)?;
+    Ok(if compressed {
+        Box::new(GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    })
+}
+
+/// Whether data starts like a capture, once decompressed
+pub fn is_capture(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+impl<R: Read> CaptureReader<R> {
+    /// Read the header of a capture
+    pub fn new(inner: R) -> io::Result<Self> {
+        let mut inner = BufReader::new(inner);
+        let mut magic = [0u8; 6];
+        inner.read_exact(&mut magic)?;
+        if &magic[..5] != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a capture file"));
+        }
+        if magic[5] != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported capture version {}", magic[5]),
+            ));
+        }
+
+        let len = read_varint(&mut inner)?;
+        if len > MAX_RECORD_LEN {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "capture header too long"));
+        }
+        let mut json = vec![0u8; len as usize];
+        inner.read_exact(&mut json)?;
+        let header: CaptureHeader = serde_json::from_slice(&json)?;
+
+        Ok(Self {
+            inner,
+            time: header.started,
+            header,
+        })
+    }
+
+    pub fn header(&self) -> &CaptureHeader {
+        &self.header
+    }
+
+    /// Read the next record, or `None` at the end of the capture
+    pub fn next_record(&mut self) -> io::Result<Option<ReceivedData>> {
+        match self.read_record() {
+            Ok(record) => Ok(Some(record)),
+            // The end of the file, or a record cut short by a crash
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn read_record(&mut self) -> io::Result<ReceivedData> {
+        let delta = read_varint(&mut self.inner)?;
+        let len = read_varint(&mut self.inner)?;
+        if len > MAX_RECORD_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("capture record of {} bytes", len),
+            ));
+        }
+        let mut data = vec![0u8; len as usize];
+        self.inner.read_exact(&mut data)?;
+
+        self.time = i64::try_from(delta)
+            .ok()
+            .and_then(|delta| self.time.checked_add_signed(Duration::microseconds(delta)))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "capture time out of range"))?;
+        Ok(ReceivedData {
+            timestamp: self.time,
+            data,
+        })
+    }
+}
+
+/// Read a LEB128 varint
+fn read_varint(reader: &mut impl Read) -> io::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0u8; 1];
+        reader.read_exact(&mut byte)?;
+        value |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::InvalidData, "varint too long"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    fn records(start: DateTime<Utc>) -> Vec<ReceivedData> {
+        vec![
+            ReceivedData {
+                timestamp: start + Duration::milliseconds(5),
+                data: b"MSG,3,1,1,A1B2C3,1,,,,,,35000,,,37.0,-122.0".to_vec(),
+            },
+            ReceivedData {
+                timestamp: start + Duration::seconds(90),
+                data: vec![0x8d, 0xa1, 0xb2, 0xc3, 0x00, 0x1a],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_capture_round_trip() {
+        let start = Utc::now();
+        let header = CaptureHeader {
+            server_name: "Local".to_string(),
+            address: "localhost:30003".to_string(),
+            protocol: ProtocolType::BaseStation,
+            started: start,
+        };
+
+        let mut writer = CaptureWriter::new(Vec::new(), &header).unwrap();
+        for record in records(start) {
+            writer.write_record(&record).unwrap();
+        }
+        let bytes = writer.finish().unwrap();
+
+        let mut reader = CaptureReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(reader.header(), &header);
+        assert_eq!(reader.next_record().unwrap().as_ref(), Some(&records(start)[0]));
+        assert_eq!(reader.next_record().unwrap().as_ref(), Some(&records(start)[1]));
+        assert_eq!(reader.next_record().unwrap(), None);
+
+        // A capture cut off mid-record reads up to the last whole record
+        let mut reader = CaptureReader::new(&bytes[..bytes.len() - 3]).unwrap();
+        assert!(reader.next_record().unwrap().is_some());
+        assert_eq!(reader.next_record().unwrap(), None);
+
+        // Compressed captures are detected when opening a file
+        let path = std::env::temp_dir().join(format!("capture-test-{}.ajcap.gz", uuid::Uuid::new_v4()));
+        let mut encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+        encoder.write_all(&bytes).unwrap();
+        encoder.finish().unwrap();
+        let mut reader = CaptureReader::open(&path).unwrap();
+        assert_eq!(reader.header(), &header);
+        assert_eq!(reader.next_record().unwrap().as_ref(), Some(&records(start)[0]));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
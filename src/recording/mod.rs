@@ -0,0 +1,13 @@
+//! Feed recording and replay.
+//!
+//! Records the raw lines and frames received from a server to capture files,
+//! with their receive times, so interesting traffic (emergencies, airshows,
+//! decoder bugs) can be analysed or attached to bug reports later. Captures,
+//! and plain SBS or BEAST logs, can be replayed as a virtual server.
+
+pub mod capture;
+pub mod recorder;
+pub mod replay;
+
+pub use recorder::{Recording, RecordingConfig, RecordingSource, RecordingStatus};
+pub use replay::{Replay, ReplayControl, ReplayFormat, ReplayStatus};
@@ -0,0 +1,406 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recording a server's feed to capture files.
+//!
+//! A [`Recording`] subscribes to the raw data of a server's client and writes
+//! every line or frame to a capture in the recordings directory. Writing and
+//! compression happen on a dedicated thread, fed from the client through a
+//! bounded queue. Files are rotated once they reach a size or age, so long
+//! sessions can be trimmed by deleting old files.
+
+use adsb_client::{Client, ProtocolType, ReceivedData};
+use chrono::{DateTime, Duration, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use tokio::runtime::Runtime;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+
+use super::capture::{CaptureHeader, CaptureWriter};
+
+/// Capture file extension, before `.gz` for compressed captures
+pub const CAPTURE_EXTENSION: &str = "ajcap";
+
+/// Frames queued for the writer thread before new ones are dropped
+const WRITE_QUEUE_FRAMES: usize = 4096;
+
+/// Where and how feeds are recorded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordingConfig {
+    /// Directory capture files are written to
+    #[serde(default = "default_directory")]
+    pub directory: PathBuf,
+
+    /// Gzip-compress capture files
+    #[serde(default = "default_true")]
+    pub compress: bool,
+
+    /// Start a new file after this many megabytes (uncompressed)
+    #[serde(default = "default_rotate_megabytes")]
+    pub rotate_megabytes: Option<u64>,
+
+    /// Start a new file after this many minutes
+    #[serde(default = "default_rotate_minutes")]
+    pub rotate_minutes: Option<u64>,
+}
+
+fn default_directory() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("airjedi-desktop")
+        .join("recordings")
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_rotate_megabytes() -> Option<u64> {
+    Some(100)
+}
+
+fn default_rotate_minutes() -> Option<u64> {
+    Some(60)
+}
+
+impl Default for RecordingConfig {
+    fn default() -> Self {
+        Self {
+            directory: default_directory(),
+            compress: true,
+            rotate_megabytes: default_rotate_megabytes(),
+            rotate_minutes: default_rotate_minutes(),
+        }
+    }
+}
+
+/// The feed being recorded, written to each file's header
+#[derive(Debug, Clone)]
+pub struct RecordingSource {
+    pub server_name: String,
+    pub address: String,
+    pub protocol: ProtocolType,
+}
+
+/// Progress of a recording, for the UI
+#[derive(Debug, Clone)]
+pub struct RecordingStatus {
+    pub started: DateTime<Utc>,
+    /// File currently written, if any data has arrived
+    pub current_file: Option<PathBuf>,
+    /// Files written, including the current one
+    pub files: usize,
+    pub frames: u64,
+    /// Bytes written before compression
+    pub bytes: u64,
+    /// Frames missed because writing fell behind
+    pub dropped: u64,
+    /// Error that stopped the recording
+    pub error: Option<String>,
+}
+
+/// A running recording, stopped when dropped
+pub struct Recording {
+    status: Arc<Mutex<RecordingStatus>>,
+    cancel_token: CancellationToken,
+}
+
+impl Recording {
+    /// Start recording a client's feed
+    pub fn spawn(runtime: &Runtime, client: &Client, source: RecordingSource, config: RecordingConfig) -> Self {
+        let status = Arc::new(Mutex::new(RecordingStatus {
+            started: Utc::now(),
+            current_file: None,
+            files: 0,
+            frames: 0,
+            bytes: 0,
+            dropped: 0,
+            error: None,
+        }));
+        let cancel_token = CancellationToken::new();
+        let (frames_tx, frames_rx) = mpsc::sync_channel(WRITE_QUEUE_FRAMES);
+
+        info!("Recording '{}' to {}", source.server_name, config.directory.display());
+        runtime.spawn(forward(
+            client.subscribe_data(),
+            frames_tx,
+            source.server_name.clone(),
+            status.clone(),
+            cancel_token.clone(),
+        ));
+
+        let thread_status = status.clone();
+        std::thread::Builder::new()
+            .name("recording".to_string())
+            .spawn(move || write_captures(&frames_rx, &source, &config, &thread_status))
+            .expect("Failed to spawn recording thread");
+
+        Self { status, cancel_token }
+    }
+
+    pub fn status(&self) -> RecordingStatus {
+        self.status.lock().unwrap().clone()
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        self.cancel_token.cancel();
+    }
+}
+
+/// Capture file being written and when it was opened
+struct OpenCapture {
+    writer: CaptureWriter<Box<dyn Write + Send>>,
+    opened: DateTime<Utc>,
+}
+
+/// Pass the client's frames to the writer thread until cancelled, the client
+/// goes away or the writer stops
+async fn forward(
+    mut data_rx: broadcast::Receiver<ReceivedData>,
+    frames_tx: SyncSender<ReceivedData>,
+    server_name: String,
+    status: Arc<Mutex<RecordingStatus>>,
+    cancel_token: CancellationToken,
+) {
+    loop {
+        let received = tokio::select! {
+            () = cancel_token.cancelled() => break,
+            received = data_rx.recv() => received,
+        };
+        match received {
+            Ok(data) => match frames_tx.try_send(data) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => status.lock().unwrap().dropped += 1,
+                // The writer stopped on an error
+                Err(TrySendError::Disconnected(_)) => break,
+            },
+            Err(RecvError::Lagged(skipped)) => {
+                warn!("Recording of '{}' fell behind, dropped {} frames", server_name, skipped);
+                status.lock().unwrap().dropped += skipped;
+            }
+            // The client was replaced or shut down
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Write queued frames until the sender is dropped, then finish the last file
+fn write_captures(
+    frames_rx: &Receiver<ReceivedData>,
+    source: &RecordingSource,
+    config: &RecordingConfig,
+    status: &Mutex<RecordingStatus>,
+) {
+    let mut capture: Option<OpenCapture> = None;
+
+    let result = frames_rx
+        .iter()
+        .try_for_each(|data| write_record(&mut capture, &data, source, config, status))
+        .and_then(|()| match capture.take() {
+            Some(capture) => capture.writer.finish().and_then(|mut inner| inner.flush()),
+            None => Ok(()),
+        });
+    match result {
+        Ok(()) => info!("Stopped recording '{}'", source.server_name),
+        Err(e) => {
+            warn!("Recording of '{}' failed: {}", source.server_name, e);
+            status.lock().unwrap().error = Some(e.to_string());
+        }
+    }
+}
+
+/// Write one frame, opening or rotating the capture file as needed
+fn write_record(
+    capture: &mut Option<OpenCapture>,
+    data: &ReceivedData,
+    source: &RecordingSource,
+    config: &RecordingConfig,
+    status: &Mutex<RecordingStatus>,
+) -> io::Result<()> {
+    let rotate = capture.as_ref().is_some_and(|open| {
+        let too_big = config.rotate_megabytes
+            .is_some_and(|megabytes| open.writer.bytes_written() >= megabytes * 1024 * 1024);
+        let too_old = config.rotate_minutes
+            .and_then(|minutes| i64::try_from(minutes).ok())
+            .is_some_and(|minutes| data.timestamp - open.opened >= Duration::minutes(minutes));
+        too_big || too_old
+    });
+    if rotate {
+        if let Some(open) = capture.take() {
+            open.writer.finish()?.flush()?;
+        }
+    }
+
+    let open = match capture {
+        Some(open) => open,
+        None => {
+            let (path, open) = open_capture(config, source, data.timestamp)?;
+            info!("Recording '{}' to {}", source.server_name, path.display());
+            let mut status = status.lock().unwrap();
+            status.current_file = Some(path);
+            status.files += 1;
+            capture.insert(open)
+        }
+    };
+
+    let before = open.writer.bytes_written();
+    open.writer.write_record(data)?;
+    let mut status = status.lock().unwrap();
+    status.frames += 1;
+    status.bytes += open.writer.bytes_written() - before;
+    Ok(())
+}
+
+/// Create a new capture file, numbering it after any file already started the
+/// same second
+fn open_capture(config: &RecordingConfig, source: &RecordingSource, started: DateTime<Utc>) -> io::Result<(PathBuf, OpenCapture)> {
+    std::fs::create_dir_all(&config.directory)?;
+    let mut sequence = 0;
+    let (path, file) = loop {
+        let path = capture_path(&config.directory, &source.server_name, started, sequence, config.compress);
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => break (path, file),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => sequence += 1,
+            Err(e) => return Err(e),
+        }
+    };
+    let inner: Box<dyn Write + Send> = if config.compress {
+        Box::new(GzEncoder::new(file, Compression::default()))
+    } else {
+        Box::new(file)
+    };
+    let header = CaptureHeader {
+        server_name: source.server_name.clone(),
+        address: source.address.clone(),
+        protocol: source.protocol,
+        started,
+    };
+    let open = OpenCapture {
+        writer: CaptureWriter::new(inner, &header)?,
+        opened: started,
+    };
+    Ok((path, open))
+}
+
+/// `<server>-<YYYYmmdd-HHMMSS>[-<sequence>].ajcap[.gz]` in the recordings directory,
+/// the sequence number being left out when zero
+fn capture_path(directory: &Path, server_name: &str, started: DateTime<Utc>, sequence: u32, compress: bool) -> PathBuf {
+    let name: String = server_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let mut file_name = format!("{}-{}", name, started.format("%Y%m%d-%H%M%S"));
+    if sequence > 0 {
+        file_name.push_str(&format!("-{}", sequence));
+    }
+    file_name.push('.');
+    file_name.push_str(CAPTURE_EXTENSION);
+    if compress {
+        file_name.push_str(".gz");
+    }
+    directory.join(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recording::capture::CaptureReader;
+
+    #[test]
+    fn test_rotation() {
+        let directory = std::env::temp_dir().join(format!("recording-test-{}", uuid::Uuid::new_v4()));
+        let config = RecordingConfig {
+            directory: directory.clone(),
+            compress: true,
+            rotate_megabytes: None,
+            rotate_minutes: Some(1),
+        };
+        let source = RecordingSource {
+            server_name: "Local feed".to_string(),
+            address: "localhost:30005".to_string(),
+            protocol: ProtocolType::Beast,
+        };
+        let status = Mutex::new(RecordingStatus {
+            started: Utc::now(),
+            current_file: None,
+            files: 0,
+            frames: 0,
+            bytes: 0,
+            dropped: 0,
+            error: None,
+        });
+
+        // Frames two minutes apart land in separate files
+        let start = Utc::now();
+        let mut capture = None;
+        for minutes in [0, 2] {
+            let data = ReceivedData {
+                timestamp: start + Duration::minutes(minutes),
+                data: vec![0x8d, 0xa1, 0xb2, 0xc3],
+            };
+            write_record(&mut capture, &data, &source, &config, &status).unwrap();
+        }
+        capture.unwrap().writer.finish().unwrap().flush().unwrap();
+
+        let status = status.into_inner().unwrap();
+        assert_eq!(status.files, 2);
+        assert_eq!(status.frames, 2);
+
+        let path = status.current_file.unwrap();
+        assert!(path.file_name().unwrap().to_string_lossy().starts_with("Local_feed-"));
+        let mut reader = CaptureReader::open(&path).unwrap();
+        assert_eq!(reader.header().protocol, ProtocolType::Beast);
+        assert_eq!(reader.next_record().unwrap().unwrap().timestamp, start + Duration::minutes(2));
+        assert_eq!(reader.next_record().unwrap(), None);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_rotation_within_a_second() {
+        let directory = std::env::temp_dir().join(format!("recording-test-{}", uuid::Uuid::new_v4()));
+        let config = RecordingConfig {
+            directory: directory.clone(),
+            compress: false,
+            rotate_megabytes: None,
+            rotate_minutes: None,
+        };
+        let source = RecordingSource {
+            server_name: "feed".to_string(),
+            address: "localhost:30005".to_string(),
+            protocol: ProtocolType::Beast,
+        };
+
+        // A file started the same second gets the next number instead of replacing the first
+        let started = Utc::now();
+        let (first, _) = open_capture(&config, &source, started).unwrap();
+        let (second, _) = open_capture(&config, &source, started).unwrap();
+        assert_ne!(first, second);
+        assert!(second.to_string_lossy().ends_with(&format!("-1.{}", CAPTURE_EXTENSION)));
+        assert!(first.exists() && second.exists());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,595 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Replaying captures and plain feed logs.
+//!
+//! A [`Replay`] reads a capture written by a recording, or a plain SBS or
+//! BEAST log, and feeds it to a client created with
+//! [`adsb_client::Client::spawn_feed`] at the pace it was received, scaled by
+//! the playback speed. The client tracks every message at its recorded time,
+//! so trails, timeouts and position checks behave as they did live.
+//!
+//! Seeking clears the client and quickly feeds the few minutes before the new
+//! position, so the map shows the traffic as it was at that time.
+//!
+//! Plain logs carry their own times: SBS lines their generated date and time,
+//! BEAST frames a 12 MHz receiver counter, which is anchored so the log ends
+//! at the file's modification time.
+
+use adsb_client::protocol::{detect_format, BaseStationParser, BeastParser, DetectedFormat, Protocol};
+use adsb_client::{FeedEvent, Framing, ProtocolType, ReceivedData};
+use chrono::{DateTime, Duration, Utc};
+use log::{info, warn};
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::Instant;
+use tokio::sync::mpsc;
+
+use super::capture::{is_capture, open_decompressed, CaptureReader};
+
+/// Playback speeds offered by the timeline
+pub const SPEEDS: [f64; 7] = [0.5, 1.0, 2.0, 4.0, 8.0, 16.0, 32.0];
+
+/// Feed time replayed at once before a seek position
+const SEEK_WARMUP_SECS: i64 = 300;
+
+/// Longest wait between checks for play, pause and seek requests
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Bytes read to work out the kind of file
+const PROBE_BYTES: u64 = 16 * 1024;
+
+/// BEAST receiver clock ticks per microsecond
+const BEAST_TICKS_PER_MICRO: u64 = 12;
+
+/// BEAST counter jumps longer than this are taken as a receiver restart
+const MAX_BEAST_GAP_SECS: i64 = 3600;
+
+/// Kind of file being replayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayFormat {
+    Capture,
+    SbsLog,
+    BeastLog,
+}
+
+impl ReplayFormat {
+    /// Work out the kind of file and the protocol to parse it with
+    pub fn probe(path: &Path) -> io::Result<(Self, ProtocolType)> {
+        let mut start = Vec::new();
+        open_decompressed(path)?.take(PROBE_BYTES).read_to_end(&mut start)?;
+
+        if is_capture(&start) {
+            let protocol = CaptureReader::open(path)?.header().protocol;
+            return Ok((Self::Capture, protocol));
+        }
+        match detect_format(&start) {
+            Some(DetectedFormat::Sbs) => Ok((Self::SbsLog, ProtocolType::BaseStation)),
+            Some(DetectedFormat::Beast) => Ok((Self::BeastLog, ProtocolType::Beast)),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a capture, SBS log or BEAST log",
+            )),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Capture => "Capture",
+            Self::SbsLog => "SBS log",
+            Self::BeastLog => "BEAST log",
+        }
+    }
+}
+
+/// The records of a file with the time each was received
+enum Records {
+    Capture(CaptureReader<Box<dyn Read + Send>>),
+    Sbs {
+        lines: BufReader<Box<dyn Read + Send>>,
+        parser: BaseStationParser,
+        /// Latest time seen in the log
+        time: Option<DateTime<Utc>>,
+        /// Time of lines before the first timestamp
+        anchor: DateTime<Utc>,
+    },
+    Beast {
+        inner: Box<dyn Read + Send>,
+        buf: Vec<u8>,
+        clock: BeastClock,
+    },
+}
+
+impl Records {
+    /// Open a file from the start. `anchor` is the time plain logs start
+    /// from until they provide one.
+    fn open(path: &Path, format: ReplayFormat, anchor: DateTime<Utc>) -> io::Result<Self> {
+        Ok(match format {
+            ReplayFormat::Capture => Self::Capture(CaptureReader::open(path)?),
+            ReplayFormat::SbsLog => Self::Sbs {
+                lines: BufReader::new(open_decompressed(path)?),
+                parser: BaseStationParser::new(),
+                time: None,
+                anchor,
+            },
+            ReplayFormat::BeastLog => Self::Beast {
+                inner: open_decompressed(path)?,
+                buf: Vec::new(),
+                clock: BeastClock::new(anchor),
+            },
+        })
+    }
+
+    fn next_record(&mut self) -> io::Result<Option<ReceivedData>> {
+        match self {
+            Self::Capture(reader) => reader.next_record(),
+            Self::Sbs { lines, parser, time, anchor } => loop {
+                let mut line = Vec::new();
+                if lines.read_until(b'\n', &mut line)? == 0 {
+                    return Ok(None);
+                }
+                while matches!(line.last(), Some(b'\n' | b'\r')) {
+                    line.pop();
+                }
+                if line.is_empty() {
+                    continue;
+                }
+
+                // Lines are replayed in order, so a timestamp slightly behind the
+                // last one does not move the clock back
+                if let Some(timestamp) = parser.parse(&line).ok().flatten().and_then(|msg| msg.timestamp()) {
+                    *time = Some(time.map_or(timestamp, |last| last.max(timestamp)));
+                }
+                return Ok(Some(ReceivedData {
+                    timestamp: time.unwrap_or(*anchor),
+                    data: line,
+                }));
+            },
+            Self::Beast { inner, buf, clock } => loop {
+                if let Some(frame) = Framing::Beast.next_frame(buf) {
+                    let ticks = BeastParser::parse_frame(&frame).map_or(0, |frame| frame.timestamp);
+                    return Ok(Some(ReceivedData {
+                        timestamp: clock.time(ticks),
+                        data: frame,
+                    }));
+                }
+                let mut chunk = [0u8; 8192];
+                let read = inner.read(&mut chunk)?;
+                if read == 0 {
+                    return Ok(None);
+                }
+                buf.extend_from_slice(&chunk[..read]);
+            },
+        }
+    }
+}
+
+/// Turns the 12 MHz receiver counter of BEAST frames into times
+struct BeastClock {
+    /// Counter value and time the current run of frames is measured from
+    base: Option<(u64, DateTime<Utc>)>,
+    last: DateTime<Utc>,
+}
+
+impl BeastClock {
+    fn new(anchor: DateTime<Utc>) -> Self {
+        Self { base: None, last: anchor }
+    }
+
+    fn time(&mut self, ticks: u64) -> DateTime<Utc> {
+        // Frames without a receiver time (e.g. MLAT results) keep the last one
+        if ticks == 0 {
+            return self.last;
+        }
+        let (base_ticks, base_time) = *self.base.get_or_insert((ticks, self.last));
+        let elapsed = ticks
+            .checked_sub(base_ticks)
+            .and_then(|ticks| i64::try_from(ticks / BEAST_TICKS_PER_MICRO).ok())
+            .map(Duration::microseconds)
+            .filter(|elapsed| elapsed.num_seconds() < MAX_BEAST_GAP_SECS);
+        match elapsed {
+            Some(elapsed) => self.last = base_time + elapsed,
+            // The receiver restarted: carry on from the last time
+            None => self.base = Some((ticks, self.last)),
+        }
+        self.last
+    }
+}
+
+/// Read a whole file for the times of its first and last records
+fn scan(path: &Path, format: ReplayFormat, anchor: DateTime<Utc>) -> io::Result<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut records = Records::open(path, format, anchor)?;
+    let mut span: Option<(DateTime<Utc>, DateTime<Utc>)> = None;
+    while let Some(record) = records.next_record()? {
+        let time = record.timestamp;
+        span = Some(span.map_or((time, time), |(first, last)| (first.min(time), last.max(time))));
+    }
+    span.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "nothing to replay"))
+}
+
+/// What the timeline shows about a replay
+#[derive(Debug, Clone)]
+pub struct ReplayStatus {
+    pub format: ReplayFormat,
+    /// Times of the first and last records, once the file has been read
+    pub span: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    /// Current replay time
+    pub position: DateTime<Utc>,
+    pub playing: bool,
+    pub speed: f64,
+    /// Whether the end of the file was reached
+    pub finished: bool,
+    /// Error that stopped the replay
+    pub error: Option<String>,
+}
+
+struct State {
+    format: ReplayFormat,
+    span: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    /// Replay time at `anchor`
+    position: DateTime<Utc>,
+    /// Wall time `position` was set, from which the clock runs while playing
+    anchor: Instant,
+    playing: bool,
+    speed: f64,
+    finished: bool,
+    seek: Option<DateTime<Utc>>,
+    stopped: bool,
+    error: Option<String>,
+}
+
+impl State {
+    fn clock(&self) -> DateTime<Utc> {
+        if !self.playing || self.finished {
+            return self.position;
+        }
+        let elapsed = self.anchor.elapsed().as_secs_f64() * self.speed;
+        let time = self.position + Duration::microseconds((elapsed * 1e6) as i64);
+        self.span.map_or(time, |(_, end)| time.min(end))
+    }
+
+    /// Restart the clock from its current time, before changing how it runs
+    fn reanchor(&mut self) {
+        self.position = self.clock();
+        self.anchor = Instant::now();
+    }
+}
+
+struct Shared {
+    state: Mutex<State>,
+    wake: Condvar,
+}
+
+/// Controls of a running replay, shared with the UI
+#[derive(Clone)]
+pub struct ReplayControl {
+    shared: Arc<Shared>,
+}
+
+impl ReplayControl {
+    pub fn status(&self) -> ReplayStatus {
+        let state = self.lock();
+        ReplayStatus {
+            format: state.format,
+            span: state.span,
+            position: state.clock(),
+            playing: state.playing,
+            speed: state.speed,
+            finished: state.finished,
+            error: state.error.clone(),
+        }
+    }
+
+    /// Current replay time, advancing smoothly between records while playing
+    pub fn clock(&self) -> DateTime<Utc> {
+        self.lock().clock()
+    }
+
+    /// Play from the current position, or from the start once finished
+    pub fn play(&self) {
+        self.update(|state| {
+            if state.finished {
+                state.seek = state.span.map(|(start, _)| start);
+            }
+            state.reanchor();
+            state.playing = true;
+        });
+    }
+
+    pub fn pause(&self) {
+        self.update(|state| {
+            state.reanchor();
+            state.playing = false;
+        });
+    }
+
+    /// Jump to a time within the replay
+    pub fn seek(&self, time: DateTime<Utc>) {
+        self.update(|state| {
+            let time = state.span.map_or(time, |(start, end)| time.clamp(start, end));
+            state.seek = Some(time);
+            state.position = time;
+            state.anchor = Instant::now();
+            state.finished = false;
+        });
+    }
+
+    /// Change the playback speed, within the range the timeline offers
+    pub fn set_speed(&self, speed: f64) {
+        self.update(|state| {
+            state.reanchor();
+            state.speed = speed.clamp(SPEEDS[0], SPEEDS[SPEEDS.len() - 1]);
+        });
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.shared.state.lock().unwrap()
+    }
+
+    fn update(&self, f: impl FnOnce(&mut State)) {
+        f(&mut self.lock());
+        self.shared.wake.notify_all();
+    }
+}
+
+/// A file being replayed into a client, stopped when dropped
+pub struct Replay {
+    control: ReplayControl,
+}
+
+impl Replay {
+    /// Start replaying a file probed with [`ReplayFormat::probe`], playing at normal speed
+    pub fn spawn(path: PathBuf, format: ReplayFormat, feed_tx: mpsc::Sender<FeedEvent>) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                format,
+                span: None,
+                position: Utc::now(),
+                anchor: Instant::now(),
+                playing: true,
+                speed: 1.0,
+                finished: false,
+                seek: None,
+                stopped: false,
+                error: None,
+            }),
+            wake: Condvar::new(),
+        });
+
+        let thread_shared = shared.clone();
+        std::thread::Builder::new()
+            .name("replay".to_string())
+            .spawn(move || {
+                info!("Replaying {} {}", format.label(), path.display());
+                if let Err(e) = run(&path, format, &feed_tx, &thread_shared) {
+                    warn!("Replay of {} failed: {}", path.display(), e);
+                    let mut state = thread_shared.state.lock().unwrap();
+                    state.reanchor();
+                    state.playing = false;
+                    state.error = Some(e.to_string());
+                }
+            })
+            .expect("Failed to spawn replay thread");
+
+        Self {
+            control: ReplayControl { shared },
+        }
+    }
+
+    pub fn control(&self) -> ReplayControl {
+        self.control.clone()
+    }
+
+    /// Current replay time, see [`ReplayControl::clock`]
+    pub fn clock(&self) -> DateTime<Utc> {
+        self.control.clock()
+    }
+}
+
+impl Drop for Replay {
+    fn drop(&mut self) {
+        self.control.update(|state| state.stopped = true);
+    }
+}
+
+/// Feed a file to the client, following the controls until stopped
+fn run(path: &Path, format: ReplayFormat, feed_tx: &mpsc::Sender<FeedEvent>, shared: &Shared) -> io::Result<()> {
+    // BEAST counters only give relative times: end the log at its modification time
+    let modified: DateTime<Utc> = std::fs::metadata(path)?
+        .modified()
+        .map_or_else(|_| Utc::now(), DateTime::from);
+    let (mut start, mut end) = scan(path, format, modified)?;
+    let mut anchor = modified;
+    if format == ReplayFormat::BeastLog {
+        let shift = end - modified;
+        anchor = modified - shift;
+        start -= shift;
+        end -= shift;
+    }
+
+    {
+        let mut state = shared.state.lock().unwrap();
+        state.span = Some((start, end));
+        // A seek made while reading is handled below
+        state.position = state.seek.unwrap_or(start).clamp(start, end);
+        state.anchor = Instant::now();
+    }
+
+    let mut records = Records::open(path, format, anchor)?;
+    let mut pending: Option<ReceivedData> = None;
+    loop {
+        let mut state = shared.state.lock().unwrap();
+        if state.stopped {
+            return Ok(());
+        }
+
+        if let Some(target) = state.seek.take() {
+            drop(state);
+            if feed_tx.blocking_send(FeedEvent::Reset).is_err() {
+                return Ok(());
+            }
+            records = Records::open(path, format, anchor)?;
+            pending = match fast_forward(&mut records, target, feed_tx, shared)? {
+                Some(pending) => pending,
+                None => return Ok(()),
+            };
+
+            // Run the clock from the target once the client has caught up
+            let mut state = shared.state.lock().unwrap();
+            if state.seek.is_none() {
+                state.position = target.clamp(start, end);
+                state.anchor = Instant::now();
+            }
+            continue;
+        }
+
+        if state.finished || !state.playing {
+            let _ = shared.wake.wait_timeout(state, POLL_INTERVAL).unwrap();
+            continue;
+        }
+
+        let Some(record) = &pending else {
+            drop(state);
+            pending = records.next_record()?;
+            let mut state = shared.state.lock().unwrap();
+            if pending.is_none() && state.seek.is_none() {
+                state.position = end;
+                state.playing = false;
+                state.finished = true;
+            }
+            continue;
+        };
+
+        // Wait for the clock to reach the record, waking early for new controls
+        let ahead = (record.timestamp - state.clock()).to_std().unwrap_or_default();
+        if !ahead.is_zero() {
+            let wait = ahead.div_f64(state.speed).min(POLL_INTERVAL);
+            let _ = shared.wake.wait_timeout(state, wait).unwrap();
+            continue;
+        }
+        drop(state);
+
+        if let Some(record) = pending.take() {
+            if feed_tx.blocking_send(FeedEvent::Data(record)).is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Feed the records shortly before `target` at once and return the first one
+/// from `target` on, or `None` if the replay stopped meanwhile
+fn fast_forward(
+    records: &mut Records,
+    target: DateTime<Utc>,
+    feed_tx: &mpsc::Sender<FeedEvent>,
+    shared: &Shared,
+) -> io::Result<Option<Option<ReceivedData>>> {
+    let warmup_from = target - Duration::seconds(SEEK_WARMUP_SECS);
+    let mut count = 0u64;
+    while let Some(record) = records.next_record()? {
+        if record.timestamp >= target {
+            return Ok(Some(Some(record)));
+        }
+
+        count += 1;
+        if count.is_multiple_of(4096) && shared.state.lock().unwrap().stopped {
+            return Ok(None);
+        }
+        if record.timestamp >= warmup_from && feed_tx.blocking_send(FeedEvent::Data(record)).is_err() {
+            return Ok(None);
+        }
+    }
+    Ok(Some(None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SBS_LOG: &str = "\
+MSG,1,1,1,A1B2C3,1,2024/01/01,12:00:00.000,2024/01/01,12:00:00.000,UAL123\r\n\
+MSG,3,1,1,A1B2C3,1,2024/01/01,12:00:01.000,2024/01/01,12:00:01.000,,35000,,,37.0,-122.0,,,0,0,0,0\r\n\
+\r\n\
+MSG,3,1,1,A1B2C3,1,2024/01/01,12:00:02.000,2024/01/01,12:00:02.000,,35000,,,37.01,-122.0,,,0,0,0,0\r\n";
+
+    fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("replay-test-{}-{}", uuid::Uuid::new_v4(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    /// A short Mode S BEAST frame at a receiver time
+    fn beast_frame(ticks: u64) -> Vec<u8> {
+        let mut frame = vec![0x1a, b'2'];
+        frame.extend_from_slice(&ticks.to_be_bytes()[2..]);
+        frame.extend_from_slice(&[0x80, 0x5d, 0x4c, 0xa2, 0x15, 0x00, 0x00, 0x00]);
+        frame
+    }
+
+    #[test]
+    fn test_plain_log_times() {
+        let path = temp_file("sbs.log", SBS_LOG.as_bytes());
+        assert_eq!(ReplayFormat::probe(&path).unwrap(), (ReplayFormat::SbsLog, ProtocolType::BaseStation));
+        let (start, end) = scan(&path, ReplayFormat::SbsLog, Utc::now()).unwrap();
+        assert_eq!(end - start, Duration::seconds(2));
+        std::fs::remove_file(&path).unwrap();
+
+        // Receiver counter at 12 MHz, restarting after the second frame
+        let log: Vec<u8> = [12_000_000, 36_000_000, 6_000_000]
+            .into_iter()
+            .flat_map(beast_frame)
+            .collect();
+        let path = temp_file("beast.bin", &log);
+        assert_eq!(ReplayFormat::probe(&path).unwrap(), (ReplayFormat::BeastLog, ProtocolType::Beast));
+        let anchor = Utc::now();
+        let mut records = Records::open(&path, ReplayFormat::BeastLog, anchor).unwrap();
+        let times: Vec<_> = std::iter::from_fn(|| records.next_record().unwrap())
+            .map(|record| record.timestamp - anchor)
+            .collect();
+        assert_eq!(times, [Duration::zero(), Duration::seconds(2), Duration::seconds(2)]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_replay_and_seek() {
+        let path = temp_file("sbs.log", SBS_LOG.as_bytes());
+        let (feed_tx, mut feed_rx) = mpsc::channel(16);
+        let replay = Replay::spawn(path.clone(), ReplayFormat::SbsLog, feed_tx);
+        let control = replay.control();
+        control.set_speed(32.0);
+
+        let mut times = Vec::new();
+        for _ in 0..3 {
+            match feed_rx.blocking_recv() {
+                Some(FeedEvent::Data(record)) => times.push(record.timestamp),
+                event => panic!("unexpected {:?}", event),
+            }
+        }
+        assert!(times.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        // Seeking clears the client, then replays the minutes before the position
+        control.seek(times[2]);
+        control.play();
+        assert!(matches!(feed_rx.blocking_recv(), Some(FeedEvent::Reset)));
+        for _ in 0..3 {
+            assert!(matches!(feed_rx.blocking_recv(), Some(FeedEvent::Data(_))));
+        }
+
+        drop(replay);
+        assert!(feed_rx.blocking_recv().is_none());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
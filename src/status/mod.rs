@@ -4,5 +4,5 @@
 
 pub mod system;
 
-pub use system::{SystemStatus, SharedSystemStatus, ConnectionStatus, DiagnosticLevel, ServerStatus};
+pub use system::{SystemStatus, SharedSystemStatus, ConnectionStatus, DiagnosticLevel, EmergencyAlert, ServerStatus};
 
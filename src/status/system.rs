@@ -18,10 +18,13 @@
 //! per-server connection status, message statistics, diagnostic messages, and
 //! performance metrics. It supports sparkline visualization of position update rates.
 
+use adsb_client::{DetectedFormat, Emergency};
 use chrono::{DateTime, Utc};
 use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, Mutex};
 
+use crate::aircraft::display_name;
+
 /// Connection status for ADS-B feed
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ConnectionStatus {
@@ -46,6 +49,29 @@ pub enum DiagnosticLevel {
     Error,
 }
 
+/// An aircraft emergency awaiting acknowledgement in the UI
+#[derive(Debug, Clone)]
+pub struct EmergencyAlert {
+    /// Increasing alert number, used by the UI to spot new alerts
+    pub id: u64,
+    pub icao: String,
+    pub callsign: Option<String>,
+    pub emergency: Emergency,
+    /// Name of the server that reported the emergency
+    pub server_name: String,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub altitude: Option<i32>,
+    pub detected_at: DateTime<Utc>,
+}
+
+impl EmergencyAlert {
+    /// Name to show for the aircraft
+    pub fn display_name(&self) -> String {
+        display_name(self.callsign.as_deref(), &self.icao)
+    }
+}
+
 /// Per-server connection status and statistics
 #[derive(Debug, Clone)]
 pub struct ServerStatus {
@@ -76,6 +102,15 @@ pub struct ServerStatus {
 
     /// Last time a message was received
     pub last_message_at: Option<DateTime<Utc>>,
+
+    /// Mode S frames repaired by CRC error correction (BEAST/AVR feeds)
+    pub frames_corrected: u64,
+
+    /// Mode S frames dropped for bad parity or unknown address (BEAST/AVR feeds)
+    pub frames_dropped: u64,
+
+    /// Feed format detected on the current connection (auto-detect servers)
+    pub detected_format: Option<DetectedFormat>,
 }
 
 impl ServerStatus {
@@ -91,6 +126,9 @@ impl ServerStatus {
             aircraft_count: 0,
             connected_at: None,
             last_message_at: None,
+            frames_corrected: 0,
+            frames_dropped: 0,
+            detected_format: None,
         }
     }
 
@@ -146,6 +184,10 @@ pub struct SystemStatus {
     pub diagnostics: VecDeque<DiagnosticMessage>,
     max_diagnostics: usize,
 
+    // Active emergencies, one per aircraft, until dismissed
+    pub emergency_alerts: Vec<EmergencyAlert>,
+    next_emergency_id: u64,
+
     // Performance metrics
     pub last_update_duration_ms: f64,
     pub average_update_duration_ms: f64,
@@ -186,6 +228,9 @@ impl SystemStatus {
             diagnostics: VecDeque::with_capacity(50),
             max_diagnostics: 50,
 
+            emergency_alerts: Vec::new(),
+            next_emergency_id: 1,
+
             last_update_duration_ms: 0.0,
             average_update_duration_ms: 0.0,
         }
@@ -319,6 +364,46 @@ impl SystemStatus {
         }
     }
 
+    /// Record an aircraft emergency, replacing any earlier alert for the same aircraft
+    ///
+    /// An emergency that is already active for the aircraft (e.g. when several servers
+    /// see it) is ignored.
+    pub fn record_emergency(&mut self, mut alert: EmergencyAlert) {
+        if self.emergency_alerts.iter()
+            .any(|active| active.icao == alert.icao && active.emergency == alert.emergency)
+        {
+            return;
+        }
+        self.emergency_alerts.retain(|active| active.icao != alert.icao);
+
+        let position = match (alert.latitude, alert.longitude) {
+            (Some(lat), Some(lon)) => format!("{:.4}°, {:.4}°", lat, lon),
+            _ => "unknown position".to_string(),
+        };
+        let altitude = alert.altitude
+            .map(|alt| format!(", {} ft", alt))
+            .unwrap_or_default();
+        self.add_diagnostic(DiagnosticLevel::Error, format!(
+            "[{}] EMERGENCY {} ({}): {} at {}{} ({})",
+            alert.server_name,
+            alert.display_name(),
+            alert.icao,
+            alert.emergency.label(),
+            position,
+            altitude,
+            alert.detected_at.format("%H:%M:%S UTC"),
+        ));
+
+        alert.id = self.next_emergency_id;
+        self.next_emergency_id += 1;
+        self.emergency_alerts.push(alert);
+    }
+
+    /// Acknowledge an emergency alert, removing it from the banner
+    pub fn dismiss_emergency(&mut self, icao: &str) {
+        self.emergency_alerts.retain(|alert| alert.icao != icao);
+    }
+
     /// Update connection uptime
     pub fn update_uptime(&mut self) {
         if self.connection_status == ConnectionStatus::Connected {
@@ -369,6 +454,7 @@ impl SystemStatus {
                         format!("[{}] Connected to {}", server_status.server_name, server_status.server_address)))
                 }
                 ConnectionStatus::Connecting => {
+                    server_status.detected_format = None;
                     Some((DiagnosticLevel::Info,
                         format!("[{}] Connecting to {}...", server_status.server_name, server_status.server_address)))
                 }
@@ -411,14 +497,39 @@ impl SystemStatus {
         }
     }
 
-    /// Increment message count for a server
-    pub fn increment_server_message_count(&mut self, server_id: &str) {
+    /// Add newly received messages to a server's count
+    pub fn add_server_message_count(&mut self, server_id: &str, count: u64) {
+        if count == 0 {
+            return;
+        }
         if let Some(server_status) = self.servers.get_mut(server_id) {
-            server_status.message_count += 1;
+            server_status.message_count += count;
             server_status.last_message_at = Some(Utc::now());
         }
     }
 
+    /// Add CRC corrected/dropped frame counts for a server
+    pub fn add_server_frame_stats(&mut self, server_id: &str, corrected: u64, dropped: u64) {
+        if let Some(server_status) = self.servers.get_mut(server_id) {
+            server_status.frames_corrected += corrected;
+            server_status.frames_dropped += dropped;
+        }
+    }
+
+    /// Record the feed format detected on a server's connection
+    pub fn set_server_detected_format(&mut self, server_id: &str, format: DetectedFormat) {
+        let diagnostic_message = if let Some(server_status) = self.servers.get_mut(server_id) {
+            server_status.detected_format = Some(format);
+            Some(format!("[{}] Detected {} feed", server_status.server_name, format.label()))
+        } else {
+            None
+        };
+
+        if let Some(message) = diagnostic_message {
+            self.add_diagnostic(DiagnosticLevel::Info, message);
+        }
+    }
+
     /// Update aircraft count for a server
     pub fn update_server_aircraft_count(&mut self, server_id: &str, count: usize) {
         if let Some(server_status) = self.servers.get_mut(server_id) {
@@ -0,0 +1,112 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Emergency banner.
+//!
+//! Lists every aircraft signalling an emergency at the top of the window
+//! until it is dismissed, and can select new emergencies as they come in.
+
+use egui;
+
+use crate::status::SharedSystemStatus;
+
+const BANNER_COLOR: egui::Color32 = egui::Color32::from_rgb(140, 20, 20);
+
+#[derive(Default)]
+pub struct EmergencyBanner {
+    /// Newest emergency alert already handled (for auto-select)
+    last_alert_id: u64,
+}
+
+impl EmergencyBanner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Render the banner while any emergency is active
+    ///
+    /// Returns the ICAO of the aircraft to select: one picked in the banner,
+    /// or the newest emergency when `auto_select` is set.
+    pub fn render(&mut self, ctx: &egui::Context, status: &SharedSystemStatus, auto_select: bool) -> Option<String> {
+        let alerts = status.lock().unwrap().emergency_alerts.clone();
+        let mut select_icao = None;
+
+        // Select the newest emergency once, if enabled
+        if let Some(newest) = alerts.iter()
+            .filter(|alert| alert.id > self.last_alert_id)
+            .max_by_key(|alert| alert.id)
+        {
+            if auto_select {
+                select_icao = Some(newest.icao.clone());
+            }
+            self.last_alert_id = newest.id;
+        }
+
+        if alerts.is_empty() {
+            return select_icao;
+        }
+
+        let mut dismiss_icao = None;
+
+        egui::TopBottomPanel::top("emergency_banner")
+            .frame(egui::Frame::NONE
+                .fill(BANNER_COLOR)
+                .inner_margin(egui::Margin::symmetric(8, 4)))
+            .show(ctx, |ui| {
+                for alert in &alerts {
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new("⚠ EMERGENCY")
+                            .color(egui::Color32::WHITE)
+                            .size(12.0)
+                            .strong());
+                        ui.label(egui::RichText::new(format!("{} ({})", alert.display_name(), alert.icao))
+                            .color(egui::Color32::WHITE)
+                            .size(12.0)
+                            .monospace());
+                        ui.label(egui::RichText::new(alert.emergency.label())
+                            .color(egui::Color32::from_rgb(255, 220, 100))
+                            .size(12.0)
+                            .strong());
+
+                        let mut details = Vec::new();
+                        if let Some(alt) = alert.altitude {
+                            details.push(format!("{} ft", alt));
+                        }
+                        if let (Some(lat), Some(lon)) = (alert.latitude, alert.longitude) {
+                            details.push(format!("{:.4}°, {:.4}°", lat, lon));
+                        }
+                        details.push(alert.detected_at.format("%H:%M:%SZ").to_string());
+                        details.push(alert.server_name.clone());
+                        ui.label(egui::RichText::new(details.join("  ·  "))
+                            .color(egui::Color32::from_rgb(230, 200, 200))
+                            .size(10.0));
+
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.button("Dismiss").clicked() {
+                                dismiss_icao = Some(alert.icao.clone());
+                            }
+                            if ui.button("Select").clicked() {
+                                select_icao = Some(alert.icao.clone());
+                            }
+                        });
+                    });
+                }
+            });
+
+        if let Some(icao) = dismiss_icao {
+            status.lock().unwrap().dismiss_emergency(&icao);
+        }
+        select_icao
+    }
+}
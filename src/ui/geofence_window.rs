@@ -0,0 +1,395 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Geofences window and zone drawing.
+//!
+//! Lists the zones with their rules and the recent hits, and lets new zones
+//! be drawn on the map. Edited zones are pushed to the geofence monitor; the
+//! caller saves the config.
+
+use adsb_client::tracker::haversine_distance_nm;
+use egui::{Color32, RichText};
+
+use crate::geofence::{GeofenceConfig, SharedGeofenceMonitor, Zone, ZoneRule, ZoneShape};
+
+/// Zone being drawn on the map
+#[derive(Debug, Clone, PartialEq)]
+pub enum ZoneDrawing {
+    /// Vertices placed so far (lat, lon); finished with a double-click or Enter
+    Polygon(Vec<(f64, f64)>),
+    /// Center (lat, lon) once placed; the next click sets the radius
+    Circle(Option<(f64, f64)>),
+}
+
+impl ZoneDrawing {
+    /// Points placed so far (lat, lon)
+    pub fn placed(&self) -> Vec<(f64, f64)> {
+        match self {
+            ZoneDrawing::Polygon(points) => points.clone(),
+            ZoneDrawing::Circle(center) => center.iter().copied().collect(),
+        }
+    }
+
+    /// Shape the zone would have with the pointer at `hover` (lat, lon)
+    pub fn preview(&self, hover: Option<(f64, f64)>) -> Option<ZoneShape> {
+        match self {
+            ZoneDrawing::Polygon(points) => {
+                let mut points = points.clone();
+                points.extend(hover);
+                Some(ZoneShape::Polygon { points })
+            }
+            ZoneDrawing::Circle(center) => center.zip(hover).map(|(center, edge)| ZoneShape::Circle {
+                center,
+                radius_nm: haversine_distance_nm(center.0, center.1, edge.0, edge.1),
+            }),
+        }
+    }
+
+    /// Map instructions while drawing
+    pub fn instructions(&self) -> &'static str {
+        match self {
+            ZoneDrawing::Polygon(_) => {
+                "Drawing zone: click to add points | Double-click or Enter to finish | Esc to cancel"
+            }
+            ZoneDrawing::Circle(_) => "Drawing zone: click the center, then the edge | Esc to cancel",
+        }
+    }
+}
+
+/// Draw a zone outline with an optional name label
+pub fn draw_zone_shape(
+    painter: &egui::Painter,
+    shape: &ZoneShape,
+    color: Color32,
+    label: Option<&str>,
+    to_screen: &dyn Fn(f64, f64) -> egui::Pos2,
+) {
+    let stroke = egui::Stroke::new(2.0, color);
+    let label_pos = match shape {
+        ZoneShape::Polygon { points } => {
+            let screen_points: Vec<egui::Pos2> = points.iter()
+                .map(|&(lat, lon)| to_screen(lat, lon))
+                .collect();
+            match screen_points.len() {
+                0 => return,
+                1 => {
+                    painter.circle_filled(screen_points[0], 3.0, color);
+                }
+                2 => {
+                    painter.line_segment([screen_points[0], screen_points[1]], stroke);
+                }
+                _ => {
+                    painter.add(egui::Shape::closed_line(screen_points.clone(), stroke));
+                }
+            }
+            // Label at the centroid of the vertices
+            let sum = screen_points.iter().fold(egui::Vec2::ZERO, |acc, pos| acc + pos.to_vec2());
+            (sum / screen_points.len() as f32).to_pos2()
+        }
+        ZoneShape::Circle { center, radius_nm } => {
+            // One nautical mile is one minute of latitude
+            let center_pos = to_screen(center.0, center.1);
+            let edge_pos = to_screen(center.0 + radius_nm / 60.0, center.1);
+            let radius = center_pos.distance(edge_pos);
+            painter.circle(center_pos, radius, color.gamma_multiply(0.1), stroke);
+            center_pos
+        }
+    };
+
+    if let Some(label) = label {
+        painter.text(
+            label_pos,
+            egui::Align2::CENTER_CENTER,
+            label,
+            egui::FontId::proportional(11.0),
+            color,
+        );
+    }
+}
+
+/// What the user did in the geofences window
+#[derive(Debug, Default)]
+pub struct GeofenceWindowResponse {
+    /// Zones were edited and need saving
+    pub changed: bool,
+    /// Aircraft picked from the recent hits
+    pub select_icao: Option<String>,
+}
+
+#[derive(Default)]
+pub struct GeofenceWindow {
+    open: bool,
+    drawing: Option<ZoneDrawing>,
+}
+
+impl GeofenceWindow {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    /// Zone being drawn on the map, if any
+    pub fn drawing(&self) -> Option<&ZoneDrawing> {
+        self.drawing.as_ref()
+    }
+
+    /// Place zone points from map clicks, finish with a double-click or Enter, cancel with Escape
+    ///
+    /// A finished zone is added to the config and the window opened to edit
+    /// its rules. Returns whether the zones changed.
+    pub fn handle_drawing_input(
+        &mut self,
+        ctx: &egui::Context,
+        clicked_position: Option<(f64, f64)>,
+        config: &mut GeofenceConfig,
+        monitor: &SharedGeofenceMonitor,
+    ) -> bool {
+        let (double_clicked, enter_pressed, escape_pressed) = ctx.input(|i| (
+            i.pointer.button_double_clicked(egui::PointerButton::Primary),
+            i.key_pressed(egui::Key::Enter),
+            i.key_pressed(egui::Key::Escape),
+        ));
+
+        if escape_pressed {
+            self.drawing = None;
+            return false;
+        }
+
+        let finished_shape = match &mut self.drawing {
+            Some(ZoneDrawing::Polygon(points)) => {
+                // The first click of a double-click already placed the last vertex
+                if let (Some(position), false) = (clicked_position, double_clicked) {
+                    points.push(position);
+                }
+                ((double_clicked || enter_pressed) && points.len() >= 3)
+                    .then(|| ZoneShape::Polygon { points: points.clone() })
+            }
+            Some(ZoneDrawing::Circle(center)) => match (*center, clicked_position) {
+                (None, Some(position)) => {
+                    *center = Some(position);
+                    None
+                }
+                (Some(center), Some(edge)) => {
+                    let radius_nm = haversine_distance_nm(center.0, center.1, edge.0, edge.1);
+                    (radius_nm > 0.0).then_some(ZoneShape::Circle { center, radius_nm })
+                }
+                _ => None,
+            },
+            None => None,
+        };
+
+        let Some(shape) = finished_shape else {
+            return false;
+        };
+        self.drawing = None;
+        let index = config.zones.len();
+        config.zones.push(Zone::new(format!("Zone {}", index + 1), shape, index));
+        monitor.lock().unwrap().set_zones(config.zones.clone());
+        self.open = true;
+        true
+    }
+
+    /// Zone list, zone rule editor and recent hits
+    ///
+    /// Edited zones are pushed to the monitor before returning.
+    pub fn render(
+        &mut self,
+        ctx: &egui::Context,
+        config: &mut GeofenceConfig,
+        monitor: &SharedGeofenceMonitor,
+    ) -> GeofenceWindowResponse {
+        let mut response = GeofenceWindowResponse::default();
+        if !self.open {
+            return response;
+        }
+        let mut open = self.open;
+        let mut changed = false;
+
+        egui::Window::new("Geofences")
+            .resizable(true)
+            .collapsible(false)
+            .default_width(360.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                changed |= ui.checkbox(&mut config.show_zones, "Show zones on map").changed();
+
+                ui.add_space(4.0);
+
+                // Drawing controls
+                ui.horizontal(|ui| {
+                    match &self.drawing {
+                        None => {
+                            if ui.button("✏ Draw Polygon").clicked() {
+                                self.drawing = Some(ZoneDrawing::Polygon(Vec::new()));
+                            }
+                            if ui.button("◯ Draw Circle").clicked() {
+                                self.drawing = Some(ZoneDrawing::Circle(None));
+                            }
+                        }
+                        Some(drawing) => {
+                            let hint = match drawing {
+                                ZoneDrawing::Polygon(_) => "Click to add points, double-click or Enter to finish",
+                                ZoneDrawing::Circle(None) => "Click the center",
+                                ZoneDrawing::Circle(Some(_)) => "Click to set the radius",
+                            };
+                            ui.label(RichText::new(hint)
+                                .color(Color32::from_rgb(255, 200, 100))
+                                .size(10.0));
+                            if ui.button("Cancel").clicked() {
+                                self.drawing = None;
+                            }
+                        }
+                    }
+                });
+
+                ui.add_space(8.0);
+                ui.separator();
+
+                changed |= Self::render_zones(ui, config);
+
+                ui.separator();
+
+                // Recent hits, newest first
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("Recent Hits")
+                        .color(Color32::from_rgb(150, 200, 200))
+                        .size(10.0)
+                        .strong());
+                    if ui.small_button("Clear").clicked() {
+                        monitor.lock().unwrap().clear_hits();
+                    }
+                });
+
+                let hits: Vec<_> = monitor.lock().unwrap().recent_hits().iter().rev().take(100).cloned().collect();
+                egui::ScrollArea::vertical()
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        for hit in &hits {
+                            let [r, g, b] = hit.zone_color;
+                            let text = format!(
+                                "{}  {} ({})  {}: {}{}",
+                                hit.timestamp.format("%H:%M:%SZ"),
+                                hit.display_name(),
+                                hit.icao,
+                                hit.zone_name,
+                                hit.rule.describe(),
+                                hit.altitude.map(|alt| format!(", {} ft", alt)).unwrap_or_default(),
+                            );
+                            if ui.add(egui::Label::new(RichText::new(text)
+                                .color(Color32::from_rgb(r, g, b))
+                                .size(9.0)
+                                .monospace())
+                                .sense(egui::Sense::click()))
+                                .on_hover_text(hit.describe())
+                                .clicked()
+                            {
+                                response.select_icao = Some(hit.icao.clone());
+                            }
+                        }
+                    });
+            });
+
+        self.open = open;
+        if changed {
+            monitor.lock().unwrap().set_zones(config.zones.clone());
+        }
+        response.changed = changed;
+        response
+    }
+
+    /// Zones and their rules; returns whether any changed
+    fn render_zones(ui: &mut egui::Ui, config: &mut GeofenceConfig) -> bool {
+        let mut changed = false;
+        let mut zones_to_remove = Vec::new();
+        for (index, zone) in config.zones.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                changed |= ui.checkbox(&mut zone.enabled, "").on_hover_text("Enabled").changed();
+                changed |= ui.color_edit_button_srgb(&mut zone.color).changed();
+                changed |= ui.add(egui::TextEdit::singleline(&mut zone.name)
+                    .desired_width(140.0))
+                    .changed();
+                ui.label(RichText::new(zone.shape.describe())
+                    .color(Color32::from_rgb(150, 150, 150))
+                    .size(9.0));
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("🗑").on_hover_text("Remove zone").clicked() {
+                        zones_to_remove.push(index);
+                    }
+                });
+            });
+
+            let mut rules_to_remove = Vec::new();
+            for (rule_index, rule) in zone.rules.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.add_space(24.0);
+                    match rule {
+                        ZoneRule::BelowAltitude { altitude } => {
+                            ui.label("Below");
+                            changed |= ui.add(egui::DragValue::new(altitude)
+                                .range(0..=60000)
+                                .speed(50.0)
+                                .suffix(" ft"))
+                                .changed();
+                        }
+                        _ => {
+                            ui.label(rule.describe());
+                        }
+                    }
+                    if ui.small_button("✕").on_hover_text("Remove rule").clicked() {
+                        rules_to_remove.push(rule_index);
+                    }
+                });
+            }
+            for rule_index in rules_to_remove.into_iter().rev() {
+                zone.rules.remove(rule_index);
+                changed = true;
+            }
+
+            ui.horizontal(|ui| {
+                ui.add_space(24.0);
+                ui.label(RichText::new("Add rule:")
+                    .color(Color32::from_rgb(150, 150, 150))
+                    .size(9.0));
+                for rule in [ZoneRule::Enter, ZoneRule::Exit, ZoneRule::BelowAltitude { altitude: 1000 }] {
+                    let already_added = zone.rules.iter()
+                        .any(|existing| std::mem::discriminant(existing) == std::mem::discriminant(&rule));
+                    let label = match rule {
+                        ZoneRule::BelowAltitude { .. } => "Below altitude".to_string(),
+                        _ => rule.describe(),
+                    };
+                    if ui.add_enabled(!already_added, egui::Button::new(label).small()).clicked() {
+                        zone.rules.push(rule);
+                        changed = true;
+                    }
+                }
+            });
+
+            ui.add_space(4.0);
+        }
+        for index in zones_to_remove.into_iter().rev() {
+            config.zones.remove(index);
+            changed = true;
+        }
+
+        if config.zones.is_empty() {
+            ui.label(RichText::new("No zones. Draw one on the map to get started.")
+                .color(Color32::from_rgb(150, 150, 150))
+                .size(9.0));
+        }
+        changed
+    }
+}
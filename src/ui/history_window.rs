@@ -0,0 +1,353 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Flight history browser window.
+//!
+//! Searches the flight history database by ICAO address, registration or
+//! callsign and lists the matching sessions. Clicking a registration lists
+//! every sighting of that tail number. The stored track of a selected
+//! session can be exported like a live one.
+
+use adsb_client::tracker::PositionPoint;
+use chrono::{Duration, Utc};
+use egui::{Color32, RichText};
+use std::path::PathBuf;
+
+use crate::export::{self, ExportFormat, FlightTrack};
+use crate::history::{FlightSession, HistoryQuery, HistoryStore};
+
+/// Most sessions shown for a search
+const RESULT_LIMIT: usize = 500;
+
+/// How far back a search looks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Period {
+    Day,
+    Week,
+    Month,
+    All,
+}
+
+impl Period {
+    const ALL: [Self; 4] = [Self::Day, Self::Week, Self::Month, Self::All];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Day => "Last 24 hours",
+            Self::Week => "Last 7 days",
+            Self::Month => "Last 30 days",
+            Self::All => "All time",
+        }
+    }
+
+    fn duration(self) -> Option<Duration> {
+        match self {
+            Self::Day => Some(Duration::days(1)),
+            Self::Week => Some(Duration::days(7)),
+            Self::Month => Some(Duration::days(30)),
+            Self::All => None,
+        }
+    }
+}
+
+/// Window listing past flight sessions
+pub struct HistoryWindow {
+    open: bool,
+    database: PathBuf,
+    /// Opened on the first search
+    store: Option<HistoryStore>,
+    search: String,
+    period: Period,
+    results: Vec<FlightSession>,
+    selected: Option<i64>,
+    /// Stored track of the selected session
+    track: Vec<PositionPoint>,
+    error: Option<String>,
+    /// Search again on the next frame
+    refresh: bool,
+}
+
+impl HistoryWindow {
+    pub fn new(database: PathBuf) -> Self {
+        Self {
+            open: true,
+            database,
+            store: None,
+            search: String::new(),
+            period: Period::Day,
+            results: Vec::new(),
+            selected: None,
+            track: Vec::new(),
+            error: None,
+            refresh: true,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Render the window
+    pub fn render(&mut self, ctx: &egui::Context) {
+        if self.refresh {
+            self.refresh = false;
+            self.run_search();
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Flight History")
+            .open(&mut open)
+            .default_size([820.0, 480.0])
+            .resizable(true)
+            .show(ctx, |ui| {
+                self.render_search(ui);
+                ui.separator();
+                self.render_details(ui);
+                self.render_results(ui);
+            });
+        self.open = open;
+    }
+
+    fn render_search(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let response = ui.add(egui::TextEdit::singleline(&mut self.search)
+                .hint_text("ICAO, registration or callsign")
+                .desired_width(200.0));
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                self.refresh = true;
+            }
+
+            let mut period = self.period;
+            egui::ComboBox::from_id_salt("history_period")
+                .selected_text(period.label())
+                .show_ui(ui, |ui| {
+                    for option in Period::ALL {
+                        ui.selectable_value(&mut period, option, option.label());
+                    }
+                });
+            if period != self.period {
+                self.period = period;
+                self.refresh = true;
+            }
+
+            if ui.button("Search").clicked() {
+                self.refresh = true;
+            }
+        });
+
+        let mut summary = format!("{} sessions", self.results.len());
+        if self.results.len() >= RESULT_LIMIT {
+            summary = format!("Newest {} sessions", RESULT_LIMIT);
+        }
+        ui.label(RichText::new(summary)
+            .size(10.0)
+            .color(Color32::from_rgb(150, 150, 150)));
+
+        if let Some(ref error) = self.error {
+            ui.label(RichText::new(error)
+                .size(10.0)
+                .color(Color32::from_rgb(255, 100, 100)));
+        }
+    }
+
+    /// The selected session's track and export buttons
+    fn render_details(&mut self, ui: &mut egui::Ui) {
+        let Some(session) = self.selected_session().cloned() else {
+            ui.label(RichText::new("Select a session to export its track")
+                .size(10.0)
+                .color(Color32::from_rgb(150, 150, 150)));
+            ui.separator();
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(session.display_name())
+                .strong()
+                .color(Color32::from_rgb(100, 255, 100)));
+            ui.label(RichText::new(format!("{} track points", self.track.len()))
+                .size(10.0)
+                .color(Color32::from_rgb(150, 150, 150)));
+
+            ui.add_enabled_ui(self.track.len() >= 2, |ui| {
+                ui.menu_button("Export Track", |ui| {
+                    for format in ExportFormat::ALL {
+                        if ui.button(format!("{}...", format.label())).clicked() {
+                            ui.close();
+                            self.export_track(&session, format);
+                        }
+                    }
+                });
+            });
+        });
+        ui.separator();
+    }
+
+    fn render_results(&mut self, ui: &mut egui::Ui) {
+        let mut clicked = None;
+        let mut sightings = None;
+
+        egui::ScrollArea::both().auto_shrink([false, false]).show(ui, |ui| {
+            egui::Grid::new("history_results")
+                .num_columns(9)
+                .striped(true)
+                .spacing([12.0, 4.0])
+                .show(ui, |ui| {
+                    for heading in ["First seen", "Duration", "ICAO", "Registration", "Callsigns", "Type", "Altitude", "Closest", "Servers"] {
+                        ui.label(RichText::new(heading).strong().size(10.0));
+                    }
+                    ui.end_row();
+
+                    for session in &self.results {
+                        let is_selected = session.id.is_some() && session.id == self.selected;
+                        if ui.selectable_label(is_selected, session.first_seen.format("%Y-%m-%d %H:%MZ").to_string()).clicked() {
+                            clicked = session.id;
+                        }
+                        ui.label(format_duration(session.last_seen - session.first_seen));
+                        ui.label(RichText::new(session.icao.to_uppercase()).monospace());
+                        match session.registration {
+                            Some(ref registration) => {
+                                if ui.link(registration).on_hover_text("Every time this registration was seen").clicked() {
+                                    sightings = Some(registration.clone());
+                                }
+                            }
+                            None => {
+                                ui.label("-");
+                            }
+                        }
+                        ui.label(if session.callsigns.is_empty() { "-".to_string() } else { session.callsigns.join(", ") });
+                        ui.label(session.aircraft_type.as_deref().unwrap_or("-"));
+                        ui.label(match (session.min_altitude, session.max_altitude) {
+                            (Some(min), Some(max)) if min == max => format!("{} ft", min),
+                            (Some(min), Some(max)) => format!("{}–{} ft", min, max),
+                            _ => "-".to_string(),
+                        });
+                        ui.label(session.closest_approach
+                            .map_or_else(|| "-".to_string(), |(distance, _)| format!("{:.1} nm", distance)));
+                        ui.label(session.servers.join(", "));
+                        ui.end_row();
+                    }
+                });
+        });
+
+        if let Some(id) = clicked {
+            self.select(id);
+        }
+        if let Some(registration) = sightings {
+            self.show_sightings(&registration);
+        }
+    }
+
+    fn selected_session(&self) -> Option<&FlightSession> {
+        let selected = self.selected?;
+        self.results.iter().find(|session| session.id == Some(selected))
+    }
+
+    /// Query the database with the current search and period
+    fn run_search(&mut self) {
+        if self.store.is_none() {
+            match HistoryStore::open(&self.database) {
+                Ok(store) => self.store = Some(store),
+                Err(e) => {
+                    self.error = Some(format!("Cannot open {}: {}", self.database.display(), e));
+                    return;
+                }
+            }
+        }
+        let Some(store) = &self.store else {
+            return;
+        };
+
+        let query = HistoryQuery {
+            search: Some(self.search.clone()),
+            since: self.period.duration().map(|duration| Utc::now() - duration),
+            limit: Some(RESULT_LIMIT),
+            ..HistoryQuery::default()
+        };
+        let results = store.search(&query);
+        self.show_results(results);
+    }
+
+    /// List every session of a registration, whatever the period
+    fn show_sightings(&mut self, registration: &str) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        self.search = registration.to_string();
+        let results = store.sightings_of_registration(registration);
+        self.show_results(results);
+    }
+
+    fn show_results(&mut self, results: rusqlite::Result<Vec<FlightSession>>) {
+        match results {
+            Ok(results) => {
+                self.results = results;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(format!("Search failed: {}", e)),
+        }
+
+        // Keep the selection if it is still listed
+        if self.selected_session().is_none() {
+            self.selected = None;
+            self.track.clear();
+        }
+    }
+
+    fn select(&mut self, id: i64) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        self.selected = Some(id);
+        match store.track(id) {
+            Ok(track) => self.track = track,
+            Err(e) => {
+                self.track.clear();
+                self.error = Some(format!("Cannot read track: {}", e));
+            }
+        }
+    }
+
+    /// Ask where to save a session's track, then write it
+    fn export_track(&mut self, session: &FlightSession, format: ExportFormat) {
+        let track = FlightTrack {
+            icao: session.icao.clone(),
+            callsign: session.callsigns.last().cloned(),
+            registration: session.registration.clone(),
+            aircraft_type: session.aircraft_type.clone(),
+            points: self.track.clone(),
+        };
+        let file_name = format!("{}-{}.{}", track.name(), session.first_seen.format("%Y%m%d-%H%M%S"), format.extension());
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Export track")
+            .set_file_name(file_name)
+            .add_filter(format.label(), &[format.extension()])
+            .save_file()
+        else {
+            return;
+        };
+
+        if let Err(e) = export::export_tracks(&path, format, &[track]) {
+            self.error = Some(format!("Failed to export track to {}: {}", path.display(), e));
+        }
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes();
+    if minutes < 60 {
+        format!("{}m", minutes.max(1))
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
@@ -4,6 +4,19 @@
 
 pub mod waterfall_window;
 pub mod status_pane;
+pub mod notifications;
+pub mod history_window;
+pub mod emergency_banner;
+pub mod watchlist_settings;
+pub mod geofence_window;
+pub mod recording;
+pub mod replay_timeline;
+pub mod output_settings;
 
 pub use waterfall_window::WaterfallWindow;
 pub use status_pane::StatusPane;
+pub use notifications::Notifications;
+pub use history_window::HistoryWindow;
+pub use emergency_banner::EmergencyBanner;
+pub use watchlist_settings::WatchlistSettings;
+pub use geofence_window::GeofenceWindow;
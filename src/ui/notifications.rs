@@ -0,0 +1,113 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! In-app notifications.
+//!
+//! Short-lived toasts stacked at the bottom of the map. Clicking a toast that
+//! refers to an aircraft selects it.
+
+use egui;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const NOTIFICATION_DURATION: Duration = Duration::from_secs(8);
+const MAX_NOTIFICATIONS: usize = 5;
+
+/// A single toast
+struct Notification {
+    title: String,
+    message: String,
+    color: egui::Color32,
+    /// Aircraft selected when the toast is clicked
+    icao: Option<String>,
+    created_at: Instant,
+}
+
+#[derive(Default)]
+pub struct Notifications {
+    items: VecDeque<Notification>,
+}
+
+impl Notifications {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show a notification, dropping the oldest if too many are visible
+    pub fn push(&mut self, title: String, message: String, color: egui::Color32, icao: Option<String>) {
+        self.items.push_back(Notification {
+            title,
+            message,
+            color,
+            icao,
+            created_at: Instant::now(),
+        });
+        while self.items.len() > MAX_NOTIFICATIONS {
+            self.items.pop_front();
+        }
+    }
+
+    /// Render the visible notifications
+    ///
+    /// Returns the ICAO of the aircraft whose notification was clicked.
+    pub fn render(&mut self, ctx: &egui::Context) -> Option<String> {
+        self.items.retain(|item| item.created_at.elapsed() < NOTIFICATION_DURATION);
+        if self.items.is_empty() {
+            return None;
+        }
+
+        let mut clicked_icao = None;
+        let mut dismissed = None;
+
+        egui::Area::new("notifications".into())
+            .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -40.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                for (index, item) in self.items.iter().enumerate() {
+                    let response = egui::Frame::new()
+                        .fill(egui::Color32::from_rgba_unmultiplied(25, 30, 35, 230))
+                        .stroke(egui::Stroke::new(1.5, item.color))
+                        .corner_radius(6.0)
+                        .inner_margin(egui::Margin::symmetric(12, 6))
+                        .show(ui, |ui| {
+                            ui.set_min_width(280.0);
+                            ui.label(egui::RichText::new(&item.title)
+                                .color(item.color)
+                                .size(12.0)
+                                .strong());
+                            ui.label(egui::RichText::new(&item.message)
+                                .color(egui::Color32::from_rgb(200, 200, 200))
+                                .size(10.0));
+                        })
+                        .response
+                        .interact(egui::Sense::click());
+
+                    if response.clicked() {
+                        clicked_icao = item.icao.clone();
+                        dismissed = Some(index);
+                    }
+                    ui.add_space(4.0);
+                }
+            });
+
+        if let Some(index) = dismissed {
+            self.items.remove(index);
+        }
+
+        // Keep repainting so toasts expire on time
+        ctx.request_repaint_after(Duration::from_millis(250));
+
+        clicked_icao
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Output sections of the settings window.
+//!
+//! The SBS output and HTTP API settings, each with the status of its
+//! listener.
+
+use egui::{Color32, RichText};
+
+use crate::network::sbs_server::SbsServerStatus;
+use crate::network::SbsOutputConfig;
+use crate::web::{WebConfig, WebServerStatus};
+
+/// Edits made in an output section
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OutputChange {
+    /// The config changed and should be saved
+    pub changed: bool,
+    /// The listener should be restarted to match the config
+    pub apply: bool,
+}
+
+impl OutputChange {
+    fn edited(&mut self) {
+        self.changed = true;
+        self.apply = true;
+    }
+}
+
+/// SBS output section, showing the `status` of the running server
+pub fn sbs_output_section(ui: &mut egui::Ui, config: &mut SbsOutputConfig, status: Option<&SbsServerStatus>) -> OutputChange {
+    heading(ui, "SBS Output", "Serves the aircraft of every server as one BaseStation (port 30003 style) feed, for Virtual Radar Server, PlanePlotter and similar tools.");
+
+    let mut change = OutputChange::default();
+    if ui.checkbox(&mut config.enabled, "Serve merged traffic").changed() {
+        change.edited();
+    }
+    port_field(ui, &mut config.port, &mut change);
+    if ui.checkbox(&mut config.utc_timestamps, "UTC timestamps").changed() {
+        change.edited();
+    }
+
+    if let Some(status) = status {
+        let (text, color) = match (&status.error, status.port) {
+            (Some(error), _) => (error.clone(), Color32::from_rgb(255, 100, 100)),
+            (None, Some(port)) => {
+                let mut text = format!("Listening on port {} · {} client{} · {} lines sent",
+                    port, status.clients.len(), if status.clients.len() == 1 { "" } else { "s" }, status.lines);
+                if status.dropped > 0 {
+                    text.push_str(&format!(" · {} updates missed by slow clients", status.dropped));
+                }
+                (text, Color32::from_rgb(100, 200, 200))
+            }
+            (None, None) => ("Starting...".to_string(), Color32::from_rgb(150, 150, 150)),
+        };
+        ui.label(RichText::new(text).color(color).size(9.0));
+    }
+
+    change
+}
+
+/// HTTP API section, showing the `status` of the running server
+pub fn http_api_section(ui: &mut egui::Ui, config: &mut WebConfig, status: Option<&WebServerStatus>) -> OutputChange {
+    heading(ui, "HTTP API", "Serves /data/aircraft.json and /data/receiver.json in readsb's format, for tar1090 and dashboards, and a live WebSocket stream of changes at /data/stream.");
+
+    let mut change = OutputChange::default();
+    if ui.checkbox(&mut config.enabled, "Serve HTTP API").changed() {
+        change.edited();
+    }
+    port_field(ui, &mut config.port, &mut change);
+
+    if let Some(status) = status {
+        match (&status.error, status.port) {
+            (Some(error), _) => {
+                ui.label(RichText::new(error)
+                    .color(Color32::from_rgb(255, 100, 100))
+                    .size(9.0));
+            }
+            (None, Some(port)) => {
+                let url = format!("http://localhost:{}/data/aircraft.json", port);
+                ui.horizontal(|ui| {
+                    ui.hyperlink_to(RichText::new(&url).size(9.0), &url);
+                    ui.label(RichText::new(format!("· {} requests · {} streaming", status.requests, status.streams))
+                        .color(Color32::from_rgb(100, 200, 200))
+                        .size(9.0));
+                });
+            }
+            (None, None) => {
+                ui.label(RichText::new("Starting...")
+                    .color(Color32::from_rgb(150, 150, 150))
+                    .size(9.0));
+            }
+        }
+    }
+
+    change
+}
+
+fn heading(ui: &mut egui::Ui, title: &str, description: &str) {
+    ui.heading(RichText::new(title)
+        .size(12.0)
+        .strong());
+
+    ui.add_space(4.0);
+
+    ui.label(RichText::new(description)
+        .size(9.0)
+        .color(Color32::from_rgb(150, 150, 150)));
+}
+
+/// Listening port of an output
+fn port_field(ui: &mut egui::Ui, port: &mut u16, change: &mut OutputChange) {
+    ui.horizontal(|ui| {
+        ui.label("Port:");
+        let response = ui.add(egui::DragValue::new(port).range(1..=65535));
+        if response.changed() {
+            change.changed = true;
+        }
+        // Don't restart the listener for every port passed while dragging
+        if (response.changed() && !response.dragged()) || response.drag_stopped() {
+            change.apply = true;
+        }
+    });
+}
@@ -0,0 +1,122 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Feed recording controls.
+//!
+//! The record toggle and progress line shown for each server, and the
+//! recording section of the settings window.
+
+use egui::{Color32, RichText};
+
+use crate::recording::{RecordingConfig, RecordingStatus};
+
+const RECORDING_COLOR: Color32 = Color32::from_rgb(255, 90, 90);
+
+/// Record/stop button for a server; returns whether it was clicked
+pub fn record_button(ui: &mut egui::Ui, recording: bool) -> bool {
+    let (label, hint) = if recording {
+        (RichText::new("⏹").color(RECORDING_COLOR), "Stop recording")
+    } else {
+        (RichText::new("⏺"), "Record this feed to the recordings directory")
+    };
+    ui.button(label).on_hover_text(hint).clicked()
+}
+
+/// One-line progress of a server's recording, or the error that stopped it
+pub fn progress_label(ui: &mut egui::Ui, recording: &RecordingStatus) {
+    let (text, color) = if let Some(ref error) = recording.error {
+        (format!("Recording failed: {}", error), Color32::from_rgb(255, 100, 100))
+    } else {
+        let file = recording.current_file.as_ref()
+            .and_then(|path| path.file_name())
+            .map_or_else(|| "waiting for data".to_string(), |name| name.to_string_lossy().into_owned());
+        let mut text = format!("● Recording {} | {} frames, {:.1} MB in {} file(s)",
+            file,
+            recording.frames,
+            recording.bytes as f64 / (1024.0 * 1024.0),
+            recording.files);
+        if recording.dropped > 0 {
+            text.push_str(&format!(", {} dropped", recording.dropped));
+        }
+        (text, RECORDING_COLOR)
+    };
+    ui.label(RichText::new(text).size(8.0).color(color))
+        .on_hover_text(format!("Started {}", recording.started.format("%Y-%m-%d %H:%M:%S UTC")));
+}
+
+/// Recording section of the settings window; returns whether the config changed
+pub fn settings_section(ui: &mut egui::Ui, config: &mut RecordingConfig) -> bool {
+    ui.heading(RichText::new("Recording")
+        .size(12.0)
+        .strong());
+
+    ui.add_space(4.0);
+
+    ui.label(RichText::new("Use ⏺ on a server to record its feed. Settings apply to recordings started afterwards.")
+        .size(9.0)
+        .color(Color32::from_rgb(150, 150, 150)));
+
+    let mut changed = false;
+
+    ui.horizontal(|ui| {
+        ui.label("Directory:");
+        ui.label(RichText::new(config.directory.display().to_string())
+            .color(Color32::from_rgb(200, 200, 200)));
+
+        if ui.button("Browse...").clicked() {
+            if let Some(path) = rfd::FileDialog::new()
+                .set_directory(&config.directory)
+                .pick_folder()
+            {
+                config.directory = path;
+                changed = true;
+            }
+        }
+    });
+
+    if ui.checkbox(&mut config.compress, "Compress (gzip)").changed() {
+        changed = true;
+    }
+
+    egui::Grid::new("recording_rotation")
+        .num_columns(2)
+        .spacing([8.0, 4.0])
+        .show(ui, |ui| {
+            let mut rotate_by_size = config.rotate_megabytes.is_some();
+            if ui.checkbox(&mut rotate_by_size, "New file after").changed() {
+                config.rotate_megabytes = rotate_by_size.then_some(100);
+                changed = true;
+            }
+            if let Some(ref mut megabytes) = config.rotate_megabytes {
+                if ui.add(egui::DragValue::new(megabytes).range(1..=10_000).suffix(" MB")).changed() {
+                    changed = true;
+                }
+            }
+            ui.end_row();
+
+            let mut rotate_by_time = config.rotate_minutes.is_some();
+            if ui.checkbox(&mut rotate_by_time, "New file after").changed() {
+                config.rotate_minutes = rotate_by_time.then_some(60);
+                changed = true;
+            }
+            if let Some(ref mut minutes) = config.rotate_minutes {
+                if ui.add(egui::DragValue::new(minutes).range(1..=1440).suffix(" min")).changed() {
+                    changed = true;
+                }
+            }
+            ui.end_row();
+        });
+
+    changed
+}
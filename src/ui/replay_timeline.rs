@@ -0,0 +1,122 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Replay timelines.
+//!
+//! One bar per replaying server along the bottom of the map, with play/pause,
+//! a scrubber to seek and the playback speed.
+
+use egui::{Color32, RichText};
+
+use crate::recording::replay::SPEEDS;
+use crate::recording::{ReplayControl, ReplayStatus};
+
+/// Draw the timeline of each replay, stacked upwards from the bottom of the map
+pub fn show(ctx: &egui::Context, map_rect: egui::Rect, replays: &[(String, ReplayControl)]) {
+    for (index, (name, replay)) in replays.iter().enumerate() {
+        let status = replay.status();
+        // Stack bars upwards, clear of the attribution text
+        let bottom = map_rect.bottom() - 30.0 - index as f32 * 44.0;
+
+        egui::Area::new(egui::Id::new(("replay_timeline", name)))
+            .fixed_pos(egui::pos2(map_rect.center().x, bottom))
+            .pivot(egui::Align2::CENTER_BOTTOM)
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                egui::Frame::new()
+                    .fill(Color32::from_rgba_unmultiplied(25, 30, 35, 200))
+                    .corner_radius(6.0)
+                    .inner_margin(egui::Margin::symmetric(10, 6))
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.spacing_mut().item_spacing.x = 6.0;
+                            timeline_bar(ui, name, replay, &status);
+                        });
+                    });
+            });
+    }
+}
+
+/// Controls of one replay
+fn timeline_bar(ui: &mut egui::Ui, name: &str, replay: &ReplayControl, status: &ReplayStatus) {
+    // Play/pause
+    let icon = if status.playing { "⏸" } else { "▶" };
+    let hint = if status.playing {
+        "Pause"
+    } else if status.finished {
+        "Play again from the start"
+    } else {
+        "Play"
+    };
+    if ui.add_enabled(status.span.is_some(), egui::Button::new(RichText::new(icon).size(14.0)))
+        .on_hover_text(hint)
+        .clicked()
+    {
+        if status.playing {
+            replay.pause();
+        } else {
+            replay.play();
+        }
+    }
+
+    ui.label(RichText::new(name)
+        .size(10.0)
+        .color(Color32::from_rgb(150, 150, 150)));
+
+    if let Some(ref error) = status.error {
+        ui.label(RichText::new(format!("Replay failed: {}", error))
+            .size(10.0)
+            .color(Color32::from_rgb(255, 100, 100)));
+        return;
+    }
+    let Some((start, end)) = status.span else {
+        ui.label(RichText::new(format!("Reading {}...", status.format.label().to_lowercase()))
+            .size(10.0)
+            .color(Color32::from_rgb(200, 200, 200)));
+        return;
+    };
+
+    ui.label(RichText::new(status.position.format("%H:%M:%SZ").to_string())
+        .monospace()
+        .color(Color32::from_rgb(220, 220, 220)))
+        .on_hover_text(status.position.format("%Y-%m-%d %H:%M:%S UTC").to_string());
+
+    // Scrubber: seek once the handle is released, or on a click
+    let total_secs = (end - start).num_milliseconds().max(1) as f64 / 1000.0;
+    let mut offset_secs = (status.position - start).num_milliseconds() as f64 / 1000.0;
+    ui.spacing_mut().slider_width = 360.0;
+    let response = ui.add(egui::Slider::new(&mut offset_secs, 0.0..=total_secs)
+        .show_value(false));
+    if response.drag_stopped() || (response.changed() && !response.dragged()) {
+        replay.seek(start + chrono::Duration::milliseconds((offset_secs * 1000.0) as i64));
+    }
+
+    ui.label(RichText::new(end.format("%H:%M:%SZ").to_string())
+        .monospace()
+        .color(Color32::from_rgb(150, 150, 150)));
+
+    // Playback speed
+    let mut speed = status.speed;
+    egui::ComboBox::from_id_salt(("replay_speed", name))
+        .selected_text(format!("{}×", speed))
+        .width(50.0)
+        .show_ui(ui, |ui| {
+            for option in SPEEDS {
+                ui.selectable_value(&mut speed, option, format!("{}×", option));
+            }
+        });
+    if speed != status.speed {
+        replay.set_speed(speed);
+    }
+}
@@ -202,9 +202,13 @@ impl StatusPane {
                 if server_status.status == ConnectionStatus::Connected {
                     ui.horizontal(|ui| {
                         ui.add_space(12.0); // Indent
-                        ui.label(egui::RichText::new(format!("{} msgs │ {} aircraft",
+                        let format = server_status.detected_format
+                            .map(|format| format!(" │ {}", format.label()))
+                            .unwrap_or_default();
+                        ui.label(egui::RichText::new(format!("{} msgs │ {} aircraft{}",
                             server_status.message_count,
-                            server_status.aircraft_count))
+                            server_status.aircraft_count,
+                            format))
                             .color(egui::Color32::from_rgb(120, 180, 140))
                             .size(7.5)
                             .monospace());
@@ -0,0 +1,166 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Watchlist section of the settings panel.
+//!
+//! Rules and outputs are edited on a draft, so a half-typed rule doesn't
+//! trigger notifications. The draft replaces the configuration when applied.
+
+use egui;
+
+use crate::watchlist::{WatchCriterion, WatchRule, WatchlistConfig};
+
+pub struct WatchlistSettings {
+    /// Settings being edited (applied with the Apply button)
+    draft: WatchlistConfig,
+}
+
+impl WatchlistSettings {
+    pub fn new(config: &WatchlistConfig) -> Self {
+        Self { draft: config.clone() }
+    }
+
+    /// Render the section, comparing the draft with the `applied` settings
+    ///
+    /// Returns the new settings when the draft is applied.
+    pub fn render(&mut self, ui: &mut egui::Ui, applied: &WatchlistConfig) -> Option<WatchlistConfig> {
+        ui.heading(egui::RichText::new("Watchlist")
+            .size(12.0)
+            .strong());
+
+        ui.add_space(4.0);
+
+        ui.label(egui::RichText::new("Notify when a matching aircraft appears (range 0 = any range)")
+            .color(egui::Color32::from_rgb(150, 150, 150))
+            .size(9.0));
+
+        ui.add_space(4.0);
+
+        let mut rules_to_remove = Vec::new();
+        egui::Grid::new("watchlist_rules")
+            .num_columns(6)
+            .spacing([6.0, 4.0])
+            .show(ui, |ui| {
+                for (index, rule) in self.draft.rules.iter_mut().enumerate() {
+                    ui.checkbox(&mut rule.enabled, "")
+                        .on_hover_text("Enabled");
+
+                    ui.add(egui::TextEdit::singleline(&mut rule.name)
+                        .hint_text("Name")
+                        .desired_width(90.0));
+
+                    egui::ComboBox::from_id_salt(("watch_criterion", &rule.id))
+                        .selected_text(rule.criterion.label())
+                        .width(100.0)
+                        .show_ui(ui, |ui| {
+                            for criterion in WatchCriterion::ALL {
+                                ui.selectable_value(&mut rule.criterion, criterion, criterion.label());
+                            }
+                        });
+
+                    ui.add_enabled(rule.criterion.takes_value(),
+                        egui::TextEdit::singleline(&mut rule.value)
+                            .hint_text("Value")
+                            .desired_width(70.0));
+
+                    let mut range = rule.max_range_nm.unwrap_or(0.0);
+                    if ui.add(egui::DragValue::new(&mut range)
+                        .range(0.0..=400.0)
+                        .speed(1.0)
+                        .suffix(" nm"))
+                        .changed()
+                    {
+                        rule.max_range_nm = (range > 0.0).then_some(range);
+                    }
+
+                    if ui.button("✕").on_hover_text("Remove rule").clicked() {
+                        rules_to_remove.push(index);
+                    }
+                    ui.end_row();
+                }
+            });
+
+        for index in rules_to_remove.into_iter().rev() {
+            self.draft.rules.remove(index);
+        }
+
+        if ui.button("➕ Add Rule").clicked() {
+            self.draft.rules.push(WatchRule::new(
+                format!("Rule {}", self.draft.rules.len() + 1),
+                WatchCriterion::CallsignPrefix,
+                String::new(),
+            ));
+        }
+
+        ui.add_space(8.0);
+
+        // Outputs that receive every watchlist event
+        ui.horizontal(|ui| {
+            ui.label("Log file:");
+            let mut log_file = self.draft.log_file.as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default();
+            if ui.add(egui::TextEdit::singleline(&mut log_file)
+                .hint_text("JSON lines, appended")
+                .desired_width(200.0))
+                .changed()
+            {
+                self.draft.log_file = (!log_file.trim().is_empty())
+                    .then(|| std::path::PathBuf::from(log_file.trim()));
+            }
+
+            if ui.button("Browse...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .set_file_name("watchlist.jsonl")
+                    .save_file()
+                {
+                    self.draft.log_file = Some(path);
+                }
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Webhook:");
+            let mut webhook_url = self.draft.webhook_url.clone().unwrap_or_default();
+            if ui.add(egui::TextEdit::singleline(&mut webhook_url)
+                .hint_text("https://example.com/hook")
+                .desired_width(260.0))
+                .changed()
+            {
+                self.draft.webhook_url = (!webhook_url.trim().is_empty())
+                    .then(|| webhook_url.trim().to_string());
+            }
+        });
+
+        ui.add_space(4.0);
+
+        let mut applied_config = None;
+        let watchlist_modified = self.draft != *applied;
+        ui.horizontal(|ui| {
+            if ui.add_enabled(watchlist_modified, egui::Button::new("Apply")).clicked() {
+                applied_config = Some(self.draft.clone());
+            }
+            if ui.add_enabled(watchlist_modified, egui::Button::new("Revert")).clicked() {
+                self.draft = applied.clone();
+            }
+            if watchlist_modified {
+                ui.label(egui::RichText::new("Unsaved changes")
+                    .color(egui::Color32::from_rgb(255, 200, 100))
+                    .size(9.0));
+            }
+        });
+
+        applied_config
+    }
+}
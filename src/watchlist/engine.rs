@@ -0,0 +1,254 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Watch rule evaluation and match events.
+//!
+//! [`Watchlist`] checks the tracked aircraft against the configured rules and
+//! broadcasts a [`WatchEvent`] whenever an aircraft starts or stops matching
+//! a rule. The UI and the outputs each subscribe to the events, the same way
+//! consumers of `adsb_client` subscribe to tracker events.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use tokio::sync::broadcast;
+
+use crate::aircraft::{display_name, Aircraft, AircraftDatabase};
+use super::outputs::{OutputTarget, WatchOutput};
+use super::rules::{AircraftIdentity, WatchRule, WatchlistConfig};
+
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// An aircraft matching a watch rule
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchMatch {
+    pub rule_id: String,
+    pub rule_name: String,
+    /// Description of the rule, e.g. "Squawk 7700"
+    pub rule: String,
+    pub icao: String,
+    pub callsign: Option<String>,
+    pub registration: Option<String>,
+    pub type_code: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub altitude: Option<i32>,
+    pub range_nm: Option<f64>,
+    pub server_name: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl WatchMatch {
+    /// Name to show for the aircraft
+    pub fn display_name(&self) -> String {
+        display_name(self.callsign.as_deref(), &self.icao)
+    }
+}
+
+/// Change in the set of aircraft matching the watchlist
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WatchEvent {
+    /// An aircraft started matching a rule
+    Matched(WatchMatch),
+    /// An aircraft stopped matching a rule (left range, changed or was lost)
+    Cleared {
+        rule_id: String,
+        rule_name: String,
+        icao: String,
+        timestamp: DateTime<Utc>,
+    },
+}
+
+/// Rules an aircraft currently matches
+struct ActiveMatches {
+    rule_ids: Vec<String>,
+    /// When the aircraft was last seen, on its feed's clock
+    last_seen: DateTime<Utc>,
+}
+
+/// Evaluates watch rules against tracked aircraft
+pub struct Watchlist {
+    rules: Vec<WatchRule>,
+    /// Rules each aircraft currently matches
+    active: HashMap<String, ActiveMatches>,
+    event_tx: broadcast::Sender<WatchEvent>,
+    outputs: Vec<WatchOutput>,
+}
+
+impl Watchlist {
+    /// Create a watchlist and start the configured outputs
+    pub fn new(config: &WatchlistConfig) -> Self {
+        let (event_tx, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let mut watchlist = Self {
+            rules: Vec::new(),
+            active: HashMap::new(),
+            event_tx,
+            outputs: Vec::new(),
+        };
+        watchlist.configure(config);
+        watchlist
+    }
+
+    /// Subscribe to watchlist events
+    pub fn subscribe(&self) -> broadcast::Receiver<WatchEvent> {
+        self.event_tx.subscribe()
+    }
+
+    /// Apply a changed configuration
+    ///
+    /// Matches of removed or changed rules are cleared, so they trigger again
+    /// if the new rule still matches. Outputs whose target is unchanged keep
+    /// running.
+    pub fn configure(&mut self, config: &WatchlistConfig) {
+        let kept: HashSet<&str> = config.rules.iter()
+            .filter(|rule| self.rules.contains(rule))
+            .map(|rule| rule.id.as_str())
+            .collect();
+        let stale: Vec<(String, String)> = self.active.iter()
+            .flat_map(|(icao, active)| active.rule_ids.iter().map(move |id| (icao.clone(), id.clone())))
+            .filter(|(_, rule_id)| !kept.contains(rule_id.as_str()))
+            .collect();
+        for (icao, rule_id) in stale {
+            self.clear(&icao, &rule_id);
+        }
+        self.rules = config.rules.clone();
+
+        let mut targets = Vec::new();
+        if let Some(path) = config.log_file.clone().filter(|path| !path.as_os_str().is_empty()) {
+            targets.push(OutputTarget::LogFile(path));
+        }
+        if let Some(url) = config.webhook_url.as_deref().map(str::trim).filter(|url| !url.is_empty()) {
+            targets.push(OutputTarget::Webhook(url.to_string()));
+        }
+
+        // Dropping an output stops it
+        self.outputs.retain(|output| targets.contains(output.target()));
+        for target in targets {
+            if !self.outputs.iter().any(|output| *output.target() == target) {
+                self.outputs.push(WatchOutput::spawn(target, self.event_tx.subscribe()));
+            }
+        }
+    }
+
+    /// Check every aircraft against the rules, broadcasting changes
+    ///
+    /// Expects each aircraft once (see [`unique_aircraft`](crate::aircraft::unique_aircraft)).
+    /// Replayed aircraft are skipped, so a recording never raises live notifications.
+    pub fn evaluate(&mut self, aircraft_list: &[Aircraft], db: &AircraftDatabase, receiver_lat: f64, receiver_lon: f64) {
+        let mut seen = HashSet::new();
+        let mut cleared = Vec::new();
+
+        for aircraft in aircraft_list.iter().filter(|aircraft| !aircraft.is_replayed()) {
+            let icao = aircraft.icao();
+            if !seen.insert(icao.clone()) {
+                continue;
+            }
+
+            let registration = aircraft.registration().or_else(|| db.get_registration(&icao));
+            let type_code = db.lookup(&icao).and_then(|info| info.icao_type.clone());
+            let callsign = aircraft.callsign();
+            let squawk = aircraft.with_data(|data| data.squawk.clone());
+            let identity = AircraftIdentity {
+                icao: &icao,
+                registration: registration.as_deref(),
+                callsign: callsign.as_deref(),
+                type_code: type_code.as_deref(),
+                squawk: squawk.as_deref(),
+                military: db.is_military(&icao),
+            };
+            let range_nm = aircraft.distance_from_nm(receiver_lat, receiver_lon);
+            let last_seen = aircraft.last_seen();
+            if let Some(active) = self.active.get_mut(&icao) {
+                active.last_seen = last_seen;
+            }
+
+            for rule in self.rules.iter().filter(|rule| rule.enabled) {
+                let in_range = match rule.max_range_nm {
+                    Some(max_range) => range_nm.is_some_and(|range| range <= max_range),
+                    None => true,
+                };
+                let matched = in_range && rule.matches(&identity);
+                let was_matched = self.active.get(&icao).is_some_and(|active| active.rule_ids.contains(&rule.id));
+
+                if matched && !was_matched {
+                    self.active.entry(icao.clone())
+                        .or_insert_with(|| ActiveMatches { rule_ids: Vec::new(), last_seen })
+                        .rule_ids
+                        .push(rule.id.clone());
+                    let _ = self.event_tx.send(WatchEvent::Matched(WatchMatch {
+                        rule_id: rule.id.clone(),
+                        rule_name: rule.name.clone(),
+                        rule: rule.describe(),
+                        icao: icao.clone(),
+                        callsign: callsign.clone(),
+                        registration: registration.clone(),
+                        type_code: type_code.clone(),
+                        latitude: aircraft.latitude(),
+                        longitude: aircraft.longitude(),
+                        altitude: aircraft.altitude(),
+                        range_nm,
+                        server_name: aircraft.source_server_name(),
+                        timestamp: last_seen,
+                    }));
+                } else if !matched && was_matched {
+                    cleared.push((icao.clone(), rule.id.clone()));
+                }
+            }
+        }
+
+        // Aircraft that are no longer tracked
+        cleared.extend(self.active.iter()
+            .filter(|(icao, _)| !seen.contains(*icao))
+            .flat_map(|(icao, active)| active.rule_ids.iter().map(move |id| (icao.clone(), id.clone()))));
+        for (icao, rule_id) in cleared {
+            self.clear(&icao, &rule_id);
+        }
+    }
+
+    /// Names of the rules an aircraft currently matches
+    pub fn matched_rules(&self, icao: &str) -> Vec<String> {
+        self.active.get(icao)
+            .map(|active| {
+                self.rules.iter()
+                    .filter(|rule| active.rule_ids.contains(&rule.id))
+                    .map(|rule| rule.name.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Clear a match, timed when the aircraft was last seen
+    fn clear(&mut self, icao: &str, rule_id: &str) {
+        let Some(active) = self.active.get_mut(icao) else {
+            return;
+        };
+        active.rule_ids.retain(|id| id != rule_id);
+        let timestamp = active.last_seen;
+        if active.rule_ids.is_empty() {
+            self.active.remove(icao);
+        }
+
+        let rule_name = self.rules.iter()
+            .find(|rule| rule.id == rule_id)
+            .map(|rule| rule.name.clone())
+            .unwrap_or_default();
+        let _ = self.event_tx.send(WatchEvent::Cleared {
+            rule_id: rule_id.to_string(),
+            rule_name,
+            icao: icao.to_string(),
+            timestamp,
+        });
+    }
+}
@@ -0,0 +1,13 @@
+//! Aircraft watchlist.
+//!
+//! Watch rules match aircraft by ICAO address, registration, callsign prefix,
+//! type code, squawk or military status, optionally within a range of the
+//! receiver. Matches are broadcast as events to the UI and to outputs such as
+//! a log file or a webhook.
+
+pub mod rules;
+pub mod engine;
+pub mod outputs;
+
+pub use rules::{WatchCriterion, WatchRule, WatchlistConfig};
+pub use engine::{WatchEvent, Watchlist};
@@ -0,0 +1,138 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Outputs that forward watchlist events outside the app.
+//!
+//! Each output subscribes to the watchlist's events and runs on its own
+//! background thread, so a slow webhook never stalls the UI:
+//! - Log file: one JSON object per line, appended
+//! - Webhook: each event POSTed as a JSON body
+
+use log::{info, warn};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+
+use super::engine::WatchEvent;
+
+const WEBHOOK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Where an output sends watchlist events
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputTarget {
+    /// Append JSON lines to a file
+    LogFile(PathBuf),
+    /// POST JSON to a URL
+    Webhook(String),
+}
+
+/// A running output, stopped when dropped
+pub struct WatchOutput {
+    target: OutputTarget,
+    cancel_token: CancellationToken,
+}
+
+impl WatchOutput {
+    /// Start forwarding events to a target
+    pub fn spawn(target: OutputTarget, events: broadcast::Receiver<WatchEvent>) -> Self {
+        let cancel_token = CancellationToken::new();
+        let task_target = target.clone();
+        let task_token = cancel_token.clone();
+
+        // Spawn background thread with its own tokio runtime
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to create tokio runtime");
+            rt.block_on(run_output(task_target, events, task_token));
+        });
+
+        Self {
+            target,
+            cancel_token,
+        }
+    }
+
+    /// Where this output sends events
+    pub fn target(&self) -> &OutputTarget {
+        &self.target
+    }
+}
+
+impl Drop for WatchOutput {
+    fn drop(&mut self) {
+        self.cancel_token.cancel();
+    }
+}
+
+async fn run_output(
+    target: OutputTarget,
+    mut events: broadcast::Receiver<WatchEvent>,
+    cancel_token: CancellationToken,
+) {
+    info!("Starting watchlist output: {:?}", target);
+    let http = reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+
+    loop {
+        let event = tokio::select! {
+            event = events.recv() => event,
+            _ = cancel_token.cancelled() => break,
+        };
+
+        match event {
+            Ok(event) => {
+                let result = match &target {
+                    OutputTarget::LogFile(path) => append_to_log(path, &event),
+                    OutputTarget::Webhook(url) => post_to_webhook(&http, url, &event).await,
+                };
+                if let Err(e) = result {
+                    warn!("Watchlist output {:?} failed: {}", target, e);
+                }
+            }
+            Err(RecvError::Lagged(skipped)) => {
+                warn!("Watchlist output {:?} skipped {} events", target, skipped);
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+
+    info!("Stopped watchlist output: {:?}", target);
+}
+
+fn append_to_log(path: &Path, event: &WatchEvent) -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let line = serde_json::to_string(event)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+async fn post_to_webhook(
+    http: &reqwest::Client,
+    url: &str,
+    event: &WatchEvent,
+) -> Result<(), Box<dyn std::error::Error>> {
+    http.post(url)
+        .json(event)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
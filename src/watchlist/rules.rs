@@ -0,0 +1,214 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Watch rule configuration.
+//!
+//! Rules are stored in `AppConfig` and describe which aircraft to watch for:
+//! a criterion on the aircraft's identity and an optional range from the
+//! receiver.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// What a watch rule matches on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WatchCriterion {
+    /// ICAO hex address (exact)
+    Icao,
+    /// Registration (exact)
+    Registration,
+    /// Callsign prefix, e.g. an airline designator
+    CallsignPrefix,
+    /// ICAO type designator from the aircraft database (exact)
+    TypeCode,
+    /// Transponder code (exact)
+    Squawk,
+    /// Flagged as military in the aircraft database
+    Military,
+}
+
+impl WatchCriterion {
+    /// All criteria, in the order shown in the UI
+    pub const ALL: [WatchCriterion; 6] = [
+        WatchCriterion::Icao,
+        WatchCriterion::Registration,
+        WatchCriterion::CallsignPrefix,
+        WatchCriterion::TypeCode,
+        WatchCriterion::Squawk,
+        WatchCriterion::Military,
+    ];
+
+    /// Human-readable name for settings UI
+    pub fn label(self) -> &'static str {
+        match self {
+            WatchCriterion::Icao => "ICAO hex",
+            WatchCriterion::Registration => "Registration",
+            WatchCriterion::CallsignPrefix => "Callsign prefix",
+            WatchCriterion::TypeCode => "Type code",
+            WatchCriterion::Squawk => "Squawk",
+            WatchCriterion::Military => "Military",
+        }
+    }
+
+    /// Whether the criterion compares against a value
+    pub fn takes_value(self) -> bool {
+        self != WatchCriterion::Military
+    }
+}
+
+/// A single watch rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchRule {
+    /// Unique identifier (UUID)
+    pub id: String,
+
+    /// Display name shown in notifications
+    pub name: String,
+
+    /// Whether the rule is evaluated
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// What the rule matches on
+    pub criterion: WatchCriterion,
+
+    /// Value compared against (ignored for `Military`)
+    #[serde(default)]
+    pub value: String,
+
+    /// Only match aircraft within this range of the receiver (nautical miles)
+    #[serde(default)]
+    pub max_range_nm: Option<f64>,
+}
+
+impl WatchRule {
+    /// Create a new enabled rule with a generated UUID
+    pub fn new(name: String, criterion: WatchCriterion, value: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            enabled: true,
+            criterion,
+            value,
+            max_range_nm: None,
+        }
+    }
+
+    /// Check an aircraft's identity against the rule's criterion
+    ///
+    /// Text comparisons ignore case and surrounding whitespace. Range is
+    /// checked separately, since it needs the aircraft's position.
+    pub fn matches(&self, identity: &AircraftIdentity) -> bool {
+        let value = self.value.trim();
+        if self.criterion.takes_value() && value.is_empty() {
+            return false;
+        }
+
+        let equals = |field: Option<&str>| {
+            field.is_some_and(|field| field.trim().eq_ignore_ascii_case(value))
+        };
+
+        match self.criterion {
+            WatchCriterion::Icao => equals(Some(identity.icao)),
+            WatchCriterion::Registration => equals(identity.registration),
+            WatchCriterion::CallsignPrefix => identity.callsign.is_some_and(|callsign| {
+                callsign
+                    .trim()
+                    .to_ascii_uppercase()
+                    .starts_with(&value.to_ascii_uppercase())
+            }),
+            WatchCriterion::TypeCode => equals(identity.type_code),
+            WatchCriterion::Squawk => equals(identity.squawk),
+            WatchCriterion::Military => identity.military,
+        }
+    }
+
+    /// Short description of the rule, e.g. "Callsign prefix RCH within 50 nm"
+    pub fn describe(&self) -> String {
+        let mut description = self.criterion.label().to_string();
+        if self.criterion.takes_value() {
+            description.push(' ');
+            description.push_str(self.value.trim());
+        }
+        if let Some(range) = self.max_range_nm {
+            description.push_str(&format!(" within {:.0} nm", range));
+        }
+        description
+    }
+}
+
+/// The fields of an aircraft that watch rules match against
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AircraftIdentity<'a> {
+    pub icao: &'a str,
+    pub registration: Option<&'a str>,
+    pub callsign: Option<&'a str>,
+    pub type_code: Option<&'a str>,
+    pub squawk: Option<&'a str>,
+    pub military: bool,
+}
+
+/// Watchlist configuration stored in `AppConfig`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WatchlistConfig {
+    /// Watch rules, evaluated in order
+    #[serde(default)]
+    pub rules: Vec<WatchRule>,
+
+    /// Append watchlist events to this file as JSON lines
+    #[serde(default)]
+    pub log_file: Option<PathBuf>,
+
+    /// POST watchlist events as JSON to this URL
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_matching() {
+        let identity = AircraftIdentity {
+            icao: "AE1234",
+            registration: Some("N123AB"),
+            callsign: Some("RCH401  "),
+            type_code: Some("C17"),
+            squawk: Some("7700"),
+            military: true,
+        };
+
+        let rule = |criterion, value: &str| WatchRule::new("test".to_string(), criterion, value.to_string());
+        assert!(rule(WatchCriterion::Icao, "ae1234").matches(&identity));
+        assert!(rule(WatchCriterion::Registration, " n123ab ").matches(&identity));
+        assert!(rule(WatchCriterion::CallsignPrefix, "rch").matches(&identity));
+        assert!(!rule(WatchCriterion::CallsignPrefix, "UAL").matches(&identity));
+        assert!(rule(WatchCriterion::TypeCode, "C17").matches(&identity));
+        assert!(rule(WatchCriterion::Squawk, "7700").matches(&identity));
+        assert!(rule(WatchCriterion::Military, "").matches(&identity));
+
+        // An empty value never matches
+        assert!(!rule(WatchCriterion::CallsignPrefix, "").matches(&identity));
+        assert!(!rule(WatchCriterion::Military, "").matches(&AircraftIdentity {
+            icao: "A00001",
+            ..Default::default()
+        }));
+    }
+}
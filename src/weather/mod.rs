@@ -1,8 +1,11 @@
 //! Weather overlay tile management.
 //!
 //! This module provides weather tile fetching from OpenWeatherMap
-//! with support for precipitation, cloud, and wind layers.
+//! with support for precipitation, cloud, and wind layers, plus
+//! winds aloft measured by the tracked aircraft themselves.
 
 pub mod openweathermap;
+pub mod winds_aloft;
 
 pub use openweathermap::{WeatherLayer, OpenWeatherMapSource, WeatherTiles};
+pub use winds_aloft::{AltitudeBand, WindsAloft};
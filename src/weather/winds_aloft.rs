@@ -0,0 +1,306 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Winds aloft measured by aircraft.
+//!
+//! Aircraft that report wind and temperature (Comm-B BDS 4,4) or whose air
+//! data lets the tracker derive them (BDS 5,0/6,0 plus ADS-B velocity) act as
+//! airborne weather stations. Their samples are grouped by altitude band and
+//! grid cell, averaged, and drawn as standard wind barbs. Unlike the
+//! OpenWeatherMap layers, this overlay needs no API key.
+
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+/// Size of a grid cell in degrees of latitude and longitude
+const GRID_SIZE_DEGREES: f64 = 0.5;
+/// Samples older than this are dropped (30 minutes)
+const SAMPLE_MAX_AGE_SECONDS: i64 = 1800;
+/// Winds below this speed are drawn as calm
+const CALM_WIND_KNOTS: f64 = 2.5;
+
+/// Altitude band used to group wind samples
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum AltitudeBand {
+    /// Surface to FL100
+    Low,
+    /// FL100 to FL200
+    Medium,
+    /// FL200 to FL300
+    High,
+    /// FL300 and above
+    #[default]
+    Cruise,
+}
+
+impl AltitudeBand {
+    pub const ALL: [AltitudeBand; 4] = [
+        AltitudeBand::Low,
+        AltitudeBand::Medium,
+        AltitudeBand::High,
+        AltitudeBand::Cruise,
+    ];
+
+    /// Band containing an altitude in feet
+    pub fn from_altitude(altitude: i32) -> Self {
+        match altitude {
+            i32::MIN..=9_999 => AltitudeBand::Low,
+            10_000..=19_999 => AltitudeBand::Medium,
+            20_000..=29_999 => AltitudeBand::High,
+            _ => AltitudeBand::Cruise,
+        }
+    }
+
+    /// Get human-readable display name
+    pub fn label(&self) -> &'static str {
+        match self {
+            AltitudeBand::Low => "SFC - FL100",
+            AltitudeBand::Medium => "FL100 - FL200",
+            AltitudeBand::High => "FL200 - FL300",
+            AltitudeBand::Cruise => "FL300+",
+        }
+    }
+}
+
+/// A single wind (and optionally temperature) observation from an aircraft
+#[derive(Debug, Clone)]
+pub struct WindSample {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Altitude in feet
+    pub altitude: i32,
+    /// Direction the wind blows from, in degrees
+    pub direction: f64,
+    /// Wind speed in knots
+    pub speed: f64,
+    /// Static air temperature in degrees Celsius
+    pub temperature: Option<f64>,
+    /// Confidence of the underlying Comm-B inference (0-1)
+    pub confidence: f32,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Averaged wind for one grid cell
+#[derive(Debug, Clone)]
+pub struct WindBarb {
+    /// Cell center latitude
+    pub latitude: f64,
+    /// Cell center longitude
+    pub longitude: f64,
+    /// Direction the wind blows from, in degrees
+    pub direction: f64,
+    /// Wind speed in knots
+    pub speed: f64,
+    /// Average static air temperature in degrees Celsius
+    pub temperature: Option<f64>,
+    /// Number of aircraft contributing to the cell
+    pub sample_count: usize,
+}
+
+type CellKey = (AltitudeBand, i32, i32);
+
+/// Grid of recent wind samples, keyed by altitude band and cell
+#[derive(Debug, Default)]
+pub struct WindsAloft {
+    // Latest sample from each aircraft in each cell
+    cells: HashMap<CellKey, HashMap<String, WindSample>>,
+}
+
+impl WindsAloft {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn cell_key(sample: &WindSample) -> CellKey {
+        (
+            AltitudeBand::from_altitude(sample.altitude),
+            (sample.latitude / GRID_SIZE_DEGREES).floor() as i32,
+            (sample.longitude / GRID_SIZE_DEGREES).floor() as i32,
+        )
+    }
+
+    /// Record the latest sample from an aircraft
+    ///
+    /// Each aircraft contributes at most one sample per cell, so recording the
+    /// same aircraft every frame does not skew the average.
+    pub fn record(&mut self, icao: &str, sample: WindSample) {
+        self.cells
+            .entry(Self::cell_key(&sample))
+            .or_default()
+            .insert(icao.to_string(), sample);
+    }
+
+    /// Drop samples older than the retention window
+    pub fn prune(&mut self, now: DateTime<Utc>) {
+        for samples in self.cells.values_mut() {
+            samples.retain(|_, sample| (now - sample.timestamp).num_seconds() < SAMPLE_MAX_AGE_SECONDS);
+        }
+        self.cells.retain(|_, samples| !samples.is_empty());
+    }
+
+    /// Number of samples currently held
+    pub fn sample_count(&self) -> usize {
+        self.cells.values().map(|samples| samples.len()).sum()
+    }
+
+    /// Averaged wind barbs for an altitude band
+    ///
+    /// Winds are averaged as vectors, weighted by inference confidence.
+    pub fn barbs(&self, band: AltitudeBand) -> Vec<WindBarb> {
+        self.cells
+            .iter()
+            .filter(|((cell_band, _, _), _)| *cell_band == band)
+            .filter_map(|(&(_, lat_index, lon_index), samples)| {
+                let mut east = 0.0;
+                let mut north = 0.0;
+                let mut weight_sum = 0.0;
+                let mut temperature_sum = 0.0;
+                let mut temperature_weight = 0.0;
+
+                for sample in samples.values() {
+                    let weight = f64::from(sample.confidence.max(0.01));
+                    let (sin, cos) = sample.direction.to_radians().sin_cos();
+                    east += weight * sample.speed * sin;
+                    north += weight * sample.speed * cos;
+                    weight_sum += weight;
+
+                    if let Some(temperature) = sample.temperature {
+                        temperature_sum += weight * temperature;
+                        temperature_weight += weight;
+                    }
+                }
+
+                if weight_sum <= 0.0 {
+                    return None;
+                }
+                east /= weight_sum;
+                north /= weight_sum;
+
+                Some(WindBarb {
+                    latitude: (lat_index as f64 + 0.5) * GRID_SIZE_DEGREES,
+                    longitude: (lon_index as f64 + 0.5) * GRID_SIZE_DEGREES,
+                    direction: east.atan2(north).to_degrees().rem_euclid(360.0),
+                    speed: east.hypot(north),
+                    temperature: (temperature_weight > 0.0).then(|| temperature_sum / temperature_weight),
+                    sample_count: samples.len(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Draw a standard wind barb at a screen position
+///
+/// The staff points towards the direction the wind comes from. Pennants are
+/// 50 kt, full barbs 10 kt and half barbs 5 kt, drawn on the clockwise side
+/// of the staff as in the northern hemisphere convention.
+pub fn draw_wind_barb(painter: &egui::Painter, pos: egui::Pos2, barb: &WindBarb, length: f32, color: egui::Color32) {
+    let stroke = egui::Stroke::new(1.5, color);
+
+    if barb.speed < CALM_WIND_KNOTS {
+        painter.circle_stroke(pos, length * 0.15, stroke);
+        return;
+    }
+
+    // Screen y grows downwards, so north is -y
+    let radians = barb.direction.to_radians() as f32;
+    let staff = egui::vec2(radians.sin(), -radians.cos());
+    let feather = egui::vec2(-staff.y, staff.x);
+
+    let tip = pos + staff * length;
+    painter.line_segment([pos, tip], stroke);
+    painter.circle_filled(pos, 2.0, color);
+
+    let feather_length = length * 0.4;
+    let spacing = length * 0.15;
+    let mut remaining = ((barb.speed / 5.0).round() * 5.0) as i32;
+    let mut offset = 0.0;
+
+    while remaining >= 50 {
+        let base = tip - staff * offset;
+        painter.add(egui::Shape::convex_polygon(
+            vec![base, base + feather * feather_length, base - staff * spacing],
+            color,
+            stroke,
+        ));
+        offset += spacing * 1.5;
+        remaining -= 50;
+    }
+
+    while remaining >= 10 {
+        let base = tip - staff * offset;
+        painter.line_segment([base, base + feather * feather_length + staff * spacing], stroke);
+        offset += spacing;
+        remaining -= 10;
+    }
+
+    if remaining >= 5 {
+        // A lone half barb is set back from the tip so it is not mistaken for a full one
+        if offset == 0.0 {
+            offset = spacing;
+        }
+        let base = tip - staff * offset;
+        painter.line_segment([base, base + (feather * feather_length + staff * spacing) * 0.5], stroke);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(latitude: f64, altitude: i32, direction: f64, speed: f64, confidence: f32) -> WindSample {
+        WindSample {
+            latitude,
+            longitude: -104.2,
+            altitude,
+            direction,
+            speed,
+            temperature: Some(-50.0),
+            confidence,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_barbs_average_by_band_and_cell() {
+        let mut winds = WindsAloft::new();
+        winds.record("A1", sample(39.7, 35_000, 270.0, 40.0, 1.0));
+        winds.record("A2", sample(39.8, 36_000, 270.0, 60.0, 1.0));
+        winds.record("A3", sample(39.8, 15_000, 180.0, 20.0, 1.0));
+
+        let cruise = winds.barbs(AltitudeBand::Cruise);
+        assert_eq!(cruise.len(), 1);
+        assert_eq!(cruise[0].sample_count, 2);
+        assert!((cruise[0].speed - 50.0).abs() < 1e-9);
+        assert!((cruise[0].direction - 270.0).abs() < 1e-9);
+        assert!((cruise[0].latitude - 39.75).abs() < 1e-9);
+
+        assert_eq!(winds.barbs(AltitudeBand::Medium).len(), 1);
+        assert!(winds.barbs(AltitudeBand::Low).is_empty());
+    }
+
+    #[test]
+    fn test_latest_sample_per_aircraft_and_pruning() {
+        let mut winds = WindsAloft::new();
+        winds.record("A1", sample(39.7, 35_000, 270.0, 40.0, 1.0));
+        winds.record("A1", sample(39.7, 35_000, 270.0, 80.0, 1.0));
+        assert_eq!(winds.sample_count(), 1);
+        assert!((winds.barbs(AltitudeBand::Cruise)[0].speed - 80.0).abs() < 1e-9);
+
+        winds.prune(Utc::now() + chrono::Duration::seconds(SAMPLE_MAX_AGE_SECONDS));
+        assert_eq!(winds.sample_count(), 0);
+    }
+}
@@ -0,0 +1,10 @@
+//! Embedded HTTP API for web frontends and dashboards.
+//!
+//! Serves the merged traffic of every server in readsb's JSON schema, polled
+//! or streamed over a WebSocket.
+
+pub mod readsb;
+pub mod server;
+pub mod stream;
+
+pub use server::{WebConfig, WebServer, WebServerStatus};
@@ -0,0 +1,357 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! readsb JSON schema.
+//!
+//! `aircraft.json` and `receiver.json` as written by readsb and read by
+//! tar1090. Fields the aircraft doesn't report are left out, as readsb does.
+//! Registration and type come from the aircraft database lookups, in the `r`
+//! and `t` fields of tar1090's own database.
+
+use adsb_client::{DataSource, EmergencyState, SelectedAltitudeSource};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::aircraft::Aircraft;
+
+/// How often clients should poll `aircraft.json`, in milliseconds
+const REFRESH_MS: u32 = 1000;
+
+/// `receiver.json`
+#[derive(Debug, Clone, Serialize)]
+pub struct ReceiverJson {
+    pub version: String,
+    /// Poll interval in milliseconds
+    pub refresh: u32,
+    /// Number of history files served (none)
+    pub history: u32,
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl ReceiverJson {
+    pub fn new(receiver: (f64, f64)) -> Self {
+        Self {
+            version: format!("airjedi-desktop {}", env!("CARGO_PKG_VERSION")),
+            refresh: REFRESH_MS,
+            history: 0,
+            lat: round(receiver.0, 6),
+            lon: round(receiver.1, 6),
+        }
+    }
+}
+
+/// `aircraft.json`
+#[derive(Debug, Clone, Serialize)]
+pub struct AircraftJson {
+    /// Seconds since the epoch
+    pub now: f64,
+    /// Messages received from every server
+    pub messages: u64,
+    pub aircraft: Vec<ReadsbAircraft>,
+}
+
+impl AircraftJson {
+    /// Build from the aircraft of every server, each one listed once (see
+    /// [`unique_aircraft`](crate::aircraft::unique_aircraft))
+    pub fn new(aircraft: &[Aircraft], messages: u64, now: DateTime<Utc>) -> Self {
+        let mut aircraft: Vec<ReadsbAircraft> = aircraft.iter().map(ReadsbAircraft::new).collect();
+        aircraft.sort_by(|a, b| a.hex.cmp(&b.hex));
+        Self {
+            now: seconds(now),
+            messages,
+            aircraft,
+        }
+    }
+}
+
+/// Barometric altitude in feet, or on the ground
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum AltBaro {
+    Feet(i32),
+    /// Always `"ground"`
+    Ground(&'static str),
+}
+
+/// One aircraft of `aircraft.json`
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadsbAircraft {
+    /// Lowercase ICAO address, `~` prefixed when not an ICAO address
+    pub hex: String,
+    /// Kind of address and link (`adsb_icao`, `mode_s`, `adsr_icao`, ...)
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    /// Callsign padded to 8 characters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flight: Option<String>,
+    /// Registration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r: Option<String>,
+    /// ICAO type designator
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub t: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alt_baro: Option<AltBaro>,
+    /// Ground speed in knots
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gs: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track: Option<f64>,
+    /// Barometric vertical rate in feet per minute
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub baro_rate: Option<i32>,
+    /// Geometric (GNSS/inertial) vertical rate in feet per minute
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub geom_rate: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub squawk: Option<String>,
+    /// TC 28 emergency state (`none`, `general`, `lifeguard`, ...)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emergency: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lat: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lon: Option<f64>,
+    /// Seconds since the position was received
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seen_pos: Option<f64>,
+    /// Seconds since any message was received
+    pub seen: f64,
+    /// Messages received from the aircraft
+    pub messages: u64,
+    /// Signal level of the last frame in dBFS
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rssi: Option<f64>,
+    /// Selected QNH in hPa
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nav_qnh: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nav_altitude_mcp: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nav_altitude_fms: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nav_heading: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nav_modes: Option<Vec<&'static str>>,
+    /// ADS-B version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nac_p: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sil: Option<u8>,
+    /// Indicated airspeed in knots
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ias: Option<f64>,
+    /// True airspeed in knots
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tas: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mach: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mag_heading: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roll: Option<f64>,
+    /// Degrees per second
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track_rate: Option<f64>,
+    /// Wind direction (from) in degrees
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wd: Option<f64>,
+    /// Wind speed in knots
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ws: Option<f64>,
+    /// Outside (static) air temperature in degrees Celsius
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oat: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alert: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spi: Option<u8>,
+}
+
+impl ReadsbAircraft {
+    pub fn new(aircraft: &Aircraft) -> Self {
+        let data = aircraft.data();
+        let now = aircraft.feed_now();
+        let age = |time: DateTime<Utc>| round((now - time).num_milliseconds().max(0) as f64 / 1000.0, 1);
+        let (nav_altitude_mcp, nav_altitude_fms) = match data.selected_altitude_source {
+            Some(SelectedAltitudeSource::Fms) => (None, data.selected_altitude),
+            _ => (data.selected_altitude, None),
+        };
+        let wind = data.wind.map(|wind| wind.value);
+
+        Self {
+            hex: data.icao.to_lowercase(),
+            kind: address_type(aircraft),
+            flight: data.callsign.as_ref().map(|callsign| format!("{:<8}", callsign)),
+            r: aircraft.registration(),
+            t: aircraft.aircraft_type(),
+            alt_baro: match data.is_on_ground {
+                Some(true) => Some(AltBaro::Ground("ground")),
+                _ => data.altitude.map(AltBaro::Feet),
+            },
+            gs: data.velocity.map(|gs| round(gs, 1)),
+            track: data.track.map(|track| round(track, 2)),
+            baro_rate: data.baro_vertical_rate.map(|rate| rate.value).or(data.vertical_rate),
+            geom_rate: data.inertial_vertical_rate.map(|rate| rate.value),
+            squawk: data.squawk.clone(),
+            emergency: data.emergency_state.map(emergency_name),
+            lat: data.latitude.map(|lat| round(lat, 6)),
+            lon: data.longitude.map(|lon| round(lon, 6)),
+            seen_pos: data.position_time.map(age),
+            seen: age(data.last_seen),
+            messages: data.messages,
+            rssi: data.rssi.map(|rssi| round(rssi, 1)),
+            nav_qnh: data.baro_setting.or(data.ehs_baro_setting.map(|qnh| qnh.value)).map(|qnh| round(qnh, 1)),
+            nav_altitude_mcp: nav_altitude_mcp.or(data.ehs_selected_altitude.map(|altitude| altitude.value)),
+            nav_altitude_fms,
+            nav_heading: data.selected_heading.map(|heading| round(heading, 1)),
+            nav_modes: data.autopilot_modes.map(|modes| {
+                [
+                    (modes.autopilot, "autopilot"),
+                    (modes.vnav, "vnav"),
+                    (modes.altitude_hold, "althold"),
+                    (modes.approach, "approach"),
+                    (modes.lnav, "lnav"),
+                    (modes.tcas, "tcas"),
+                ]
+                .into_iter()
+                .filter_map(|(engaged, name)| engaged.then_some(name))
+                .collect()
+            }),
+            version: data.adsb_version,
+            nac_p: data.nac_p,
+            sil: data.sil,
+            ias: data.indicated_airspeed.map(|ias| ias.value),
+            tas: data.true_airspeed.map(|tas| tas.value),
+            mach: data.mach.map(|mach| round(mach.value, 3)),
+            mag_heading: data.magnetic_heading.map(|heading| round(heading.value, 1)),
+            roll: data.roll.map(|roll| round(roll.value, 1)),
+            track_rate: data.track_rate.map(|rate| round(rate.value, 2)),
+            wd: wind.map(|wind| wind.direction.round()),
+            ws: wind.map(|wind| wind.speed.round()),
+            oat: data.static_air_temperature.map(|oat| round(oat.value, 1)),
+            alert: data.alert.map(u8::from),
+            spi: data.spi.map(u8::from),
+        }
+    }
+}
+
+/// readsb's address type: ADS-B for aircraft sending extended squitters, UAT
+/// traffic as rebroadcast (ADS-R), and `_other` for non-ICAO addresses
+fn address_type(aircraft: &Aircraft) -> &'static str {
+    let data = aircraft.data();
+    let non_icao = data.icao.starts_with('~');
+    match data.source {
+        DataSource::Uat if non_icao => "adsr_other",
+        DataSource::Uat => "adsr_icao",
+        DataSource::ModeS if non_icao => "adsb_other",
+        DataSource::ModeS if data.latitude.is_some() || data.velocity.is_some() || data.adsb_version.is_some() => {
+            "adsb_icao"
+        }
+        DataSource::ModeS => "mode_s",
+    }
+}
+
+fn emergency_name(state: EmergencyState) -> &'static str {
+    match state {
+        EmergencyState::None => "none",
+        EmergencyState::General => "general",
+        EmergencyState::Medical => "lifeguard",
+        EmergencyState::MinimumFuel => "minfuel",
+        EmergencyState::NoCommunications => "nordo",
+        EmergencyState::UnlawfulInterference => "unlawful",
+        EmergencyState::Downed => "downed",
+        EmergencyState::Reserved => "reserved",
+    }
+}
+
+/// Seconds since the epoch, to the millisecond
+pub(super) fn seconds(time: DateTime<Utc>) -> f64 {
+    time.timestamp_millis() as f64 / 1000.0
+}
+
+fn round(value: f64, decimals: i32) -> f64 {
+    let scale = 10f64.powi(decimals);
+    (value * scale).round() / scale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aircraft::{unique_aircraft, AircraftDetails};
+    use chrono::Duration;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_aircraft_json() {
+        let now = Utc::now();
+        let mut data = adsb_client::Aircraft::new("A1B2C3".to_string());
+        data.callsign = Some("UAL123".to_string());
+        data.latitude = Some(37.6188056);
+        data.longitude = Some(-122.3754167);
+        data.altitude = Some(35000);
+        data.velocity = Some(451.23);
+        data.squawk = Some("7700".to_string());
+        data.emergency_state = Some(EmergencyState::General);
+        data.last_seen = now - Duration::seconds(2);
+        data.position_time = Some(now - Duration::seconds(5));
+        data.messages = 120;
+        data.rssi = Some(-12.34);
+        data.extensions.insert(AircraftDetails {
+            registration: Some("N12345".to_string()),
+            aircraft_type: Some("B738".to_string()),
+            ..Default::default()
+        });
+
+        // An older copy of the same aircraft from another server is left out
+        let mut stale = data.clone();
+        stale.last_seen = now - Duration::seconds(30);
+        let mut on_ground = adsb_client::Aircraft::new("~00AB12".to_string());
+        on_ground.source = DataSource::Uat;
+        on_ground.altitude = Some(0);
+        on_ground.is_on_ground = Some(true);
+
+        let aircraft = unique_aircraft([stale, data, on_ground]
+            .into_iter()
+            .map(|data| Aircraft::new(Arc::new(data), "server".to_string(), "Server".to_string())));
+        let json = serde_json::to_value(AircraftJson::new(&aircraft, 42, now)).unwrap();
+        assert_eq!(json["messages"], 42);
+
+        let aircraft = json["aircraft"].as_array().unwrap();
+        assert_eq!(aircraft.len(), 2);
+        assert_eq!(aircraft[0]["hex"], "a1b2c3");
+        assert_eq!(aircraft[0]["type"], "adsb_icao");
+        assert_eq!(aircraft[0]["flight"], "UAL123  ");
+        assert_eq!(aircraft[0]["r"], "N12345");
+        assert_eq!(aircraft[0]["t"], "B738");
+        assert_eq!(aircraft[0]["alt_baro"], 35000);
+        assert_eq!(aircraft[0]["gs"], 451.2);
+        assert_eq!(aircraft[0]["emergency"], "general");
+        assert_eq!(aircraft[0]["lat"], 37.618806);
+        // Ages are measured when the aircraft is converted, a moment after `now`
+        assert!((aircraft[0]["seen_pos"].as_f64().unwrap() - 5.0).abs() < 0.5);
+        assert!((aircraft[0]["seen"].as_f64().unwrap() - 2.0).abs() < 0.5);
+        assert_eq!(aircraft[0]["messages"], 120);
+        assert_eq!(aircraft[0]["rssi"], -12.3);
+        assert!(aircraft[0].get("track").is_none());
+
+        assert_eq!(aircraft[1]["hex"], "~00ab12");
+        assert_eq!(aircraft[1]["type"], "adsr_other");
+        assert_eq!(aircraft[1]["alt_baro"], "ground");
+        assert!(aircraft[1].get("rssi").is_none());
+    }
+}
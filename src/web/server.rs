@@ -0,0 +1,336 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Embedded HTTP server.
+//!
+//! Serves the merged traffic picture in readsb's JSON schema, so tar1090,
+//! dashboards and scripts written for readsb can read it:
+//! - `/data/aircraft.json`: every aircraft of every server
+//! - `/data/receiver.json`: receiver location and poll interval
+//! - `/data/stream`: WebSocket stream of changes (see [`super::stream`])
+//!
+//! The server runs on its own thread and runtime, and locks the connection
+//! manager only for as long as it takes to snapshot or look up aircraft.
+
+use adsb_client::TrackerEvent;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::http::{header, HeaderName, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::Utc;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+use tokio::time::{interval_at, Instant, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+
+use super::readsb::{seconds, AircraftJson, ReceiverJson};
+use super::stream::{event_icao, ClientMessage, StreamAircraft, StreamMessage, StreamState};
+use crate::network::ConnectionManager;
+use crate::status::SharedSystemStatus;
+
+/// Let pages on other origins poll, and never serve a cached picture
+const HEADERS: [(HeaderName, &str); 2] = [
+    (header::ACCESS_CONTROL_ALLOW_ORIGIN, "*"),
+    (header::CACHE_CONTROL, "no-cache"),
+];
+
+/// How often streams are checked against every aircraft, catching changes whose events were missed
+const STREAM_SYNC_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Whether and where the HTTP API is served
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebConfig {
+    /// Serve the HTTP API
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// TCP port to listen on, on all interfaces
+    #[serde(default = "default_port")]
+    pub port: u16,
+}
+
+fn default_port() -> u16 {
+    8080
+}
+
+impl Default for WebConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_port(),
+        }
+    }
+}
+
+/// State of the server, for the UI
+#[derive(Debug, Clone, Default)]
+pub struct WebServerStatus {
+    /// Port listened on, once listening
+    pub port: Option<u16>,
+    /// Requests answered since the server started
+    pub requests: u64,
+    /// WebSocket clients following the stream
+    pub streams: usize,
+    /// Error that stopped the server
+    pub error: Option<String>,
+}
+
+/// What the request handlers read
+#[derive(Clone)]
+struct Source {
+    /// Weak so the server never keeps the connections alive on shutdown
+    connection_manager: Weak<Mutex<ConnectionManager>>,
+    system_status: SharedSystemStatus,
+    status: Arc<Mutex<WebServerStatus>>,
+    /// Ends the streams when the server stops
+    cancel_token: CancellationToken,
+}
+
+/// A running HTTP server, stopped when dropped
+pub struct WebServer {
+    config: WebConfig,
+    status: Arc<Mutex<WebServerStatus>>,
+    cancel_token: CancellationToken,
+}
+
+impl WebServer {
+    /// Start serving the aircraft of the connection manager
+    pub fn spawn(
+        config: WebConfig,
+        connection_manager: Weak<Mutex<ConnectionManager>>,
+        system_status: SharedSystemStatus,
+    ) -> Self {
+        let status = Arc::new(Mutex::new(WebServerStatus::default()));
+        let cancel_token = CancellationToken::new();
+        let source = Source {
+            connection_manager,
+            system_status,
+            status: status.clone(),
+            cancel_token: cancel_token.clone(),
+        };
+        let port = config.port;
+        let task_token = cancel_token.clone();
+
+        // Spawn background thread with its own tokio runtime
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to create tokio runtime");
+            rt.block_on(serve(port, source, task_token));
+        });
+
+        Self {
+            config,
+            status,
+            cancel_token,
+        }
+    }
+
+    pub fn config(&self) -> &WebConfig {
+        &self.config
+    }
+
+    pub fn status(&self) -> WebServerStatus {
+        self.status.lock().unwrap().clone()
+    }
+}
+
+impl Drop for WebServer {
+    fn drop(&mut self) {
+        self.cancel_token.cancel();
+    }
+}
+
+async fn serve(port: u16, source: Source, cancel_token: CancellationToken) {
+    let status = source.status.clone();
+    let listener = match TcpListener::bind(("0.0.0.0", port)).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!("HTTP API cannot listen on port {}: {}", port, e);
+            status.lock().unwrap().error = Some(format!("Cannot listen on port {}: {}", port, e));
+            return;
+        }
+    };
+    let port = listener.local_addr().map_or(port, |address| address.port());
+    info!("Serving HTTP API on port {}", port);
+    status.lock().unwrap().port = Some(port);
+
+    let result = axum::serve(listener, router(source))
+        .with_graceful_shutdown(cancel_token.cancelled_owned())
+        .await;
+    match result {
+        Ok(()) => info!("Stopped HTTP API"),
+        Err(e) => {
+            warn!("HTTP API failed: {}", e);
+            status.lock().unwrap().error = Some(e.to_string());
+        }
+    }
+}
+
+fn router(source: Source) -> Router {
+    Router::new()
+        .route("/data/aircraft.json", get(aircraft_json))
+        .route("/data/receiver.json", get(receiver_json))
+        .route("/data/stream", get(stream))
+        .with_state(source)
+}
+
+/// Every aircraft, or `None` once the connection manager is gone
+fn aircraft(source: &Source) -> Option<AircraftJson> {
+    let aircraft = source.connection_manager.upgrade()?.lock().unwrap().get_all_aircraft_unique();
+    let messages = source.system_status.lock().unwrap().get_total_server_messages();
+    Some(AircraftJson::new(&aircraft, messages, Utc::now()))
+}
+
+/// Every aircraft as streamed, with the time, or `None` once the connection manager is gone
+fn stream_aircraft(source: &Source) -> Option<(Vec<StreamAircraft>, f64)> {
+    let aircraft = source.connection_manager.upgrade()?.lock().unwrap().get_all_aircraft_unique();
+    let mut aircraft: Vec<StreamAircraft> = aircraft.iter().map(StreamAircraft::new).collect();
+    aircraft.sort_by(|a, b| a.hex().cmp(b.hex()));
+    Some((aircraft, seconds(Utc::now())))
+}
+
+async fn aircraft_json(State(source): State<Source>) -> Response {
+    source.status.lock().unwrap().requests += 1;
+    match aircraft(&source) {
+        Some(aircraft) => (HEADERS, Json(aircraft)).into_response(),
+        None => StatusCode::SERVICE_UNAVAILABLE.into_response(),
+    }
+}
+
+async fn receiver_json(State(source): State<Source>) -> Response {
+    source.status.lock().unwrap().requests += 1;
+    let Some(connection_manager) = source.connection_manager.upgrade() else {
+        return StatusCode::SERVICE_UNAVAILABLE.into_response();
+    };
+    let receiver = connection_manager.lock().unwrap().center();
+    (HEADERS, Json(ReceiverJson::new(receiver))).into_response()
+}
+
+async fn stream(upgrade: WebSocketUpgrade, State(source): State<Source>) -> Response {
+    source.status.lock().unwrap().requests += 1;
+    upgrade.on_upgrade(|socket| stream_events(socket, source))
+}
+
+/// Send a client a snapshot, then the deltas of every server's tracker events, following the
+/// filters it subscribes with
+async fn stream_events(mut socket: WebSocket, source: Source) {
+    // Subscribe before the snapshot, so no change falls in between
+    let Some(mut events) = source.connection_manager.upgrade().map(|manager| manager.lock().unwrap().subscribe())
+    else {
+        return;
+    };
+    let mut state = StreamState::default();
+    let Some(snapshot) = stream_aircraft(&source).map(|(aircraft, now)| state.snapshot(&aircraft, now)) else {
+        return;
+    };
+    let mut outgoing = vec![snapshot];
+    let mut sync = interval_at(Instant::now() + STREAM_SYNC_INTERVAL, STREAM_SYNC_INTERVAL);
+    sync.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    source.status.lock().unwrap().streams += 1;
+
+    'stream: loop {
+        for message in outgoing.drain(..) {
+            let json = serde_json::to_string(&message).expect("stream messages are serializable");
+            if socket.send(Message::Text(json.into())).await.is_err() {
+                break 'stream;
+            }
+        }
+
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => {
+                    // Take every pending event, to look their aircraft up together
+                    let mut batch = vec![event];
+                    let mut lagged = false;
+                    loop {
+                        match events.try_recv() {
+                            Ok(event) => batch.push(event),
+                            Err(TryRecvError::Lagged(_)) => lagged = true,
+                            Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+                        }
+                    }
+
+                    let Some(current) = lookup(&source, &batch) else {
+                        break;
+                    };
+                    let now = seconds(Utc::now());
+                    for event in &batch {
+                        let hex = event_icao(event).to_lowercase();
+                        outgoing.extend(state.update(event, current.get(&hex).cloned(), now));
+                    }
+                    if lagged {
+                        match stream_aircraft(&source) {
+                            Some((aircraft, now)) => outgoing.push(state.snapshot(&aircraft, now)),
+                            None => break,
+                        }
+                    }
+                }
+                // Missed changes are caught up with a new snapshot
+                Err(RecvError::Lagged(_)) => match stream_aircraft(&source) {
+                    Some((aircraft, now)) => outgoing.push(state.snapshot(&aircraft, now)),
+                    None => break,
+                },
+                Err(RecvError::Closed) => break,
+            },
+
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str::<ClientMessage>(&text) {
+                    Ok(ClientMessage::Subscribe(filter)) => {
+                        state.set_filter(filter);
+                        match stream_aircraft(&source) {
+                            Some((aircraft, now)) => outgoing.push(state.snapshot(&aircraft, now)),
+                            None => break,
+                        }
+                    }
+                    Err(e) => outgoing.push(StreamMessage::Error { message: e.to_string() }),
+                },
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                // Pings are answered by axum
+                Some(Ok(_)) => {}
+            },
+
+            _ = sync.tick() => match stream_aircraft(&source) {
+                Some((aircraft, now)) => outgoing.extend(state.reconcile(&aircraft, now)),
+                None => break,
+            },
+
+            _ = source.cancel_token.cancelled() => break,
+        }
+    }
+
+    source.status.lock().unwrap().streams -= 1;
+}
+
+/// Current state of the aircraft some events are about by hex, without those no server tracks,
+/// or `None` once the connection manager is gone
+fn lookup(source: &Source, events: &[TrackerEvent]) -> Option<HashMap<String, StreamAircraft>> {
+    let icaos: Vec<&str> = events.iter().map(event_icao).collect();
+    let aircraft = source.connection_manager.upgrade()?.lock().unwrap().get_aircraft_unique(&icaos);
+    Some(aircraft
+        .iter()
+        .map(|aircraft| {
+            let aircraft = StreamAircraft::new(aircraft);
+            (aircraft.hex().to_string(), aircraft)
+        })
+        .collect())
+}
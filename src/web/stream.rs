@@ -0,0 +1,354 @@
+// Copyright 2025 Chris Custine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! WebSocket event stream protocol.
+//!
+//! A client is first sent a `Snapshot` of every aircraft, then
+//! `AircraftAdded`, `PositionUpdated` and `AircraftRemoved` deltas as the
+//! trackers of every server report changes. Aircraft are in the readsb schema
+//! of `aircraft.json`, with every field the aircraft reports, plus what readsb
+//! has no field for: the link and server, the validator's counters, the
+//! smoothed and dead-reckoned positions, and photo and video links.
+//!
+//! A client may send a `Subscribe` message with a bounding box and altitude
+//! band, and is then sent a new snapshot of only the aircraft inside them.
+//! Aircraft entering or leaving are added and removed like any other:
+//!
+//! ```json
+//! {"type": "Subscribe", "bbox": [37.0, -123.0, 38.5, -121.5], "min_altitude": 10000}
+//! ```
+
+use adsb_client::{DataSource, PositionStats, PredictedPosition, TrackEstimate, TrackerEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use super::readsb::{AltBaro, ReadsbAircraft};
+use crate::aircraft::Aircraft;
+use crate::video::protocol::VideoLink;
+
+/// Message sent to a client
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
+pub enum StreamMessage {
+    /// Every aircraft passing the filter, replacing all the client knows
+    Snapshot { now: f64, aircraft: Vec<StreamAircraft> },
+    /// An aircraft now tracked and passing the filter
+    AircraftAdded { now: f64, aircraft: StreamAircraft },
+    /// New state of an aircraft after a position update
+    PositionUpdated { now: f64, aircraft: StreamAircraft },
+    /// An aircraft no longer tracked by any server, or no longer passing the filter
+    AircraftRemoved { now: f64, hex: String },
+    /// A client message that couldn't be understood
+    Error { message: String },
+}
+
+/// An aircraft as streamed: its readsb fields and the rest of its state
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamAircraft {
+    #[serde(flatten)]
+    pub readsb: ReadsbAircraft,
+    /// Link the aircraft is received on
+    pub source: DataSource,
+    /// Server the aircraft's state comes from
+    pub server: String,
+    /// Positions accepted and rejected by the tracker's validator
+    pub position_stats: PositionStats,
+    /// Smoothed position and velocity, when track smoothing is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track_estimate: Option<TrackEstimate>,
+    /// Position dead-reckoned to the time of the message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub predicted: Option<PredictedPosition>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub photo_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub photo_thumbnail_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub photographer: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub video_links: Vec<VideoLink>,
+}
+
+impl StreamAircraft {
+    pub fn new(aircraft: &Aircraft) -> Self {
+        let data = aircraft.data();
+        Self {
+            readsb: ReadsbAircraft::new(aircraft),
+            source: data.source,
+            server: aircraft.source_server_name(),
+            position_stats: data.position_stats,
+            track_estimate: data.track_estimate,
+            predicted: aircraft.predicted_position(aircraft.feed_now()),
+            photo_url: aircraft.photo_url(),
+            photo_thumbnail_url: aircraft.photo_thumbnail_url(),
+            photographer: aircraft.photographer(),
+            video_links: aircraft.video_links(),
+        }
+    }
+
+    pub fn hex(&self) -> &str {
+        &self.readsb.hex
+    }
+}
+
+/// Message sent by a client
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+pub enum ClientMessage {
+    /// Only follow the aircraft passing a filter
+    Subscribe(StreamFilter),
+}
+
+/// Which aircraft a client follows, every aircraft by default
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct StreamFilter {
+    /// `[south, west, north, east]` in degrees, crossing the antimeridian when
+    /// west is greater than east
+    pub bbox: Option<[f64; 4]>,
+    /// Lowest barometric altitude in feet, aircraft on the ground being at 0
+    pub min_altitude: Option<i32>,
+    /// Highest barometric altitude in feet
+    pub max_altitude: Option<i32>,
+}
+
+impl StreamFilter {
+    /// Whether an aircraft passes the filter; aircraft without a position (or
+    /// altitude) never pass a bounding box (or altitude band)
+    pub fn matches(&self, aircraft: &ReadsbAircraft) -> bool {
+        if let Some([south, west, north, east]) = self.bbox {
+            let (Some(lat), Some(lon)) = (aircraft.lat, aircraft.lon) else {
+                return false;
+            };
+            let inside_lon = if west <= east {
+                (west..=east).contains(&lon)
+            } else {
+                lon >= west || lon <= east
+            };
+            if !(south..=north).contains(&lat) || !inside_lon {
+                return false;
+            }
+        }
+
+        if self.min_altitude.is_some() || self.max_altitude.is_some() {
+            let altitude = match aircraft.alt_baro {
+                Some(AltBaro::Feet(feet)) => feet,
+                Some(AltBaro::Ground(_)) => 0,
+                None => return false,
+            };
+            if self.min_altitude.is_some_and(|min| altitude < min)
+                || self.max_altitude.is_some_and(|max| altitude > max)
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// ICAO address an event is about
+pub fn event_icao(event: &TrackerEvent) -> &str {
+    match event {
+        TrackerEvent::AircraftAdded(icao)
+        | TrackerEvent::PositionUpdated(icao)
+        | TrackerEvent::AircraftRemoved(icao)
+        | TrackerEvent::Emergency { icao, .. } => icao,
+    }
+}
+
+/// What one client follows and has been sent
+#[derive(Debug, Default)]
+pub struct StreamState {
+    filter: StreamFilter,
+    /// Hex of every aircraft the client knows about
+    sent: HashSet<String>,
+}
+
+impl StreamState {
+    /// Follow other aircraft; the client needs a new snapshot
+    pub fn set_filter(&mut self, filter: StreamFilter) {
+        self.filter = filter;
+    }
+
+    /// Every aircraft passing the filter
+    pub fn snapshot(&mut self, aircraft: &[StreamAircraft], now: f64) -> StreamMessage {
+        let aircraft: Vec<StreamAircraft> = aircraft
+            .iter()
+            .filter(|aircraft| self.filter.matches(&aircraft.readsb))
+            .cloned()
+            .collect();
+        self.sent = aircraft.iter().map(|aircraft| aircraft.hex().to_string()).collect();
+        StreamMessage::Snapshot { now, aircraft }
+    }
+
+    /// Delta for a tracker event, given the aircraft's state on any server
+    /// (`None` once no server tracks it)
+    ///
+    /// Events that change nothing the client follows, like an aircraft
+    /// already known being added by another server, give no delta.
+    pub fn update(&mut self, event: &TrackerEvent, aircraft: Option<StreamAircraft>, now: f64) -> Option<StreamMessage> {
+        match aircraft.filter(|aircraft| self.filter.matches(&aircraft.readsb)) {
+            Some(aircraft) if self.sent.insert(aircraft.hex().to_string()) => {
+                Some(StreamMessage::AircraftAdded { now, aircraft })
+            }
+            Some(aircraft) if matches!(event, TrackerEvent::PositionUpdated(_)) => {
+                Some(StreamMessage::PositionUpdated { now, aircraft })
+            }
+            Some(_) => None,
+            None => {
+                let hex = event_icao(event).to_lowercase();
+                self.sent
+                    .remove(&hex)
+                    .then_some(StreamMessage::AircraftRemoved { now, hex })
+            }
+        }
+    }
+
+    /// Deltas bringing the client up to date with every aircraft, for
+    /// additions and removals whose events were missed
+    pub fn reconcile(&mut self, aircraft: &[StreamAircraft], now: f64) -> Vec<StreamMessage> {
+        let current: Vec<&StreamAircraft> = aircraft
+            .iter()
+            .filter(|aircraft| self.filter.matches(&aircraft.readsb))
+            .collect();
+        let hexes: HashSet<&str> = current.iter().map(|aircraft| aircraft.hex()).collect();
+
+        let mut removed: Vec<String> = self
+            .sent
+            .iter()
+            .filter(|hex| !hexes.contains(hex.as_str()))
+            .cloned()
+            .collect();
+        removed.sort();
+        for hex in &removed {
+            self.sent.remove(hex);
+        }
+        let mut messages: Vec<StreamMessage> = removed
+            .into_iter()
+            .map(|hex| StreamMessage::AircraftRemoved { now, hex })
+            .collect();
+
+        for aircraft in current {
+            if self.sent.insert(aircraft.hex().to_string()) {
+                messages.push(StreamMessage::AircraftAdded {
+                    now,
+                    aircraft: aircraft.clone(),
+                });
+            }
+        }
+        messages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn aircraft(icao: &str, lat: f64, lon: f64, altitude: i32) -> StreamAircraft {
+        let mut data = adsb_client::Aircraft::new(icao.to_string());
+        data.latitude = Some(lat);
+        data.longitude = Some(lon);
+        data.altitude = Some(altitude);
+        StreamAircraft::new(&Aircraft::new(Arc::new(data), "server".to_string(), "Server".to_string()))
+    }
+
+    fn hexes(message: &StreamMessage) -> Vec<&str> {
+        match message {
+            StreamMessage::Snapshot { aircraft, .. } => aircraft.iter().map(StreamAircraft::hex).collect(),
+            StreamMessage::AircraftAdded { aircraft, .. } | StreamMessage::PositionUpdated { aircraft, .. } => {
+                vec![aircraft.hex()]
+            }
+            StreamMessage::AircraftRemoved { hex, .. } => vec![hex.as_str()],
+            StreamMessage::Error { .. } => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_stream_deltas() {
+        let ClientMessage::Subscribe(filter) =
+            serde_json::from_str(r#"{"type": "Subscribe", "bbox": [37.0, -123.0, 38.0, -122.0], "min_altitude": 10000}"#)
+                .unwrap();
+        let mut state = StreamState::default();
+        state.set_filter(filter);
+
+        let inside = aircraft("AAAAAA", 37.5, -122.5, 35000);
+        let outside = aircraft("BBBBBB", 40.0, -122.5, 35000);
+        let low = aircraft("CCCCCC", 37.5, -122.5, 5000);
+        let snapshot = state.snapshot(&[inside.clone(), outside, low], 0.0);
+        assert_eq!(hexes(&snapshot), ["aaaaaa"]);
+
+        let now = 0.0;
+        let moved = TrackerEvent::PositionUpdated("AAAAAA".to_string());
+        let update = state.update(&moved, Some(aircraft("AAAAAA", 37.6, -122.5, 35000)), now).unwrap();
+        assert!(matches!(update, StreamMessage::PositionUpdated { .. }));
+
+        // Already known aircraft added by another server
+        let added = TrackerEvent::AircraftAdded("AAAAAA".to_string());
+        assert!(state.update(&added, Some(inside), now).is_none());
+
+        let entered = TrackerEvent::PositionUpdated("BBBBBB".to_string());
+        let update = state.update(&entered, Some(aircraft("BBBBBB", 37.9, -122.5, 35000)), now).unwrap();
+        assert!(matches!(update, StreamMessage::AircraftAdded { .. }));
+        assert_eq!(hexes(&update), ["bbbbbb"]);
+
+        // Leaving the box, and timing out on every server
+        let update = state.update(&moved, Some(aircraft("AAAAAA", 36.9, -122.5, 35000)), now).unwrap();
+        assert!(matches!(update, StreamMessage::AircraftRemoved { .. }));
+        assert_eq!(hexes(&update), ["aaaaaa"]);
+        let removed = TrackerEvent::AircraftRemoved("BBBBBB".to_string());
+        let update = state.update(&removed, None, now).unwrap();
+        assert_eq!(hexes(&update), ["bbbbbb"]);
+        assert!(state.update(&removed, None, now).is_none());
+
+        // Changes whose events were missed
+        let updates = state.reconcile(&[aircraft("CCCCCC", 37.5, -122.5, 12000)], 0.0);
+        assert_eq!(updates.len(), 1);
+        assert!(matches!(updates[0], StreamMessage::AircraftAdded { .. }));
+        assert_eq!(hexes(&updates[0]), ["cccccc"]);
+        let updates = state.reconcile(&[], 0.0);
+        assert!(matches!(updates[..], [StreamMessage::AircraftRemoved { .. }]));
+    }
+
+    #[test]
+    fn test_stream_aircraft_fields() {
+        let mut data = adsb_client::Aircraft::new("A1B2C3".to_string());
+        data.source = DataSource::Uat;
+        data.rssi = Some(-20.04);
+        data.position_stats.accepted = 3;
+        let aircraft = StreamAircraft::new(&Aircraft::new(Arc::new(data), "server".to_string(), "Server".to_string()));
+
+        // readsb fields sit beside the rest, not nested
+        let json = serde_json::to_value(&aircraft).unwrap();
+        assert_eq!(json["hex"], "a1b2c3");
+        assert_eq!(json["type"], "adsr_icao");
+        assert_eq!(json["rssi"], -20.0);
+        assert_eq!(json["source"], "Uat");
+        assert_eq!(json["server"], "Server");
+        assert_eq!(json["position_stats"]["accepted"], 3);
+        assert!(json.get("track_estimate").is_none());
+        assert!(json.get("video_links").is_none());
+    }
+
+    #[test]
+    fn test_filter_across_antimeridian() {
+        let filter = StreamFilter {
+            bbox: Some([50.0, 170.0, 60.0, -170.0]),
+            ..Default::default()
+        };
+        assert!(filter.matches(&aircraft("AAAAAA", 55.0, 175.0, 35000).readsb));
+        assert!(filter.matches(&aircraft("AAAAAA", 55.0, -175.0, 35000).readsb));
+        assert!(!filter.matches(&aircraft("AAAAAA", 55.0, 0.0, 35000).readsb));
+        assert!(StreamFilter::default().matches(&aircraft("AAAAAA", 0.0, 0.0, 0).readsb));
+    }
+}